@@ -0,0 +1,128 @@
+//! Baselines: findings a team has reviewed and accepted, keyed by
+//! [`Finding::fingerprint`], so that later runs only surface new ones.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Finding, Suppression, SuppressionKind};
+
+const VERSION: u32 = 1;
+
+/// An accepted finding. Everything but the fingerprint is there for the
+/// people reviewing the file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub fingerprint: String,
+    #[serde(default)]
+    pub check_name: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub version: u32,
+    pub findings: Vec<Entry>,
+}
+
+impl Default for Baseline {
+    fn default() -> Self {
+        Self {
+            version: VERSION,
+            findings: Vec::new(),
+        }
+    }
+}
+
+impl Baseline {
+    /// Accepts every finding not already suppressed in the source.
+    pub fn from_findings<'a>(findings: impl IntoIterator<Item = &'a Finding>) -> Self {
+        let mut baseline = Self::default();
+        for finding in findings {
+            let suppression = finding.extra_data.suppression.as_ref();
+            if suppression.is_some_and(|s| s.kind == SuppressionKind::InSource) {
+                continue;
+            }
+            let fingerprint = finding.fingerprint();
+            if baseline
+                .findings
+                .iter()
+                .any(|e| e.fingerprint == fingerprint)
+            {
+                continue;
+            }
+            baseline.findings.push(Entry {
+                fingerprint,
+                check_name: finding.check_name.clone(),
+                title: finding.title.clone(),
+                file: finding.location.as_ref().map(|l| l.file.clone()),
+                line: finding.location.as_ref().map(|l| l.line),
+            });
+        }
+        baseline
+    }
+
+    pub fn contains(&self, finding: &Finding) -> bool {
+        let fingerprint = finding.fingerprint();
+        self.findings.iter().any(|e| e.fingerprint == fingerprint)
+    }
+
+    /// Suppresses the findings in the baseline that are not suppressed yet.
+    pub fn apply(&self, findings: &mut [Finding]) {
+        for finding in findings {
+            if !finding.is_suppressed() && self.contains(finding) {
+                finding.extra_data.suppression = Some(Suppression {
+                    kind: SuppressionKind::Baseline,
+                    justification: None,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Location;
+
+    fn finding(check_name: &str, line: usize) -> Finding {
+        Finding {
+            check_name: check_name.into(),
+            title: check_name.into(),
+            location: Some(Location {
+                file: "Vault.sol".into(),
+                line,
+                column: 1,
+                end_line: line,
+                end_column: 2,
+            }),
+            code_snippet: Some(format!("line {line}")),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_baseline_round_trip() {
+        let mut inline = finding("arithmetic", 3);
+        inline.extra_data.suppression = Some(Suppression {
+            kind: SuppressionKind::InSource,
+            justification: None,
+        });
+        let accepted = [finding("reentrancy", 1), inline];
+        let baseline = Baseline::from_findings(&accepted);
+        assert_eq!(baseline.findings.len(), 1);
+        let json = serde_json::to_string(&baseline).unwrap();
+        let baseline: Baseline = serde_json::from_str(&json).unwrap();
+
+        let mut findings = vec![finding("reentrancy", 1), finding("reentrancy", 2)];
+        baseline.apply(&mut findings);
+        assert_eq!(
+            findings[0].extra_data.suppression.as_ref().map(|s| s.kind),
+            Some(SuppressionKind::Baseline)
+        );
+        assert!(!findings[1].is_suppressed());
+    }
+}
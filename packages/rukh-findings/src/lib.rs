@@ -0,0 +1,359 @@
+/*!
+ * RUKH Findings
+ * Author: Volodymyr Stetsenko (Zero2Auditor)
+ *
+ * Finding model shared by the Rust analysis engines. A [`Finding`] serializes
+ * to exactly the columns of the api-gateway `vulnerabilities` table; everything
+ * without a column of its own (SWC/CWE ids, secondary spans, dataflow traces)
+ * travels in `extra_data`.
+ */
+
+pub mod baseline;
+pub mod merge;
+pub mod sarif;
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+/// Gateway `VulnerabilitySeverity`, ordered from least to most severe.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Informational,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+/// How likely a finding is a true positive.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 5] = [
+        Severity::Critical,
+        Severity::High,
+        Severity::Medium,
+        Severity::Low,
+        Severity::Informational,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Critical => "critical",
+            Severity::High => "high",
+            Severity::Medium => "medium",
+            Severity::Low => "low",
+            Severity::Informational => "informational",
+        }
+    }
+}
+
+impl Confidence {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Confidence::High => "high",
+            Confidence::Medium => "medium",
+            Confidence::Low => "low",
+        }
+    }
+}
+
+/// Error of parsing a [`Severity`] or [`Confidence`] from text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownLevel(pub String);
+
+impl fmt::Display for UnknownLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown level `{}`", self.0)
+    }
+}
+
+impl std::error::Error for UnknownLevel {}
+
+impl FromStr for Severity {
+    type Err = UnknownLevel;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Severity::ALL
+            .into_iter()
+            .find(|level| level.as_str() == s)
+            .ok_or_else(|| UnknownLevel(s.to_string()))
+    }
+}
+
+impl FromStr for Confidence {
+    type Err = UnknownLevel;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Confidence::High, Confidence::Medium, Confidence::Low]
+            .into_iter()
+            .find(|level| level.as_str() == s)
+            .ok_or_else(|| UnknownLevel(s.to_string()))
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A resolved source range. Lines and columns are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Location {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+/// A secondary location of a finding, such as the state write of a reentrancy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub location: Location,
+    pub label: String,
+}
+
+/// One hop of a dataflow trace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceStep {
+    /// Qualified name of the function the step happens in: `Vault.withdraw`.
+    pub function: String,
+    pub description: String,
+    pub location: Option<Location>,
+}
+
+/// How a finding was silenced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuppressionKind {
+    /// A comment in the analysed source.
+    InSource,
+    /// An entry of the baseline of accepted findings.
+    Baseline,
+}
+
+/// Marks a finding that was reported but silenced; it is kept for audit trails.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Suppression {
+    pub kind: SuppressionKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub justification: Option<String>,
+}
+
+/// Another engine's report of the same issue, folded in by [`merge::merge`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Corroboration {
+    pub source_engine: String,
+    pub check_name: String,
+}
+
+/// Structured part of the gateway's `extra_data` column.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExtraData {
+    /// SWC registry ids, e.g. `SWC-107`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub swc: Vec<String>,
+    /// CWE ids, e.g. `CWE-841`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cwe: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related_locations: Vec<Span>,
+    /// Source→sink path for dataflow findings, source first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trace: Vec<TraceStep>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suppression: Option<Suppression>,
+    /// Enclosing declaration, e.g. `Vault.withdraw(uint256)`. Part of the
+    /// fingerprint, and how engines without source positions line up.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<String>,
+    /// Other engines that reported the same issue.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub corroborated_by: Vec<Corroboration>,
+    /// Engine-specific data.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// A finding as persisted by the gateway: one row of `vulnerabilities`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Finding {
+    /// Id of the check that produced the finding, e.g. `reentrancy`.
+    pub check_name: String,
+    pub severity: Severity,
+    pub confidence: Confidence,
+    pub title: String,
+    pub description: String,
+    pub location: Option<Location>,
+    pub code_snippet: Option<String>,
+    pub remediation: Option<String>,
+    /// Reference URLs.
+    #[serde(default)]
+    pub references: Vec<String>,
+    #[serde(default)]
+    pub extra_data: ExtraData,
+    /// Engine that produced the finding, e.g. `static-intel`.
+    pub source_engine: String,
+}
+
+impl Finding {
+    /// Dataflow trace, source first.
+    pub fn trace(&self) -> &[TraceStep] {
+        &self.extra_data.trace
+    }
+
+    pub fn is_suppressed(&self) -> bool {
+        self.extra_data.suppression.is_some()
+    }
+
+    /// Path of the finding's file, without a leading `./` and with `/` separators.
+    pub fn file(&self) -> Option<String> {
+        let file = self.location.as_ref()?.file.replace('\\', "/");
+        Some(file.trim_start_matches("./").to_string())
+    }
+
+    /// Identifies the finding across runs: a hash of the check, the file, the
+    /// enclosing declaration, the title and the code without whitespace. Line
+    /// numbers are left out, so reformatting and moving code keep it.
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        let file = self.file().unwrap_or_default();
+        let anchor = self.extra_data.anchor.as_deref().unwrap_or_default();
+        let code = self.code_snippet.as_deref().unwrap_or(&self.description);
+        for part in [self.check_name.as_str(), &file, anchor, &self.title] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        for c in code.chars().filter(|c| !c.is_whitespace()) {
+            hasher.update(c.encode_utf8(&mut [0; 4]).as_bytes());
+        }
+        hasher.finalize()[..16]
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_serializes_to_gateway_columns() {
+        let location = Location {
+            file: "Vault.sol".into(),
+            line: 12,
+            column: 9,
+            end_line: 12,
+            end_column: 40,
+        };
+        let finding = Finding {
+            check_name: "reentrancy".into(),
+            severity: Severity::High,
+            confidence: Confidence::Medium,
+            title: "Reentrancy".into(),
+            description: "State written after call.".into(),
+            location: Some(location.clone()),
+            extra_data: ExtraData {
+                swc: vec!["SWC-107".into()],
+                related_locations: vec![Span {
+                    location,
+                    label: "state write".into(),
+                }],
+                ..Default::default()
+            },
+            source_engine: "static-intel".into(),
+            ..Default::default()
+        };
+        let value = serde_json::to_value(&finding).unwrap();
+        let mut columns: Vec<&str> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(|k| k.as_str())
+            .collect();
+        columns.sort_unstable();
+        assert_eq!(
+            columns,
+            [
+                "check_name",
+                "code_snippet",
+                "confidence",
+                "description",
+                "extra_data",
+                "location",
+                "references",
+                "remediation",
+                "severity",
+                "source_engine",
+                "title"
+            ]
+        );
+        assert_eq!(value["severity"], "high");
+        assert_eq!(value["extra_data"]["swc"], json!(["SWC-107"]));
+        assert_eq!(
+            value["extra_data"]["related_locations"][0]["label"],
+            "state write"
+        );
+        assert_eq!(serde_json::from_value::<Finding>(value).unwrap(), finding);
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_layout() {
+        let finding = |line, snippet: &str| Finding {
+            check_name: "reentrancy".into(),
+            title: "Reentrancy".into(),
+            location: Some(Location {
+                file: "Vault.sol".into(),
+                line,
+                column: 1,
+                end_line: line,
+                end_column: 10,
+            }),
+            code_snippet: Some(snippet.into()),
+            ..Default::default()
+        };
+        let original = finding(12, "msg.sender.call{value: amount}(\"\");");
+        let moved = finding(40, "msg.sender.call{ value: amount }(\"\");");
+        assert_eq!(original.fingerprint(), moved.fingerprint());
+        assert_eq!(original.fingerprint().len(), 32);
+        assert_ne!(
+            original.fingerprint(),
+            finding(12, "to.call{value: amount}(\"\");").fingerprint()
+        );
+    }
+
+    #[test]
+    fn test_levels() {
+        assert_eq!("critical".parse(), Ok(Severity::Critical));
+        assert!("severe".parse::<Severity>().is_err());
+        assert!(Severity::High > Severity::Medium);
+        assert_eq!(Confidence::Low.to_string(), "low");
+    }
+}
@@ -0,0 +1,257 @@
+//! Cross-engine deduplication. Engines overlap heavily (static-intel, the
+//! Slither wrapper and bytecode-intel all find the same reentrancy), so
+//! reports of one issue are collapsed into a single finding that lists the
+//! other engines under `corroborated_by`. Independent engines agreeing makes a
+//! finding more credible, so each one beyond the first raises its confidence.
+
+use crate::{Confidence, Corroboration, Finding};
+
+fn overlaps<T: PartialEq>(a: &[T], b: &[T]) -> bool {
+    a.iter().any(|x| b.contains(x))
+}
+
+fn normalize_anchor(anchor: &str) -> String {
+    anchor.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+/// Whether `a` and `b` report the same issue: the same place (enclosing
+/// declaration, or overlapping lines when either has none) and the same check
+/// or a shared SWC/CWE id.
+pub fn same_issue(a: &Finding, b: &Finding) -> bool {
+    let same_kind = a.check_name == b.check_name
+        || overlaps(&a.extra_data.swc, &b.extra_data.swc)
+        || overlaps(&a.extra_data.cwe, &b.extra_data.cwe);
+    if !same_kind {
+        return false;
+    }
+    if let (Some(x), Some(y)) = (a.file(), b.file()) {
+        if x != y {
+            return false;
+        }
+    }
+    match (&a.extra_data.anchor, &b.extra_data.anchor) {
+        (Some(x), Some(y)) => normalize_anchor(x) == normalize_anchor(y),
+        _ => match (&a.location, &b.location) {
+            (Some(x), Some(y)) => x.line <= y.end_line && y.line <= x.end_line,
+            _ => false,
+        },
+    }
+}
+
+fn raise(confidence: Confidence, levels: usize) -> Confidence {
+    match levels {
+        0 => confidence,
+        _ => raise(
+            match confidence {
+                Confidence::Low => Confidence::Medium,
+                Confidence::Medium | Confidence::High => Confidence::High,
+            },
+            levels - 1,
+        ),
+    }
+}
+
+/// Folds `other` into `primary`.
+fn absorb(primary: &mut Finding, other: Finding) {
+    primary.severity = primary.severity.max(other.severity);
+    primary.confidence = primary.confidence.max(other.confidence);
+    if primary.location.is_none() {
+        primary.location = other.location;
+    }
+    if primary.code_snippet.is_none() {
+        primary.code_snippet = other.code_snippet;
+    }
+    if primary.remediation.is_none() {
+        primary.remediation = other.remediation;
+    }
+    for reference in other.references {
+        if !primary.references.contains(&reference) {
+            primary.references.push(reference);
+        }
+    }
+    let extra = &mut primary.extra_data;
+    for id in other.extra_data.swc {
+        if !extra.swc.contains(&id) {
+            extra.swc.push(id);
+        }
+    }
+    for id in other.extra_data.cwe {
+        if !extra.cwe.contains(&id) {
+            extra.cwe.push(id);
+        }
+    }
+    for span in other.extra_data.related_locations {
+        if !extra.related_locations.contains(&span) {
+            extra.related_locations.push(span);
+        }
+    }
+    if extra.trace.is_empty() {
+        extra.trace = other.extra_data.trace;
+    }
+    if extra.anchor.is_none() {
+        extra.anchor = other.extra_data.anchor;
+    }
+    let corroborations = std::iter::once(Corroboration {
+        source_engine: other.source_engine,
+        check_name: other.check_name,
+    })
+    .chain(other.extra_data.corroborated_by);
+    for corroboration in corroborations {
+        let duplicate = corroboration.source_engine == primary.source_engine
+            && corroboration.check_name == primary.check_name;
+        if !duplicate && !extra.corroborated_by.contains(&corroboration) {
+            extra.corroborated_by.push(corroboration);
+        }
+    }
+}
+
+/// Collapses findings reporting the same issue into the first of them, which
+/// takes the highest severity and confidence of the group. Confidence is then
+/// raised one level for every other engine that reported the issue. Findings
+/// of one engine are only collapsed when their fingerprints match, as an
+/// engine reports distinct issues in one function on purpose.
+pub fn merge(findings: impl IntoIterator<Item = Finding>) -> Vec<Finding> {
+    let mut merged: Vec<Finding> = Vec::new();
+    for finding in findings {
+        let fingerprint = finding.fingerprint();
+        let group = merged.iter_mut().find(|m| {
+            if m.source_engine == finding.source_engine {
+                m.fingerprint() == fingerprint
+            } else {
+                same_issue(m, &finding)
+            }
+        });
+        match group {
+            Some(primary) => absorb(primary, finding),
+            None => merged.push(finding),
+        }
+    }
+    for finding in &mut merged {
+        let mut engines = vec![finding.source_engine.as_str()];
+        for corroboration in &finding.extra_data.corroborated_by {
+            if !engines.contains(&corroboration.source_engine.as_str()) {
+                engines.push(&corroboration.source_engine);
+            }
+        }
+        finding.confidence = raise(finding.confidence, engines.len() - 1);
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExtraData, Location, Severity};
+
+    fn finding(
+        engine: &str,
+        check_name: &str,
+        swc: &str,
+        anchor: Option<&str>,
+        line: usize,
+    ) -> Finding {
+        Finding {
+            check_name: check_name.into(),
+            severity: Severity::Medium,
+            confidence: Confidence::Low,
+            title: check_name.into(),
+            location: Some(Location {
+                file: "./src/Vault.sol".into(),
+                line,
+                column: 1,
+                end_line: line,
+                end_column: 10,
+            }),
+            extra_data: ExtraData {
+                swc: vec![swc.into()],
+                anchor: anchor.map(str::to_string),
+                ..Default::default()
+            },
+            source_engine: engine.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_merge_across_engines() {
+        let mut slither = finding("slither", "reentrancy-eth", "SWC-107", None, 34);
+        slither.severity = Severity::High;
+        slither.location.as_mut().unwrap().file = "src/Vault.sol".into();
+        let mut second_call = finding(
+            "static-intel",
+            "reentrancy",
+            "SWC-107",
+            Some("Vault.withdraw(uint256)"),
+            36,
+        );
+        second_call.code_snippet = Some("token.transfer(to, amount);".into());
+        let merged = merge([
+            finding(
+                "static-intel",
+                "reentrancy",
+                "SWC-107",
+                Some("Vault.withdraw(uint256)"),
+                34,
+            ),
+            finding(
+                "static-intel",
+                "reentrancy",
+                "SWC-107",
+                Some("Vault.withdraw(uint256)"),
+                34,
+            ),
+            second_call,
+            slither,
+            finding(
+                "bytecode-intel",
+                "reentrancy",
+                "SWC-107",
+                Some("Vault.withdraw( uint256 )"),
+                0,
+            ),
+            finding(
+                "static-intel",
+                "arithmetic",
+                "SWC-101",
+                Some("Vault.withdraw(uint256)"),
+                35,
+            ),
+        ]);
+        assert_eq!(merged.len(), 3);
+        let reentrancy = &merged[0];
+        assert_eq!(reentrancy.source_engine, "static-intel");
+        assert_eq!(reentrancy.severity, Severity::High);
+        let engines: Vec<&str> = reentrancy
+            .extra_data
+            .corroborated_by
+            .iter()
+            .map(|c| c.source_engine.as_str())
+            .collect();
+        assert_eq!(engines, ["slither", "bytecode-intel"]);
+        // Low, raised once for each of the two other engines.
+        assert_eq!(reentrancy.confidence, Confidence::High);
+        assert_eq!(merged[1].extra_data.corroborated_by, []);
+        assert_eq!(merged[2].confidence, Confidence::Low);
+    }
+
+    #[test]
+    fn test_fingerprint_survives_line_shifts() {
+        let mut moved = finding(
+            "static-intel",
+            "reentrancy",
+            "SWC-107",
+            Some("Vault.withdraw(uint256)"),
+            90,
+        );
+        let original = finding(
+            "static-intel",
+            "reentrancy",
+            "SWC-107",
+            Some("Vault.withdraw(uint256)"),
+            34,
+        );
+        assert_eq!(moved.fingerprint(), original.fingerprint());
+        moved.extra_data.anchor = Some("Vault.withdrawAll()".into());
+        assert_ne!(moved.fingerprint(), original.fingerprint());
+    }
+}
@@ -0,0 +1,327 @@
+//! SARIF 2.1.0 export, as accepted by GitHub code scanning and the VS Code
+//! SARIF viewer. Each check becomes a rule, each finding a result; dataflow
+//! traces become code flows and [`Finding::fingerprint`] the partial
+//! fingerprint that lets viewers track a result across runs.
+
+use serde_json::{json, Map, Value};
+
+use crate::{Finding, Location, Severity, SuppressionKind};
+
+pub const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+pub const VERSION: &str = "2.1.0";
+
+/// Key of [`Finding::fingerprint`] in `partialFingerprints`.
+const FINGERPRINT_KEY: &str = "rukhFingerprint/v1";
+
+/// The engine producing the log.
+#[derive(Debug, Clone, Default)]
+pub struct Tool {
+    pub name: String,
+    pub version: String,
+    pub information_uri: Option<String>,
+    /// Checks the engine ran. Checks of findings missing here are described
+    /// from the findings themselves.
+    pub rules: Vec<Rule>,
+}
+
+/// A check, the SARIF `reportingDescriptor`.
+#[derive(Debug, Clone, Default)]
+pub struct Rule {
+    pub id: String,
+    pub description: String,
+    pub severity: Severity,
+    pub help_uri: Option<String>,
+    /// SWC and CWE ids and the like.
+    pub tags: Vec<String>,
+}
+
+impl Rule {
+    fn from_finding(finding: &Finding) -> Self {
+        Self {
+            id: finding.check_name.clone(),
+            description: finding.title.clone(),
+            severity: finding.severity,
+            help_uri: finding.references.first().cloned(),
+            tags: tags(finding),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let mut tags = vec!["security".to_string()];
+        tags.extend(self.tags.iter().cloned());
+        let mut rule = json!({
+            "id": self.id,
+            "name": self.id,
+            "shortDescription": {"text": self.description},
+            "defaultConfiguration": {"level": level(self.severity)},
+            "properties": {
+                "tags": tags,
+                "security-severity": security_severity(self.severity),
+            },
+        });
+        if let Some(uri) = &self.help_uri {
+            rule["helpUri"] = json!(uri);
+        }
+        rule
+    }
+}
+
+/// SARIF `level` of a severity.
+pub fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low | Severity::Informational => "note",
+    }
+}
+
+/// CVSS-like score GitHub uses to rank security alerts.
+fn security_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "9.5",
+        Severity::High => "8.0",
+        Severity::Medium => "5.5",
+        Severity::Low => "3.0",
+        Severity::Informational => "0.0",
+    }
+}
+
+fn tags(finding: &Finding) -> Vec<String> {
+    let extra = &finding.extra_data;
+    extra.swc.iter().chain(&extra.cwe).cloned().collect()
+}
+
+fn physical_location(location: &Location, snippet: Option<&str>) -> Value {
+    let mut region = json!({
+        "startLine": location.line,
+        "startColumn": location.column,
+        "endLine": location.end_line,
+        "endColumn": location.end_column,
+    });
+    if let Some(text) = snippet {
+        region["snippet"] = json!({"text": text});
+    }
+    json!({
+        "physicalLocation": {
+            "artifactLocation": {"uri": location.file},
+            "region": region,
+        }
+    })
+}
+
+fn result(finding: &Finding, rule_index: usize) -> Value {
+    let mut result = json!({
+        "ruleId": finding.check_name,
+        "ruleIndex": rule_index,
+        "level": level(finding.severity),
+        "message": {"text": if finding.description.is_empty() {
+            &finding.title
+        } else {
+            &finding.description
+        }},
+        "partialFingerprints": {FINGERPRINT_KEY: finding.fingerprint()},
+        "properties": {
+            "title": finding.title,
+            "severity": finding.severity,
+            "confidence": finding.confidence,
+            "sourceEngine": finding.source_engine,
+        },
+    });
+    if let Some(location) = &finding.location {
+        result["locations"] = json!([physical_location(location, finding.code_snippet.as_deref())]);
+    }
+    let related = &finding.extra_data.related_locations;
+    if !related.is_empty() {
+        result["relatedLocations"] = related
+            .iter()
+            .enumerate()
+            .map(|(id, span)| {
+                let mut location = physical_location(&span.location, None);
+                location["id"] = json!(id);
+                location["message"] = json!({"text": span.label});
+                location
+            })
+            .collect();
+    }
+    let steps: Vec<Value> = finding
+        .trace()
+        .iter()
+        .filter_map(|step| {
+            let mut location = physical_location(step.location.as_ref()?, None);
+            location["message"] = json!({
+                "text": format!("{}: {}", step.function, step.description)
+            });
+            Some(json!({"location": location}))
+        })
+        .collect();
+    if !steps.is_empty() {
+        result["codeFlows"] = json!([{"threadFlows": [{"locations": steps}]}]);
+    }
+    if let Some(remediation) = &finding.remediation {
+        result["properties"]["remediation"] = json!(remediation);
+    }
+    if let Some(suppression) = &finding.extra_data.suppression {
+        let mut entry = json!({"kind": match suppression.kind {
+            SuppressionKind::InSource => "inSource",
+            SuppressionKind::Baseline => "external",
+        }});
+        if let Some(justification) = &suppression.justification {
+            entry["justification"] = json!(justification);
+        }
+        result["suppressions"] = json!([entry]);
+    }
+    result
+}
+
+/// A SARIF log with a single run of `tool` reporting `findings`.
+pub fn to_sarif(tool: &Tool, findings: &[Finding]) -> Value {
+    let mut rules = tool.rules.clone();
+    let results: Vec<Value> = findings
+        .iter()
+        .map(|finding| {
+            let index = match rules.iter().position(|r| r.id == finding.check_name) {
+                Some(index) => index,
+                None => {
+                    rules.push(Rule::from_finding(finding));
+                    rules.len() - 1
+                }
+            };
+            result(finding, index)
+        })
+        .collect();
+    let mut driver = Map::new();
+    driver.insert("name".into(), json!(tool.name));
+    driver.insert("version".into(), json!(tool.version));
+    if let Some(uri) = &tool.information_uri {
+        driver.insert("informationUri".into(), json!(uri));
+    }
+    driver.insert(
+        "rules".into(),
+        rules.iter().map(Rule::to_json).collect::<Value>(),
+    );
+    json!({
+        "$schema": SCHEMA,
+        "version": VERSION,
+        "runs": [{
+            "tool": {"driver": driver},
+            "results": results,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExtraData, Span, TraceStep};
+
+    fn location(line: usize) -> Location {
+        Location {
+            file: "src/Vault.sol".into(),
+            line,
+            column: 9,
+            end_line: line,
+            end_column: 30,
+        }
+    }
+
+    #[test]
+    fn test_to_sarif() {
+        let tool = Tool {
+            name: "static-intel".into(),
+            version: "0.1.0".into(),
+            rules: vec![Rule {
+                id: "reentrancy".into(),
+                description: "State written after an external call".into(),
+                severity: Severity::High,
+                help_uri: Some("https://swcregistry.io/docs/SWC-107".into()),
+                tags: vec!["SWC-107".into()],
+            }],
+            ..Default::default()
+        };
+        let findings = [
+            Finding {
+                check_name: "user-input".into(),
+                severity: Severity::Medium,
+                title: "User-controlled call target".into(),
+                description: "`Vault.poke` calls a caller-supplied address.".into(),
+                location: Some(location(20)),
+                extra_data: ExtraData {
+                    trace: vec![
+                        TraceStep {
+                            function: "Vault.poke".into(),
+                            description: "parameter `target`".into(),
+                            location: Some(location(18)),
+                        },
+                        TraceStep {
+                            function: "Vault.poke".into(),
+                            description: "call target".into(),
+                            location: Some(location(20)),
+                        },
+                    ],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Finding {
+                check_name: "reentrancy".into(),
+                severity: Severity::High,
+                title: "Reentrancy".into(),
+                location: Some(location(12)),
+                code_snippet: Some("msg.sender.call{value: amount}(\"\");".into()),
+                extra_data: ExtraData {
+                    related_locations: vec![Span {
+                        location: location(13),
+                        label: "state write".into(),
+                    }],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        ];
+        let log = to_sarif(&tool, &findings);
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        assert_eq!(
+            rules.iter().map(|r| &r["id"]).collect::<Vec<_>>(),
+            ["reentrancy", "user-input"]
+        );
+        assert_eq!(
+            rules[0]["properties"]["tags"],
+            json!(["security", "SWC-107"])
+        );
+
+        let taint = &run["results"][0];
+        assert_eq!(
+            (&taint["ruleIndex"], &taint["level"]),
+            (&json!(1), &json!("warning"))
+        );
+        let steps = &taint["codeFlows"][0]["threadFlows"][0]["locations"];
+        assert_eq!(
+            steps[0]["location"]["message"]["text"],
+            "Vault.poke: parameter `target`"
+        );
+        assert_eq!(
+            steps[1]["location"]["physicalLocation"]["region"]["startLine"],
+            20
+        );
+
+        let reentrancy = &run["results"][1];
+        assert_eq!(reentrancy["ruleIndex"], 0);
+        assert_eq!(reentrancy["level"], "error");
+        assert_eq!(reentrancy["message"]["text"], "Reentrancy");
+        assert_eq!(
+            reentrancy["partialFingerprints"][FINGERPRINT_KEY],
+            findings[1].fingerprint()
+        );
+        let region = &reentrancy["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(
+            region["snippet"]["text"],
+            "msg.sender.call{value: amount}(\"\");"
+        );
+        assert_eq!(
+            reentrancy["relatedLocations"][0]["message"]["text"],
+            "state write"
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! Owned, typed model of a parsed Solidity source unit.
+//!
+//! Declarations are flattened into plain structs so analyses never have to walk
+//! solang's `ContractPart` enums. Function bodies, types and initializers keep the
+//! solang parse tree (`pt`) since later passes lower them further.
+
+use solang_parser::pt::{self, Loc};
+
+use crate::source::FileId;
+
+#[derive(Debug, Clone)]
+pub struct SourceUnit {
+    pub file: FileId,
+    pub path: String,
+    pub pragmas: Vec<Pragma>,
+    pub imports: Vec<Import>,
+    pub contracts: Vec<Contract>,
+    /// Free (file-level) functions.
+    pub functions: Vec<Function>,
+    pub structs: Vec<StructDef>,
+    pub enums: Vec<EnumDef>,
+    /// User-defined value types (`type Price is uint128`).
+    pub types: Vec<TypeDef>,
+    pub events: Vec<EventDef>,
+    pub errors: Vec<ErrorDef>,
+    pub using: Vec<UsingDirective>,
+}
+
+impl SourceUnit {
+    pub fn contract(&self, name: &str) -> Option<&Contract> {
+        self.contracts.iter().find(|c| c.name == name)
+    }
+
+    /// Value of `pragma solidity ...;`, if present.
+    pub fn solidity_pragma(&self) -> Option<&str> {
+        self.pragmas
+            .iter()
+            .find(|p| p.name == "solidity")
+            .map(|p| p.value.as_str())
+    }
+
+    /// Lowest compiler version the `pragma solidity` range admits, as
+    /// `(major, minor, patch)`; `None` without a pragma.
+    pub fn min_solidity_version(&self) -> Option<(u32, u32, u32)> {
+        self.solidity_pragma()?
+            .split("||")
+            .map(|range| {
+                let mut lower = (0, 0, 0);
+                let mut comparators = range.split_whitespace();
+                while let Some(comparator) = comparators.next() {
+                    if comparator == "-" {
+                        // Upper end of a hyphen range.
+                        comparators.next();
+                        continue;
+                    }
+                    if comparator.starts_with('<') {
+                        continue;
+                    }
+                    let version = comparator.trim_start_matches(['^', '~', '>', '=']);
+                    let mut parts = version.split('.').map(|p| p.parse::<u32>().unwrap_or(0));
+                    let version = (
+                        parts.next().unwrap_or(0),
+                        parts.next().unwrap_or(0),
+                        parts.next().unwrap_or(0),
+                    );
+                    lower = lower.max(version);
+                }
+                lower
+            })
+            .min()
+    }
+
+    /// Whether arithmetic in this file reverts on overflow (Solidity 0.8 and
+    /// later); assumed so without a pragma.
+    pub fn has_checked_arithmetic(&self) -> bool {
+        self.min_solidity_version()
+            .is_none_or(|version| version >= (0, 8, 0))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Pragma {
+    pub name: String,
+    pub value: String,
+    pub loc: Loc,
+}
+
+#[derive(Debug, Clone)]
+pub struct Import {
+    /// Path exactly as written in the import directive.
+    pub path: String,
+    /// `import * as X from "..."` / `import "..." as X`.
+    pub alias: Option<String>,
+    /// `import {A, B as C} from "..."`: (symbol, alias).
+    pub symbols: Vec<(String, Option<String>)>,
+    /// File the import resolved to, filled in when the unit is loaded into a project.
+    pub resolved: Option<FileId>,
+    pub loc: Loc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContractKind {
+    Contract,
+    Abstract,
+    Interface,
+    Library,
+}
+
+#[derive(Debug, Clone)]
+pub struct Contract {
+    pub name: String,
+    pub kind: ContractKind,
+    pub bases: Vec<InheritanceSpecifier>,
+    pub state_variables: Vec<StateVariable>,
+    pub functions: Vec<Function>,
+    pub modifiers: Vec<Function>,
+    pub events: Vec<EventDef>,
+    pub errors: Vec<ErrorDef>,
+    pub structs: Vec<StructDef>,
+    pub enums: Vec<EnumDef>,
+    pub types: Vec<TypeDef>,
+    pub using: Vec<UsingDirective>,
+    pub loc: Loc,
+}
+
+impl Contract {
+    pub fn function(&self, name: &str) -> Option<&Function> {
+        self.functions.iter().find(|f| f.name == name)
+    }
+
+    pub fn modifier(&self, name: &str) -> Option<&Function> {
+        self.modifiers.iter().find(|m| m.name == name)
+    }
+
+    pub fn state_variable(&self, name: &str) -> Option<&StateVariable> {
+        self.state_variables.iter().find(|v| v.name == name)
+    }
+
+    pub fn constructor(&self) -> Option<&Function> {
+        self.functions
+            .iter()
+            .find(|f| f.kind == FunctionKind::Constructor)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InheritanceSpecifier {
+    /// Base name as written; may be qualified (`Lib.Base`).
+    pub name: String,
+    pub args: Option<Vec<pt::Expression>>,
+    pub loc: Loc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Visibility {
+    External,
+    Public,
+    Internal,
+    Private,
+}
+
+impl Visibility {
+    /// Callable from outside the contract (directly by a transaction).
+    pub fn is_entry_point(self) -> bool {
+        matches!(self, Visibility::External | Visibility::Public)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StateMutability {
+    NonPayable,
+    Payable,
+    View,
+    Pure,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VariableMutability {
+    Mutable,
+    Constant,
+    Immutable,
+}
+
+#[derive(Debug, Clone)]
+pub struct StateVariable {
+    pub name: String,
+    pub ty: pt::Expression,
+    pub type_name: String,
+    pub visibility: Visibility,
+    pub mutability: VariableMutability,
+    pub initializer: Option<pt::Expression>,
+    pub loc: Loc,
+}
+
+impl StateVariable {
+    /// Whether the variable occupies a storage slot.
+    pub fn is_stored(&self) -> bool {
+        self.mutability == VariableMutability::Mutable
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FunctionKind {
+    Constructor,
+    Function,
+    Fallback,
+    Receive,
+    Modifier,
+}
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    /// Empty for constructors, fallback and receive functions.
+    pub name: String,
+    pub kind: FunctionKind,
+    pub visibility: Visibility,
+    pub mutability: StateMutability,
+    pub is_virtual: bool,
+    /// `Some` if marked `override`, with the explicitly listed bases.
+    pub overrides: Option<Vec<String>>,
+    pub params: Vec<Parameter>,
+    pub returns: Vec<Parameter>,
+    /// Modifier invocations and, for constructors, base constructor calls.
+    pub modifiers: Vec<ModifierInvocation>,
+    pub body: Option<pt::Statement>,
+    pub loc: Loc,
+}
+
+impl Function {
+    /// Name used in reports: `constructor`, `fallback` and `receive` for unnamed kinds.
+    pub fn display_name(&self) -> &str {
+        match self.kind {
+            FunctionKind::Constructor => "constructor",
+            FunctionKind::Fallback => "fallback",
+            FunctionKind::Receive => "receive",
+            FunctionKind::Function | FunctionKind::Modifier => &self.name,
+        }
+    }
+
+    /// Canonical signature, e.g. `transfer(address,uint256)`.
+    pub fn signature(&self) -> String {
+        let params: Vec<&str> = self
+            .params
+            .iter()
+            .map(|p| p.type_name.trim_end_matches(" payable"))
+            .collect();
+        format!("{}({})", self.display_name(), params.join(","))
+    }
+
+    pub fn is_entry_point(&self) -> bool {
+        match self.kind {
+            FunctionKind::Fallback | FunctionKind::Receive => true,
+            FunctionKind::Function => self.visibility.is_entry_point(),
+            FunctionKind::Constructor | FunctionKind::Modifier => false,
+        }
+    }
+
+    pub fn is_view(&self) -> bool {
+        matches!(
+            self.mutability,
+            StateMutability::View | StateMutability::Pure
+        )
+    }
+
+    pub fn has_modifier(&self, name: &str) -> bool {
+        self.modifiers.iter().any(|m| m.name == name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    pub name: Option<String>,
+    pub ty: pt::Expression,
+    pub type_name: String,
+    pub storage: Option<DataLocation>,
+    pub loc: Loc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DataLocation {
+    Memory,
+    Storage,
+    Calldata,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModifierInvocation {
+    pub name: String,
+    pub args: Vec<pt::Expression>,
+    pub loc: Loc,
+}
+
+#[derive(Debug, Clone)]
+pub struct EventDef {
+    pub name: String,
+    pub params: Vec<EventParam>,
+    pub anonymous: bool,
+    pub loc: Loc,
+}
+
+#[derive(Debug, Clone)]
+pub struct EventParam {
+    pub name: Option<String>,
+    pub type_name: String,
+    pub indexed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ErrorDef {
+    pub name: String,
+    pub params: Vec<Parameter>,
+    pub loc: Loc,
+}
+
+#[derive(Debug, Clone)]
+pub struct StructDef {
+    pub name: String,
+    pub fields: Vec<Parameter>,
+    pub loc: Loc,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnumDef {
+    pub name: String,
+    pub values: Vec<String>,
+    pub loc: Loc,
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeDef {
+    pub name: String,
+    /// The underlying elementary type.
+    pub ty: pt::Expression,
+    pub loc: Loc,
+}
+
+/// `using L for T;` / `using {f, g} for T global;`
+#[derive(Debug, Clone)]
+pub struct UsingDirective {
+    pub library: Option<String>,
+    pub functions: Vec<String>,
+    /// `None` for `using L for *`.
+    pub target: Option<String>,
+    pub global: bool,
+    pub loc: Loc,
+}
@@ -0,0 +1,9 @@
+/*!
+ * RUKH Static Intelligence command line tool
+ * Author: Volodymyr Stetsenko (Zero2Auditor)
+ */
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    std::process::exit(static_intel::cli::main(&args));
+}
@@ -0,0 +1,410 @@
+//! Whole-project call graph.
+//!
+//! Built from the lowered IR of every function, analysed in the context of the
+//! contract that defines it so `super` and virtual calls resolve the way the
+//! contract itself sees them. Calls whose target is not known statically — low-level
+//! calls, high-level calls through an unknown type, function pointers — end at
+//! `external` nodes named after the call.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ast::ContractKind;
+use crate::ir::{EnvVar, IrFunction, Op};
+use crate::source::Location;
+use crate::symbols::{ContractId, FunctionId, SymbolTable};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallNodeKind {
+    Function,
+    Modifier,
+    /// Call target outside the analysed sources.
+    External,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallEdgeKind {
+    /// Call of a function of the same contract, a base or a free function.
+    Internal,
+    /// Invocation of a modifier by a function.
+    Modifier,
+    /// High-level call through a contract or interface type.
+    External,
+    /// `this.f()`: an external call back into the calling contract.
+    SelfCall,
+    /// Library call, directly or through `using L for T`.
+    Library,
+    /// Low-level call or call to a target the sources do not define.
+    Unresolved,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallNode {
+    /// `Contract.function(types)` for analysed functions, `Type.name` for external targets.
+    pub id: String,
+    pub contract: Option<String>,
+    pub name: String,
+    pub kind: CallNodeKind,
+    pub location: Option<Location>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: CallEdgeKind,
+    /// First call site.
+    pub location: Option<Location>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallGraph {
+    pub nodes: Vec<CallNode>,
+    pub edges: Vec<CallEdge>,
+}
+
+impl CallGraph {
+    pub fn build(symbols: &SymbolTable) -> Self {
+        let mut builder = Builder {
+            symbols,
+            nodes: BTreeMap::new(),
+            edges: BTreeMap::new(),
+        };
+        for info in &symbols.contracts {
+            if info.def.kind == ContractKind::Interface {
+                continue;
+            }
+            for &func in info.functions.iter().chain(&info.modifiers) {
+                builder.function_node(func);
+            }
+            // Functions are analysed where they are declared; inherited ones were
+            // visited with their own contract.
+            for &func in info
+                .functions
+                .iter()
+                .filter(|f| f.contract() == Some(info.id))
+            {
+                builder.calls(Some(info.id), func);
+            }
+        }
+        for func in symbols.all_functions() {
+            if let FunctionId::Free { .. } = func {
+                builder.function_node(func);
+                builder.calls(None, func);
+            }
+        }
+        CallGraph {
+            nodes: builder.nodes.into_values().collect(),
+            edges: builder.edges.into_values().collect(),
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("call graph serializes")
+    }
+
+    /// Graphviz rendering, one cluster per contract.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "digraph \"callgraph\" {{");
+        let _ = writeln!(out, "  node [shape=box, fontname=\"monospace\"];");
+        let index: BTreeMap<&str, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.id.as_str(), i))
+            .collect();
+        let mut clusters: BTreeMap<Option<&str>, Vec<usize>> = BTreeMap::new();
+        for (i, node) in self.nodes.iter().enumerate() {
+            let cluster = match node.kind {
+                CallNodeKind::External => None,
+                _ => node.contract.as_deref(),
+            };
+            clusters.entry(cluster).or_default().push(i);
+        }
+        for (cluster, members) in &clusters {
+            let indent = match cluster {
+                Some(contract) => {
+                    let _ = writeln!(out, "  subgraph \"cluster_{}\" {{", escape(contract));
+                    let _ = writeln!(out, "    label=\"{}\";", escape(contract));
+                    "    "
+                }
+                None => "  ",
+            };
+            for &i in members {
+                let node = &self.nodes[i];
+                let style = match node.kind {
+                    CallNodeKind::Function => "",
+                    CallNodeKind::Modifier => ", shape=hexagon",
+                    CallNodeKind::External => ", style=dashed",
+                };
+                let _ = writeln!(out, "{indent}n{i} [label=\"{}\"{style}];", escape(&node.id));
+            }
+            if cluster.is_some() {
+                let _ = writeln!(out, "  }}");
+            }
+        }
+        for edge in &self.edges {
+            let style = match edge.kind {
+                CallEdgeKind::Internal => "",
+                CallEdgeKind::Modifier => ", style=dotted",
+                CallEdgeKind::External | CallEdgeKind::SelfCall => ", color=red",
+                CallEdgeKind::Library => ", color=blue",
+                CallEdgeKind::Unresolved => ", color=red, style=dashed",
+            };
+            let kind = format!("{:?}", edge.kind).to_lowercase();
+            let _ = writeln!(
+                out,
+                "  n{} -> n{} [label=\"{kind}\"{style}];",
+                index[edge.from.as_str()],
+                index[edge.to.as_str()]
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Direct callees of the node `id`.
+    pub fn callees<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a CallEdge> {
+        self.edges.iter().filter(move |edge| edge.from == id)
+    }
+}
+
+struct Builder<'s, 'p> {
+    symbols: &'s SymbolTable<'p>,
+    nodes: BTreeMap<String, CallNode>,
+    edges: BTreeMap<(String, String, CallEdgeKind), CallEdge>,
+}
+
+impl Builder<'_, '_> {
+    fn function_node(&mut self, func: FunctionId) -> String {
+        let def = self.symbols.function(func);
+        let contract = func
+            .contract()
+            .map(|c| self.symbols.contract(c).name().to_string());
+        let id = match &contract {
+            Some(contract) => format!("{contract}.{}", def.signature()),
+            None => def.signature(),
+        };
+        let kind = match func {
+            FunctionId::Modifier { .. } => CallNodeKind::Modifier,
+            _ => CallNodeKind::Function,
+        };
+        self.nodes.entry(id.clone()).or_insert_with(|| CallNode {
+            id: id.clone(),
+            contract,
+            name: def.display_name().to_string(),
+            kind,
+            location: self.symbols.project.location(&def.loc),
+        });
+        id
+    }
+
+    fn external_node(&mut self, owner: &str, name: &str) -> String {
+        let id = format!("{owner}.{name}");
+        self.nodes.entry(id.clone()).or_insert_with(|| CallNode {
+            id: id.clone(),
+            contract: Some(owner.to_string()),
+            name: name.to_string(),
+            kind: CallNodeKind::External,
+            location: None,
+        });
+        id
+    }
+
+    fn edge(&mut self, from: &str, to: String, kind: CallEdgeKind, location: Option<Location>) {
+        self.edges
+            .entry((from.to_string(), to.clone(), kind))
+            .or_insert_with(|| CallEdge {
+                from: from.to_string(),
+                to,
+                kind,
+                location,
+            });
+    }
+
+    fn calls(&mut self, context: Option<ContractId>, func: FunctionId) {
+        let symbols = self.symbols;
+        let ir = IrFunction::build(symbols, context, func);
+        let caller = self.function_node(func);
+        if let Some(context) = context {
+            for modifier in symbols.modifiers_of(context, func) {
+                let to = self.function_node(modifier);
+                let location = symbols.project.location(&symbols.function(func).loc);
+                self.edge(&caller, to, CallEdgeKind::Modifier, location);
+            }
+        }
+        for (block, inst) in ir.instructions() {
+            // Instructions of inlined modifiers belong to the modifier.
+            let origin = ir.cfg.node(block).origin;
+            let from = self.function_node(origin);
+            let location = symbols.project.location(&inst.loc);
+            let (to, kind) = match &inst.op {
+                Op::InternalCall {
+                    callee: Some(callee),
+                    ..
+                } => (self.function_node(*callee), CallEdgeKind::Internal),
+                Op::InternalCall { name, .. } => {
+                    (self.external_node("?", name), CallEdgeKind::Unresolved)
+                }
+                Op::LibraryCall {
+                    callee: Some(callee),
+                    ..
+                } => (self.function_node(*callee), CallEdgeKind::Library),
+                Op::LibraryCall { library, name, .. } => {
+                    (self.external_node(library, name), CallEdgeKind::Library)
+                }
+                Op::ExternalCall {
+                    target,
+                    name,
+                    args,
+                    interface,
+                    ..
+                } => {
+                    let self_call = target
+                        .value()
+                        .is_some_and(|v| matches!(ir.def(v).op, Op::Env(EnvVar::This)));
+                    let kind = match self_call {
+                        true => CallEdgeKind::SelfCall,
+                        false => CallEdgeKind::External,
+                    };
+                    let file = symbols.file_of(func);
+                    let callee = interface
+                        .as_deref()
+                        .and_then(|ty| symbols.contract_by_name(ty, Some(file)))
+                        .and_then(|info| {
+                            info.functions.iter().copied().find(|&f| {
+                                let def = symbols.function(f);
+                                def.name == *name && def.params.len() == args.len()
+                            })
+                        });
+                    match (callee, interface) {
+                        (Some(callee), _) => (self.function_node(callee), kind),
+                        (None, Some(ty)) => (self.external_node(ty, name), kind),
+                        (None, None) => (self.external_node("?", name), CallEdgeKind::Unresolved),
+                    }
+                }
+                Op::LowLevelCall { kind, .. } => (
+                    self.external_node("address", kind.name()),
+                    CallEdgeKind::Unresolved,
+                ),
+                _ => continue,
+            };
+            self.edge(&from, to, kind, location);
+        }
+    }
+}
+
+/// Callees reachable from `id`, including itself.
+pub fn reachable<'a>(graph: &'a CallGraph, id: &'a str) -> BTreeSet<&'a str> {
+    let mut seen = BTreeSet::from([id]);
+    let mut stack = vec![id];
+    while let Some(node) = stack.pop() {
+        for edge in graph.callees(node) {
+            if seen.insert(edge.to.as_str()) {
+                stack.push(edge.to.as_str());
+            }
+        }
+    }
+    seen
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Project, ProjectBundle};
+
+    const SOURCE: &str = "
+        interface IERC20 { function transfer(address to, uint256 amount) external returns (bool); }
+        library SafeMath { function add(uint a, uint b) internal pure returns (uint) { return a + b; } }
+        abstract contract Base {
+            modifier onlyOwner() { _check(); _; }
+            function _check() internal view virtual {}
+        }
+        contract Vault is Base {
+            using SafeMath for uint;
+            uint total;
+            IERC20 token;
+            function deposit(uint amount) external onlyOwner {
+                total = total.add(amount);
+                this.sync();
+                token.transfer(msg.sender, amount);
+            }
+            function sync() external {
+                (bool ok, ) = msg.sender.call(\"\");
+                require(ok);
+                IOracle(msg.sender).poke();
+            }
+        }";
+
+    fn edges(graph: &CallGraph) -> Vec<(&str, &str, CallEdgeKind)> {
+        graph
+            .edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str(), e.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_call_graph_edges() {
+        let project = Project::load(&ProjectBundle::single("Test.sol", SOURCE));
+        let symbols = SymbolTable::build(&project);
+        let graph = CallGraph::build(&symbols);
+        let edges = edges(&graph);
+        for expected in [
+            (
+                "Vault.deposit(uint256)",
+                "Base.onlyOwner()",
+                CallEdgeKind::Modifier,
+            ),
+            ("Base.onlyOwner()", "Base._check()", CallEdgeKind::Internal),
+            (
+                "Vault.deposit(uint256)",
+                "SafeMath.add(uint256,uint256)",
+                CallEdgeKind::Library,
+            ),
+            (
+                "Vault.deposit(uint256)",
+                "Vault.sync()",
+                CallEdgeKind::SelfCall,
+            ),
+            (
+                "Vault.deposit(uint256)",
+                "IERC20.transfer(address,uint256)",
+                CallEdgeKind::External,
+            ),
+            ("Vault.sync()", "address.call", CallEdgeKind::Unresolved),
+            ("Vault.sync()", "?.poke", CallEdgeKind::Unresolved),
+        ] {
+            assert!(
+                edges.contains(&expected),
+                "missing {expected:?} in {edges:?}"
+            );
+        }
+        let reach = reachable(&graph, "Vault.deposit(uint256)");
+        assert!(reach.contains("address.call"));
+    }
+
+    #[test]
+    fn test_call_graph_exports() {
+        let project = Project::load(&ProjectBundle::single("Test.sol", SOURCE));
+        let symbols = SymbolTable::build(&project);
+        let graph = CallGraph::build(&symbols);
+        let json = graph.to_json();
+        let decoded: CallGraph = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(decoded, graph);
+        assert!(json["edges"][0]["kind"].is_string());
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph \"callgraph\" {"));
+        assert!(dot.contains("subgraph \"cluster_Vault\""));
+        assert!(dot.contains("[label=\"selfcall\", color=red]"));
+    }
+}
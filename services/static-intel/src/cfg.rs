@@ -0,0 +1,696 @@
+//! Per-function control flow graphs.
+//!
+//! A CFG is built for a function as executed in a given (most-derived) contract:
+//! the modifiers applied in that contract are inlined around the body, with each
+//! `_` placeholder replaced by the next modifier or the function body itself.
+//! Nodes are single statements or branch conditions; `require`, `assert` and
+//! `revert` get explicit edges into a dedicated revert node so path-sensitive
+//! detectors can tell aborting paths from completing ones.
+
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+use solang_parser::pt::{self, CodeLocation, Expression, Loc, Statement};
+
+use crate::ast::{Function, ModifierInvocation};
+use crate::symbols::{ContractId, FunctionId, SymbolTable};
+
+pub type NodeId = usize;
+
+#[derive(Debug, Clone)]
+pub enum NodeKind<'p> {
+    Entry,
+    /// Normal completion of the function.
+    Exit,
+    /// Aborted execution (failed `require`/`assert`, `revert`).
+    Revert,
+    /// Start of an inlined modifier: binds the invocation arguments to its parameters.
+    ModifierEntry {
+        modifier: FunctionId,
+        invocation: &'p ModifierInvocation,
+    },
+    /// `_` in a modifier CFG built on its own.
+    Placeholder,
+    Expression(&'p Expression),
+    VariableDefinition(&'p pt::VariableDeclaration, Option<&'p Expression>),
+    /// Branch on a condition; successors are reached through `True`/`False` edges.
+    Condition(&'p Expression),
+    Return(Option<&'p Expression>),
+    Emit(&'p Expression),
+    /// `revert(...)` / `revert Error(...)` statements.
+    RevertStatement(&'p Statement),
+    /// External call of a `try` statement; successors via `Success`/`Catch` edges.
+    Try(&'p Expression),
+    Assembly(&'p pt::YulBlock),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    Normal,
+    True,
+    False,
+    Success,
+    Catch,
+    Revert,
+}
+
+#[derive(Debug, Clone)]
+pub struct Node<'p> {
+    pub id: NodeId,
+    pub kind: NodeKind<'p>,
+    /// Function or modifier whose body the node belongs to.
+    pub origin: FunctionId,
+    /// Inside an `unchecked { }` block.
+    pub unchecked: bool,
+    pub loc: Loc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub from: NodeId,
+    pub to: NodeId,
+    pub kind: EdgeKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cfg<'p> {
+    pub function: FunctionId,
+    pub context: Option<ContractId>,
+    pub nodes: Vec<Node<'p>>,
+    pub edges: Vec<Edge>,
+    pub entry: NodeId,
+    pub exit: NodeId,
+    pub revert: NodeId,
+}
+
+type Pending = Vec<(NodeId, EdgeKind)>;
+
+impl<'p> Cfg<'p> {
+    /// Builds the CFG of `func` as executed in `context`, inlining the modifiers
+    /// that apply there. Without a context, modifiers are not inlined.
+    pub fn build(symbols: &SymbolTable<'p>, context: Option<ContractId>, func: FunctionId) -> Self {
+        let mut builder = Builder {
+            symbols,
+            cfg: Cfg {
+                function: func,
+                context,
+                nodes: Vec::new(),
+                edges: Vec::new(),
+                entry: 0,
+                exit: 0,
+                revert: 0,
+            },
+            levels: Vec::new(),
+            loops: Vec::new(),
+            returns: vec![Vec::new()],
+            origin: func,
+            unchecked: false,
+        };
+        let function = symbols.function(func);
+        let entry = builder.node(NodeKind::Entry, function.loc);
+        builder.cfg.exit = builder.node(NodeKind::Exit, function.loc);
+        builder.cfg.revert = builder.node(NodeKind::Revert, function.loc);
+        builder.cfg.entry = entry;
+
+        if let Some(context) = context {
+            for invocation in &function.modifiers {
+                if let Some(modifier) = symbols.resolve_modifier(context, &invocation.name) {
+                    builder.levels.push(Level::Modifier(modifier, invocation));
+                }
+            }
+        }
+        builder.levels.push(Level::Body(func, function));
+        builder.levels.reverse();
+
+        let exits = builder.build_level(vec![(entry, EdgeKind::Normal)]);
+        let returns = builder.returns.pop().unwrap_or_default();
+        let exit = builder.cfg.exit;
+        builder.connect(exits.into_iter().chain(returns), exit);
+        builder.cfg
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node<'p> {
+        &self.nodes[id]
+    }
+
+    pub fn successors(&self, id: NodeId) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |e| e.from == id)
+    }
+
+    pub fn predecessors(&self, id: NodeId) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |e| e.to == id)
+    }
+
+    /// Nodes reachable from `from` (excluding `from` unless it lies on a cycle).
+    pub fn reachable_from(&self, from: NodeId) -> Vec<bool> {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut queue: VecDeque<NodeId> = self.successors(from).map(|e| e.to).collect();
+        while let Some(id) = queue.pop_front() {
+            if !std::mem::replace(&mut seen[id], true) {
+                queue.extend(self.successors(id).map(|e| e.to));
+            }
+        }
+        seen
+    }
+
+    /// Whether some execution path runs `to` after `from`.
+    pub fn can_reach(&self, from: NodeId, to: NodeId) -> bool {
+        self.reachable_from(from)[to]
+    }
+
+    /// Nodes in reverse post-order from the entry; unreachable nodes are omitted.
+    pub fn reverse_postorder(&self) -> Vec<NodeId> {
+        let mut order = Vec::new();
+        let mut seen = vec![false; self.nodes.len()];
+        let mut stack = vec![(self.entry, false)];
+        while let Some((id, done)) = stack.pop() {
+            if done {
+                order.push(id);
+                continue;
+            }
+            if std::mem::replace(&mut seen[id], true) {
+                continue;
+            }
+            stack.push((id, true));
+            let succs: Vec<NodeId> = self.successors(id).map(|e| e.to).collect();
+            for succ in succs.into_iter().rev() {
+                if !seen[succ] {
+                    stack.push((succ, false));
+                }
+            }
+        }
+        order.reverse();
+        order
+    }
+
+    /// Graphviz rendering for debugging.
+    pub fn to_dot(&self, symbols: &SymbolTable) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "digraph \"{}\" {{",
+            escape(&symbols.qualified_name(self.function))
+        );
+        let _ = writeln!(out, "  node [shape=box, fontname=\"monospace\"];");
+        for node in &self.nodes {
+            let shape = match node.kind {
+                NodeKind::Entry | NodeKind::Exit | NodeKind::Revert => ", shape=ellipse",
+                NodeKind::Condition(_) | NodeKind::Try(_) => ", shape=diamond",
+                _ => "",
+            };
+            let style = if node.unchecked { ", style=dashed" } else { "" };
+            let _ = writeln!(
+                out,
+                "  n{} [label=\"{}\"{shape}{style}];",
+                node.id,
+                escape(&node.label(symbols))
+            );
+        }
+        for edge in &self.edges {
+            let label = match edge.kind {
+                EdgeKind::Normal => String::new(),
+                kind => format!(" [label=\"{}\"]", format!("{kind:?}").to_lowercase()),
+            };
+            let _ = writeln!(out, "  n{} -> n{}{label};", edge.from, edge.to);
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+impl Node<'_> {
+    pub fn label(&self, symbols: &SymbolTable) -> String {
+        match &self.kind {
+            NodeKind::Entry => "ENTRY".into(),
+            NodeKind::Exit => "EXIT".into(),
+            NodeKind::Revert => "REVERT".into(),
+            NodeKind::ModifierEntry { modifier, .. } => {
+                format!("modifier {}", symbols.qualified_name(*modifier))
+            }
+            NodeKind::Placeholder => "_".into(),
+            NodeKind::Expression(expr) => expr.to_string(),
+            NodeKind::VariableDefinition(decl, init) => match init {
+                Some(init) => format!("{decl} = {init}"),
+                None => decl.to_string(),
+            },
+            NodeKind::Condition(expr) => format!("if {expr}"),
+            NodeKind::Return(Some(expr)) => format!("return {expr}"),
+            NodeKind::Return(None) => "return".into(),
+            NodeKind::Emit(expr) => format!("emit {expr}"),
+            NodeKind::RevertStatement(stmt) => stmt.to_string(),
+            NodeKind::Try(expr) => format!("try {expr}"),
+            NodeKind::Assembly(_) => "assembly { ... }".into(),
+        }
+    }
+}
+
+enum Level<'p> {
+    Modifier(FunctionId, &'p ModifierInvocation),
+    Body(FunctionId, &'p Function),
+}
+
+struct LoopTargets {
+    breaks: Pending,
+    continues: Pending,
+}
+
+struct Builder<'s, 'p> {
+    symbols: &'s SymbolTable<'p>,
+    cfg: Cfg<'p>,
+    /// Remaining nesting levels, innermost (the function body) first.
+    levels: Vec<Level<'p>>,
+    loops: Vec<LoopTargets>,
+    /// `return` edges per inlining level; they resume after the enclosing `_`.
+    returns: Vec<Pending>,
+    origin: FunctionId,
+    unchecked: bool,
+}
+
+impl<'p> Builder<'_, 'p> {
+    fn node(&mut self, kind: NodeKind<'p>, loc: Loc) -> NodeId {
+        let id = self.cfg.nodes.len();
+        self.cfg.nodes.push(Node {
+            id,
+            kind,
+            origin: self.origin,
+            unchecked: self.unchecked,
+            loc,
+        });
+        id
+    }
+
+    fn edge(&mut self, from: NodeId, to: NodeId, kind: EdgeKind) {
+        self.cfg.edges.push(Edge { from, to, kind });
+    }
+
+    fn connect(&mut self, pending: impl IntoIterator<Item = (NodeId, EdgeKind)>, to: NodeId) {
+        for (from, kind) in pending {
+            self.edge(from, to, kind);
+        }
+    }
+
+    /// Appends a straight-line node after `pending` and returns its exit.
+    fn append(&mut self, pending: Pending, kind: NodeKind<'p>, loc: Loc) -> (NodeId, Pending) {
+        let id = self.node(kind, loc);
+        self.connect(pending, id);
+        (id, vec![(id, EdgeKind::Normal)])
+    }
+
+    /// Builds the outermost remaining level (a modifier or the body).
+    fn build_level(&mut self, pending: Pending) -> Pending {
+        let Some(level) = self.levels.pop() else {
+            // A standalone modifier CFG keeps its placeholder as a node.
+            return self.append(pending, NodeKind::Placeholder, Loc::Implicit).1;
+        };
+        let saved_origin = self.origin;
+        let (origin, body, pending) = match level {
+            Level::Modifier(modifier, invocation) => {
+                self.origin = modifier;
+                let (_, pending) = self.append(
+                    pending,
+                    NodeKind::ModifierEntry {
+                        modifier,
+                        invocation,
+                    },
+                    invocation.loc,
+                );
+                (
+                    modifier,
+                    self.symbols.function(modifier).body.as_ref(),
+                    pending,
+                )
+            }
+            Level::Body(func, function) => (func, function.body.as_ref(), pending),
+        };
+        self.origin = origin;
+        let exits = match body {
+            Some(body) => self.statement(body, pending),
+            None => pending,
+        };
+        self.origin = saved_origin;
+        self.levels.push(level);
+        exits
+    }
+
+    fn statement(&mut self, stmt: &'p Statement, pending: Pending) -> Pending {
+        match stmt {
+            Statement::Block {
+                statements,
+                unchecked,
+                ..
+            } => {
+                let saved = self.unchecked;
+                self.unchecked |= *unchecked;
+                let mut pending = pending;
+                for stmt in statements {
+                    pending = self.statement(stmt, pending);
+                }
+                self.unchecked = saved;
+                pending
+            }
+            Statement::Expression(loc, expr) => self.expression_statement(*loc, expr, pending),
+            Statement::VariableDefinition(loc, decl, init) => {
+                self.append(
+                    pending,
+                    NodeKind::VariableDefinition(decl, init.as_ref()),
+                    *loc,
+                )
+                .1
+            }
+            Statement::Emit(loc, expr) => self.append(pending, NodeKind::Emit(expr), *loc).1,
+            Statement::If(loc, cond, then, otherwise) => {
+                let (cond, _) = self.append(pending, NodeKind::Condition(cond), *loc);
+                let mut exits = self.statement(then, vec![(cond, EdgeKind::True)]);
+                match otherwise {
+                    Some(otherwise) => {
+                        exits.extend(self.statement(otherwise, vec![(cond, EdgeKind::False)]))
+                    }
+                    None => exits.push((cond, EdgeKind::False)),
+                }
+                exits
+            }
+            Statement::While(loc, cond, body) => {
+                let (head, _) = self.append(pending, NodeKind::Condition(cond), *loc);
+                let (body_exits, targets) = self.loop_body(body, vec![(head, EdgeKind::True)]);
+                self.connect(body_exits.into_iter().chain(targets.continues), head);
+                let mut exits = targets.breaks;
+                exits.push((head, EdgeKind::False));
+                exits
+            }
+            Statement::DoWhile(loc, body, cond) => {
+                // Body first; the condition node loops back to the body's first node.
+                let first = self.cfg.nodes.len();
+                let (body_exits, targets) = self.loop_body(body, pending);
+                let (head, _) = self.append(body_exits, NodeKind::Condition(cond), *loc);
+                self.connect(targets.continues, head);
+                if first < head {
+                    self.edge(head, first, EdgeKind::True);
+                }
+                let mut exits = targets.breaks;
+                exits.push((head, EdgeKind::False));
+                exits
+            }
+            Statement::For(loc, init, cond, next, body) => {
+                let pending = match init {
+                    Some(init) => self.statement(init, pending),
+                    None => pending,
+                };
+                let (head, body_entry) = match cond {
+                    Some(cond) => {
+                        let (head, _) = self.append(pending, NodeKind::Condition(cond), *loc);
+                        (Some(head), vec![(head, EdgeKind::True)])
+                    }
+                    None => (None, pending),
+                };
+                let first = self.cfg.nodes.len();
+                let (body_exits, targets) = match body {
+                    Some(body) => self.loop_body(body, body_entry),
+                    None => (
+                        body_entry,
+                        LoopTargets {
+                            breaks: Vec::new(),
+                            continues: Vec::new(),
+                        },
+                    ),
+                };
+                let mut latch: Pending = body_exits.into_iter().chain(targets.continues).collect();
+                if let Some(next) = next {
+                    latch = self.append(latch, NodeKind::Expression(next), next.loc()).1;
+                }
+                match head {
+                    Some(head) => self.connect(latch, head),
+                    None if first < self.cfg.nodes.len() => self.connect(latch, first),
+                    None => {}
+                }
+                let mut exits = targets.breaks;
+                if let Some(head) = head {
+                    exits.push((head, EdgeKind::False));
+                }
+                exits
+            }
+            Statement::Continue(_) => {
+                if let Some(targets) = self.loops.last_mut() {
+                    targets.continues.extend(pending);
+                }
+                Vec::new()
+            }
+            Statement::Break(_) => {
+                if let Some(targets) = self.loops.last_mut() {
+                    targets.breaks.extend(pending);
+                }
+                Vec::new()
+            }
+            Statement::Return(loc, expr) => {
+                let (id, _) = self.append(pending, NodeKind::Return(expr.as_ref()), *loc);
+                if let Some(returns) = self.returns.last_mut() {
+                    returns.push((id, EdgeKind::Normal));
+                }
+                Vec::new()
+            }
+            Statement::Revert(loc, ..) | Statement::RevertNamedArgs(loc, ..) => {
+                let (id, _) = self.append(pending, NodeKind::RevertStatement(stmt), *loc);
+                let revert = self.cfg.revert;
+                self.edge(id, revert, EdgeKind::Revert);
+                Vec::new()
+            }
+            Statement::Try(loc, expr, returns, catches) => {
+                let (id, _) = self.append(pending, NodeKind::Try(expr), *loc);
+                let mut exits = match returns {
+                    Some((_, body)) => self.statement(body, vec![(id, EdgeKind::Success)]),
+                    None => vec![(id, EdgeKind::Success)],
+                };
+                for clause in catches {
+                    let body = match clause {
+                        pt::CatchClause::Simple(_, _, body)
+                        | pt::CatchClause::Named(_, _, _, body) => body,
+                    };
+                    exits.extend(self.statement(body, vec![(id, EdgeKind::Catch)]));
+                }
+                if catches.is_empty() {
+                    let revert = self.cfg.revert;
+                    self.edge(id, revert, EdgeKind::Revert);
+                }
+                exits
+            }
+            Statement::Assembly { loc, block, .. } => {
+                self.append(pending, NodeKind::Assembly(block), *loc).1
+            }
+            Statement::Args(..) | Statement::Error(_) => pending,
+        }
+    }
+
+    fn expression_statement(
+        &mut self,
+        loc: Loc,
+        expr: &'p Expression,
+        pending: Pending,
+    ) -> Pending {
+        if is_placeholder(expr) {
+            self.returns.push(Vec::new());
+            let mut exits = self.build_level(pending);
+            exits.extend(self.returns.pop().unwrap_or_default());
+            return exits;
+        }
+        let (id, exits) = self.append(pending, NodeKind::Expression(expr), loc);
+        let revert = self.cfg.revert;
+        match builtin_call(expr) {
+            Some("require" | "assert") => {
+                self.edge(id, revert, EdgeKind::Revert);
+                exits
+            }
+            Some("revert") => {
+                self.edge(id, revert, EdgeKind::Revert);
+                Vec::new()
+            }
+            _ => exits,
+        }
+    }
+
+    fn loop_body(&mut self, body: &'p Statement, entry: Pending) -> (Pending, LoopTargets) {
+        self.loops.push(LoopTargets {
+            breaks: Vec::new(),
+            continues: Vec::new(),
+        });
+        let exits = self.statement(body, entry);
+        let targets = self.loops.pop().expect("loop stack underflow");
+        (exits, targets)
+    }
+}
+
+fn is_placeholder(expr: &Expression) -> bool {
+    matches!(expr, Expression::Variable(id) if id.name == "_")
+}
+
+/// Name of a called global builtin such as `require`, if `expr` is such a call.
+pub fn builtin_call(expr: &Expression) -> Option<&str> {
+    match expr {
+        Expression::FunctionCall(_, callee, _) => match callee.as_ref() {
+            Expression::Variable(id) => Some(id.name.as_str()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Project, ProjectBundle};
+
+    fn project(src: &str) -> Project {
+        Project::load(&ProjectBundle::single("Test.sol", src))
+    }
+
+    fn build<'p>(symbols: &SymbolTable<'p>, contract: &str, func: &str) -> Cfg<'p> {
+        let info = symbols.contract_by_name(contract, None).unwrap();
+        let id = info
+            .functions
+            .iter()
+            .copied()
+            .find(|&f| symbols.function(f).name == func)
+            .unwrap();
+        Cfg::build(symbols, Some(info.id), id)
+    }
+
+    fn find(cfg: &Cfg, symbols: &SymbolTable, label: &str) -> NodeId {
+        cfg.nodes
+            .iter()
+            .find(|n| n.label(symbols) == label)
+            .unwrap_or_else(|| panic!("no node `{label}`"))
+            .id
+    }
+
+    #[test]
+    fn test_branches_and_require() {
+        let project = project(
+            "contract A { uint x;
+                function f(uint a) public returns (uint) {
+                    require(a > 0, \"zero\");
+                    if (a > 10) { x = a; return 1; } else { x = 0; }
+                    x += 1;
+                    return x;
+                } }",
+        );
+        let symbols = SymbolTable::build(&project);
+        let cfg = build(&symbols, "A", "f");
+
+        let require = find(&cfg, &symbols, "require(a > 0, \"zero\")");
+        assert!(cfg
+            .successors(require)
+            .any(|e| e.to == cfg.revert && e.kind == EdgeKind::Revert));
+        let cond = find(&cfg, &symbols, "if a > 10");
+        let kinds: Vec<EdgeKind> = cfg.successors(cond).map(|e| e.kind).collect();
+        assert_eq!(kinds, [EdgeKind::True, EdgeKind::False]);
+
+        let early = find(&cfg, &symbols, "return 1");
+        let increment = find(&cfg, &symbols, "x += 1");
+        assert!(!cfg.can_reach(early, increment));
+        assert!(cfg.can_reach(early, cfg.exit));
+        assert!(cfg.can_reach(find(&cfg, &symbols, "x = 0"), increment));
+    }
+
+    #[test]
+    fn test_loops_break_continue() {
+        let project = project(
+            "contract A { function f(uint n) public returns (uint s) {
+                for (uint i = 0; i < n; i++) {
+                    if (i == 3) continue;
+                    if (i == 7) break;
+                    s += i;
+                }
+                while (s > 100) { s -= 1; }
+                s = 0;
+            } }",
+        );
+        let symbols = SymbolTable::build(&project);
+        let cfg = build(&symbols, "A", "f");
+        let head = find(&cfg, &symbols, "if i < n");
+        let next = find(&cfg, &symbols, "i++");
+        let cont = find(&cfg, &symbols, "if i == 3");
+        let brk = find(&cfg, &symbols, "if i == 7");
+        let after = find(&cfg, &symbols, "if s > 100");
+
+        assert!(cfg
+            .successors(cont)
+            .any(|e| e.to == next && e.kind == EdgeKind::True));
+        assert!(cfg
+            .successors(brk)
+            .any(|e| e.to == after && e.kind == EdgeKind::True));
+        assert!(cfg.successors(next).any(|e| e.to == head));
+        assert!(cfg.can_reach(head, head));
+        let body = find(&cfg, &symbols, "s -= 1");
+        assert!(cfg.successors(body).any(|e| e.to == after));
+    }
+
+    #[test]
+    fn test_modifiers_inlined_in_order() {
+        let project = project(
+            "abstract contract Guard {
+                uint status;
+                modifier nonReentrant() { status = 2; _; status = 1; }
+             }
+             contract A is Guard {
+                address owner;
+                modifier onlyOwner() { require(msg.sender == owner); _; }
+                function f(uint a) public onlyOwner nonReentrant returns (uint) {
+                    if (a == 0) return 0;
+                    unchecked { a++; }
+                    return a;
+                }
+             }",
+        );
+        let symbols = SymbolTable::build(&project);
+        let cfg = build(&symbols, "A", "f");
+
+        let check = find(&cfg, &symbols, "require(msg.sender == owner)");
+        let lock = find(&cfg, &symbols, "status = 2");
+        let unlock = find(&cfg, &symbols, "status = 1");
+        let early = find(&cfg, &symbols, "return 0");
+        let increment = find(&cfg, &symbols, "a++");
+        assert!(cfg.can_reach(check, lock) && cfg.can_reach(lock, increment));
+        // `return` in the body resumes after the placeholder of the enclosing modifier.
+        assert!(cfg.successors(early).any(|e| e.to == unlock));
+        assert!(cfg.successors(unlock).any(|e| e.to == cfg.exit));
+        assert!(cfg.node(increment).unchecked && !cfg.node(early).unchecked);
+
+        let guard = symbols.contract_by_name("Guard", None).unwrap().id;
+        assert!(
+            matches!(cfg.node(lock).origin, FunctionId::Modifier { contract, .. } if contract == guard)
+        );
+        let dot = cfg.to_dot(&symbols);
+        assert!(dot.starts_with("digraph \"A.f\""));
+        assert!(dot.contains("modifier Guard.nonReentrant"));
+        assert!(dot.contains("[label=\"revert\"]"));
+    }
+
+    #[test]
+    fn test_try_catch_and_revert() {
+        let project = project(
+            "interface I { function g() external returns (uint); }
+             contract A { I i; uint x;
+                function f() public {
+                    try i.g() returns (uint v) { x = v; } catch { revert(\"failed\"); }
+                    x = 1;
+                } }",
+        );
+        let symbols = SymbolTable::build(&project);
+        let cfg = build(&symbols, "A", "f");
+        let try_node = find(&cfg, &symbols, "try i.g()");
+        let kinds: Vec<EdgeKind> = cfg.successors(try_node).map(|e| e.kind).collect();
+        assert_eq!(kinds, [EdgeKind::Success, EdgeKind::Catch]);
+        let revert = cfg
+            .nodes
+            .iter()
+            .find(|n| matches!(n.kind, NodeKind::RevertStatement(_)))
+            .unwrap()
+            .id;
+        let after = find(&cfg, &symbols, "x = 1");
+        assert!(!cfg.can_reach(revert, after));
+        assert!(cfg.can_reach(find(&cfg, &symbols, "x = v"), after));
+        assert_eq!(cfg.reverse_postorder()[0], cfg.entry);
+    }
+}
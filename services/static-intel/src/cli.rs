@@ -0,0 +1,733 @@
+//! `rukh-static`: the detectors of the service as a standalone command for
+//! pre-commit hooks and CI, with no NATS, Postgres or Redis involved.
+//!
+//! ```text
+//! rukh-static analyze <path> [--format json|sarif|markdown] [--fail-on <severity>]
+//! rukh-static analyze <path> --previous <path> [--contract <name>]
+//! rukh-static layout <path> [--contract <name>] [--format json|markdown]
+//! rukh-static callgraph <path> [--format dot|json]
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context as _};
+
+use crate::callgraph::CallGraph;
+use crate::detectors::{self, Detector, Registry};
+use crate::finding::{sarif, Baseline, Finding, Severity, SuppressionKind};
+use crate::layout::{self, StorageLayout};
+use crate::parser::DiagnosticLevel;
+use crate::project::{self, Project};
+use crate::rules;
+use crate::symbols::SymbolTable;
+use crate::task::{process_task_with, PreviousVersion, StaticTask, TaskResult};
+
+pub const USAGE: &str = "\
+Usage: rukh-static analyze <path> [options]
+       rukh-static layout <path> [options]
+       rukh-static callgraph <path> [options]
+       rukh-static detectors
+
+Analyzes a Solidity file or a Foundry/Hardhat project directory, or prints
+the storage layout or call graph of its contracts. Dependencies, tests and
+scripts (lib, node_modules, test, script) resolve imports but are not
+reported on; with a foundry.toml, only its `src` directory is.
+
+Options:
+  -f, --format <format>     json, sarif or markdown [default: markdown];
+                            dot or json for callgraph [default: dot]
+  -o, --output <file>       Write the report to <file> instead of stdout
+      --fail-on <severity>  Exit with status 1 if a finding is at least this
+                            severe: critical, high, medium, low, informational
+  -d, --detectors <ids>     Comma-separated detectors to run [default: all]
+  -x, --exclude <ids>       Comma-separated detectors to skip
+      --rules <dir>         Load project rules from <dir> (repeatable)
+      --baseline <file>     Report findings accepted in <file> as suppressed
+      --write-baseline <file>
+                            Accept all current findings into <file>
+      --remap <remapping>   Extra import remapping, prefix=target (repeatable)
+      --previous <path>     Check that storage stays compatible with the
+                            deployed version at <path> (analyze only)
+  -c, --contract <name>     Contract to check or print the layout of
+                            [default: analyze --previous: all upgradeable
+                            contracts; layout: all deployable contracts]
+  -h, --help                Print this help
+  -V, --version             Print the version
+";
+
+/// Directories never holding sources worth analysing.
+const SKIPPED_DIRS: &[&str] = &["out", "cache", "artifacts", "broadcast", "target"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Sarif,
+    Markdown,
+    /// Graphviz, for `callgraph`.
+    Dot,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub path: PathBuf,
+    pub format: Format,
+    pub output: Option<PathBuf>,
+    pub fail_on: Option<Severity>,
+    pub detectors: Vec<String>,
+    pub exclude: Vec<String>,
+    pub rules: Vec<PathBuf>,
+    pub remappings: Vec<String>,
+    pub baseline: Option<PathBuf>,
+    pub write_baseline: Option<PathBuf>,
+    pub previous: Option<PathBuf>,
+    pub contract: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Analyze(Options),
+    Layout(Options),
+    CallGraph(Options),
+    Detectors,
+    Help,
+    Version,
+}
+
+fn ids(list: &str) -> impl Iterator<Item = String> + '_ {
+    list.split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+}
+
+/// Parses the arguments after the program name.
+pub fn parse_args(args: &[String]) -> anyhow::Result<Command> {
+    let mut args = args.iter();
+    let command: fn(Options) -> Command = match args.next().map(String::as_str) {
+        Some("analyze") => Command::Analyze,
+        Some("layout") => Command::Layout,
+        Some("callgraph") => Command::CallGraph,
+        Some("detectors") => return Ok(Command::Detectors),
+        Some("-h" | "--help") | None => return Ok(Command::Help),
+        Some("-V" | "--version") => return Ok(Command::Version),
+        Some(other) => bail!("unknown command `{other}`"),
+    };
+
+    let mut path = None;
+    let mut options = Options {
+        path: PathBuf::new(),
+        format: Format::Markdown,
+        output: None,
+        fail_on: None,
+        detectors: Vec::new(),
+        exclude: Vec::new(),
+        rules: Vec::new(),
+        remappings: Vec::new(),
+        baseline: None,
+        write_baseline: None,
+        previous: None,
+        contract: None,
+    };
+    while let Some(arg) = args.next() {
+        // `--flag=value` and `--flag value` are both accepted.
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| args.next().cloned())
+                .with_context(|| format!("`{flag}` needs a value"))
+        };
+        match flag {
+            "-f" | "--format" => {
+                options.format = match value()?.as_str() {
+                    "json" => Format::Json,
+                    "sarif" => Format::Sarif,
+                    "markdown" | "md" => Format::Markdown,
+                    "dot" => Format::Dot,
+                    other => bail!("unknown format `{other}`"),
+                }
+            }
+            "-o" | "--output" => options.output = Some(value()?.into()),
+            "--fail-on" => {
+                let level = value()?;
+                options.fail_on = match level.as_str() {
+                    "none" => None,
+                    level => Some(level.parse().map_err(|_| {
+                        anyhow::anyhow!("unknown severity `{level}` for `--fail-on`")
+                    })?),
+                }
+            }
+            "-d" | "--detectors" => options.detectors.extend(ids(&value()?)),
+            "-x" | "--exclude" => options.exclude.extend(ids(&value()?)),
+            "--rules" => options.rules.push(value()?.into()),
+            "--remap" => options.remappings.push(value()?),
+            "--baseline" => options.baseline = Some(value()?.into()),
+            "--write-baseline" => options.write_baseline = Some(value()?.into()),
+            "--previous" => options.previous = Some(value()?.into()),
+            "-c" | "--contract" => options.contract = Some(value()?),
+            "-h" | "--help" => return Ok(Command::Help),
+            flag if flag.starts_with('-') => bail!("unknown option `{flag}`"),
+            _ if path.is_none() => path = Some(PathBuf::from(arg)),
+            _ => bail!("unexpected argument `{arg}`"),
+        }
+    }
+    options.path = path.context("missing <path> to analyze")?;
+    match command(options) {
+        Command::Layout(options) if options.previous.is_some() => {
+            bail!("`--previous` applies to `analyze`, which checks the upgrade")
+        }
+        command => Ok(command),
+    }
+}
+
+/// `.sol` files and `remappings.txt` under `root`, keyed by path relative to it.
+fn collect_sources(root: &Path) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut sources = BTreeMap::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries =
+            std::fs::read_dir(&dir).with_context(|| format!("failed to read {}", dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if path.is_dir() {
+                if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_ref()) {
+                    pending.push(path);
+                }
+                continue;
+            }
+            if !(name.ends_with(".sol") || name == "remappings.txt") {
+                continue;
+            }
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            sources.insert(relative.to_string_lossy().replace('\\', "/"), content);
+        }
+    }
+    Ok(sources)
+}
+
+/// Sources of a file or project directory.
+fn read_sources(path: &Path) -> anyhow::Result<BTreeMap<String, String>> {
+    let sources = if path.is_dir() {
+        collect_sources(path)?
+    } else {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        BTreeMap::from([(name.into_owned(), content)])
+    };
+    if !sources.keys().any(|p| p.ends_with(".sol")) {
+        bail!("no Solidity sources found in {}", path.display());
+    }
+    Ok(sources)
+}
+
+/// The `src` directory of the Foundry project at `root`, if it is one.
+fn foundry_src(root: &Path) -> Option<String> {
+    let config = std::fs::read_to_string(root.join("foundry.toml")).ok()?;
+    let mut section = "";
+    let mut src = None;
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            section = line;
+        } else if let Some((key, value)) = line.split_once('=') {
+            if key.trim() == "src" && section == "[profile.default]" {
+                let value = value.split('#').next().unwrap_or_default().trim();
+                src = Some(
+                    value
+                        .trim_matches(['"', '\''])
+                        .trim_matches('/')
+                        .to_string(),
+                );
+            }
+        }
+    }
+    Some(src.unwrap_or_else(|| "src".into()))
+}
+
+/// Whether findings in `file`, relative to the analyzed directory, are
+/// reported: those in the Foundry `src` directory, or without a
+/// `foundry.toml`, those outside dependencies, tests and scripts.
+fn is_project_source(src: Option<&str>, file: &str) -> bool {
+    match src {
+        Some(src) => file
+            .strip_prefix(src)
+            .is_some_and(|rest| rest.starts_with('/')),
+        None => !project::is_dependency(file),
+    }
+}
+
+/// Drops the findings of a project directory at `path` outside its own sources.
+pub fn retain_project_findings(path: &Path, result: &mut TaskResult) {
+    if !path.is_dir() {
+        return;
+    }
+    let src = foundry_src(path);
+    result.vulnerabilities.retain(|f| {
+        f.location
+            .as_ref()
+            .is_none_or(|l| is_project_source(src.as_deref(), &l.file))
+    });
+}
+
+/// The task the service would receive for `options`.
+pub fn task(options: &Options) -> anyhow::Result<StaticTask> {
+    let sources = read_sources(&options.path)?;
+    let previous = match &options.previous {
+        Some(path) => Some(PreviousVersion {
+            sources: Some(read_sources(path)?),
+            remappings: options.remappings.clone(),
+            ..Default::default()
+        }),
+        None => None,
+    };
+    let mut rules = Vec::new();
+    for dir in &options.rules {
+        rules.extend(rules::load_dir(dir)?);
+    }
+    let baseline = match &options.baseline {
+        Some(path) => {
+            let json = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            Some(
+                serde_json::from_str(&json)
+                    .with_context(|| format!("invalid baseline {}", path.display()))?,
+            )
+        }
+        None => None,
+    };
+    Ok(StaticTask {
+        job_id: "local".into(),
+        phase: None,
+        contract_id: None,
+        contract_name: options.contract.clone(),
+        source_code: None,
+        compiler_version: None,
+        sources: Some(sources),
+        archive: None,
+        remappings: options.remappings.clone(),
+        detectors: options.detectors.clone(),
+        disabled_detectors: options.exclude.clone(),
+        rules,
+        baseline,
+        previous,
+    })
+}
+
+/// Renders `result` in `format`; `task` supplies the rules of the SARIF run.
+pub fn render(
+    format: Format,
+    registry: &Registry,
+    task: &StaticTask,
+    result: &TaskResult,
+) -> anyhow::Result<String> {
+    Ok(match format {
+        Format::Json => serde_json::to_string_pretty(result)? + "\n",
+        Format::Sarif => {
+            let tool = detectors::sarif_tool(
+                registry
+                    .select(&task.detectors, &task.disabled_detectors)
+                    .into_iter()
+                    .chain(task.rules.iter().map(|r| r as &dyn Detector)),
+            );
+            serde_json::to_string_pretty(&sarif::to_sarif(&tool, &result.vulnerabilities))? + "\n"
+        }
+        Format::Markdown => markdown(result),
+        Format::Dot => bail!("`analyze` prints json, sarif or markdown"),
+    })
+}
+
+pub fn markdown(result: &TaskResult) -> String {
+    let (suppressed, mut findings): (Vec<&Finding>, Vec<&Finding>) = result
+        .vulnerabilities
+        .iter()
+        .partition(|f| f.is_suppressed());
+    findings.sort_by_key(|f| std::cmp::Reverse(f.severity));
+
+    let mut out = String::from("# RUKH static analysis\n\n");
+    if findings.is_empty() {
+        out.push_str("No findings.\n");
+        standards_section(&mut out, result);
+        suppressed_section(&mut out, &suppressed);
+        return out;
+    }
+    let counts: Vec<String> = Severity::ALL
+        .iter()
+        .filter_map(|&severity| {
+            let count = findings.iter().filter(|f| f.severity == severity).count();
+            (count > 0).then(|| format!("{count} {severity}"))
+        })
+        .collect();
+    let _ = writeln!(
+        out,
+        "**{} findings:** {}\n",
+        findings.len(),
+        counts.join(", ")
+    );
+    out.push_str("| # | Severity | Check | Title | Location |\n");
+    out.push_str("|---|----------|-------|-------|----------|\n");
+    for (index, finding) in findings.iter().enumerate() {
+        let location = finding
+            .location
+            .as_ref()
+            .map(|l| format!("`{}:{}`", l.file, l.line))
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "| {} | {} | `{}` | {} | {} |",
+            index + 1,
+            finding.severity,
+            finding.check_name,
+            finding.title.replace('|', "\\|"),
+            location
+        );
+    }
+    for (index, finding) in findings.iter().enumerate() {
+        let _ = write!(
+            out,
+            "\n## {}. {}\n\n**Severity:** {} · **Confidence:** {} · **Check:** `{}`",
+            index + 1,
+            finding.title,
+            finding.severity,
+            finding.confidence,
+            finding.check_name
+        );
+        if let Some(location) = &finding.location {
+            let _ = write!(out, " · `{}:{}`", location.file, location.line);
+        }
+        let _ = writeln!(out, "\n\n{}", finding.description);
+        if let Some(snippet) = &finding.code_snippet {
+            let _ = writeln!(out, "\n```solidity\n{snippet}\n```");
+        }
+        if let Some(remediation) = &finding.remediation {
+            let _ = writeln!(out, "\n**Remediation:** {remediation}");
+        }
+    }
+    standards_section(&mut out, result);
+    suppressed_section(&mut out, &suppressed);
+    out
+}
+
+fn standards_section(out: &mut String, result: &TaskResult) {
+    if result.conformance.is_empty() {
+        return;
+    }
+    out.push_str("\n## Token standards\n\n");
+    out.push_str("| Contract | Standard | Claimed by | Status |\n");
+    out.push_str("|----------|----------|------------|--------|\n");
+    for report in &result.conformance {
+        let status = match report.issues.len() {
+            0 => "conforms".to_string(),
+            1 => "1 issue".to_string(),
+            n => format!("{n} issues"),
+        };
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} |",
+            report.contract, report.standard, report.claimed_by, status
+        );
+    }
+}
+
+fn suppressed_section(out: &mut String, suppressed: &[&Finding]) {
+    if suppressed.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "\n## Suppressed ({})\n", suppressed.len());
+    for finding in suppressed {
+        let Some(suppression) = &finding.extra_data.suppression else {
+            continue;
+        };
+        let _ = write!(out, "- `{}` {}", finding.check_name, finding.title);
+        if let Some(location) = &finding.location {
+            let _ = write!(out, " (`{}:{}`)", location.file, location.line);
+        }
+        let _ = match (suppression.kind, &suppression.justification) {
+            (SuppressionKind::InSource, Some(justification)) => {
+                writeln!(out, ": in source, {justification}")
+            }
+            (SuppressionKind::InSource, None) => writeln!(out, ": in source"),
+            (SuppressionKind::Baseline, _) => writeln!(out, ": baseline"),
+        };
+    }
+}
+
+/// Storage layouts of the deployable contracts of `task`, or of its
+/// `contract_name` only.
+pub fn layouts(task: &StaticTask) -> anyhow::Result<Vec<StorageLayout>> {
+    let project = Project::load(&task.bundle()?);
+    let symbols = SymbolTable::build(&project);
+    let layouts: Vec<StorageLayout> = layout::layouts(&symbols)
+        .into_iter()
+        .filter(|l| {
+            task.contract_name
+                .as_ref()
+                .is_none_or(|name| &l.contract == name)
+        })
+        .collect();
+    if let (Some(name), true) = (&task.contract_name, layouts.is_empty()) {
+        bail!("no deployable contract `{name}`");
+    }
+    Ok(layouts)
+}
+
+/// `layouts` as a table per contract, in the columns of `forge inspect`.
+pub fn layout_markdown(layouts: &[StorageLayout]) -> String {
+    let mut out = String::new();
+    for layout in layouts {
+        let _ = writeln!(out, "## {} (`{}`)\n", layout.contract, layout.file);
+        if layout.entries.is_empty() {
+            out.push_str("No storage.\n\n");
+            continue;
+        }
+        out.push_str("| Name | Type | Slot | Offset | Bytes | Contract |\n");
+        out.push_str("|------|------|------|--------|-------|----------|\n");
+        for entry in &layout.entries {
+            let _ = writeln!(
+                out,
+                "| {} | `{}` | {} | {} | {} | {} |",
+                entry.label, entry.type_name, entry.slot, entry.offset, entry.bytes, entry.contract
+            );
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Whether any finding that is not suppressed is at least as severe as `threshold`.
+pub fn fails(result: &TaskResult, threshold: Option<Severity>) -> bool {
+    threshold.is_some_and(|t| {
+        result
+            .vulnerabilities
+            .iter()
+            .any(|f| f.severity >= t && !f.is_suppressed())
+    })
+}
+
+/// Runs the command line; returns the exit status: 0 on success, 1 when
+/// `--fail-on` trips, 2 on usage and input errors.
+pub fn main(args: &[String]) -> i32 {
+    match run(args) {
+        Ok(status) => status,
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            2
+        }
+    }
+}
+
+/// Writes `report` to `--output`, or to stdout.
+fn write_report(options: &Options, report: String) -> anyhow::Result<()> {
+    match &options.output {
+        Some(path) => std::fs::write(path, report)
+            .with_context(|| format!("failed to write {}", path.display()))?,
+        None => print!("{report}"),
+    }
+    Ok(())
+}
+
+fn run(args: &[String]) -> anyhow::Result<i32> {
+    let options = match parse_args(args).map_err(|err| anyhow::anyhow!("{err}\n\n{USAGE}"))? {
+        Command::Analyze(options) => options,
+        Command::Layout(options) => {
+            let layouts = layouts(&task(&options)?)?;
+            let report = match options.format {
+                Format::Json => serde_json::to_string_pretty(&layouts)? + "\n",
+                Format::Markdown => layout_markdown(&layouts),
+                Format::Sarif | Format::Dot => bail!("`layout` prints json or markdown"),
+            };
+            write_report(&options, report)?;
+            return Ok(0);
+        }
+        Command::CallGraph(options) => {
+            let project = Project::load(&task(&options)?.bundle()?);
+            let graph = CallGraph::build(&SymbolTable::build(&project));
+            let report = match options.format {
+                Format::Json => serde_json::to_string_pretty(&graph)? + "\n",
+                // Markdown is only the default of the other commands.
+                Format::Dot | Format::Markdown => graph.to_dot(),
+                Format::Sarif => bail!("`callgraph` prints dot or json"),
+            };
+            write_report(&options, report)?;
+            return Ok(0);
+        }
+        Command::Detectors => {
+            for detector in Registry::builtin().detectors() {
+                println!(
+                    "{:<22} {:<8} {}",
+                    detector.id(),
+                    detector.severity().as_str(),
+                    detector.description()
+                );
+            }
+            return Ok(0);
+        }
+        Command::Help => {
+            print!("{USAGE}");
+            return Ok(0);
+        }
+        Command::Version => {
+            println!("rukh-static {}", env!("CARGO_PKG_VERSION"));
+            return Ok(0);
+        }
+    };
+
+    let registry = Registry::builtin();
+    let task = task(&options)?;
+    let mut result = process_task_with(&registry, &task);
+    retain_project_findings(&options.path, &mut result);
+    for diagnostic in &result.diagnostics {
+        let level = match diagnostic.level {
+            DiagnosticLevel::Error => "error",
+            DiagnosticLevel::Warning => "warning",
+            DiagnosticLevel::Info => "info",
+        };
+        match &diagnostic.location {
+            Some(l) => eprintln!("{level}: {}:{}: {}", l.file, l.line, diagnostic.message),
+            None => eprintln!("{level}: {}", diagnostic.message),
+        }
+    }
+    if let Some(path) = &options.write_baseline {
+        let baseline = Baseline::from_findings(&result.vulnerabilities);
+        std::fs::write(path, serde_json::to_string_pretty(&baseline)? + "\n")
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        eprintln!(
+            "accepted {} findings into {}",
+            baseline.findings.len(),
+            path.display()
+        );
+    }
+    let report = render(options.format, &registry, &task, &result)?;
+    write_report(&options, report)?;
+    Ok(if fails(&result, options.fail_on) {
+        1
+    } else {
+        0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let Command::Analyze(options) = parse_args(&args(
+            "analyze contracts --format=sarif --fail-on high -x arithmetic,user-input --remap @oz/=lib/oz/",
+        ))
+        .unwrap() else {
+            panic!("expected analyze");
+        };
+        assert_eq!(options.path, PathBuf::from("contracts"));
+        assert_eq!(options.format, Format::Sarif);
+        assert_eq!(options.fail_on, Some(Severity::High));
+        assert_eq!(options.exclude, ["arithmetic", "user-input"]);
+        assert_eq!(options.remappings, ["@oz/=lib/oz/"]);
+
+        let Command::Layout(options) =
+            parse_args(&args("layout . -c Vault --format json")).unwrap()
+        else {
+            panic!("expected layout");
+        };
+        assert_eq!(options.contract.as_deref(), Some("Vault"));
+        assert!(parse_args(&args("layout . --previous v1")).is_err());
+        let Command::Analyze(options) =
+            parse_args(&args("analyze . -c Vault --previous v1")).unwrap()
+        else {
+            panic!("expected analyze");
+        };
+        assert_eq!(options.previous, Some(PathBuf::from("v1")));
+
+        let Command::CallGraph(options) = parse_args(&args("callgraph src -f dot")).unwrap() else {
+            panic!("expected callgraph");
+        };
+        assert_eq!(options.format, Format::Dot);
+
+        assert_eq!(parse_args(&[]).unwrap(), Command::Help);
+        assert!(parse_args(&args("analyze")).is_err());
+        assert!(parse_args(&args("analyze . --fail-on severe")).is_err());
+        assert!(parse_args(&args("analyze . --format")).is_err());
+    }
+
+    #[test]
+    fn test_analyze_project_directory() {
+        let root = std::env::temp_dir().join(format!("rukh-static-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("out")).unwrap();
+        std::fs::write(
+            root.join("src/Vault.sol"),
+            include_str!("../../../integrations/foundry/src/ReentrancyVault.sol"),
+        )
+        .unwrap();
+        std::fs::write(root.join("out/Broken.sol"), "contract {").unwrap();
+        std::fs::create_dir_all(root.join("lib/dep/src")).unwrap();
+        std::fs::write(
+            root.join("lib/dep/src/Kill.sol"),
+            "contract Kill {\n    function kill() external {\n        selfdestruct(payable(msg.sender));\n    }\n}\n",
+        )
+        .unwrap();
+
+        let Command::Analyze(options) = parse_args(&[
+            "analyze".into(),
+            root.display().to_string(),
+            "--fail-on".into(),
+            "high".into(),
+        ])
+        .unwrap() else {
+            panic!("expected analyze");
+        };
+        let registry = Registry::builtin();
+        let task = task(&options).unwrap();
+        // Dependencies are loaded, but only the project's own findings reported.
+        assert_eq!(
+            task.sources.as_ref().unwrap().keys().collect::<Vec<_>>(),
+            ["lib/dep/src/Kill.sol", "src/Vault.sol"]
+        );
+        let mut result = process_task_with(&registry, &task);
+        assert!(result.vulnerabilities.iter().any(|f| f
+            .location
+            .as_ref()
+            .unwrap()
+            .file
+            .starts_with("lib/")));
+        retain_project_findings(&root, &mut result);
+        assert!(result
+            .vulnerabilities
+            .iter()
+            .all(|f| f.location.as_ref().unwrap().file == "src/Vault.sol"));
+        std::fs::write(
+            root.join("foundry.toml"),
+            "[profile.default]\nsrc = \"contracts\" # sources\nlibs = [\"lib\"]\n",
+        )
+        .unwrap();
+        assert_eq!(foundry_src(&root).as_deref(), Some("contracts"));
+        assert!(!is_project_source(Some("contracts"), "src/Vault.sol"));
+        std::fs::remove_dir_all(&root).unwrap();
+        assert!(fails(&result, options.fail_on));
+        assert!(!fails(&result, None));
+
+        let report = render(Format::Markdown, &registry, &task, &result).unwrap();
+        assert!(report.contains("| 1 | high | `reentrancy` |"), "{report}");
+        assert!(report.contains("`src/Vault.sol:"));
+        let sarif = render(Format::Sarif, &registry, &task, &result).unwrap();
+        let sarif: serde_json::Value = serde_json::from_str(&sarif).unwrap();
+        assert_eq!(sarif["runs"][0]["results"][0]["ruleId"], "reentrancy");
+
+        Baseline::from_findings(&result.vulnerabilities).apply(&mut result.vulnerabilities);
+        assert!(!fails(&result, options.fail_on));
+        let report = markdown(&result);
+        assert!(report.contains("No findings."));
+        assert!(report.contains("## Suppressed (2)"), "{report}");
+    }
+}
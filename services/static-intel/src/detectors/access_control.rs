@@ -0,0 +1,421 @@
+//! Access control: privileged state anyone can overwrite, and initializers
+//! anyone can call. Locking upgradeable implementations is checked by
+//! [`upgradeability`](super::upgradeability).
+//!
+//! State is privileged when some function checks the caller against it (see
+//! [`GuardAnalysis`]), when its name marks it as an authority or a payout address
+//! (`owner`, `implementation`, `feeRecipient`), or when it is an EIP-1967 slot
+//! written through assembly or `StorageSlot`.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use solang_parser::pt::Loc;
+
+use crate::ast::{ContractKind, StateMutability};
+use crate::detectors::Detector;
+use crate::finding::{Confidence, ExtraData, Finding, Severity, TraceStep};
+use crate::guards::GuardAnalysis;
+use crate::ir::{IrFunction, Op};
+use crate::symbols::{ContractId, FunctionId, StateVarId, SymbolTable};
+use crate::taint::{SinkKind, TaintAnalysis};
+
+/// Name suffixes of address-typed state that holds authority or receives funds.
+const PRIVILEGED_NAMES: &[&str] = &[
+    "owner",
+    "admin",
+    "implementation",
+    "beacon",
+    "governance",
+    "governor",
+    "guardian",
+    "operator",
+    "controller",
+    "minter",
+    "pauser",
+    "keeper",
+    "oracle",
+    "treasury",
+    "feerecipient",
+    "feereceiver",
+    "feecollector",
+    "feeto",
+];
+
+/// Modifiers that make a function callable once (OpenZeppelin `Initializable`).
+pub(crate) const INITIALIZER_MODIFIERS: &[&str] =
+    &["initializer", "reinitializer", "onlyInitializing"];
+
+/// State a function touches, including through the internal functions it calls.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Summary {
+    pub reads: BTreeSet<StateVarId>,
+    /// First write of each variable, with the function performing it.
+    pub writes: BTreeMap<StateVarId, (FunctionId, Loc)>,
+    /// EIP-1967 slot constants written through assembly or `StorageSlot`.
+    pub slots: BTreeMap<StateVarId, (FunctionId, Loc)>,
+    /// Names of internal functions called, transitively.
+    pub calls: BTreeSet<String>,
+}
+
+type Key = (Option<ContractId>, FunctionId);
+
+pub(crate) struct Analysis<'s, 'p> {
+    symbols: &'s SymbolTable<'p>,
+    summaries: HashMap<Key, Summary>,
+    active: HashSet<Key>,
+}
+
+impl<'s, 'p> Analysis<'s, 'p> {
+    pub fn new(symbols: &'s SymbolTable<'p>) -> Self {
+        Self {
+            symbols,
+            summaries: HashMap::new(),
+            active: HashSet::new(),
+        }
+    }
+
+    pub fn summary(&mut self, context: Option<ContractId>, func: FunctionId) -> Summary {
+        let key = (context, func);
+        if let Some(summary) = self.summaries.get(&key) {
+            return summary.clone();
+        }
+        if !self.active.insert(key) {
+            return Summary::default();
+        }
+        let summary = self.compute(context, func);
+        self.active.remove(&key);
+        self.summaries.insert(key, summary.clone());
+        summary
+    }
+
+    fn compute(&mut self, context: Option<ContractId>, func: FunctionId) -> Summary {
+        let ir = IrFunction::build(self.symbols, context, func);
+        let mut summary = Summary::default();
+        let mut slot_constants = Vec::new();
+        let mut slot_store = None;
+        for (block, inst) in ir.instructions() {
+            let origin = ir.cfg.node(block).origin;
+            match &inst.op {
+                Op::StorageRead { var, .. } => {
+                    summary.reads.insert(*var);
+                }
+                Op::StorageWrite { var, .. } => {
+                    summary.writes.entry(*var).or_insert((origin, inst.loc));
+                }
+                Op::StateConstant(var) if is_slot(&self.symbols.state_variable(*var).name) => {
+                    slot_constants.push(*var);
+                }
+                Op::Assembly { calls } if calls.iter().any(|c| c == "sstore") => {
+                    slot_store.get_or_insert((origin, inst.loc));
+                }
+                Op::LibraryCall { name, .. }
+                    if name.starts_with("get") && name.ends_with("Slot") =>
+                {
+                    slot_store.get_or_insert((origin, inst.loc));
+                }
+                _ => {}
+            }
+            if let Op::InternalCall { name, .. } | Op::LibraryCall { name, .. } = &inst.op {
+                summary.calls.insert(name.clone());
+            }
+            if let Op::InternalCall {
+                callee: Some(callee),
+                ..
+            }
+            | Op::LibraryCall {
+                callee: Some(callee),
+                ..
+            } = &inst.op
+            {
+                let inner = self.summary(self.symbols.dispatch_context(context, *callee), *callee);
+                summary.reads.extend(inner.reads);
+                for (var, site) in inner.writes {
+                    summary.writes.entry(var).or_insert(site);
+                }
+                for (var, site) in inner.slots {
+                    summary.slots.entry(var).or_insert(site);
+                }
+                summary.calls.extend(inner.calls);
+            }
+        }
+        if let Some(site) = slot_store {
+            for var in slot_constants {
+                summary.slots.entry(var).or_insert(site);
+            }
+        }
+        summary
+    }
+}
+
+/// `_IMPLEMENTATION_SLOT`, `ADMIN_SLOT`, `_BEACON_SLOT`.
+pub(crate) fn is_slot(name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    name.ends_with("SLOT")
+        && ["IMPLEMENTATION", "ADMIN", "BEACON", "OWNER"]
+            .iter()
+            .any(|kind| name.contains(kind))
+}
+
+fn is_privileged_name(symbols: &SymbolTable, var: StateVarId) -> bool {
+    let def = symbols.state_variable(var);
+    let ty = def.type_name.trim_end_matches(" payable");
+    // Addresses and contract references; mappings are privileged only when guarded.
+    let address_like = ty == "address" || ty.starts_with(|c: char| c.is_ascii_uppercase());
+    let name = def.name.trim_start_matches('_').to_ascii_lowercase();
+    address_like && PRIVILEGED_NAMES.iter().any(|suffix| name.ends_with(suffix))
+}
+
+pub(crate) fn has_initializer_modifier(symbols: &SymbolTable, func: FunctionId) -> bool {
+    symbols
+        .function(func)
+        .modifiers
+        .iter()
+        .any(|m| INITIALIZER_MODIFIERS.contains(&m.name.as_str()))
+}
+
+fn is_initializer(symbols: &SymbolTable, func: FunctionId) -> bool {
+    let name = &symbols.function(func).name;
+    name.starts_with("initialize") || name == "init" || has_initializer_modifier(symbols, func)
+}
+
+/// Privileged state anyone can overwrite and initializers anyone can call.
+pub struct AccessControl;
+
+impl Detector for AccessControl {
+    fn id(&self) -> &str {
+        "access-control"
+    }
+
+    fn description(&self) -> &str {
+        "Privileged state anyone can overwrite and initializers anyone can call"
+    }
+
+    fn swc(&self) -> &[&str] {
+        &["SWC-105", "SWC-118"]
+    }
+
+    fn cwe(&self) -> &[&str] {
+        &["CWE-284"]
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::High
+    }
+
+    fn run(&self, symbols: &SymbolTable) -> Vec<Finding> {
+        detect(symbols)
+    }
+}
+
+pub fn detect(symbols: &SymbolTable) -> Vec<Finding> {
+    let mut guards = GuardAnalysis::new(symbols);
+    let guard_variables = guards.guard_variables();
+    let mut analysis = Analysis::new(symbols);
+    // Traces of caller-controlled values reaching access-control state.
+    let traces: HashMap<(ContractId, FunctionId, Loc), Vec<TraceStep>> =
+        TaintAnalysis::new(symbols)
+            .flows()
+            .into_iter()
+            .filter(|flow| flow.sink == SinkKind::AccessControlStorage)
+            .map(|flow| ((flow.context, flow.entry, flow.loc), flow.trace))
+            .collect();
+    let step = |function: FunctionId, description: String, loc: &Loc| TraceStep {
+        function: symbols.qualified_name(function),
+        description,
+        location: symbols.project.location(loc),
+    };
+
+    let mut findings = Vec::new();
+    for info in &symbols.contracts {
+        if info.def.kind != ContractKind::Contract {
+            continue;
+        }
+        let context = info.id;
+        for &func in &info.functions {
+            let def = symbols.function(func);
+            if !def.is_entry_point()
+                || matches!(
+                    def.mutability,
+                    StateMutability::View | StateMutability::Pure
+                )
+            {
+                continue;
+            }
+            if guards.is_guarded(Some(context), func) {
+                continue;
+            }
+            let summary = analysis.summary(Some(context), func);
+            let name = format!("{}.{}", info.name(), def.display_name());
+
+            if is_initializer(symbols, func) {
+                // A flag the function checks and sets also makes it one-shot.
+                let one_shot = summary.reads.iter().any(|var| {
+                    summary.writes.contains_key(var)
+                        && symbols.state_variable(*var).type_name == "bool"
+                });
+                if !has_initializer_modifier(symbols, func)
+                    && !one_shot
+                    && !summary.writes.is_empty()
+                {
+                    findings.push(Finding {
+                        severity: Severity::High,
+                        confidence: Confidence::High,
+                        title: "Unprotected initializer".into(),
+                        description: format!(
+                            "`{name}` sets up the contract but can be called by anyone, any number \
+                             of times. Protect it with the `initializer` modifier or a one-time flag."
+                        ),
+                        location: symbols.project.location(&def.loc),
+                        extra_data: ExtraData { trace: vec![step(func, "initializer".into(), &def.loc)], ..Default::default() },
+                        ..Default::default()
+                    });
+                }
+                continue;
+            }
+
+            let privileged =
+                summary
+                    .writes
+                    .iter()
+                    .filter(|(var, _)| {
+                        guard_variables.contains(var) || is_privileged_name(symbols, **var)
+                    })
+                    .map(|(var, site)| (symbols.state_variable(*var).name.clone(), *var, *site))
+                    .chain(summary.slots.iter().map(|(var, site)| {
+                        (symbols.state_variable(*var).name.clone(), *var, *site)
+                    }));
+            for (var_name, var, (function, loc)) in privileged {
+                // Checked against the caller elsewhere, rather than privileged by name only.
+                let confidence = match guard_variables.contains(&var) {
+                    true => Confidence::High,
+                    false => Confidence::Medium,
+                };
+                let trace = traces
+                    .get(&(context, func, loc))
+                    .cloned()
+                    .unwrap_or_else(|| vec![step(function, format!("writes `{var_name}`"), &loc)]);
+                findings.push(Finding {
+                    severity: Severity::High,
+                    confidence,
+                    title: "Unprotected write to privileged state".into(),
+                    description: format!(
+                        "`{name}` writes `{var_name}` without checking the caller, so anyone can \
+                         change who controls the contract or where its funds go."
+                    ),
+                    location: symbols.project.location(&loc),
+                    extra_data: ExtraData {
+                        trace,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                });
+            }
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Project, ProjectBundle};
+
+    fn findings(src: &str) -> Vec<Finding> {
+        let project = Project::load(&ProjectBundle::single("Test.sol", src));
+        let symbols = SymbolTable::build(&project);
+        detect(&symbols)
+    }
+
+    #[test]
+    fn test_unprotected_privileged_writes() {
+        let findings = findings(
+            "contract Fees {
+                address public owner;
+                address public feeRecipient;
+                uint256 public fee;
+                constructor() { owner = msg.sender; }
+                modifier onlyOwner() { require(msg.sender == owner); _; }
+                function setFee(uint256 f) external onlyOwner { fee = f; }
+                function setFeeRecipient(address r) external { feeRecipient = r; }
+                function claimOwnership() external { _setOwner(msg.sender); }
+                function _setOwner(address o) internal { owner = o; }
+                mapping(address => bool) admins;
+                function addAdmin(address a) external { require(admins[msg.sender]); admins[a] = true; }
+                function setAdminFee(uint256 f) external { require(admins[msg.sender]); fee = f; }
+                mapping(address => bool) claimed;
+                function setOwnerOnce(address o) external {
+                    require(!claimed[msg.sender]);
+                    claimed[msg.sender] = true;
+                    owner = o;
+                }
+             }",
+        );
+        let described: Vec<(&str, &str)> = findings
+            .iter()
+            .map(|f| {
+                (
+                    f.title.as_str(),
+                    f.description.split(" without").next().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            described,
+            [
+                (
+                    "Unprotected write to privileged state",
+                    "`Fees.setFeeRecipient` writes `feeRecipient`"
+                ),
+                (
+                    "Unprotected write to privileged state",
+                    "`Fees.claimOwnership` writes `owner`"
+                ),
+                (
+                    "Unprotected write to privileged state",
+                    "`Fees.setOwnerOnce` writes `owner`"
+                ),
+            ]
+        );
+        // The taint trace shows where the new owner comes from.
+        let trace: Vec<&str> = findings[1]
+            .trace()
+            .iter()
+            .map(|s| s.description.as_str())
+            .collect();
+        assert_eq!(
+            trace,
+            [
+                "`msg.sender`",
+                "passed to `Fees._setOwner` as `o`",
+                "reaches the access-control state"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_initializers() {
+        let findings = findings(
+            "abstract contract Initializable {
+                bool private _initialized;
+                modifier initializer() { require(!_initialized); _initialized = true; _; }
+                function _disableInitializers() internal { _initialized = true; }
+             }
+             contract Open {
+                address public owner;
+                function initialize(address o) external { owner = o; }
+             }
+             contract Flagged {
+                address public owner;
+                bool initialized;
+                function init(address o) external { require(!initialized); initialized = true; owner = o; }
+             }
+             contract Upgradeable is Initializable {
+                address public owner;
+                function initialize(address o) external initializer { owner = o; }
+             }",
+        );
+        let titles: Vec<&str> = findings.iter().map(|f| f.title.as_str()).collect();
+        assert_eq!(titles, ["Unprotected initializer"]);
+        assert!(findings[0].description.starts_with("`Open.initialize`"));
+    }
+}
@@ -0,0 +1,406 @@
+//! Arithmetic safety, aware of the compiler version: unchecked overflow in
+//! pre-0.8 files that do not use SafeMath, user input in `unchecked` blocks,
+//! truncating downcasts and precision lost by dividing before multiplying.
+//!
+//! An operation counts as bounds-checked when its result or one of its operands
+//! is compared anywhere in the function, which covers `require(b <= a)`, the
+//! SafeMath `c >= a` idiom, loop bounds and `SafeCast`. Recomputations of a
+//! compared expression count too, as in `require(a + b >= a); a = a + b;`.
+
+use std::collections::HashSet;
+
+use solang_parser::pt::{Loc, Statement};
+
+use crate::ast::FunctionKind;
+use crate::detectors::Detector;
+use crate::finding::{Confidence, Finding, Severity};
+use crate::ir::{BinaryOp, EnvVar, IrFunction, Op, Operand, PathElem, ValueId};
+use crate::symbols::SymbolTable;
+use crate::visit::{self, Visitor};
+
+/// Source ranges of `unchecked { ... }` blocks.
+#[derive(Default)]
+struct UncheckedBlocks {
+    locs: Vec<Loc>,
+}
+
+impl UncheckedBlocks {
+    fn contains(&self, loc: &Loc) -> bool {
+        self.locs.iter().any(|block| match (block, loc) {
+            (Loc::File(file, start, end), Loc::File(f, s, e)) => {
+                file == f && start <= s && e <= end
+            }
+            _ => false,
+        })
+    }
+}
+
+impl Visitor for UncheckedBlocks {
+    fn visit_statement(&mut self, stmt: &Statement) -> bool {
+        if let Statement::Block {
+            loc,
+            unchecked: true,
+            ..
+        } = stmt
+        {
+            self.locs.push(*loc);
+        }
+        true
+    }
+}
+
+/// `(signed, bits)` of an integer type name.
+fn int_type(ty: &str) -> Option<(bool, u32)> {
+    let (signed, bits) = match ty.strip_prefix("uint") {
+        Some(bits) => (false, bits),
+        None => (true, ty.strip_prefix("int")?),
+    };
+    match bits {
+        "" => Some((signed, 256)),
+        bits => Some((signed, bits.parse().ok()?)),
+    }
+}
+
+struct Function<'i, 'p> {
+    ir: &'i IrFunction<'p>,
+    /// Values compared anywhere in the function, with the values they copy.
+    compared: HashSet<ValueId>,
+    /// [`shape`]s of the compared values.
+    compared_shapes: HashSet<String>,
+    entry_point: bool,
+}
+
+/// Most nested operations [`shape`] follows.
+const SHAPE_DEPTH: usize = 8;
+
+/// The expression computing `operand` over parameters, constants, state and
+/// the environment, equal for every computation of the same expression; `None`
+/// for values from calls, branches and the like.
+fn shape(ir: &IrFunction, operand: &Operand, depth: usize) -> Option<String> {
+    let value = match operand {
+        Operand::Const(constant) => return Some(format!("{constant:?}")),
+        operand => operand.value()?,
+    };
+    let depth = depth.checked_sub(1)?;
+    Some(match &ir.def(value).op {
+        Op::Param(index) => format!("param{index}"),
+        Op::Env(var) => format!("{var:?}"),
+        Op::StateConstant(var) => format!("{var:?}"),
+        Op::StorageRead { var, path } => {
+            let mut read = format!("{var:?}");
+            for elem in path {
+                match elem {
+                    PathElem::Index(index) => read += &format!("[{}]", shape(ir, index, depth)?),
+                    elem => read += &format!(".{elem:?}"),
+                }
+            }
+            read
+        }
+        Op::Copy(inner) => shape(ir, inner, depth)?,
+        Op::Conversion { ty, value } => format!("{ty}({})", shape(ir, value, depth)?),
+        Op::Binary(op, a, b) => {
+            format!("({} {op:?} {})", shape(ir, a, depth)?, shape(ir, b, depth)?)
+        }
+        _ => return None,
+    })
+}
+
+impl<'i, 'p> Function<'i, 'p> {
+    fn new(ir: &'i IrFunction<'p>, entry_point: bool) -> Self {
+        let mut compared = HashSet::new();
+        let mut pending: Vec<ValueId> = Vec::new();
+        for (_, inst) in ir.instructions() {
+            if let Op::Binary(op, a, b) = &inst.op {
+                if op.is_comparison() {
+                    pending.extend(a.value());
+                    pending.extend(b.value());
+                }
+            }
+        }
+        while let Some(value) = pending.pop() {
+            if compared.insert(value) {
+                if let Op::Copy(Operand::Value(source)) = &ir.def(value).op {
+                    pending.push(*source);
+                }
+            }
+        }
+        let compared_shapes = compared
+            .iter()
+            .filter_map(|&v| shape(ir, &Operand::Value(v), SHAPE_DEPTH))
+            .collect();
+        Function {
+            ir,
+            compared,
+            compared_shapes,
+            entry_point,
+        }
+    }
+
+    fn is_compared(&self, dst: Option<ValueId>, operands: &[&Operand]) -> bool {
+        dst.map(Operand::Value)
+            .iter()
+            .chain(operands.iter().copied())
+            .filter(|o| o.value().is_some())
+            .any(|o| {
+                o.value().is_some_and(|v| self.compared.contains(&v))
+                    || shape(self.ir, o, SHAPE_DEPTH)
+                        .is_some_and(|shape| self.compared_shapes.contains(&shape))
+            })
+    }
+
+    /// Whether `operand` derives from an entry-point parameter, `msg.value` or
+    /// `msg.data` through local computation.
+    fn is_user_input(&self, operand: &Operand, seen: &mut HashSet<ValueId>) -> bool {
+        let Some(value) = operand.value() else {
+            return false;
+        };
+        if !seen.insert(value) {
+            return false;
+        }
+        match &self.ir.def(value).op {
+            Op::Param(_) => self.entry_point,
+            Op::Env(EnvVar::MsgValue | EnvVar::MsgData) => true,
+            op @ (Op::Copy(_)
+            | Op::Phi(_)
+            | Op::Binary(..)
+            | Op::Unary(..)
+            | Op::Conversion { .. }
+            | Op::Select(..)
+            | Op::Extract(..)) => op
+                .operands()
+                .into_iter()
+                .any(|o| self.is_user_input(o, seen)),
+            _ => false,
+        }
+    }
+
+    /// Whether `operand` is the result of a division, possibly copied or converted.
+    fn is_quotient(&self, operand: &Operand) -> bool {
+        let Some(value) = operand.value() else {
+            return false;
+        };
+        match &self.ir.def(value).op {
+            Op::Binary(BinaryOp::Div, ..) => true,
+            Op::Copy(inner) | Op::Conversion { value: inner, .. } => self.is_quotient(inner),
+            _ => false,
+        }
+    }
+
+    fn value_type(&self, operand: &Operand) -> Option<(bool, u32)> {
+        int_type(self.ir.values[operand.value()?].ty.as_deref()?)
+    }
+}
+
+/// Overflow, unsafe downcasts and divide-before-multiply.
+pub struct Arithmetic;
+
+impl Detector for Arithmetic {
+    fn id(&self) -> &str {
+        "arithmetic"
+    }
+
+    fn description(&self) -> &str {
+        "Overflow, unsafe downcasts and divide-before-multiply"
+    }
+
+    fn swc(&self) -> &[&str] {
+        &["SWC-101"]
+    }
+
+    fn cwe(&self) -> &[&str] {
+        &["CWE-190", "CWE-682"]
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Medium
+    }
+
+    fn run(&self, symbols: &SymbolTable) -> Vec<Finding> {
+        detect(symbols)
+    }
+}
+
+pub fn detect(symbols: &SymbolTable) -> Vec<Finding> {
+    let mut unchecked = UncheckedBlocks::default();
+    for func in symbols.all_functions() {
+        if let Some(body) = &symbols.function(func).body {
+            visit::walk_statement(&mut unchecked, body);
+        }
+    }
+
+    let mut findings = Vec::new();
+    let mut reported = HashSet::new();
+    for func in symbols.all_functions() {
+        let def = symbols.function(func);
+        if def.body.is_none() || def.kind == FunctionKind::Modifier {
+            continue;
+        }
+        let Some(unit) = symbols.project.unit(symbols.file_of(func)) else {
+            continue;
+        };
+        let checked = unit.has_checked_arithmetic();
+        let name = symbols.qualified_name(func);
+        let ir = IrFunction::build(symbols, func.contract(), func);
+        let function = Function::new(&ir, def.is_entry_point());
+        for (_, inst) in ir.instructions() {
+            // (severity, title, description)
+            let mut found = Vec::new();
+            match &inst.op {
+                Op::Binary(op @ (BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul), a, b)
+                    if (a.value().is_some() || b.value().is_some())
+                        && !function.is_compared(inst.dst, &[a, b]) =>
+                {
+                    if !checked {
+                        found.push((
+                            Severity::Medium,
+                            "Integer overflow or underflow",
+                            format!(
+                                "`{name}` computes `{}` without SafeMath or a bounds check; \
+                                 `pragma solidity {}` compiles to arithmetic that wraps around \
+                                 silently.",
+                                op.symbol(),
+                                unit.solidity_pragma().unwrap_or_default()
+                            ),
+                        ));
+                    } else if unchecked.contains(&inst.loc)
+                        && (function.is_user_input(a, &mut HashSet::new())
+                            || function.is_user_input(b, &mut HashSet::new()))
+                    {
+                        found.push((
+                            Severity::Medium,
+                            "Unchecked arithmetic on user input",
+                            format!(
+                                "`{name}` applies `{}` to caller-supplied values inside an \
+                                 `unchecked` block without a bounds check, so the result can \
+                                 wrap around.",
+                                op.symbol()
+                            ),
+                        ));
+                    }
+                }
+                Op::Conversion { ty, value } => {
+                    if let (Some((_, to)), Some((_, from))) =
+                        (int_type(ty), function.value_type(value))
+                    {
+                        if to < from && !function.is_compared(inst.dst, &[value]) {
+                            found.push((
+                                Severity::Medium,
+                                "Unsafe integer downcast",
+                                format!(
+                                    "`{name}` converts a {from}-bit integer to `{ty}` without a \
+                                     range check; larger values are silently truncated. Use \
+                                     SafeCast or check the bound first."
+                                ),
+                            ));
+                        }
+                    }
+                }
+                _ => {}
+            }
+            if let Op::Binary(BinaryOp::Mul, a, b) = &inst.op {
+                if function.is_quotient(a) || function.is_quotient(b) {
+                    found.push((
+                        Severity::Medium,
+                        "Divide before multiply",
+                        format!(
+                            "`{name}` multiplies the result of a division, so the remainder \
+                             discarded by the division is scaled up. Multiply first."
+                        ),
+                    ));
+                }
+            }
+            for (severity, title, description) in found {
+                if reported.insert((title, inst.loc)) {
+                    findings.push(Finding {
+                        severity,
+                        confidence: Confidence::Medium,
+                        title: title.into(),
+                        description,
+                        location: symbols.project.location(&inst.loc),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Project, ProjectBundle};
+
+    fn findings(src: &str) -> Vec<(String, usize)> {
+        let project = Project::load(&ProjectBundle::single("Test.sol", src));
+        let symbols = SymbolTable::build(&project);
+        detect(&symbols)
+            .into_iter()
+            .map(|f| (f.title, f.location.unwrap().line))
+            .collect()
+    }
+
+    #[test]
+    fn test_legacy_overflow() {
+        let findings = findings(
+            "pragma solidity ^0.6.12;
+             contract Token {
+                mapping(address => uint256) balances;
+                function transfer(address to, uint256 amount) external {
+                    balances[msg.sender] = balances[msg.sender] - amount;
+                    balances[to] = balances[to] + amount;
+                }
+                function safeTransfer(address to, uint256 amount) external {
+                    require(balances[msg.sender] >= amount);
+                    uint256 total = balances[to] + amount;
+                    require(total >= amount);
+                    balances[msg.sender] -= amount;
+                    balances[to] = total;
+                }
+                uint256 supply;
+                function mint(uint256 amount) external {
+                    require(supply + amount >= supply);
+                    supply = supply + amount;
+                }
+             }",
+        );
+        assert_eq!(
+            findings,
+            [
+                ("Integer overflow or underflow".to_string(), 5),
+                ("Integer overflow or underflow".to_string(), 6),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let findings = findings(
+            "pragma solidity ^0.8.20;
+             contract Vault {
+                uint256 total;
+                uint128 packed;
+                function deposit(uint256 amount, uint256 bonus) external {
+                    unchecked { total = total + amount; }
+                    unchecked { total = total + 1; }
+                    total = total + bonus;
+                    packed = uint128(amount);
+                }
+                function depositSmall(uint256 amount) external {
+                    if (amount <= type(uint64).max) { packed = uint64(amount); }
+                }
+                function share(uint256 amount, uint256 supply, uint256 assets) external pure returns (uint256) {
+                    return amount / supply * assets;
+                }
+             }",
+        );
+        assert_eq!(
+            findings,
+            [
+                ("Unchecked arithmetic on user input".to_string(), 6),
+                ("Unsafe integer downcast".to_string(), 9),
+                ("Divide before multiply".to_string(), 15),
+            ]
+        );
+    }
+}
@@ -0,0 +1,990 @@
+//! Token standard conformance: ERC20, ERC721, ERC1155 and ERC4626.
+//!
+//! A contract claims a standard when it inherits a contract or interface
+//! named after it (`IERC20`, `ERC721Enumerable`), carries its name
+//! (`MockERC20`), or implements its characteristic functions. Claimed
+//! standards are checked for their function signatures and return types
+//! (public state variables count as their getters), their event declarations,
+//! and what the standards ask of behaviour: every balance change emits
+//! `Transfer`, approvals emit `Approval`, and ERC4626 previews round in the
+//! vault's favour.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use solang_parser::pt::{self, Expression};
+
+use crate::ast::{ContractKind, EventDef, FunctionKind, StateVariable};
+use crate::detectors::Detector;
+use crate::finding::{Confidence, Finding, Severity};
+use crate::ir::{BinaryOp, Constant, IrFunction, Op, Operand};
+use crate::symbols::{ContractId, ContractInfo, FunctionId, StateVarId, SymbolTable};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Standard {
+    #[serde(rename = "ERC20")]
+    Erc20,
+    #[serde(rename = "ERC721")]
+    Erc721,
+    #[serde(rename = "ERC1155")]
+    Erc1155,
+    #[serde(rename = "ERC4626")]
+    Erc4626,
+}
+
+impl Standard {
+    pub const ALL: [Standard; 4] = [
+        Standard::Erc20,
+        Standard::Erc721,
+        Standard::Erc1155,
+        Standard::Erc4626,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Standard::Erc20 => "ERC20",
+            Standard::Erc721 => "ERC721",
+            Standard::Erc1155 => "ERC1155",
+            Standard::Erc4626 => "ERC4626",
+        }
+    }
+
+    fn spec(self) -> &'static Spec {
+        match self {
+            Standard::Erc20 => &ERC20,
+            Standard::Erc721 => &ERC721,
+            Standard::Erc1155 => &ERC1155,
+            Standard::Erc4626 => &ERC4626,
+        }
+    }
+}
+
+impl fmt::Display for Standard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+struct FunctionSpec {
+    name: &'static str,
+    params: &'static [&'static str],
+    returns: &'static [&'static str],
+    /// Optional metadata such as `decimals()`, checked only when present.
+    optional: bool,
+}
+
+const fn required(
+    name: &'static str,
+    params: &'static [&'static str],
+    returns: &'static [&'static str],
+) -> FunctionSpec {
+    FunctionSpec {
+        name,
+        params,
+        returns,
+        optional: false,
+    }
+}
+
+const fn optional(
+    name: &'static str,
+    params: &'static [&'static str],
+    returns: &'static [&'static str],
+) -> FunctionSpec {
+    FunctionSpec {
+        name,
+        params,
+        returns,
+        optional: true,
+    }
+}
+
+impl FunctionSpec {
+    fn signature(&self) -> String {
+        format!("{}({})", self.name, self.params.join(","))
+    }
+}
+
+struct EventSpec {
+    name: &'static str,
+    /// Types and whether they are indexed.
+    params: &'static [(&'static str, bool)],
+}
+
+impl EventSpec {
+    fn signature(&self) -> String {
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|(ty, indexed)| match indexed {
+                true => format!("{ty} indexed"),
+                false => ty.to_string(),
+            })
+            .collect();
+        format!("{}({})", self.name, params.join(","))
+    }
+}
+
+struct Spec {
+    functions: &'static [FunctionSpec],
+    /// Functions only this standard has; implementing them together with
+    /// half of the required functions claims the standard.
+    characteristic: &'static [&'static str],
+    events: &'static [EventSpec],
+    /// Functions that must emit one of the events, by name.
+    emissions: &'static [(&'static str, &'static [&'static str])],
+    /// Events that must accompany every balance change.
+    transfer_events: &'static [&'static str],
+}
+
+const ERC20: Spec = Spec {
+    functions: &[
+        required("totalSupply", &[], &["uint256"]),
+        required("balanceOf", &["address"], &["uint256"]),
+        required("transfer", &["address", "uint256"], &["bool"]),
+        required(
+            "transferFrom",
+            &["address", "address", "uint256"],
+            &["bool"],
+        ),
+        required("approve", &["address", "uint256"], &["bool"]),
+        required("allowance", &["address", "address"], &["uint256"]),
+        optional("name", &[], &["string"]),
+        optional("symbol", &[], &["string"]),
+        optional("decimals", &[], &["uint8"]),
+    ],
+    characteristic: &["transfer(address,uint256)", "allowance(address,address)"],
+    events: &[
+        EventSpec {
+            name: "Transfer",
+            params: &[("address", true), ("address", true), ("uint256", false)],
+        },
+        EventSpec {
+            name: "Approval",
+            params: &[("address", true), ("address", true), ("uint256", false)],
+        },
+    ],
+    emissions: &[("approve", &["Approval"])],
+    transfer_events: &["Transfer"],
+};
+
+const ERC721: Spec = Spec {
+    functions: &[
+        required("balanceOf", &["address"], &["uint256"]),
+        required("ownerOf", &["uint256"], &["address"]),
+        required(
+            "safeTransferFrom",
+            &["address", "address", "uint256", "bytes"],
+            &[],
+        ),
+        required("safeTransferFrom", &["address", "address", "uint256"], &[]),
+        required("transferFrom", &["address", "address", "uint256"], &[]),
+        required("approve", &["address", "uint256"], &[]),
+        required("setApprovalForAll", &["address", "bool"], &[]),
+        required("getApproved", &["uint256"], &["address"]),
+        required("isApprovedForAll", &["address", "address"], &["bool"]),
+        required("supportsInterface", &["bytes4"], &["bool"]),
+    ],
+    characteristic: &["ownerOf(uint256)"],
+    events: &[
+        EventSpec {
+            name: "Transfer",
+            params: &[("address", true), ("address", true), ("uint256", true)],
+        },
+        EventSpec {
+            name: "Approval",
+            params: &[("address", true), ("address", true), ("uint256", true)],
+        },
+        EventSpec {
+            name: "ApprovalForAll",
+            params: &[("address", true), ("address", true), ("bool", false)],
+        },
+    ],
+    emissions: &[
+        ("approve", &["Approval"]),
+        ("setApprovalForAll", &["ApprovalForAll"]),
+    ],
+    transfer_events: &["Transfer"],
+};
+
+const ERC1155: Spec = Spec {
+    functions: &[
+        required(
+            "safeTransferFrom",
+            &["address", "address", "uint256", "uint256", "bytes"],
+            &[],
+        ),
+        required(
+            "safeBatchTransferFrom",
+            &["address", "address", "uint256[]", "uint256[]", "bytes"],
+            &[],
+        ),
+        required("balanceOf", &["address", "uint256"], &["uint256"]),
+        required(
+            "balanceOfBatch",
+            &["address[]", "uint256[]"],
+            &["uint256[]"],
+        ),
+        required("setApprovalForAll", &["address", "bool"], &[]),
+        required("isApprovedForAll", &["address", "address"], &["bool"]),
+        required("supportsInterface", &["bytes4"], &["bool"]),
+    ],
+    characteristic: &["balanceOf(address,uint256)"],
+    events: &[
+        EventSpec {
+            name: "TransferSingle",
+            params: &[
+                ("address", true),
+                ("address", true),
+                ("address", true),
+                ("uint256", false),
+                ("uint256", false),
+            ],
+        },
+        EventSpec {
+            name: "TransferBatch",
+            params: &[
+                ("address", true),
+                ("address", true),
+                ("address", true),
+                ("uint256[]", false),
+                ("uint256[]", false),
+            ],
+        },
+        EventSpec {
+            name: "ApprovalForAll",
+            params: &[("address", true), ("address", true), ("bool", false)],
+        },
+        EventSpec {
+            name: "URI",
+            params: &[("string", false), ("uint256", true)],
+        },
+    ],
+    emissions: &[("setApprovalForAll", &["ApprovalForAll"])],
+    transfer_events: &["TransferSingle", "TransferBatch"],
+};
+
+const ERC4626: Spec = Spec {
+    functions: &[
+        required("asset", &[], &["address"]),
+        required("totalAssets", &[], &["uint256"]),
+        required("convertToShares", &["uint256"], &["uint256"]),
+        required("convertToAssets", &["uint256"], &["uint256"]),
+        required("maxDeposit", &["address"], &["uint256"]),
+        required("previewDeposit", &["uint256"], &["uint256"]),
+        required("deposit", &["uint256", "address"], &["uint256"]),
+        required("maxMint", &["address"], &["uint256"]),
+        required("previewMint", &["uint256"], &["uint256"]),
+        required("mint", &["uint256", "address"], &["uint256"]),
+        required("maxWithdraw", &["address"], &["uint256"]),
+        required("previewWithdraw", &["uint256"], &["uint256"]),
+        required("withdraw", &["uint256", "address", "address"], &["uint256"]),
+        required("maxRedeem", &["address"], &["uint256"]),
+        required("previewRedeem", &["uint256"], &["uint256"]),
+        required("redeem", &["uint256", "address", "address"], &["uint256"]),
+    ],
+    characteristic: &["asset()", "totalAssets()"],
+    events: &[
+        EventSpec {
+            name: "Deposit",
+            params: &[
+                ("address", true),
+                ("address", true),
+                ("uint256", false),
+                ("uint256", false),
+            ],
+        },
+        EventSpec {
+            name: "Withdraw",
+            params: &[
+                ("address", true),
+                ("address", true),
+                ("address", true),
+                ("uint256", false),
+                ("uint256", false),
+            ],
+        },
+    ],
+    emissions: &[
+        ("deposit", &["Deposit"]),
+        ("mint", &["Deposit"]),
+        ("withdraw", &["Withdraw"]),
+        ("redeem", &["Withdraw"]),
+    ],
+    transfer_events: &[],
+};
+
+/// ERC4626 conversions with the rounding direction that favours the vault:
+/// `true` for up.
+pub(crate) const ROUNDING: &[(&str, bool)] = &[
+    ("convertToShares", false),
+    ("convertToAssets", false),
+    ("previewDeposit", false),
+    ("previewMint", true),
+    ("previewWithdraw", true),
+    ("previewRedeem", false),
+];
+
+/// Type as the ABI sees it: contracts are addresses, `uint` is `uint256`.
+fn abi_type(ty: &str) -> String {
+    let ty = ty.trim_end_matches(" payable");
+    let (base, array) = ty.split_at(ty.find('[').unwrap_or(ty.len()));
+    let base = match base {
+        "uint" => "uint256",
+        "int" => "int256",
+        "byte" => "bytes1",
+        base if base.starts_with(|c: char| c.is_ascii_uppercase()) => "address",
+        base => base,
+    };
+    format!("{base}{array}")
+}
+
+/// Whether `name` mentions `standard` as a token, not a receiver or helper:
+/// `ERC20`, `IERC721Enumerable`, `MockERC1155`, but not `ERC2098`,
+/// `IERC721Receiver` or `ERC1155Holder`.
+fn names_standard(name: &str, standard: Standard) -> bool {
+    let token = standard.as_str();
+    if ["Receiver", "Holder", "Utils"]
+        .iter()
+        .any(|helper| name.contains(helper))
+    {
+        return false;
+    }
+    name.match_indices(token)
+        .any(|(index, _)| !name[index + token.len()..].starts_with(|c: char| c.is_ascii_digit()))
+}
+
+/// An externally callable function of a contract, or the getter of a public
+/// state variable.
+pub(crate) struct Callable {
+    pub params: Vec<String>,
+    pub returns: Vec<String>,
+    pub function: Option<FunctionId>,
+    pub loc: pt::Loc,
+}
+
+/// Parameter and return types of the getter of `var`.
+fn getter(var: &StateVariable) -> (Vec<String>, Vec<String>) {
+    let mut params = Vec::new();
+    let mut ty = &var.ty;
+    loop {
+        match ty {
+            Expression::Type(_, pt::Type::Mapping { key, value, .. }) => {
+                params.push(abi_type(&key.to_string()));
+                ty = value;
+            }
+            Expression::ArraySubscript(_, element, _) => {
+                params.push("uint256".into());
+                ty = element;
+            }
+            _ => break,
+        }
+    }
+    (params, vec![abi_type(&ty.to_string())])
+}
+
+pub(crate) fn callables(symbols: &SymbolTable, info: &ContractInfo, name: &str) -> Vec<Callable> {
+    let functions = info.functions.iter().filter_map(|&f| {
+        let def = symbols.function(f);
+        (def.kind == FunctionKind::Function && def.is_entry_point() && def.name == name).then(
+            || Callable {
+                params: def.params.iter().map(|p| abi_type(&p.type_name)).collect(),
+                returns: def.returns.iter().map(|p| abi_type(&p.type_name)).collect(),
+                function: Some(f),
+                loc: def.loc,
+            },
+        )
+    });
+    let getters = info.state_variables.iter().filter_map(|&var| {
+        let def = symbols.state_variable(var);
+        (def.visibility.is_entry_point() && def.name == name).then(|| {
+            let (params, returns) = getter(def);
+            Callable {
+                params,
+                returns,
+                function: None,
+                loc: def.loc,
+            }
+        })
+    });
+    functions.chain(getters).collect()
+}
+
+fn implements(symbols: &SymbolTable, info: &ContractInfo, function: &FunctionSpec) -> bool {
+    callables(symbols, info, function.name)
+        .iter()
+        .any(|c| c.params == function.params)
+}
+
+/// Why `info` is taken to implement `standard`, if it does.
+pub(crate) fn claim(
+    symbols: &SymbolTable,
+    info: &ContractInfo,
+    standard: Standard,
+) -> Option<String> {
+    if let Some(&base) = info.linearization[1..]
+        .iter()
+        .find(|&&base| names_standard(symbols.contract(base).name(), standard))
+    {
+        return Some(format!("inherits `{}`", symbols.contract(base).name()));
+    }
+    if names_standard(info.name(), standard) {
+        return Some("named after it".into());
+    }
+    let spec = standard.spec();
+    let characteristic = spec.characteristic.iter().all(|signature| {
+        spec.functions
+            .iter()
+            .any(|f| &f.signature() == signature && implements(symbols, info, f))
+    });
+    let required: Vec<&FunctionSpec> = spec.functions.iter().filter(|f| !f.optional).collect();
+    let found = required
+        .iter()
+        .filter(|f| implements(symbols, info, f))
+        .count();
+    (characteristic && found * 2 >= required.len())
+        .then(|| format!("implements {found} of its {} functions", required.len()))
+}
+
+/// Events `info` can emit: its own and inherited ones first, then those of
+/// files and other contracts.
+fn events<'p>(symbols: &SymbolTable<'p>, info: &ContractInfo<'p>, name: &str) -> Vec<&'p EventDef> {
+    let inherited = info
+        .linearization
+        .iter()
+        .flat_map(|&c| &symbols.contract(c).def.events);
+    let units = symbols.project.units.iter();
+    let file_level = units.clone().flat_map(|u| &u.events);
+    let nested = units.flat_map(|u| &u.contracts).flat_map(|c| &c.events);
+    let mut found: Vec<&EventDef> = Vec::new();
+    for event in inherited.chain(file_level).chain(nested) {
+        if event.name == name && !found.iter().any(|e| std::ptr::eq(*e, event)) {
+            found.push(event);
+        }
+    }
+    found
+}
+
+fn matches_event(event: &EventDef, spec: &EventSpec) -> bool {
+    event.params.len() == spec.params.len()
+        && event
+            .params
+            .iter()
+            .zip(spec.params)
+            .all(|(p, (ty, indexed))| abi_type(&p.type_name) == *ty && p.indexed == *indexed)
+}
+
+/// What a function does, including through the contract functions it calls.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Summary {
+    writes: BTreeSet<StateVarId>,
+    emits: BTreeSet<String>,
+    /// Internal and library functions called, and enum members and other
+    /// names used (`Rounding.Ceil`), for the rounding direction.
+    names: BTreeSet<String>,
+    divides: bool,
+}
+
+impl Summary {
+    /// Rounding direction, from `mulDivUp`, `Rounding.Ceil` and the like or
+    /// a plain division; `None` when unclear.
+    pub fn rounds_up(&self) -> Option<bool> {
+        let up = self.names.iter().any(|name| {
+            let lower = name.to_ascii_lowercase();
+            lower.ends_with("up") || lower.contains("ceil") || name == "Expand"
+        });
+        let down = self.divides
+            || self.names.iter().any(|name| {
+                let lower = name.to_ascii_lowercase();
+                lower.ends_with("down") || lower.contains("floor") || name == "Trunc"
+            });
+        match (up, down) {
+            (true, false) => Some(true),
+            (false, true) => Some(false),
+            _ => None,
+        }
+    }
+}
+
+type Key = (Option<ContractId>, FunctionId);
+
+pub(crate) struct Analysis<'s, 'p> {
+    symbols: &'s SymbolTable<'p>,
+    summaries: HashMap<Key, Summary>,
+    active: HashSet<Key>,
+}
+
+impl<'s, 'p> Analysis<'s, 'p> {
+    pub fn new(symbols: &'s SymbolTable<'p>) -> Self {
+        Self {
+            symbols,
+            summaries: HashMap::new(),
+            active: HashSet::new(),
+        }
+    }
+
+    pub fn summary(&mut self, context: Option<ContractId>, func: FunctionId) -> Summary {
+        let key = (context, func);
+        if let Some(summary) = self.summaries.get(&key) {
+            return summary.clone();
+        }
+        if !self.active.insert(key) {
+            return Summary::default();
+        }
+        let ir = IrFunction::build(self.symbols, context, func);
+        let mut summary = Summary::default();
+        for (_, inst) in ir.instructions() {
+            match &inst.op {
+                Op::StorageWrite { var, .. } => {
+                    summary.writes.insert(*var);
+                }
+                Op::Emit { event, .. } => {
+                    let name = event.rsplit('.').next().unwrap_or(event);
+                    summary.emits.insert(name.to_string());
+                }
+                Op::Binary(BinaryOp::Div, ..) => summary.divides = true,
+                Op::Member(_, member) => {
+                    summary.names.insert(member.clone());
+                }
+                Op::LibraryCall { name, .. } => {
+                    summary.names.insert(name.clone());
+                }
+                Op::InternalCall { callee, name, .. } => {
+                    summary.names.insert(name.clone());
+                    if let Some(callee) = callee {
+                        let inner =
+                            self.summary(self.symbols.dispatch_context(context, *callee), *callee);
+                        summary.writes.extend(inner.writes);
+                        summary.emits.extend(inner.emits);
+                        summary.names.extend(inner.names);
+                        summary.divides |= inner.divides;
+                    }
+                }
+                _ => {}
+            }
+            for operand in inst.op.operands() {
+                if let Operand::Const(Constant::Name(name)) = operand {
+                    summary
+                        .names
+                        .insert(name.rsplit('.').next().unwrap_or(name).to_string());
+                }
+            }
+        }
+        self.active.remove(&key);
+        self.summaries.insert(key, summary.clone());
+        summary
+    }
+}
+
+/// How a contract measures up to a standard it claims.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conformance {
+    pub contract: String,
+    pub standard: Standard,
+    /// Why the contract is taken to implement the standard.
+    pub claimed_by: String,
+    /// Titles of the conformance findings; empty when the contract conforms.
+    pub issues: Vec<String>,
+}
+
+/// Conformance of every deployable contract to the standards it claims, with
+/// the findings behind each report.
+fn analyze(symbols: &SymbolTable) -> Vec<(Conformance, Vec<Finding>)> {
+    let mut analysis = Analysis::new(symbols);
+    let mut reports = Vec::new();
+    for info in &symbols.contracts {
+        if info.def.kind != ContractKind::Contract {
+            continue;
+        }
+        let mut claims: Vec<(Standard, String)> = Standard::ALL
+            .iter()
+            .filter_map(|&standard| Some((standard, claim(symbols, info, standard)?)))
+            .collect();
+        if claims.iter().any(|(s, _)| *s == Standard::Erc4626)
+            && !claims.iter().any(|(s, _)| *s == Standard::Erc20)
+        {
+            claims.insert(0, (Standard::Erc20, "required by ERC4626".into()));
+        }
+        for (standard, claimed_by) in claims {
+            let findings = check(&mut analysis, info, standard, &claimed_by);
+            reports.push((
+                Conformance {
+                    contract: info.name().to_string(),
+                    standard,
+                    claimed_by,
+                    issues: findings.iter().map(|f| f.title.clone()).collect(),
+                },
+                findings,
+            ));
+        }
+    }
+    reports
+}
+
+fn check(
+    analysis: &mut Analysis,
+    info: &ContractInfo,
+    standard: Standard,
+    claimed_by: &str,
+) -> Vec<Finding> {
+    let symbols = analysis.symbols;
+    let spec = standard.spec();
+    let name = info.name();
+    // Inheriting the standard is an explicit claim; the rest is inferred.
+    let confidence = match claimed_by.starts_with("inherits") {
+        true => Confidence::High,
+        false => Confidence::Medium,
+    };
+    let finding = |severity, title: String, description: String, loc: &pt::Loc| Finding {
+        severity,
+        confidence,
+        title,
+        description,
+        location: symbols.project.location(loc),
+        ..Default::default()
+    };
+    let mut findings = Vec::new();
+
+    for function in spec.functions {
+        let candidates = callables(symbols, info, function.name);
+        let Some(callable) = candidates.iter().find(|c| c.params == function.params) else {
+            if !function.optional {
+                findings.push(finding(
+                    Severity::Medium,
+                    format!("Missing {standard} function"),
+                    format!(
+                        "`{name}` claims {standard} ({claimed_by}) but has no external \
+                         `{}`, so integrations calling it revert.",
+                        function.signature()
+                    ),
+                    &info.def.loc,
+                ));
+            }
+            continue;
+        };
+        if callable.returns != function.returns {
+            let returns = match callable.returns.is_empty() {
+                true => "nothing".to_string(),
+                false => format!("`({})`", callable.returns.join(",")),
+            };
+            let expected = match function.returns.is_empty() {
+                true => "nothing".to_string(),
+                false => format!("`({})`", function.returns.join(",")),
+            };
+            findings.push(finding(
+                match function.optional {
+                    true => Severity::Low,
+                    false => Severity::Medium,
+                },
+                format!("Non-standard {standard} return type"),
+                format!(
+                    "`{name}.{}` returns {returns} where {standard} specifies {expected}; callers \
+                     decoding the standard return data revert or misread it.",
+                    function.signature()
+                ),
+                &callable.loc,
+            ));
+        }
+    }
+
+    for event in spec.events {
+        let declared = events(symbols, info, event.name);
+        if declared.iter().any(|e| matches_event(e, event)) {
+            continue;
+        }
+        let (title, detail) = match declared.is_empty() {
+            true => (format!("Missing {standard} event"), "does not declare"),
+            false => (
+                format!("Non-standard {standard} event"),
+                "declares a different",
+            ),
+        };
+        findings.push(finding(
+            Severity::Low,
+            title,
+            format!(
+                "`{name}` {detail} `{}`; indexers and wallets filtering on the standard event \
+                 miss its activity.",
+                event.signature()
+            ),
+            &info.def.loc,
+        ));
+    }
+
+    // Balances are what `balanceOf` reads.
+    let balances: BTreeSet<StateVarId> = match spec.transfer_events.is_empty() {
+        true => BTreeSet::new(),
+        false => {
+            let read = info
+                .functions
+                .iter()
+                .copied()
+                .filter(|&f| {
+                    let def = symbols.function(f);
+                    def.name == "balanceOf" && def.is_entry_point()
+                })
+                .flat_map(|f| {
+                    IrFunction::build(symbols, Some(info.id), f)
+                        .instructions()
+                        .filter_map(|(_, inst)| match &inst.op {
+                            Op::StorageRead { var, .. } => Some(*var),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>()
+                });
+            let public = info.state_variables.iter().copied().filter(|&var| {
+                let def = symbols.state_variable(var);
+                def.name == "balanceOf" && def.visibility.is_entry_point()
+            });
+            read.chain(public)
+                .filter(|&var| symbols.state_variable(var).type_name.starts_with("mapping"))
+                .collect()
+        }
+    };
+    for &func in &info.functions {
+        let def = symbols.function(func);
+        if !(def.is_entry_point() || def.kind == FunctionKind::Constructor) || def.is_view() {
+            continue;
+        }
+        let summary = analysis.summary(Some(info.id), func);
+        let function = format!("{name}.{}", def.display_name());
+        let changes_balances = summary.writes.iter().any(|var| balances.contains(var));
+        let required = spec
+            .emissions
+            .iter()
+            .filter(|(f, _)| *f == def.name)
+            .map(|(_, events)| (*events, "does not emit"))
+            .chain(
+                changes_balances
+                    .then_some((spec.transfer_events, "changes balances without emitting")),
+            );
+        for (events, what) in required {
+            if events.iter().any(|e| summary.emits.contains(*e)) {
+                continue;
+            }
+            let events: Vec<String> = events.iter().map(|e| format!("`{e}`")).collect();
+            findings.push(finding(
+                Severity::Medium,
+                format!("{standard} event not emitted"),
+                format!(
+                    "`{function}` {what} {}, as {standard} requires; off-chain accounting built \
+                     on the events drifts from the contract.",
+                    events.join(" or ")
+                ),
+                &def.loc,
+            ));
+        }
+    }
+
+    if standard == Standard::Erc4626 {
+        for &(conversion, up) in ROUNDING {
+            let Some(func) = callables(symbols, info, conversion)
+                .iter()
+                .find(|c| c.params == ["uint256"])
+                .and_then(|c| c.function)
+            else {
+                continue;
+            };
+            let rounds_up = analysis.summary(Some(info.id), func).rounds_up();
+            if rounds_up == Some(!up) {
+                let def = symbols.function(func);
+                findings.push(finding(
+                    Severity::Medium,
+                    "ERC4626 rounding favors the caller".into(),
+                    format!(
+                        "`{name}.{conversion}` rounds {} where ERC4626 requires rounding {}, in \
+                         favour of the vault; repeated small operations then extract value \
+                         from other depositors.",
+                        if up { "down" } else { "up" },
+                        if up { "up" } else { "down" }
+                    ),
+                    &def.loc,
+                ));
+            }
+        }
+    }
+    findings
+}
+
+/// Standards each deployable contract claims, and whether it conforms.
+pub fn conformance(symbols: &SymbolTable) -> Vec<Conformance> {
+    analyze(symbols)
+        .into_iter()
+        .map(|(report, _)| report)
+        .collect()
+}
+
+/// Deviations from the token standards contracts claim to implement.
+pub struct ErcConformance;
+
+impl Detector for ErcConformance {
+    fn id(&self) -> &str {
+        "erc-conformance"
+    }
+
+    fn description(&self) -> &str {
+        "Tokens deviating from the ERC20, ERC721, ERC1155 or ERC4626 standard they claim"
+    }
+
+    fn cwe(&self) -> &[&str] {
+        &["CWE-573"]
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Medium
+    }
+
+    fn run(&self, symbols: &SymbolTable) -> Vec<Finding> {
+        analyze(symbols)
+            .into_iter()
+            .flat_map(|(_, findings)| findings)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Project, ProjectBundle};
+
+    fn reports(src: &str) -> Vec<(Conformance, Vec<Finding>)> {
+        let project = Project::load(&ProjectBundle::single("Test.sol", src));
+        let symbols = SymbolTable::build(&project);
+        analyze(&symbols)
+    }
+
+    #[test]
+    fn test_claims() {
+        assert!(names_standard("IERC20Metadata", Standard::Erc20));
+        assert!(names_standard("MockERC1155", Standard::Erc1155));
+        assert!(!names_standard("ERC2098", Standard::Erc20));
+        assert!(!names_standard("IERC721Receiver", Standard::Erc721));
+        assert_eq!(abi_type("IERC20[]"), "address[]");
+        assert_eq!(abi_type("uint"), "uint256");
+
+        let reports = reports(
+            "contract Points {
+                mapping(address => uint256) public balanceOf;
+                mapping(address => mapping(address => uint256)) public allowance;
+                function transfer(address to, uint256 amount) external returns (bool) {}
+                function approve(address spender, uint256 amount) external returns (bool) {}
+             }
+             contract Registry { mapping(uint256 => address) public ownerOf; }",
+        );
+        let claims: Vec<(&str, Standard, &str)> = reports
+            .iter()
+            .map(|(r, _)| (r.contract.as_str(), r.standard, r.claimed_by.as_str()))
+            .collect();
+        assert_eq!(
+            claims,
+            [("Points", Standard::Erc20, "implements 4 of its 6 functions")]
+        );
+    }
+
+    #[test]
+    fn test_erc20_conformance() {
+        let reports = reports(
+            "interface IERC20 {
+                event Transfer(address indexed from, address indexed to, uint256 value);
+                event Approval(address indexed owner, address indexed spender, uint256 value);
+             }
+             contract Tether is IERC20 {
+                uint256 public totalSupply;
+                uint256 public decimals = 6;
+                mapping(address => uint256) balances;
+                mapping(address => mapping(address => uint256)) public allowance;
+                function balanceOf(address who) external view returns (uint256) { return balances[who]; }
+                function transfer(address to, uint256 value) external {
+                    balances[msg.sender] -= value;
+                    balances[to] += value;
+                    emit Transfer(msg.sender, to, value);
+                }
+                function transferFrom(address from, address to, uint256 value) external returns (bool) {
+                    allowance[from][msg.sender] -= value;
+                    _move(from, to, value);
+                    return true;
+                }
+                function approve(address spender, uint256 value) external returns (bool) {
+                    allowance[msg.sender][spender] = value;
+                    return true;
+                }
+                function issue(uint256 amount) external {
+                    balances[msg.sender] += amount;
+                    totalSupply += amount;
+                }
+                function _move(address from, address to, uint256 value) internal {
+                    balances[from] -= value;
+                    balances[to] += value;
+                    emit Transfer(from, to, value);
+                }
+             }",
+        );
+        assert_eq!(reports.len(), 1);
+        let (report, findings) = &reports[0];
+        assert_eq!(report.claimed_by, "inherits `IERC20`");
+        let described: Vec<(Severity, &str)> = findings
+            .iter()
+            .map(|f| (f.severity, f.description.split(';').next().unwrap()))
+            .collect();
+        assert_eq!(
+            described,
+            [
+                (
+                    Severity::Medium,
+                    "`Tether.transfer(address,uint256)` returns nothing where ERC20 specifies `(bool)`"
+                ),
+                (
+                    Severity::Low,
+                    "`Tether.decimals()` returns `(uint256)` where ERC20 specifies `(uint8)`"
+                ),
+                (
+                    Severity::Medium,
+                    "`Tether.approve` does not emit `Approval`, as ERC20 requires"
+                ),
+                (
+                    Severity::Medium,
+                    "`Tether.issue` changes balances without emitting `Transfer`, as ERC20 requires"
+                ),
+            ]
+        );
+        assert_eq!(report.issues.len(), 4);
+    }
+
+    #[test]
+    fn test_erc4626_rounding() {
+        let reports = reports(
+            "library Math {
+                function mulDivDown(uint256 x, uint256 y, uint256 d) internal pure returns (uint256) { return x * y / d; }
+                function mulDivUp(uint256 x, uint256 y, uint256 d) internal pure returns (uint256) { return (x * y + d - 1) / d; }
+             }
+             abstract contract ERC4626 {
+                using Math for uint256;
+                uint256 public totalSupply;
+                uint256 internal assets;
+                function totalAssets() public view returns (uint256) { return assets; }
+                function convertToShares(uint256 a) public view returns (uint256) { return a.mulDivDown(totalSupply, assets); }
+                function convertToAssets(uint256 s) public view returns (uint256) { return s.mulDivDown(assets, totalSupply); }
+                function previewDeposit(uint256 a) public view returns (uint256) { return convertToShares(a); }
+                function previewRedeem(uint256 s) public view returns (uint256) { return convertToAssets(s); }
+                function previewWithdraw(uint256 a) public view returns (uint256) { return a.mulDivUp(totalSupply, assets); }
+             }
+             contract Vault is ERC4626 {
+                function previewMint(uint256 s) public view returns (uint256) { return s.mulDivDown(assets, totalSupply); }
+             }",
+        );
+        let (report, findings) = reports
+            .iter()
+            .find(|(r, _)| r.standard == Standard::Erc4626)
+            .unwrap();
+        assert_eq!(report.contract, "Vault");
+        let rounding: Vec<&str> = findings
+            .iter()
+            .filter(|f| f.title == "ERC4626 rounding favors the caller")
+            .map(|f| f.description.split(" where").next().unwrap())
+            .collect();
+        assert_eq!(rounding, ["`Vault.previewMint` rounds down"]);
+        // ERC20 is implied by ERC4626.
+        assert_eq!(reports[0].0.standard, Standard::Erc20);
+    }
+}
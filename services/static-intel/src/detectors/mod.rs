@@ -0,0 +1,351 @@
+//! Vulnerability detectors and the registry jobs select them from.
+//!
+//! Every detector implements [`Detector`]. The service runs the built-in set
+//! ([`Registry::builtin`]); in-house detectors live in their own crates and are
+//! added with [`Registry::register`] without touching this one.
+
+pub mod access_control;
+pub mod arithmetic;
+pub mod erc;
+pub mod oracle;
+pub mod primitives;
+pub mod reentrancy;
+pub mod unchecked;
+pub mod upgradeability;
+pub mod vault;
+
+use solang_parser::pt::{CodeLocation, Loc};
+
+use crate::finding::{sarif, Finding, Severity, SOURCE_ENGINE};
+use crate::layout::StorageLayoutCheck;
+use crate::source::Location;
+use crate::symbols::SymbolTable;
+use crate::taint;
+
+/// Most source lines copied into a finding's `code_snippet`.
+const SNIPPET_LINES: usize = 5;
+
+pub trait Detector: Send + Sync {
+    /// Stable identifier used in job payloads, e.g. `reentrancy`.
+    fn id(&self) -> &str;
+
+    /// One-line summary of what the detector looks for.
+    fn description(&self) -> &str {
+        self.id()
+    }
+
+    /// SWC registry entries the detector covers, e.g. `SWC-107`.
+    fn swc(&self) -> &[&str] {
+        &[]
+    }
+
+    /// CWE entries the detector covers, e.g. `CWE-841`.
+    fn cwe(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Severity of the most serious finding the detector reports.
+    fn severity(&self) -> Severity;
+
+    fn run(&self, symbols: &SymbolTable) -> Vec<Finding>;
+}
+
+/// Caller-controlled call targets and storage array indices ([`taint::report`]).
+pub struct UserInput;
+
+impl Detector for UserInput {
+    fn id(&self) -> &str {
+        "user-input"
+    }
+
+    fn description(&self) -> &str {
+        "Caller-controlled call targets and storage array indices"
+    }
+
+    fn swc(&self) -> &[&str] {
+        &["SWC-124"]
+    }
+
+    fn cwe(&self) -> &[&str] {
+        &["CWE-20"]
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Medium
+    }
+
+    fn run(&self, symbols: &SymbolTable) -> Vec<Finding> {
+        taint::report(symbols)
+    }
+}
+
+/// Whether a job naming `enable` (everything when empty) and `disable` runs `id`.
+pub fn is_selected(id: &str, enable: &[String], disable: &[String]) -> bool {
+    (enable.is_empty() || enable.iter().any(|e| e == id)) && !disable.iter().any(|d| d == id)
+}
+
+/// SARIF description of static-intel with `detectors` as its rules.
+pub fn sarif_tool<'a>(detectors: impl IntoIterator<Item = &'a dyn Detector>) -> sarif::Tool {
+    sarif::Tool {
+        name: SOURCE_ENGINE.to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        information_uri: Some("https://github.com/VolodymyrStetsenko/rukh".into()),
+        rules: detectors
+            .into_iter()
+            .map(|d| sarif::Rule {
+                id: d.id().to_string(),
+                description: d.description().to_string(),
+                severity: d.severity(),
+                help_uri: references(d.swc(), d.cwe()).into_iter().next(),
+                tags: d
+                    .swc()
+                    .iter()
+                    .chain(d.cwe())
+                    .map(|t| t.to_string())
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+/// Detectors by id, in registration order.
+#[derive(Default)]
+pub struct Registry {
+    detectors: Vec<Box<dyn Detector>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The detectors shipped with static-intel.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(UserInput));
+        registry.register(Box::new(reentrancy::Reentrancy));
+        registry.register(Box::new(access_control::AccessControl));
+        registry.register(Box::new(primitives::Primitives));
+        registry.register(Box::new(unchecked::UncheckedCalls));
+        registry.register(Box::new(arithmetic::Arithmetic));
+        registry.register(Box::new(upgradeability::Upgradeability));
+        registry.register(Box::new(erc::ErcConformance));
+        registry.register(Box::new(oracle::PriceOracle));
+        registry.register(Box::new(vault::VaultInflation));
+        registry.register(Box::new(StorageLayoutCheck::default()));
+        registry
+    }
+
+    /// Adds `detector`, replacing a registered one with the same id.
+    pub fn register(&mut self, detector: Box<dyn Detector>) {
+        match self.detectors.iter_mut().find(|d| d.id() == detector.id()) {
+            Some(slot) => *slot = detector,
+            None => self.detectors.push(detector),
+        }
+    }
+
+    pub fn detectors(&self) -> impl Iterator<Item = &dyn Detector> {
+        self.detectors.iter().map(|d| d.as_ref())
+    }
+
+    pub fn get(&self, id: &str) -> Option<&dyn Detector> {
+        self.detectors().find(|d| d.id() == id)
+    }
+
+    /// Detectors a job asked for: those named in `enable` (all when empty),
+    /// minus those in `disable`.
+    pub fn select(&self, enable: &[String], disable: &[String]) -> Vec<&dyn Detector> {
+        self.detectors()
+            .filter(|d| is_selected(d.id(), enable, disable))
+            .collect()
+    }
+
+    /// Findings of the selected detectors, in registration order, completed
+    /// with [`complete`].
+    pub fn run(
+        &self,
+        symbols: &SymbolTable,
+        enable: &[String],
+        disable: &[String],
+    ) -> Vec<Finding> {
+        self.run_with(symbols, enable, disable, &[])
+    }
+
+    /// [`run`](Self::run) with `overrides` in place of the registered
+    /// detectors of the same id, for detectors configured by the job.
+    pub fn run_with(
+        &self,
+        symbols: &SymbolTable,
+        enable: &[String],
+        disable: &[String],
+        overrides: &[&dyn Detector],
+    ) -> Vec<Finding> {
+        self.select(enable, disable)
+            .into_iter()
+            .map(|d| {
+                overrides
+                    .iter()
+                    .copied()
+                    .find(|o| o.id() == d.id())
+                    .unwrap_or(d)
+            })
+            .flat_map(|d| {
+                let mut findings = d.run(symbols);
+                for finding in &mut findings {
+                    complete(finding, d, symbols);
+                }
+                findings
+            })
+            .collect()
+    }
+}
+
+/// Registry pages of SWC and CWE ids.
+fn references(swc: &[impl AsRef<str>], cwe: &[impl AsRef<str>]) -> Vec<String> {
+    swc.iter()
+        .map(|id| format!("https://swcregistry.io/docs/{}", id.as_ref()))
+        .chain(cwe.iter().filter_map(|id| {
+            let number = id.as_ref().strip_prefix("CWE-")?;
+            Some(format!(
+                "https://cwe.mitre.org/data/definitions/{number}.html"
+            ))
+        }))
+        .collect()
+}
+
+/// Fills in what every finding of `detector` shares: the check name, engine,
+/// SWC/CWE ids with their references, and the source lines of the location.
+pub fn complete(finding: &mut Finding, detector: &dyn Detector, symbols: &SymbolTable) {
+    if finding.check_name.is_empty() {
+        finding.check_name = detector.id().to_string();
+    }
+    finding.source_engine = SOURCE_ENGINE.to_string();
+    let extra = &mut finding.extra_data;
+    if extra.swc.is_empty() {
+        extra.swc = detector.swc().iter().map(|id| id.to_string()).collect();
+    }
+    if extra.cwe.is_empty() {
+        extra.cwe = detector.cwe().iter().map(|id| id.to_string()).collect();
+    }
+    if finding.references.is_empty() {
+        finding.references = references(&extra.swc, &extra.cwe);
+    }
+    if finding.code_snippet.is_none() {
+        finding.code_snippet = finding
+            .location
+            .as_ref()
+            .and_then(|location| symbols.project.sources.lines(location, SNIPPET_LINES))
+            .map(str::to_string);
+    }
+    if finding.extra_data.anchor.is_none() {
+        finding.extra_data.anchor = finding
+            .location
+            .as_ref()
+            .and_then(|location| anchor(symbols, location));
+    }
+}
+
+/// `Contract.signature(...)` of the innermost function enclosing `location`,
+/// or the name of the enclosing contract.
+fn anchor(symbols: &SymbolTable, location: &Location) -> Option<String> {
+    // Lines spanned by `loc` if it encloses `location`.
+    let enclosing = |loc: &Loc| {
+        let outer = symbols.project.location(loc)?;
+        (outer.file == location.file
+            && (outer.line, outer.column) <= (location.line, location.column)
+            && (location.end_line, location.end_column) <= (outer.end_line, outer.end_column))
+            .then_some(outer.end_line - outer.line)
+    };
+    let function = symbols
+        .all_functions()
+        .into_iter()
+        .filter_map(|func| {
+            let def = symbols.function(func);
+            let loc = match &def.body {
+                Some(body) => def.loc.with_end_from(&body.loc()),
+                None => def.loc,
+            };
+            let lines = enclosing(&loc)?;
+            let name = match func.contract() {
+                Some(contract) => {
+                    format!("{}.{}", symbols.contracts[contract].name(), def.signature())
+                }
+                None => def.signature(),
+            };
+            Some((lines, name))
+        })
+        .min_by_key(|(lines, _)| *lines);
+    match function {
+        Some((_, name)) => Some(name),
+        None => symbols
+            .contracts
+            .iter()
+            .find(|info| enclosing(&info.def.loc).is_some())
+            .map(|info| info.name().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finding::Confidence;
+    use crate::project::{Project, ProjectBundle};
+
+    struct Marker;
+
+    impl Detector for Marker {
+        fn id(&self) -> &str {
+            "marker"
+        }
+
+        fn severity(&self) -> Severity {
+            Severity::Informational
+        }
+
+        fn run(&self, symbols: &SymbolTable) -> Vec<Finding> {
+            symbols
+                .contracts
+                .iter()
+                .map(|info| Finding {
+                    severity: Severity::Informational,
+                    confidence: Confidence::High,
+                    title: format!("Saw {}", info.name()),
+                    description: String::new(),
+                    location: None,
+                    ..Default::default()
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_registry_selection() {
+        let mut registry = Registry::builtin();
+        registry.register(Box::new(Marker));
+        let ids = |selected: Vec<&dyn Detector>| -> Vec<String> {
+            selected.iter().map(|d| d.id().to_string()).collect()
+        };
+        assert_eq!(registry.get("reentrancy").unwrap().swc(), ["SWC-107"]);
+        assert_eq!(
+            ids(registry.select(&["marker".into(), "reentrancy".into()], &[])),
+            ["reentrancy", "marker"]
+        );
+        assert_eq!(registry.select(&[], &["marker".into()]).len(), 11);
+
+        let project = Project::load(&ProjectBundle::single("A.sol", "contract A {}"));
+        let symbols = SymbolTable::build(&project);
+        let findings = registry.run(&symbols, &["marker".into()], &[]);
+        assert_eq!(findings[0].title, "Saw A");
+        assert_eq!(findings[0].check_name, "marker");
+        assert_eq!(findings[0].source_engine, "static-intel");
+
+        let tool = sarif_tool(registry.select(&["reentrancy".into()], &[]));
+        let log = sarif::to_sarif(&tool, &findings);
+        let rules = &log["runs"][0]["tool"]["driver"]["rules"];
+        assert_eq!(
+            (&rules[0]["id"], &rules[1]["id"]),
+            (&"reentrancy".into(), &"marker".into())
+        );
+        assert_eq!(rules[0]["helpUri"], "https://swcregistry.io/docs/SWC-107");
+    }
+}
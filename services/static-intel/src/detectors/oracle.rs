@@ -0,0 +1,756 @@
+//! Price oracle misuse: spot prices read from AMM pools (`getReserves`,
+//! `slot0`) feeding value calculations, Chainlink answers used without
+//! staleness or sanity checks, Chainlink feeds read on an L2 without checking
+//! the sequencer, and accounting on the contract's own token or ether balance.
+//!
+//! Reads are followed through each function's values and, with parameters
+//! substituted, through the internal functions it calls. A read counts as
+//! used in a calculation once it reaches a multiplication or division
+//! (`mulDiv` included); a spot price also counts when an entry point returns
+//! it, as it is then an oracle for others. Comparisons alone are not uses.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use solang_parser::pt::Loc;
+
+use crate::ast::ContractKind;
+use crate::detectors::Detector;
+use crate::finding::{Confidence, Finding, Severity};
+use crate::ir::{BinaryOp, EnvVar, IrFunction, Op, Operand};
+use crate::project;
+use crate::symbols::{ContractId, FunctionId, SymbolTable};
+
+/// Pool and router functions returning prices derived from current reserves.
+const SPOT_FUNCTIONS: &[&str] = &["getReserves", "slot0", "getAmountsOut", "getAmountsIn"];
+
+/// Source text suggesting the project is deployed on a rollup.
+const L2_MARKERS: &[&str] = &["sequencer", "arbitrum", "arbsys", "optimism"];
+
+/// Components of the `latestRoundData` result.
+const ROUND_ID: usize = 0;
+const ANSWER: usize = 1;
+const STARTED_AT: usize = 2;
+const UPDATED_AT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReadKind {
+    /// Price derived from a pool's current reserves.
+    Spot,
+    /// `token.balanceOf(address(this))` or `address(this).balance`.
+    Balance,
+}
+
+/// A price or balance read, in the function it is written in.
+#[derive(Debug, Clone)]
+struct Read {
+    kind: ReadKind,
+    function: FunctionId,
+    loc: Loc,
+    call: String,
+}
+
+/// A Chainlink feed read and what the reading function checks of it.
+#[derive(Debug, Clone)]
+struct Feed {
+    function: FunctionId,
+    loc: Loc,
+    /// `latestAnswer`, which carries no timestamp to check.
+    deprecated: bool,
+    /// `updatedAt` (or `startedAt`) is compared against `block.timestamp`.
+    fresh: bool,
+    /// The answer or round is compared, e.g. `answer > 0`.
+    validated: bool,
+}
+
+/// What a value depends on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Atom {
+    /// Parameter of the summarised function, substituted at call sites.
+    Param(usize),
+    /// Index into [`Analysis::reads`].
+    Read(usize),
+    Timestamp,
+    /// Result of the function's `i`-th feed read.
+    Feed(usize),
+    /// Component of the function's `i`-th feed read.
+    Field(usize, usize),
+}
+
+impl Atom {
+    /// Atoms meaningful outside the function that produced them.
+    fn is_portable(&self) -> bool {
+        matches!(self, Atom::Param(_) | Atom::Read(_))
+    }
+}
+
+type Atoms = BTreeSet<Atom>;
+
+#[derive(Debug, Clone, Default)]
+struct Summary {
+    /// Dependencies of the returned values.
+    returns: Atoms,
+    /// Dependencies of values multiplied or divided.
+    math: Atoms,
+    /// Feeds read by the function and the internal functions it calls.
+    feeds: Vec<Feed>,
+    /// The function or one it calls looks at an L2 sequencer.
+    sequencer: bool,
+}
+
+type Key = (Option<ContractId>, FunctionId);
+
+struct Analysis<'s, 'p> {
+    symbols: &'s SymbolTable<'p>,
+    reads: Vec<Read>,
+    read_ids: HashMap<Loc, usize>,
+    summaries: HashMap<Key, Summary>,
+    active: HashSet<Key>,
+}
+
+fn operand_deps(deps: &[Atoms], operand: &Operand) -> Atoms {
+    operand.value().map(|v| deps[v].clone()).unwrap_or_default()
+}
+
+/// Replaces parameter atoms of a callee by the atoms of the call arguments.
+fn substitute(atoms: &Atoms, args: &[Atoms]) -> Atoms {
+    atoms
+        .iter()
+        .flat_map(|atom| match atom {
+            Atom::Param(index) => args.get(*index).cloned().unwrap_or_default(),
+            other => Atoms::from([*other]),
+        })
+        .collect()
+}
+
+fn mentions_sequencer(name: &str) -> bool {
+    name.to_lowercase().contains("sequencer")
+}
+
+/// Whether `operand` is `this` or `address(this)`.
+fn is_this(ir: &IrFunction, operand: &Operand) -> bool {
+    let Some(value) = operand.value() else {
+        return false;
+    };
+    match &ir.def(value).op {
+        Op::Env(EnvVar::This) => true,
+        Op::Copy(a) | Op::Conversion { value: a, .. } => is_this(ir, a),
+        _ => false,
+    }
+}
+
+impl Analysis<'_, '_> {
+    fn summary(&mut self, context: Option<ContractId>, func: FunctionId) -> Summary {
+        let key = (context, func);
+        if let Some(summary) = self.summaries.get(&key) {
+            return summary.clone();
+        }
+        if !self.active.insert(key) {
+            return Summary::default();
+        }
+        let summary = self.compute(context, func);
+        self.active.remove(&key);
+        self.summaries.insert(key, summary.clone());
+        summary
+    }
+
+    fn read(&mut self, kind: ReadKind, function: FunctionId, loc: Loc, call: &str) -> Atoms {
+        let next = self.reads.len();
+        let id = *self.read_ids.entry(loc).or_insert(next);
+        if id == next {
+            self.reads.push(Read {
+                kind,
+                function,
+                loc,
+                call: call.to_string(),
+            });
+        }
+        Atoms::from([Atom::Read(id)])
+    }
+
+    /// Whether `operand` is loaded from a variable named after the sequencer.
+    fn is_sequencer(&self, ir: &IrFunction, operand: &Operand) -> bool {
+        let Some(value) = operand.value() else {
+            return false;
+        };
+        if let Some(local) = ir.values[value].local {
+            if mentions_sequencer(&ir.locals[local].name) {
+                return true;
+            }
+        }
+        match &ir.def(value).op {
+            Op::StorageRead { var, .. } | Op::StateConstant(var) => {
+                mentions_sequencer(&self.symbols.state_variable(*var).name)
+            }
+            Op::Copy(a) | Op::Conversion { value: a, .. } => self.is_sequencer(ir, a),
+            _ => false,
+        }
+    }
+
+    fn compute(&mut self, context: Option<ContractId>, func: FunctionId) -> Summary {
+        let ir = IrFunction::build(self.symbols, context, func);
+        let entry = self.symbols.function(func).is_entry_point();
+        let mut deps: Vec<Atoms> = vec![Atoms::new(); ir.values.len()];
+        let mut feeds: Vec<Feed> = Vec::new();
+        let mut feed_ids: HashMap<Loc, usize> = HashMap::new();
+
+        // Values only flow forward except through phis, so a few passes settle loops.
+        for _ in 0..4 {
+            let mut changed = false;
+            for (block, inst) in ir.instructions() {
+                let Some(dst) = inst.dst else {
+                    continue;
+                };
+                let function = ir.cfg.node(block).origin;
+                let atoms = match &inst.op {
+                    Op::ExternalCall {
+                        name, args, target, ..
+                    } if (name == "latestRoundData" || name == "latestAnswer")
+                        && args.is_empty() =>
+                    {
+                        let next = feeds.len();
+                        let id = *feed_ids.entry(inst.loc).or_insert(next);
+                        if id == next {
+                            // The sequencer uptime feed is checked through its
+                            // answer and `startedAt`, not for staleness.
+                            let sequencer = self.is_sequencer(&ir, target);
+                            feeds.push(Feed {
+                                function,
+                                loc: inst.loc,
+                                deprecated: name == "latestAnswer",
+                                fresh: sequencer,
+                                validated: sequencer,
+                            });
+                        }
+                        let component = (name == "latestAnswer").then_some(ANSWER);
+                        Atoms::from([match component {
+                            Some(index) => Atom::Field(id, index),
+                            None => Atom::Feed(id),
+                        }])
+                    }
+                    Op::ExternalCall { name, .. } if SPOT_FUNCTIONS.contains(&name.as_str()) => {
+                        self.read(ReadKind::Spot, function, inst.loc, name)
+                    }
+                    Op::ExternalCall { name, args, .. }
+                        if name == "balanceOf" && args.len() == 1 && is_this(&ir, &args[0]) =>
+                    {
+                        self.read(
+                            ReadKind::Balance,
+                            function,
+                            inst.loc,
+                            "balanceOf(address(this))",
+                        )
+                    }
+                    Op::Balance(target) if is_this(&ir, target) => self.read(
+                        ReadKind::Balance,
+                        function,
+                        inst.loc,
+                        "address(this).balance",
+                    ),
+                    _ => self.transfer(context, &inst.op, &deps),
+                };
+                if atoms != deps[dst] {
+                    deps[dst] = atoms;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let mut summary = Summary {
+            sequencer: ir.locals.iter().any(|l| mentions_sequencer(&l.name)),
+            ..Default::default()
+        };
+        // Feeds whose components the function hands to a caller or callee to check.
+        let delegated = |atoms: &Atoms, feeds: &mut Vec<Feed>| {
+            for atom in atoms {
+                match *atom {
+                    Atom::Feed(id) => {
+                        feeds[id].fresh = true;
+                        feeds[id].validated = true;
+                    }
+                    Atom::Field(id, UPDATED_AT) => feeds[id].fresh = true,
+                    Atom::Field(id, ANSWER) => feeds[id].validated = true,
+                    _ => {}
+                }
+            }
+        };
+        for (_, inst) in ir.instructions() {
+            match &inst.op {
+                Op::Binary(op, a, b) => {
+                    let atoms: Atoms = operand_deps(&deps, a)
+                        .union(&operand_deps(&deps, b))
+                        .copied()
+                        .collect();
+                    if matches!(op, BinaryOp::Mul | BinaryOp::Div) {
+                        summary
+                            .math
+                            .extend(atoms.iter().filter(|a| a.is_portable()));
+                    } else if op.is_comparison() {
+                        let timestamp = atoms.contains(&Atom::Timestamp);
+                        for atom in &atoms {
+                            match *atom {
+                                Atom::Field(id, UPDATED_AT | STARTED_AT) if timestamp => {
+                                    feeds[id].fresh = true
+                                }
+                                Atom::Field(id, ANSWER | ROUND_ID) => feeds[id].validated = true,
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+                Op::StorageRead { var, .. } | Op::StateConstant(var)
+                    if mentions_sequencer(&self.symbols.state_variable(*var).name) =>
+                {
+                    summary.sequencer = true;
+                }
+                Op::ExternalCall { name, .. } if mentions_sequencer(name) => {
+                    summary.sequencer = true;
+                }
+                Op::InternalCall { callee, name, args }
+                | Op::LibraryCall {
+                    callee, name, args, ..
+                } => {
+                    let args: Vec<Atoms> = args.iter().map(|a| operand_deps(&deps, a)).collect();
+                    for arg in &args {
+                        delegated(arg, &mut feeds);
+                    }
+                    if mentions_sequencer(name) {
+                        summary.sequencer = true;
+                    }
+                    // `mulDiv` is usually written in assembly.
+                    if name.to_lowercase().contains("muldiv") {
+                        for arg in &args {
+                            summary.math.extend(arg.iter().filter(|a| a.is_portable()));
+                        }
+                    }
+                    if let Some(callee) = callee {
+                        let callee_context = self.symbols.dispatch_context(context, *callee);
+                        let inner = self.summary(callee_context, *callee);
+                        summary.math.extend(substitute(&inner.math, &args));
+                        summary.feeds.extend(inner.feeds);
+                        summary.sequencer |= inner.sequencer;
+                    }
+                }
+                Op::Return(values) => {
+                    for value in values {
+                        let atoms = operand_deps(&deps, value);
+                        // Entry points hand the answer to callers who trust it.
+                        if !entry {
+                            delegated(&atoms, &mut feeds);
+                        }
+                        summary
+                            .returns
+                            .extend(atoms.into_iter().filter(Atom::is_portable));
+                    }
+                }
+                _ => {}
+            }
+        }
+        summary.feeds.extend(feeds);
+        summary
+    }
+
+    /// Dependencies of the value defined by `op`.
+    fn transfer(&mut self, context: Option<ContractId>, op: &Op, deps: &[Atoms]) -> Atoms {
+        let union = |operands: Vec<&Operand>| -> Atoms {
+            operands
+                .into_iter()
+                .flat_map(|o| operand_deps(deps, o))
+                .collect()
+        };
+        match op {
+            Op::Param(index) => Atoms::from([Atom::Param(*index)]),
+            Op::Env(EnvVar::BlockTimestamp) => Atoms::from([Atom::Timestamp]),
+            Op::Extract(a, index) => operand_deps(deps, a)
+                .into_iter()
+                .map(|atom| match atom {
+                    Atom::Feed(id) => Atom::Field(id, *index),
+                    other => other,
+                })
+                .collect(),
+            Op::Binary(op, a, b) => {
+                if op.is_comparison() {
+                    return Atoms::new();
+                }
+                let (da, db) = (operand_deps(deps, a), operand_deps(deps, b));
+                let atoms = da.union(&db).copied();
+                // `balanceAfter - balanceBefore` measures what was received.
+                let is_balance = |atoms: &Atoms| {
+                    atoms
+                        .iter()
+                        .any(|atom| matches!(atom, Atom::Read(id) if self.reads[*id].kind == ReadKind::Balance))
+                };
+                if *op == BinaryOp::Sub && is_balance(&da) && is_balance(&db) {
+                    atoms
+                        .filter(|atom| !matches!(atom, Atom::Read(id) if self.reads[*id].kind == ReadKind::Balance))
+                        .collect()
+                } else {
+                    atoms.collect()
+                }
+            }
+            Op::InternalCall {
+                callee: Some(callee),
+                args,
+                ..
+            }
+            | Op::LibraryCall {
+                callee: Some(callee),
+                args,
+                ..
+            } => {
+                let callee_context = self.symbols.dispatch_context(context, *callee);
+                let inner = self.summary(callee_context, *callee);
+                let args: Vec<Atoms> = args.iter().map(|a| operand_deps(deps, a)).collect();
+                let mut atoms = substitute(&inner.returns, &args);
+                // Helpers such as `mulDiv` in assembly return nothing traceable.
+                if inner.returns.is_empty() {
+                    atoms.extend(args.into_iter().flatten());
+                }
+                atoms
+            }
+            Op::ExternalCall { .. }
+            | Op::StorageRead { .. }
+            | Op::LowLevelCall { .. }
+            | Op::New { .. } => Atoms::new(),
+            other => union(other.operands()),
+        }
+    }
+}
+
+/// Whether the project's own sources mention a rollup, making sequencer
+/// downtime a concern. Dependencies do not count: OpenZeppelin ships
+/// `vendor/optimism` to mainnet projects too.
+fn targets_l2(symbols: &SymbolTable) -> bool {
+    symbols
+        .project
+        .sources
+        .files()
+        .iter()
+        .filter(|file| !project::is_dependency(&file.path))
+        .any(|file| {
+            let content = file.content.to_lowercase();
+            L2_MARKERS.iter().any(|marker| content.contains(marker))
+        })
+}
+
+/// AMM spot prices, unchecked Chainlink data and balance-based accounting.
+pub struct PriceOracle;
+
+impl Detector for PriceOracle {
+    fn id(&self) -> &str {
+        "oracle-manipulation"
+    }
+
+    fn description(&self) -> &str {
+        "AMM spot prices, unchecked Chainlink data and balance-based accounting"
+    }
+
+    fn cwe(&self) -> &[&str] {
+        &["CWE-345", "CWE-682"]
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::High
+    }
+
+    fn run(&self, symbols: &SymbolTable) -> Vec<Finding> {
+        detect(symbols)
+    }
+}
+
+pub fn detect(symbols: &SymbolTable) -> Vec<Finding> {
+    let mut analysis = Analysis {
+        symbols,
+        reads: Vec::new(),
+        read_ids: HashMap::new(),
+        summaries: HashMap::new(),
+        active: HashSet::new(),
+    };
+    let l2 = targets_l2(symbols);
+    let mut used = BTreeSet::new();
+    let mut feeds: Vec<Feed> = Vec::new();
+    // Feeds read through an entry point that never looks at the sequencer.
+    let mut unsequenced = HashSet::new();
+    for info in &symbols.contracts {
+        if info.def.kind == ContractKind::Interface {
+            continue;
+        }
+        for &func in &info.functions {
+            let summary = analysis.summary(Some(info.id), func);
+            let entry = symbols.function(func).is_entry_point();
+            for atom in &summary.math {
+                if let Atom::Read(id) = *atom {
+                    used.insert(id);
+                }
+            }
+            if entry {
+                for atom in &summary.returns {
+                    match *atom {
+                        Atom::Read(id) if analysis.reads[id].kind == ReadKind::Spot => {
+                            used.insert(id);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            for feed in summary.feeds {
+                if entry && !summary.sequencer {
+                    unsequenced.insert(feed.loc);
+                }
+                match feeds.iter_mut().find(|f| f.loc == feed.loc) {
+                    // A feed is fine if every function reading it checks it.
+                    Some(known) => {
+                        known.fresh &= feed.fresh;
+                        known.validated &= feed.validated;
+                    }
+                    None => feeds.push(feed),
+                }
+            }
+        }
+    }
+
+    let mut findings = Vec::new();
+    for id in used {
+        let read = &analysis.reads[id];
+        let name = symbols.qualified_name(read.function);
+        let (severity, title, description) = match read.kind {
+            ReadKind::Spot => (
+                Severity::High,
+                "Spot price used as oracle",
+                format!(
+                    "`{name}` derives a value from `{}`, the pool's current state. Anyone can move \
+                     it within one transaction, e.g. with a flash loan, and profit from the \
+                     mispriced calculation. Use a TWAP or an external oracle.",
+                    read.call
+                ),
+            ),
+            ReadKind::Balance => (
+                Severity::Medium,
+                "Accounting based on own balance",
+                format!(
+                    "`{name}` computes amounts from `{}`. Tokens or ether sent to the contract \
+                     directly, or borrowed with a flash loan and donated, change the result; \
+                     track deposits in storage instead.",
+                    read.call
+                ),
+            ),
+        };
+        findings.push(Finding {
+            severity,
+            confidence: Confidence::Medium,
+            title: title.into(),
+            description,
+            location: symbols.project.location(&read.loc),
+            ..Default::default()
+        });
+    }
+    for feed in &feeds {
+        let name = symbols.qualified_name(feed.function);
+        let location = symbols.project.location(&feed.loc);
+        if feed.deprecated {
+            findings.push(Finding {
+                severity: Severity::Medium,
+                confidence: Confidence::High,
+                title: "Stale Chainlink price".into(),
+                description: format!(
+                    "`{name}` reads the deprecated `latestAnswer`, which reports neither when the \
+                     answer was updated nor whether the round completed. Use `latestRoundData` \
+                     and check `updatedAt`."
+                ),
+                location: location.clone(),
+                ..Default::default()
+            });
+        } else if !feed.fresh {
+            findings.push(Finding {
+                severity: Severity::Medium,
+                confidence: Confidence::High,
+                title: "Stale Chainlink price".into(),
+                description: format!(
+                    "`{name}` uses `latestRoundData` without comparing `updatedAt` against \
+                     `block.timestamp`; a feed that stopped updating keeps returning its last \
+                     answer. Reject answers older than the feed's heartbeat."
+                ),
+                location: location.clone(),
+                ..Default::default()
+            });
+        }
+        if !feed.validated {
+            findings.push(Finding {
+                severity: Severity::Low,
+                confidence: Confidence::Medium,
+                title: "Unvalidated Chainlink answer".into(),
+                description: format!(
+                    "`{name}` never checks the Chainlink answer, so a zero or negative price is \
+                     used as is. Require `answer > 0`."
+                ),
+                location: location.clone(),
+                ..Default::default()
+            });
+        }
+        if l2 && unsequenced.contains(&feed.loc) {
+            findings.push(Finding {
+                severity: Severity::Medium,
+                confidence: Confidence::Low,
+                title: "Missing L2 sequencer uptime check".into(),
+                description: format!(
+                    "`{name}` reads a Chainlink feed without checking the sequencer uptime feed. \
+                     While an L2 sequencer is down, prices go stale and only some users can \
+                     transact; revert while it is down and for a grace period after."
+                ),
+                location,
+                ..Default::default()
+            });
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Project, ProjectBundle};
+
+    fn findings(src: &str) -> Vec<(String, usize)> {
+        let project = Project::load(&ProjectBundle::single("Test.sol", src));
+        let symbols = SymbolTable::build(&project);
+        detect(&symbols)
+            .into_iter()
+            .map(|f| (f.title, f.location.unwrap().line))
+            .collect()
+    }
+
+    #[test]
+    fn test_spot_price_and_balance() {
+        let findings = findings(
+            "interface IPair { function getReserves() external view returns (uint112, uint112, uint32); }
+             interface IERC20 { function balanceOf(address) external view returns (uint256); }
+             library FullMath {
+                function mulDiv(uint256 a, uint256 b, uint256 d) internal pure returns (uint256 r) {
+                    assembly { r := div(mul(a, b), d) }
+                }
+             }
+             contract Lender {
+                IPair pair;
+                IERC20 token;
+                uint256 totalShares;
+                function price() public view returns (uint256) {
+                    (uint112 r0, uint112 r1, ) = pair.getReserves();
+                    return uint256(r1) * 1e18 / r0;
+                }
+                function reserves() external view returns (uint112 r0) {
+                    (r0, , ) = pair.getReserves();
+                    require(r0 > 0);
+                }
+                function liquid() external view returns (bool) {
+                    (uint112 r0, , ) = pair.getReserves();
+                    return r0 > 1000;
+                }
+                function totalAssets() public view returns (uint256) {
+                    return token.balanceOf(address(this));
+                }
+                function deposit(uint256 assets) external returns (uint256) {
+                    uint256 before = token.balanceOf(address(this));
+                    uint256 received = token.balanceOf(address(this)) - before;
+                    return FullMath.mulDiv(received, totalShares, totalAssets());
+                }
+             }",
+        );
+        assert_eq!(
+            findings,
+            [
+                ("Spot price used as oracle".to_string(), 13),
+                ("Spot price used as oracle".to_string(), 17),
+                ("Accounting based on own balance".to_string(), 25),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chainlink_feeds() {
+        let findings = findings(
+            "interface AggregatorV3Interface {
+                function latestRoundData() external view returns (uint80, int256, uint256, uint256, uint80);
+                function latestAnswer() external view returns (int256);
+             }
+             contract PriceFeed {
+                AggregatorV3Interface feed;
+                AggregatorV3Interface sequencerUptimeFeed;
+                function spot() external view returns (uint256) {
+                    (, int256 answer, , , ) = feed.latestRoundData();
+                    return uint256(answer);
+                }
+                function legacy() external view returns (int256) {
+                    return feed.latestAnswer();
+                }
+                function checked() external view returns (uint256) {
+                    _checkSequencer();
+                    (, int256 answer, , uint256 updatedAt, ) = feed.latestRoundData();
+                    require(answer > 0);
+                    if (block.timestamp - updatedAt > 1 hours) revert();
+                    return uint256(answer);
+                }
+                function _checkSequencer() internal view {
+                    (, int256 up, uint256 startedAt, , ) = sequencerUptimeFeed.latestRoundData();
+                    require(up == 0 && block.timestamp - startedAt > 1 hours);
+                }
+             }",
+        );
+        assert_eq!(
+            findings,
+            [
+                ("Stale Chainlink price".to_string(), 9),
+                ("Unvalidated Chainlink answer".to_string(), 9),
+                ("Missing L2 sequencer uptime check".to_string(), 9),
+                ("Stale Chainlink price".to_string(), 13),
+                ("Unvalidated Chainlink answer".to_string(), 13),
+                ("Missing L2 sequencer uptime check".to_string(), 13),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_l2_markers_in_dependencies() {
+        let titles = |vendor: &str| {
+            let sources = [
+                (
+                    "src/Feed.sol",
+                    "interface AggregatorV3Interface {
+                        function latestRoundData() external view returns (uint80, int256, uint256, uint256, uint80);
+                     }
+                     contract Feed {
+                        AggregatorV3Interface feed;
+                        function price() external view returns (int256 answer) {
+                            uint256 updatedAt;
+                            (, answer, , updatedAt, ) = feed.latestRoundData();
+                            require(answer > 0 && block.timestamp - updatedAt < 1 hours);
+                        }
+                     }",
+                ),
+                (vendor, "interface ICrossDomainMessenger {} // Optimism"),
+            ];
+            let bundle = ProjectBundle::from_map(
+                sources
+                    .into_iter()
+                    .map(|(path, src)| (path.to_string(), src.to_string()))
+                    .collect(),
+                &[],
+            )
+            .unwrap();
+            let project = Project::load(&bundle);
+            let symbols = SymbolTable::build(&project);
+            detect(&symbols)
+                .into_iter()
+                .map(|f| f.title)
+                .collect::<Vec<_>>()
+        };
+        let vendored =
+            "lib/openzeppelin-contracts/contracts/vendor/optimism/ICrossDomainMessenger.sol";
+        assert!(titles(vendored).is_empty());
+        assert_eq!(
+            titles("src/bridge/ICrossDomainMessenger.sol"),
+            ["Missing L2 sequencer uptime check"]
+        );
+    }
+}
@@ -0,0 +1,437 @@
+//! Dangerous primitives: `tx.origin` authorization, `delegatecall` to targets
+//! callers control or can change, `selfdestruct` reachable without a caller
+//! check, and the deprecated `callcode`.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use solang_parser::pt::Loc;
+
+use crate::ast::{ContractKind, FunctionKind, VariableMutability};
+use crate::detectors::Detector;
+use crate::finding::{Confidence, ExtraData, Finding, Severity, TraceStep};
+use crate::guards::GuardAnalysis;
+use crate::ir::{CallKind, IrFunction, Op, Operand, ValueId};
+use crate::symbols::{ContractId, FunctionId, StateVarId, SymbolTable};
+use crate::taint::{SinkKind, TaintAnalysis, TaintFlow};
+
+/// A use of a primitive, in the function it is written in.
+#[derive(Debug, Clone)]
+struct Site {
+    function: FunctionId,
+    loc: Loc,
+    /// For `delegatecall`: state variables the target is loaded from.
+    vars: BTreeSet<StateVarId>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Summary {
+    /// State variables the returned values are loaded from.
+    returns: BTreeSet<StateVarId>,
+    writes: BTreeSet<StateVarId>,
+    delegatecalls: Vec<Site>,
+    selfdestructs: Vec<Site>,
+    callcodes: Vec<Site>,
+}
+
+type Key = (Option<ContractId>, FunctionId);
+
+struct Analysis<'s, 'p> {
+    symbols: &'s SymbolTable<'p>,
+    summaries: HashMap<Key, Summary>,
+    active: HashSet<Key>,
+}
+
+impl Analysis<'_, '_> {
+    fn summary(&mut self, context: Option<ContractId>, func: FunctionId) -> Summary {
+        let key = (context, func);
+        if let Some(summary) = self.summaries.get(&key) {
+            return summary.clone();
+        }
+        if !self.active.insert(key) {
+            return Summary::default();
+        }
+        let summary = self.compute(context, func);
+        self.active.remove(&key);
+        self.summaries.insert(key, summary.clone());
+        summary
+    }
+
+    fn compute(&mut self, context: Option<ContractId>, func: FunctionId) -> Summary {
+        let ir = IrFunction::build(self.symbols, context, func);
+        let mut summary = Summary::default();
+        for (block, inst) in ir.instructions() {
+            let site = |vars| Site {
+                function: ir.cfg.node(block).origin,
+                loc: inst.loc,
+                vars,
+            };
+            match &inst.op {
+                Op::StorageWrite { var, .. } => {
+                    summary.writes.insert(*var);
+                }
+                Op::LowLevelCall {
+                    kind: CallKind::DelegateCall,
+                    target,
+                    ..
+                } => {
+                    let vars = self.loaded_from(context, &ir, target, &mut BTreeSet::new());
+                    summary.delegatecalls.push(site(vars));
+                }
+                Op::LowLevelCall {
+                    kind: CallKind::CallCode,
+                    ..
+                } => summary.callcodes.push(site(BTreeSet::new())),
+                Op::Assembly { calls } if calls.iter().any(|c| c == "callcode") => {
+                    summary.callcodes.push(site(BTreeSet::new()))
+                }
+                Op::Selfdestruct(_) => summary.selfdestructs.push(site(BTreeSet::new())),
+                Op::Return(values) => {
+                    for value in values {
+                        let vars = self.loaded_from(context, &ir, value, &mut BTreeSet::new());
+                        summary.returns.extend(vars);
+                    }
+                }
+                Op::InternalCall {
+                    callee: Some(callee),
+                    ..
+                }
+                | Op::LibraryCall {
+                    callee: Some(callee),
+                    ..
+                } => {
+                    let inner =
+                        self.summary(self.symbols.dispatch_context(context, *callee), *callee);
+                    summary.writes.extend(inner.writes);
+                    summary.delegatecalls.extend(inner.delegatecalls);
+                    summary.selfdestructs.extend(inner.selfdestructs);
+                    summary.callcodes.extend(inner.callcodes);
+                }
+                _ => {}
+            }
+        }
+        summary
+    }
+
+    /// State variables `operand` is a copy or conversion of, through getters such
+    /// as `_implementation()`.
+    fn loaded_from(
+        &mut self,
+        context: Option<ContractId>,
+        ir: &IrFunction,
+        operand: &Operand,
+        seen: &mut BTreeSet<ValueId>,
+    ) -> BTreeSet<StateVarId> {
+        let Some(value) = operand.value() else {
+            return BTreeSet::new();
+        };
+        if !seen.insert(value) {
+            return BTreeSet::new();
+        }
+        match &ir.def(value).op {
+            Op::StorageRead { var, .. } | Op::StateConstant(var) => BTreeSet::from([*var]),
+            Op::Copy(a) | Op::Conversion { value: a, .. } => self.loaded_from(context, ir, a, seen),
+            Op::Select(_, a, b) => {
+                let mut vars = self.loaded_from(context, ir, a, seen);
+                vars.extend(self.loaded_from(context, ir, b, seen));
+                vars
+            }
+            Op::Phi(incoming) => {
+                let mut vars = BTreeSet::new();
+                for (_, operand) in incoming {
+                    vars.extend(self.loaded_from(context, ir, operand, seen));
+                }
+                vars
+            }
+            Op::InternalCall {
+                callee: Some(callee),
+                ..
+            } => {
+                self.summary(self.symbols.dispatch_context(context, *callee), *callee)
+                    .returns
+            }
+            _ => BTreeSet::new(),
+        }
+    }
+}
+
+/// `tx.origin` authorization, risky `delegatecall`, unprotected `selfdestruct` and `callcode`.
+pub struct Primitives;
+
+impl Detector for Primitives {
+    fn id(&self) -> &str {
+        "dangerous-primitives"
+    }
+
+    fn description(&self) -> &str {
+        "tx.origin authorization, risky delegatecall, unprotected selfdestruct and callcode"
+    }
+
+    fn swc(&self) -> &[&str] {
+        &["SWC-106", "SWC-111", "SWC-112", "SWC-115"]
+    }
+
+    fn cwe(&self) -> &[&str] {
+        &["CWE-284", "CWE-477", "CWE-829"]
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::High
+    }
+
+    fn run(&self, symbols: &SymbolTable) -> Vec<Finding> {
+        detect(symbols)
+    }
+}
+
+pub fn detect(symbols: &SymbolTable) -> Vec<Finding> {
+    let mut guards = GuardAnalysis::new(symbols);
+    let flows = TaintAnalysis::new(symbols).flows();
+    let mut analysis = Analysis {
+        symbols,
+        summaries: HashMap::new(),
+        active: HashSet::new(),
+    };
+    let step = |function: FunctionId, description: &str, loc: &Loc| TraceStep {
+        function: symbols.qualified_name(function),
+        description: description.to_string(),
+        location: symbols.project.location(loc),
+    };
+    // Unguarded caller-controlled flows into `sink`, by entry point and sink location.
+    let flow = |context: ContractId,
+                entry: FunctionId,
+                sink: SinkKind,
+                loc: Loc|
+     -> Option<&TaintFlow> {
+        flows.iter().find(|f| {
+            f.context == context && f.entry == entry && f.sink == sink && f.loc == loc && !f.guarded
+        })
+    };
+
+    let mut findings = Vec::new();
+    let mut reported = HashSet::new();
+    for info in &symbols.contracts {
+        if info.def.kind != ContractKind::Contract {
+            continue;
+        }
+        let context = info.id;
+        // State any function other than the constructor can change.
+        let mut mutable = BTreeSet::new();
+        for &func in &info.functions {
+            if symbols.function(func).kind != FunctionKind::Constructor {
+                mutable.extend(analysis.summary(Some(context), func).writes);
+            }
+        }
+
+        for &func in &info.functions {
+            let def = symbols.function(func);
+            if !def.is_entry_point() {
+                continue;
+            }
+            let entry = format!("{}.{}", info.name(), def.display_name());
+            let func_guards = guards.guards(Some(context), func);
+            let summary = analysis.summary(Some(context), func);
+
+            for guard in func_guards.iter().filter(|g| g.tx_origin) {
+                if !reported.insert(("tx-origin", guard.loc)) {
+                    continue;
+                }
+                let vars: Vec<String> = guard
+                    .vars
+                    .iter()
+                    .map(|&v| format!("`{}`", symbols.state_variable(v).name))
+                    .collect();
+                findings.push(Finding {
+                    severity: Severity::Medium,
+                    confidence: Confidence::High,
+                    title: "tx.origin used for authorization".into(),
+                    description: format!(
+                        "`{entry}` authorizes the caller by comparing `tx.origin` with {}. A contract \
+                         the authorized account interacts with can call in on its behalf; use \
+                         `msg.sender` instead.",
+                        vars.join(", ")
+                    ),
+                    location: symbols.project.location(&guard.loc),
+                    ..Default::default()
+                });
+            }
+
+            for site in &summary.delegatecalls {
+                if let Some(flow) = flow(context, func, SinkKind::DelegateCallTarget, site.loc) {
+                    if reported.insert(("delegatecall", site.loc)) {
+                        findings.push(Finding {
+                            severity: Severity::High,
+                            confidence: Confidence::High,
+                            title: "Delegatecall to user-controlled target".into(),
+                            description: format!(
+                                "Anyone can make `{entry}` delegatecall into code of their choosing \
+                                 ({} reaches the target), which runs with this contract's storage \
+                                 and balance.",
+                                flow.source.description()
+                            ),
+                            location: symbols.project.location(&site.loc),
+                            extra_data: ExtraData { trace: flow.trace.clone(), ..Default::default() },
+                            ..Default::default()
+                        });
+                    }
+                    continue;
+                }
+                let changeable: Vec<String> = site
+                    .vars
+                    .iter()
+                    .filter(|&&var| {
+                        symbols.state_variable(var).mutability == VariableMutability::Mutable
+                            && mutable.contains(&var)
+                    })
+                    .map(|&v| format!("`{}`", symbols.state_variable(v).name))
+                    .collect();
+                if !changeable.is_empty() && reported.insert(("delegatecall", site.loc)) {
+                    findings.push(Finding {
+                        severity: Severity::Medium,
+                        confidence: Confidence::Medium,
+                        title: "Delegatecall to mutable target".into(),
+                        description: format!(
+                            "`{entry}` delegatecalls the address stored in {}, which can be \
+                             changed after deployment; whoever controls it controls this \
+                             contract's storage and balance.",
+                            changeable.join(", ")
+                        ),
+                        location: symbols.project.location(&site.loc),
+                        extra_data: ExtraData {
+                            trace: vec![step(site.function, "delegatecall", &site.loc)],
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    });
+                }
+            }
+
+            if func_guards.is_empty() {
+                for site in &summary.selfdestructs {
+                    if !reported.insert(("selfdestruct", site.loc)) {
+                        continue;
+                    }
+                    let trace =
+                        match flow(context, func, SinkKind::SelfdestructBeneficiary, site.loc) {
+                            Some(flow) => flow.trace.clone(),
+                            None => vec![step(site.function, "selfdestruct", &site.loc)],
+                        };
+                    findings.push(Finding {
+                        severity: Severity::High,
+                        confidence: Confidence::High,
+                        title: "Unprotected selfdestruct".into(),
+                        description: format!(
+                            "Anyone can call `{entry}` to destroy the contract and send its \
+                             balance away."
+                        ),
+                        location: symbols.project.location(&site.loc),
+                        extra_data: ExtraData {
+                            trace,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    });
+                }
+            }
+
+            for site in &summary.callcodes {
+                if !reported.insert(("callcode", site.loc)) {
+                    continue;
+                }
+                findings.push(Finding {
+                    severity: Severity::Medium,
+                    confidence: Confidence::High,
+                    title: "Use of callcode".into(),
+                    description: format!(
+                        "`{}` uses `callcode`, which is deprecated and does not preserve \
+                         `msg.sender` and `msg.value`; use `delegatecall`.",
+                        symbols.qualified_name(site.function)
+                    ),
+                    location: symbols.project.location(&site.loc),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Project, ProjectBundle};
+
+    fn findings(src: &str) -> Vec<Finding> {
+        let project = Project::load(&ProjectBundle::single("Test.sol", src));
+        let symbols = SymbolTable::build(&project);
+        detect(&symbols)
+    }
+
+    #[test]
+    fn test_dangerous_primitives() {
+        let findings = findings(
+            "contract Wallet {
+                address owner;
+                address public implementation;
+                constructor() { owner = msg.sender; }
+                function pay(address payable to, uint256 amount) external {
+                    require(tx.origin == owner);
+                    to.transfer(amount);
+                }
+                function upgrade(address impl) external {
+                    require(msg.sender == owner);
+                    implementation = impl;
+                }
+                function forward(bytes calldata data) external {
+                    (bool ok, ) = _implementation().delegatecall(data);
+                    require(ok);
+                }
+                function exec(address target, bytes calldata data) external {
+                    (bool ok, ) = target.delegatecall(data);
+                    require(ok);
+                }
+                function kill() external { selfdestruct(payable(msg.sender)); }
+                function ownerKill() external { require(msg.sender == owner); selfdestruct(payable(owner)); }
+                function _implementation() internal view returns (address) { return implementation; }
+             }",
+        );
+        let summary: Vec<(&str, &str, &str, usize)> = findings
+            .iter()
+            .map(|f| {
+                (
+                    f.title.as_str(),
+                    f.severity.as_str(),
+                    f.confidence.as_str(),
+                    f.location.as_ref().unwrap().line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("tx.origin used for authorization", "medium", "high", 6),
+                ("Delegatecall to mutable target", "medium", "medium", 14),
+                ("Delegatecall to user-controlled target", "high", "high", 18),
+                ("Unprotected selfdestruct", "high", "high", 21),
+            ]
+        );
+        let trace: Vec<&str> = findings[2]
+            .trace()
+            .iter()
+            .map(|s| s.description.as_str())
+            .collect();
+        assert_eq!(
+            trace,
+            ["parameter `target`", "reaches the delegatecall target"]
+        );
+    }
+
+    #[test]
+    fn test_callcode() {
+        let findings = findings(
+            "contract Old {
+                function f(address t) external { (bool ok, ) = t.callcode(\"\"); require(ok); }
+             }",
+        );
+        assert_eq!(findings[0].title, "Use of callcode");
+    }
+}
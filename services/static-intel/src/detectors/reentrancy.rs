@@ -0,0 +1,818 @@
+//! Reentrancy: state the contract relies on is updated only after control has been
+//! handed to another contract.
+//!
+//! Every function is summarised by the external calls it makes (directly or
+//! through internal calls) together with the state it reads before and writes
+//! after each of them. From the summaries of the entry points we report:
+//!
+//! - classic reentrancy: a variable read before a call is written after it;
+//! - cross-function reentrancy: a variable written after a call is read by
+//!   another entry point without the same lock;
+//! - read-only reentrancy: a variable written after a call is exposed by a view
+//!   function, which other protocols may query mid-call;
+//! - callback reentrancy through ERC721/ERC1155 receiver and ERC777 hooks.
+//!
+//! Entry points carrying a reentrancy lock — `nonReentrant`-style modifiers,
+//! resolved through inheritance — are not reentered.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use solang_parser::pt::Loc;
+
+use crate::ast::{ContractKind, StateMutability, Visibility};
+use crate::detectors::Detector;
+use crate::finding::{Confidence, ExtraData, Finding, Severity, TraceStep};
+use crate::ir::{CallKind, EnvVar, Instruction, IrFunction, Op, Operand};
+use crate::symbols::{ContractId, FunctionId, StateVarId, SymbolTable};
+
+/// Internal functions that invoke a receiver hook on their recipient.
+const HOOK_FUNCTIONS: &[(&str, Hook)] = &[
+    ("_safeMint", Hook::Erc721),
+    ("_safeTransfer", Hook::Erc721),
+    ("_checkOnERC721Received", Hook::Erc721),
+    ("_mintBatch", Hook::Erc1155),
+    ("_safeTransferFrom", Hook::Erc1155),
+    ("_safeBatchTransferFrom", Hook::Erc1155),
+    ("_doSafeTransferAcceptanceCheck", Hook::Erc1155),
+    ("_doSafeBatchTransferAcceptanceCheck", Hook::Erc1155),
+    ("_callTokensReceived", Hook::Erc777),
+    ("_callTokensToSend", Hook::Erc777),
+];
+
+/// Internal functions that invoke a hook only in tokens of the given standard;
+/// ERC20 tokens have a `_mint` too.
+const STANDARD_HOOK_FUNCTIONS: &[(&str, Hook)] =
+    &[("_mint", Hook::Erc1155), ("_send", Hook::Erc777)];
+
+/// Hook functions implemented by token recipients and senders.
+const HOOK_CALLBACKS: &[(&str, Hook)] = &[
+    ("onERC721Received", Hook::Erc721),
+    ("onERC1155Received", Hook::Erc1155),
+    ("onERC1155BatchReceived", Hook::Erc1155),
+    ("tokensReceived", Hook::Erc777),
+    ("tokensToSend", Hook::Erc777),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Hook {
+    Erc721,
+    Erc1155,
+    Erc777,
+}
+
+impl Hook {
+    fn name(self) -> &'static str {
+        match self {
+            Hook::Erc721 => "ERC721",
+            Hook::Erc1155 => "ERC1155",
+            Hook::Erc777 => "ERC777",
+        }
+    }
+
+    /// Number in the names of the standard's contracts and interfaces.
+    fn number(self) -> &'static str {
+        match self {
+            Hook::Erc721 => "721",
+            Hook::Erc1155 => "1155",
+            Hook::Erc777 => "777",
+        }
+    }
+}
+
+/// An external call that can hand control to untrusted code.
+#[derive(Debug, Clone)]
+struct Call {
+    function: FunctionId,
+    loc: Loc,
+    description: String,
+    sends_value: bool,
+    hook: Option<Hook>,
+    /// State read on some path before the call.
+    before: BTreeSet<StateVarId>,
+    /// State written on some path after the call, with the first such write.
+    after: BTreeMap<StateVarId, (FunctionId, Loc)>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Summary {
+    reads: BTreeSet<StateVarId>,
+    writes: BTreeSet<StateVarId>,
+    calls: Vec<Call>,
+}
+
+/// Effects of one instruction.
+#[derive(Default)]
+struct Effects {
+    reads: BTreeSet<StateVarId>,
+    writes: BTreeMap<StateVarId, (FunctionId, Loc)>,
+    calls: Vec<Call>,
+}
+
+type Key = (Option<ContractId>, FunctionId);
+
+struct Analysis<'s, 'p> {
+    symbols: &'s SymbolTable<'p>,
+    summaries: HashMap<Key, Summary>,
+    active: HashSet<Key>,
+}
+
+impl<'s, 'p> Analysis<'s, 'p> {
+    fn summary(&mut self, context: Option<ContractId>, func: FunctionId) -> Summary {
+        let key = (context, func);
+        if let Some(summary) = self.summaries.get(&key) {
+            return summary.clone();
+        }
+        if !self.active.insert(key) {
+            return Summary::default();
+        }
+        let summary = self.compute(context, func);
+        self.active.remove(&key);
+        self.summaries.insert(key, summary.clone());
+        summary
+    }
+
+    fn compute(&mut self, context: Option<ContractId>, func: FunctionId) -> Summary {
+        let ir = IrFunction::build(self.symbols, context, func);
+        let mut sites: Vec<((usize, usize), Effects)> = Vec::new();
+        for block in ir.cfg.reverse_postorder() {
+            let origin = ir.cfg.node(block).origin;
+            for (index, inst) in ir.blocks[block].instructions.iter().enumerate() {
+                let effects = self.effects(context, &ir, origin, inst);
+                sites.push(((block, index), effects));
+            }
+        }
+
+        let reachable: BTreeMap<usize, Vec<bool>> = sites
+            .iter()
+            .map(|&((block, _), _)| block)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|block| (block, ir.cfg.reachable_from(block)))
+            .collect();
+        // Whether some path executes the instruction at `to` after the one at `from`.
+        let reaches = |from: (usize, usize), to: (usize, usize)| {
+            (from.0 == to.0 && to.1 > from.1) || reachable[&from.0][to.0]
+        };
+        let mut summary = Summary::default();
+        for (position, effects) in &sites {
+            for mut call in effects.calls.iter().cloned() {
+                for (other, other_effects) in &sites {
+                    if other == position {
+                        continue;
+                    }
+                    if reaches(*position, *other) {
+                        for (var, site) in &other_effects.writes {
+                            call.after.entry(*var).or_insert(*site);
+                        }
+                    }
+                    if reaches(*other, *position) {
+                        call.before.extend(&other_effects.reads);
+                    }
+                }
+                summary.calls.push(call);
+            }
+            summary.reads.extend(&effects.reads);
+            summary.writes.extend(effects.writes.keys());
+        }
+        summary
+    }
+
+    fn effects(
+        &mut self,
+        context: Option<ContractId>,
+        ir: &IrFunction,
+        function: FunctionId,
+        inst: &Instruction,
+    ) -> Effects {
+        let mut effects = Effects::default();
+        let call = |description: String, sends_value: bool, hook: Option<Hook>| Call {
+            function,
+            loc: inst.loc,
+            description,
+            sends_value,
+            hook,
+            before: BTreeSet::new(),
+            after: BTreeMap::new(),
+        };
+        match &inst.op {
+            Op::StorageRead { var, .. } => {
+                effects.reads.insert(*var);
+            }
+            Op::StorageWrite { var, .. } => {
+                effects.writes.insert(*var, (function, inst.loc));
+            }
+            Op::LowLevelCall {
+                kind: kind @ (CallKind::Call | CallKind::DelegateCall | CallKind::CallCode),
+                value,
+                ..
+            } => {
+                let sends_value = value.as_ref().is_some_and(|v| !is_zero(v));
+                effects.calls.push(call(
+                    format!("low-level `{}`", kind.name()),
+                    sends_value,
+                    None,
+                ));
+            }
+            Op::ExternalCall {
+                target,
+                name,
+                args,
+                value,
+                interface,
+                ..
+            } => {
+                let self_call = target
+                    .value()
+                    .is_some_and(|v| matches!(ir.def(v).op, Op::Env(EnvVar::This)));
+                if !self_call && !self.is_view(ir, interface.as_deref(), name, args.len()) {
+                    let hook = HOOK_CALLBACKS
+                        .iter()
+                        .find(|(callback, _)| callback == name)
+                        .map(|&(_, hook)| hook)
+                        .or_else(|| self.erc777_transfer(ir, interface.as_deref(), name));
+                    let sends_value = value.as_ref().is_some_and(|v| !is_zero(v));
+                    let description = match interface {
+                        Some(ty) => format!("external call `{ty}.{name}`"),
+                        None => format!("external call `{name}`"),
+                    };
+                    effects.calls.push(call(description, sends_value, hook));
+                }
+            }
+            Op::InternalCall {
+                callee: Some(callee),
+                ..
+            }
+            | Op::LibraryCall {
+                callee: Some(callee),
+                ..
+            } => {
+                let inner = self.summary(self.symbols.dispatch_context(context, *callee), *callee);
+                effects.reads = inner.reads;
+                effects.writes = inner
+                    .writes
+                    .into_iter()
+                    .map(|var| (var, (function, inst.loc)))
+                    .collect();
+                effects.calls = inner.calls;
+                // A hook helper implemented in the sources is attributed by its external call;
+                // mark the calls with the standard if it is only known by name.
+                if let Some(hook) =
+                    self.hook_function(context, &self.symbols.function(*callee).name)
+                {
+                    for call in &mut effects.calls {
+                        call.hook.get_or_insert(hook);
+                    }
+                }
+            }
+            // `_safeMint` and friends from dependencies that were not provided.
+            Op::InternalCall {
+                callee: None, name, ..
+            } => {
+                if let Some(hook) = self.hook_function(context, name) {
+                    effects
+                        .calls
+                        .push(call(format!("`{name}`"), false, Some(hook)));
+                }
+            }
+            _ => {}
+        }
+        effects
+    }
+
+    /// The hook `name` invokes when called in `context`.
+    fn hook_function(&self, context: Option<ContractId>, name: &str) -> Option<Hook> {
+        if let Some(&(_, hook)) = HOOK_FUNCTIONS
+            .iter()
+            .find(|(function, _)| *function == name)
+        {
+            return Some(hook);
+        }
+        let &(_, hook) = STANDARD_HOOK_FUNCTIONS
+            .iter()
+            .find(|(function, _)| *function == name)?;
+        self.implements(context?, hook).then_some(hook)
+    }
+
+    /// Whether `context` or one of its bases, resolved or not, is named after
+    /// the standard, as `ERC1155Supply` and `IERC777` are.
+    fn implements(&self, context: ContractId, hook: Hook) -> bool {
+        self.symbols
+            .contract(context)
+            .linearization
+            .iter()
+            .map(|&id| self.symbols.contract(id).def)
+            .any(|def| {
+                def.name.contains(hook.number())
+                    || def
+                        .bases
+                        .iter()
+                        .any(|base| base.name.contains(hook.number()))
+            })
+    }
+
+    /// ERC777 `send`/`operatorSend`, and transfers, on a token whose type
+    /// declares ERC777 functions.
+    fn erc777_transfer(
+        &self,
+        ir: &IrFunction,
+        interface: Option<&str>,
+        name: &str,
+    ) -> Option<Hook> {
+        if !matches!(
+            name,
+            "send" | "operatorSend" | "burn" | "operatorBurn" | "transfer" | "transferFrom"
+        ) {
+            return None;
+        }
+        let file = self.symbols.file_of(ir.cfg.function);
+        let info = self.symbols.contract_by_name(interface?, Some(file))?;
+        let erc777 = info.linearization.iter().any(|&id| {
+            self.symbols.contract(id).def.functions.iter().any(|f| {
+                matches!(
+                    f.name.as_str(),
+                    "operatorSend" | "granularity" | "defaultOperators"
+                )
+            })
+        });
+        erc777.then_some(Hook::Erc777)
+    }
+
+    /// Calls of `view`/`pure` interface functions compile to `STATICCALL`.
+    fn is_view(&self, ir: &IrFunction, interface: Option<&str>, name: &str, arity: usize) -> bool {
+        let file = self.symbols.file_of(ir.cfg.function);
+        interface
+            .and_then(|ty| self.symbols.contract_by_name(ty, Some(file)))
+            .and_then(|info| {
+                info.functions.iter().find(|&&f| {
+                    let def = self.symbols.function(f);
+                    def.name == name && def.params.len() == arity
+                })
+            })
+            .is_some_and(|&f| {
+                matches!(
+                    self.symbols.function(f).mutability,
+                    StateMutability::View | StateMutability::Pure
+                )
+            })
+    }
+
+    /// Whether `func` runs behind a reentrancy lock in `context`.
+    fn locked(&mut self, context: ContractId, func: FunctionId) -> bool {
+        self.symbols
+            .modifiers_of(context, func)
+            .into_iter()
+            .any(|modifier| self.is_lock(context, modifier))
+    }
+
+    /// `nonReentrant`, `lock` and similar by name, or any modifier that both
+    /// reads and writes the same value-typed state variable (a mutex flag).
+    fn is_lock(&mut self, context: ContractId, modifier: FunctionId) -> bool {
+        let name = self.symbols.function(modifier).name.to_ascii_lowercase();
+        if name.contains("reentran") || name == "lock" || name == "mutex" {
+            return true;
+        }
+        let summary = self.summary(Some(context), modifier);
+        summary.reads.intersection(&summary.writes).any(|&var| {
+            let ty = &self.symbols.state_variable(var).type_name;
+            !ty.starts_with("mapping") && !ty.ends_with(']')
+        })
+    }
+}
+
+fn is_zero(operand: &Operand) -> bool {
+    matches!(operand, Operand::Const(crate::ir::Constant::Number(n)) if n == "0")
+}
+
+fn is_entry(symbols: &SymbolTable, func: FunctionId) -> bool {
+    symbols.function(func).is_entry_point()
+}
+
+fn is_view(symbols: &SymbolTable, func: FunctionId) -> bool {
+    matches!(
+        symbols.function(func).mutability,
+        StateMutability::View | StateMutability::Pure
+    )
+}
+
+/// Classic, cross-function, read-only and token-hook reentrancy.
+pub struct Reentrancy;
+
+impl Detector for Reentrancy {
+    fn id(&self) -> &str {
+        "reentrancy"
+    }
+
+    fn description(&self) -> &str {
+        "State written or read around external calls a callee can re-enter"
+    }
+
+    fn swc(&self) -> &[&str] {
+        &["SWC-107"]
+    }
+
+    fn cwe(&self) -> &[&str] {
+        &["CWE-841"]
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::High
+    }
+
+    fn run(&self, symbols: &SymbolTable) -> Vec<Finding> {
+        detect(symbols)
+    }
+}
+
+pub fn detect(symbols: &SymbolTable) -> Vec<Finding> {
+    let mut analysis = Analysis {
+        symbols,
+        summaries: HashMap::new(),
+        active: HashSet::new(),
+    };
+    let mut findings = Vec::new();
+    for info in &symbols.contracts {
+        if info.def.kind != ContractKind::Contract {
+            continue;
+        }
+        let context = info.id;
+        let entries: Vec<FunctionId> = info
+            .functions
+            .iter()
+            .copied()
+            .filter(|&f| is_entry(symbols, f))
+            .collect();
+        let mut summaries = BTreeMap::new();
+        let mut locked = BTreeMap::new();
+        for &func in &entries {
+            summaries.insert(func, analysis.summary(Some(context), func));
+            locked.insert(func, analysis.locked(context, func));
+        }
+        let var_name = |var: StateVarId| symbols.state_variable(var).name.as_str();
+        let step = |function: FunctionId, description: String, loc: &Loc| TraceStep {
+            function: symbols.qualified_name(function),
+            description,
+            location: symbols.project.location(loc),
+        };
+        let mut reported = HashSet::new();
+        let mut cross_reported = HashSet::new();
+        let mut read_only_reported = HashSet::new();
+
+        for &func in &entries {
+            if is_view(symbols, func) {
+                continue;
+            }
+            for call in &summaries[&func].calls {
+                if !reported.insert((func, call.loc)) {
+                    continue;
+                }
+                let call_step = step(
+                    call.function,
+                    format!("{} hands over control", call.description),
+                    &call.loc,
+                );
+                let stale: Vec<StateVarId> = call
+                    .after
+                    .keys()
+                    .copied()
+                    .filter(|var| call.before.contains(var))
+                    .collect();
+                if !locked[&func] && !stale.is_empty() {
+                    let names: Vec<String> = stale
+                        .iter()
+                        .map(|&v| format!("`{}`", var_name(v)))
+                        .collect();
+                    let mut trace = vec![call_step.clone()];
+                    for &var in &stale {
+                        let (function, loc) = call.after[&var];
+                        trace.push(step(
+                            function,
+                            format!("`{}` is written after the call", var_name(var)),
+                            &loc,
+                        ));
+                    }
+                    let (severity, title) = match (call.hook, call.sends_value) {
+                        (Some(hook), _) => (
+                            Severity::High,
+                            format!("Reentrancy through {} hook", hook.name()),
+                        ),
+                        (None, true) => (
+                            Severity::High,
+                            "Reentrancy: state written after external call".to_string(),
+                        ),
+                        (None, false) => (
+                            Severity::Medium,
+                            "Reentrancy: state written after external call".to_string(),
+                        ),
+                    };
+                    findings.push(Finding {
+                        severity,
+                        confidence: Confidence::High,
+                        title,
+                        description: format!(
+                            "`{}` reads {} before the {} and writes {} only afterwards; \
+                             the callee can reenter while the state is stale.",
+                            symbols.qualified_name(func),
+                            names.join(", "),
+                            call.description,
+                            match names.len() {
+                                1 => "it",
+                                _ => "them",
+                            },
+                        ),
+                        location: symbols.project.location(&call.loc),
+                        remediation: Some(
+                            "Update state before the call (checks-effects-interactions) or add a \
+                             reentrancy guard."
+                                .into(),
+                        ),
+                        extra_data: ExtraData {
+                            trace,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    });
+                }
+
+                // Other entry points that observe the stale state mid-call.
+                for (&var, &(write_fn, write_loc)) in &call.after {
+                    let classic = !locked[&func] && call.before.contains(&var);
+                    let reenterable: Vec<FunctionId> = entries
+                        .iter()
+                        .copied()
+                        .filter(|&g| {
+                            g != func
+                                && !locked[&g]
+                                && !is_view(symbols, g)
+                                && summaries[&g].reads.contains(&var)
+                        })
+                        .collect();
+                    if !classic
+                        && !reenterable.is_empty()
+                        && cross_reported.insert((func, call.loc, var))
+                    {
+                        let names: Vec<String> = reenterable
+                            .iter()
+                            .map(|&g| format!("`{}`", symbols.function(g).display_name()))
+                            .collect();
+                        findings.push(Finding {
+                            severity: Severity::Medium,
+                            confidence: Confidence::Medium,
+                            title: "Cross-function reentrancy".into(),
+                            description: format!(
+                                "`{}` writes `{}` after the {}; {} read{} it and can be reentered in between{}.",
+                                symbols.qualified_name(func),
+                                var_name(var),
+                                call.description,
+                                names.join(", "),
+                                if names.len() == 1 { "s" } else { "" },
+                                if locked[&func] { " because they do not share its reentrancy lock" } else { "" },
+                            ),
+                            location: symbols.project.location(&call.loc),
+                            extra_data: ExtraData {
+                                trace: vec![
+                                    call_step.clone(),
+                                    step(write_fn, format!("`{}` is written after the call", var_name(var)), &write_loc),
+                                ],
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        });
+                    }
+
+                    let mut views: Vec<String> = entries
+                        .iter()
+                        .copied()
+                        .filter(|&g| is_view(symbols, g) && summaries[&g].reads.contains(&var))
+                        .map(|g| format!("`{}`", symbols.function(g).display_name()))
+                        .collect();
+                    let getter = symbols.state_variable(var);
+                    if getter.visibility == Visibility::Public {
+                        views.push(format!("`{}`", getter.name));
+                    }
+                    if !views.is_empty() && read_only_reported.insert(var) {
+                        findings.push(Finding {
+                            severity: Severity::Medium,
+                            confidence: Confidence::Low,
+                            title: "Read-only reentrancy".into(),
+                            description: format!(
+                                "`{}` writes `{}` after the {}; {} return{} the stale value to anyone \
+                                 querying the contract during the call, such as protocols pricing against it.",
+                                symbols.qualified_name(func),
+                                var_name(var),
+                                call.description,
+                                views.join(", "),
+                                if views.len() == 1 { "s" } else { "" },
+                            ),
+                            location: symbols.project.location(&call.loc),
+                            extra_data: ExtraData {
+                                trace: vec![
+                                    call_step.clone(),
+                                    step(write_fn, format!("`{}` is written after the call", var_name(var)), &write_loc),
+                                ],
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        });
+                    }
+                }
+            }
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Project, ProjectBundle};
+
+    fn findings(src: &str) -> Vec<Finding> {
+        let project = Project::load(&ProjectBundle::single("Test.sol", src));
+        let symbols = SymbolTable::build(&project);
+        detect(&symbols)
+    }
+
+    fn titles(findings: &[Finding]) -> Vec<&str> {
+        findings.iter().map(|f| f.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_reentrancy_vault_fixture() {
+        let findings = findings(include_str!(
+            "../../../../integrations/foundry/src/ReentrancyVault.sol"
+        ));
+        assert_eq!(
+            titles(&findings),
+            [
+                "Reentrancy: state written after external call",
+                "Read-only reentrancy"
+            ]
+        );
+        let classic = &findings[0];
+        assert_eq!(classic.severity, Severity::High);
+        let lines: Vec<usize> = classic
+            .trace()
+            .iter()
+            .map(|s| s.location.as_ref().unwrap().line)
+            .collect();
+        assert_eq!(lines, [34, 38]);
+        assert!(findings[1]
+            .description
+            .contains("`getUserBalance`, `balances`"));
+    }
+
+    #[test]
+    fn test_inherited_lock_prevents_reentrancy() {
+        let findings = findings(
+            "abstract contract ReentrancyGuard {
+                uint256 private _status = 1;
+                modifier guarded() {
+                    require(_status == 1);
+                    _status = 2;
+                    _;
+                    _status = 1;
+                }
+             }
+             contract Vault is ReentrancyGuard {
+                mapping(address => uint256) balances;
+                function withdraw() external guarded {
+                    uint256 amount = balances[msg.sender];
+                    (bool ok, ) = msg.sender.call{value: amount}(\"\");
+                    require(ok);
+                    balances[msg.sender] = 0;
+                }
+                function transfer(address to, uint256 amount) external {
+                    balances[msg.sender] -= amount;
+                    balances[to] += amount;
+                }
+             }",
+        );
+        // The lock stops `withdraw` being reentered, but not `transfer`.
+        assert_eq!(titles(&findings), ["Cross-function reentrancy"]);
+        assert!(findings[0].description.contains("`transfer`"));
+        assert!(findings[0]
+            .description
+            .contains("do not share its reentrancy lock"));
+    }
+
+    #[test]
+    fn test_hook_reentrancy_through_safe_mint() {
+        let findings = findings(
+            "contract Drop is ERC721 {
+                mapping(address => bool) claimed;
+                uint256 next;
+                function claim() external {
+                    require(!claimed[msg.sender]);
+                    _safeMint(msg.sender, next);
+                    claimed[msg.sender] = true;
+                    next += 1;
+                }
+             }",
+        );
+        assert_eq!(findings[0].title, "Reentrancy through ERC721 hook");
+        assert_eq!(findings[0].severity, Severity::High);
+    }
+
+    #[test]
+    fn test_mint_is_a_hook_only_in_erc1155() {
+        let airdrop = |base: &str| {
+            findings(&format!(
+                "contract Airdrop is {base} {{
+                    mapping(address => bool) public claimed;
+                    function claim() external {{
+                        require(!claimed[msg.sender]);
+                        _mint(msg.sender, 100e18);
+                        claimed[msg.sender] = true;
+                    }}
+                 }}"
+            ))
+        };
+        assert!(airdrop("ERC20").is_empty());
+        assert_eq!(
+            titles(&airdrop("ERC1155"))[0],
+            "Reentrancy through ERC1155 hook"
+        );
+    }
+
+    #[test]
+    fn test_cross_function_reentrancy_per_call() {
+        let findings = findings(
+            "interface IERC20 {
+                function transferFrom(address, address, uint256) external returns (bool);
+             }
+             contract Vault {
+                IERC20 token;
+                mapping(address => uint256) balances;
+                modifier nonReentrant() { _; }
+                function deposit(uint256 amount) external nonReentrant {
+                    token.transferFrom(msg.sender, address(this), amount);
+                    balances[msg.sender] += amount;
+                }
+                function withdraw() external nonReentrant {
+                    uint256 amount = balances[msg.sender];
+                    (bool ok, ) = msg.sender.call{value: amount}(\"\");
+                    require(ok);
+                    balances[msg.sender] = 0;
+                }
+                function safeWithdraw(uint256 amount) external {
+                    require(balances[msg.sender] >= amount);
+                    balances[msg.sender] -= amount;
+                    payable(msg.sender).transfer(amount);
+                }
+             }",
+        );
+        let cross: Vec<&str> = findings
+            .iter()
+            .filter(|f| f.title == "Cross-function reentrancy")
+            .map(|f| f.description.split(" writes").next().unwrap())
+            .collect();
+        assert_eq!(cross, ["`Vault.deposit`", "`Vault.withdraw`"]);
+    }
+
+    #[test]
+    fn test_erc777_hook_by_interface() {
+        let token = |functions: &str| {
+            findings(&format!(
+                "interface IToken {{
+                    function transfer(address, uint256) external returns (bool);
+                    {functions}
+                 }}
+                 contract Pool {{
+                    IToken token;
+                    mapping(address => uint256) shares;
+                    function exit() external {{
+                        token.transfer(msg.sender, shares[msg.sender]);
+                        shares[msg.sender] = 0;
+                    }}
+                 }}"
+            ))
+        };
+        let erc777 = token("function granularity() external view returns (uint256);");
+        assert_eq!(titles(&erc777)[0], "Reentrancy through ERC777 hook");
+        assert_eq!(
+            titles(&token(""))[0],
+            "Reentrancy: state written after external call"
+        );
+    }
+
+    #[test]
+    fn test_checks_effects_interactions_is_clean() {
+        let findings = findings(
+            "interface IERC20 {
+                function transfer(address to, uint256 amount) external returns (bool);
+                function balanceOf(address who) external view returns (uint256);
+             }
+             contract Pool {
+                IERC20 token;
+                mapping(address => uint256) shares;
+                function withdraw() external {
+                    uint256 amount = shares[msg.sender];
+                    shares[msg.sender] = 0;
+                    require(token.balanceOf(address(this)) >= amount);
+                    token.transfer(msg.sender, amount);
+                }
+             }",
+        );
+        assert!(findings.is_empty(), "{findings:?}");
+    }
+}
@@ -0,0 +1,323 @@
+//! Ignored failures: low-level calls and `send` whose success flag is dropped,
+//! ERC20 calls whose boolean result is discarded, and `catch` blocks that
+//! swallow the error.
+
+use std::collections::{HashMap, HashSet};
+
+use solang_parser::pt::{CatchClause, Loc, Statement};
+
+use crate::ast::FunctionKind;
+use crate::detectors::Detector;
+use crate::finding::{Confidence, Finding, Severity};
+use crate::ir::{CallKind, Instruction, IrFunction, Op, TransferKind, ValueId};
+use crate::symbols::{FunctionId, SymbolTable};
+use crate::visit::{self, Visitor};
+
+/// ERC20 functions returning `bool`, with their arity.
+const ERC20_BOOL_FUNCTIONS: &[(&str, usize)] =
+    &[("transfer", 2), ("transferFrom", 3), ("approve", 2)];
+
+/// Def-use chains of one function.
+struct Uses<'i> {
+    uses: HashMap<ValueId, Vec<&'i Instruction>>,
+    conditions: HashSet<ValueId>,
+}
+
+impl<'i> Uses<'i> {
+    fn new(ir: &'i IrFunction) -> Self {
+        let mut uses: HashMap<ValueId, Vec<&Instruction>> = HashMap::new();
+        for (_, inst) in ir.instructions() {
+            for operand in inst.op.operands() {
+                if let Some(value) = operand.value() {
+                    uses.entry(value).or_default().push(inst);
+                }
+            }
+        }
+        let conditions = ir
+            .blocks
+            .iter()
+            .filter_map(|b| b.condition.as_ref()?.value())
+            .collect();
+        Uses { uses, conditions }
+    }
+
+    /// Whether `value` reaches anything other than unused copies: a condition,
+    /// `require`, a return, storage, an event or another call.
+    fn is_used(&self, value: ValueId, seen: &mut HashSet<ValueId>) -> bool {
+        if !seen.insert(value) {
+            return false;
+        }
+        if self.conditions.contains(&value) {
+            return true;
+        }
+        self.uses
+            .get(&value)
+            .into_iter()
+            .flatten()
+            .any(|inst| match &inst.op {
+                Op::Copy(_) | Op::Phi(_) => inst.dst.is_some_and(|dst| self.is_used(dst, seen)),
+                _ => true,
+            })
+    }
+
+    /// Whether the success flag of a `(bool, bytes)` call result is used.
+    fn is_success_used(&self, result: ValueId) -> bool {
+        self.uses
+            .get(&result)
+            .into_iter()
+            .flatten()
+            .any(|inst| match &inst.op {
+                Op::Extract(_, 0) => inst
+                    .dst
+                    .is_some_and(|dst| self.is_used(dst, &mut HashSet::new())),
+                Op::Extract(..) => false,
+                _ => true,
+            })
+    }
+}
+
+/// Whether `interface.name` is declared to return `bool`; assumed so when the
+/// interface is not part of the project.
+fn returns_bool(
+    symbols: &SymbolTable,
+    from: FunctionId,
+    interface: Option<&str>,
+    name: &str,
+    arity: usize,
+) -> bool {
+    let Some(info) =
+        interface.and_then(|i| symbols.contract_by_name(i, Some(symbols.file_of(from))))
+    else {
+        return true;
+    };
+    let declared: Vec<_> = info
+        .linearization
+        .iter()
+        .flat_map(|&c| &symbols.contract(c).def.functions)
+        .filter(|f| f.name == name && f.params.len() == arity)
+        .collect();
+    declared.is_empty()
+        || declared
+            .iter()
+            .any(|f| f.returns.first().is_some_and(|r| r.type_name == "bool"))
+}
+
+/// Locations of `catch` clauses with empty bodies.
+#[derive(Default)]
+struct EmptyCatches {
+    locs: Vec<Loc>,
+}
+
+impl Visitor for EmptyCatches {
+    fn visit_statement(&mut self, stmt: &Statement) -> bool {
+        if let Statement::Try(_, _, _, catches) = stmt {
+            for clause in catches {
+                let (CatchClause::Simple(loc, _, body) | CatchClause::Named(loc, _, _, body)) =
+                    clause;
+                if matches!(body, Statement::Block { statements, .. } if statements.is_empty()) {
+                    self.locs.push(*loc);
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Ignored call results and empty `catch` blocks.
+pub struct UncheckedCalls;
+
+impl Detector for UncheckedCalls {
+    fn id(&self) -> &str {
+        "unchecked-calls"
+    }
+
+    fn description(&self) -> &str {
+        "Ignored call results and empty catch blocks"
+    }
+
+    fn swc(&self) -> &[&str] {
+        &["SWC-104"]
+    }
+
+    fn cwe(&self) -> &[&str] {
+        &["CWE-252"]
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Medium
+    }
+
+    fn run(&self, symbols: &SymbolTable) -> Vec<Finding> {
+        detect(symbols)
+    }
+}
+
+pub fn detect(symbols: &SymbolTable) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut reported = HashSet::new();
+    for func in symbols.all_functions() {
+        let def = symbols.function(func);
+        let Some(body) = &def.body else {
+            continue;
+        };
+        if def.kind == FunctionKind::Modifier {
+            // Analysed where inlined.
+            continue;
+        }
+        let name = symbols.qualified_name(func);
+        let ir = IrFunction::build(symbols, func.contract(), func);
+        let uses = Uses::new(&ir);
+        for (_, inst) in ir.instructions() {
+            let Some(dst) = inst.dst else {
+                continue;
+            };
+            let finding = match &inst.op {
+                Op::LowLevelCall { kind, .. } if !uses.is_success_used(dst) => {
+                    let (severity, what) = match kind {
+                        CallKind::StaticCall => (Severity::Low, "staticcall"),
+                        CallKind::Call => (Severity::Medium, "call"),
+                        CallKind::DelegateCall => (Severity::Medium, "delegatecall"),
+                        CallKind::CallCode => (Severity::Medium, "callcode"),
+                    };
+                    Some((
+                        severity,
+                        "Unchecked low-level call",
+                        format!(
+                            "`{name}` ignores whether the `{what}` succeeded; a failing call does not \
+                             revert and execution continues as if it had. Check the returned flag."
+                        ),
+                    ))
+                }
+                Op::Transfer {
+                    kind: TransferKind::Send,
+                    ..
+                } if !uses.is_used(dst, &mut HashSet::new()) => Some((
+                    Severity::Medium,
+                    "Unchecked send",
+                    format!(
+                        "`{name}` ignores the result of `send`, which returns `false` instead of \
+                         reverting when the transfer fails."
+                    ),
+                )),
+                Op::ExternalCall {
+                    name: member,
+                    args,
+                    interface,
+                    ..
+                } if ERC20_BOOL_FUNCTIONS.contains(&(member.as_str(), args.len()))
+                    && returns_bool(symbols, func, interface.as_deref(), member, args.len())
+                    && !uses.is_used(dst, &mut HashSet::new()) =>
+                {
+                    Some((
+                        Severity::Medium,
+                        "Unchecked ERC20 return value",
+                        format!(
+                            "`{name}` discards the boolean returned by `{member}`. Tokens that \
+                             return `false` instead of reverting fail silently; use SafeERC20 or \
+                             check the result."
+                        ),
+                    ))
+                }
+                _ => None,
+            };
+            if let Some((severity, title, description)) = finding {
+                if reported.insert(inst.loc) {
+                    findings.push(Finding {
+                        severity,
+                        confidence: Confidence::High,
+                        title: title.into(),
+                        description,
+                        location: symbols.project.location(&inst.loc),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+
+        let mut catches = EmptyCatches::default();
+        visit::walk_statement(&mut catches, body);
+        for loc in catches.locs {
+            findings.push(Finding {
+                severity: Severity::Low,
+                confidence: Confidence::Medium,
+                title: "Failure swallowed by empty catch".into(),
+                description: format!(
+                    "A `catch` block in `{name}` is empty, so the failed call is silently ignored \
+                     and execution continues."
+                ),
+                location: symbols.project.location(&loc),
+                ..Default::default()
+            });
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Project, ProjectBundle};
+
+    fn findings(src: &str) -> Vec<(String, usize)> {
+        let project = Project::load(&ProjectBundle::single("Test.sol", src));
+        let symbols = SymbolTable::build(&project);
+        detect(&symbols)
+            .into_iter()
+            .map(|f| (f.title, f.location.unwrap().line))
+            .collect()
+    }
+
+    #[test]
+    fn test_unchecked_calls() {
+        let findings = findings(
+            "interface IERC20 {
+                function transfer(address to, uint256 amount) external returns (bool);
+             }
+             interface IUSDT { function transfer(address to, uint256 amount) external; }
+             contract Payout {
+                function pay(address payable to, IERC20 token, IUSDT usdt) external {
+                    to.call{value: 1}(\"\");
+                    (bool ok, ) = to.call(\"\");
+                    bool sent = to.send(1);
+                    token.transfer(to, 1);
+                    usdt.transfer(to, 1);
+                }
+                function checked(address payable to, IERC20 token) external {
+                    (bool ok, bytes memory data) = to.call(\"\");
+                    require(ok);
+                    if (!to.send(1)) revert();
+                    require(token.transfer(to, 1));
+                    bool sent = token.transfer(to, 2);
+                    if (sent) { data = \"\"; }
+                }
+             }",
+        );
+        assert_eq!(
+            findings,
+            [
+                ("Unchecked low-level call".to_string(), 7),
+                ("Unchecked low-level call".to_string(), 8),
+                ("Unchecked send".to_string(), 9),
+                ("Unchecked ERC20 return value".to_string(), 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_catch() {
+        let findings = findings(
+            "interface IOracle { function price() external returns (uint256); }
+             contract Reader {
+                uint256 last;
+                function read(IOracle oracle) external {
+                    try oracle.price() returns (uint256 p) { last = p; } catch {}
+                    try oracle.price() returns (uint256 p) { last = p; } catch { revert(); }
+                }
+             }",
+        );
+        assert_eq!(
+            findings,
+            [("Failure swallowed by empty catch".to_string(), 5)]
+        );
+    }
+}
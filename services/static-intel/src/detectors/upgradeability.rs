@@ -0,0 +1,513 @@
+//! Proxies and upgradeable implementations.
+//!
+//! Contracts are classified by the proxy pattern they take part in
+//! ([`proxy_kind`]), either by the OpenZeppelin contract they inherit or by
+//! their shape: `proxiableUUID` for UUPS, an `ifAdmin` fallback for
+//! Transparent proxies, a beacon slot for Beacon proxies. Upgradeable
+//! implementations are then checked for the usual pitfalls: constructors that
+//! set state the proxy never sees, an `_authorizeUpgrade` anyone passes,
+//! initializers that are never locked or can run again, and base contracts
+//! without a `__gap` to grow into.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use solang_parser::pt::{Expression, Loc};
+
+use crate::ast::{ContractKind, FunctionKind};
+use crate::detectors::access_control::{has_initializer_modifier, is_slot, Analysis};
+use crate::detectors::Detector;
+use crate::finding::{Confidence, ExtraData, Finding, Severity, TraceStep};
+use crate::guards::GuardAnalysis;
+use crate::ir::{CallKind, IrFunction, Op};
+use crate::symbols::{ContractId, ContractInfo, FunctionId, SymbolTable};
+
+/// Proxy pattern a contract takes part in, as the proxy or its implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyKind {
+    /// EIP-1822 implementation carrying its own upgrade logic.
+    Uups,
+    /// Proxy whose admin can only upgrade and everyone else is forwarded.
+    Transparent,
+    /// Proxy reading its implementation from a beacon, or the beacon itself.
+    Beacon,
+    /// Any other contract delegating from its fallback (EIP-1967, clones).
+    Generic,
+}
+
+fn inherits_named(symbols: &SymbolTable, info: &ContractInfo, names: &[&str]) -> bool {
+    info.linearization
+        .iter()
+        .any(|&base| names.contains(&symbols.contract(base).name()))
+}
+
+fn has_function(symbols: &SymbolTable, info: &ContractInfo, name: &str) -> bool {
+    info.functions
+        .iter()
+        .any(|&f| symbols.function(f).name == name)
+}
+
+/// Whether the fallback of `info` forwards calls with `delegatecall`.
+fn delegates(symbols: &SymbolTable, info: &ContractInfo) -> bool {
+    info.functions.iter().any(|&f| {
+        symbols.function(f).kind == FunctionKind::Fallback
+            && IrFunction::build(symbols, Some(info.id), f)
+                .instructions()
+                .any(|(_, inst)| match &inst.op {
+                    Op::LowLevelCall { kind, .. } => *kind == CallKind::DelegateCall,
+                    Op::Assembly { calls } => calls.iter().any(|c| c == "delegatecall"),
+                    _ => false,
+                })
+    })
+}
+
+/// Proxy pattern of `info`, if any.
+pub fn proxy_kind(symbols: &SymbolTable, info: &ContractInfo) -> Option<ProxyKind> {
+    if inherits_named(symbols, info, &["UUPSUpgradeable"])
+        || has_function(symbols, info, "proxiableUUID")
+        || has_function(symbols, info, "_authorizeUpgrade")
+    {
+        return Some(ProxyKind::Uups);
+    }
+    if inherits_named(symbols, info, &["TransparentUpgradeableProxy"]) {
+        return Some(ProxyKind::Transparent);
+    }
+    if inherits_named(symbols, info, &["BeaconProxy", "UpgradeableBeacon"]) {
+        return Some(ProxyKind::Beacon);
+    }
+    if !delegates(symbols, info) {
+        return None;
+    }
+    // `_BEACON_SLOT` or a plain `beacon` address.
+    let beacon = info.state_variables.iter().any(|&var| {
+        let name = &symbols.state_variable(var).name;
+        is_slot(name) && name.to_ascii_uppercase().contains("BEACON")
+            || name.trim_start_matches('_') == "beacon"
+    });
+    if beacon {
+        Some(ProxyKind::Beacon)
+    } else if symbols.resolve_modifier(info.id, "ifAdmin").is_some() {
+        Some(ProxyKind::Transparent)
+    } else {
+        Some(ProxyKind::Generic)
+    }
+}
+
+/// Whether `info` is written to live behind a proxy: it inherits
+/// `Initializable` or an `*Upgradeable` contract, or is a UUPS implementation.
+pub fn is_upgradeable(symbols: &SymbolTable, info: &ContractInfo) -> bool {
+    info.linearization.iter().any(|&base| {
+        let name = symbols.contract(base).name();
+        name == "Initializable" || name.ends_with("Upgradeable")
+    }) || info
+        .functions
+        .iter()
+        .any(|&f| has_initializer_modifier(symbols, f))
+        || has_function(symbols, info, "proxiableUUID")
+        || has_function(symbols, info, "_authorizeUpgrade")
+}
+
+/// Whether a `reinitializer` version is fixed at compile time, rather than
+/// chosen by the caller.
+fn is_fixed_version(symbols: &SymbolTable, info: &ContractInfo, version: &Expression) -> bool {
+    match version {
+        Expression::NumberLiteral(..) | Expression::HexNumberLiteral(..) => true,
+        Expression::Variable(name) => info.state_variables.iter().any(|&var| {
+            let def = symbols.state_variable(var);
+            def.name == name.name && !def.is_stored()
+        }),
+        Expression::Parenthesis(_, inner) => is_fixed_version(symbols, info, inner),
+        _ => false,
+    }
+}
+
+/// Whether a hand-written `initializer` modifier checks state it never sets.
+/// Modifiers calling helpers or using assembly (OpenZeppelin 5's namespaced
+/// storage) are given the benefit of the doubt.
+fn never_locks(symbols: &SymbolTable, context: ContractId, modifier: FunctionId) -> bool {
+    let ir = IrFunction::build(symbols, Some(context), modifier);
+    let mut reads = false;
+    for (_, inst) in ir.instructions() {
+        match &inst.op {
+            Op::StorageRead { .. } => reads = true,
+            Op::StorageWrite { .. }
+            | Op::Assembly { .. }
+            | Op::InternalCall { .. }
+            | Op::LibraryCall { .. } => return false,
+            _ => {}
+        }
+    }
+    reads
+}
+
+/// Proxy-pattern pitfalls of upgradeable implementations.
+pub struct Upgradeability;
+
+impl Detector for Upgradeability {
+    fn id(&self) -> &str {
+        "upgradeability"
+    }
+
+    fn description(&self) -> &str {
+        "Unlocked, re-initializable or freely upgradeable proxy implementations"
+    }
+
+    fn cwe(&self) -> &[&str] {
+        &["CWE-284", "CWE-665"]
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::High
+    }
+
+    fn run(&self, symbols: &SymbolTable) -> Vec<Finding> {
+        detect(symbols)
+    }
+}
+
+pub fn detect(symbols: &SymbolTable) -> Vec<Finding> {
+    let mut guards = GuardAnalysis::new(symbols);
+    let mut analysis = Analysis::new(symbols);
+    let step = |function: FunctionId, description: String, loc: &Loc| TraceStep {
+        function: symbols.qualified_name(function),
+        description,
+        location: symbols.project.location(loc),
+    };
+
+    let mut findings = Vec::new();
+    let mut reported_modifiers = BTreeSet::new();
+    let mut reported_bases = BTreeSet::new();
+    for info in &symbols.contracts {
+        if info.def.kind != ContractKind::Contract || !is_upgradeable(symbols, info) {
+            continue;
+        }
+        let context = info.id;
+        let constructor = info
+            .functions
+            .iter()
+            .copied()
+            .find(|&f| symbols.function(f).kind == FunctionKind::Constructor);
+
+        // State set at deployment lives in the implementation, not the proxy.
+        let mut trace = Vec::new();
+        if let Some(constructor) = constructor {
+            for (var, (function, loc)) in analysis.summary(Some(context), constructor).writes {
+                // Immutables live in the implementation's code, which the proxy runs.
+                let def = symbols.state_variable(var);
+                if def.is_stored() && symbols.function(function).name != "_disableInitializers" {
+                    trace.push(step(function, format!("writes `{}`", def.name), &loc));
+                }
+            }
+        }
+        for &var in &info.state_variables {
+            let def = symbols.state_variable(var);
+            if def.is_stored() && def.initializer.is_some() {
+                trace.push(TraceStep {
+                    function: symbols.contract(var.contract).name().to_string(),
+                    description: format!("initializes `{}` inline", def.name),
+                    location: symbols.project.location(&def.loc),
+                });
+            }
+        }
+        if !trace.is_empty() {
+            let loc = constructor.map_or(info.def.loc, |f| symbols.function(f).loc);
+            findings.push(Finding {
+                severity: Severity::Medium,
+                confidence: Confidence::High,
+                title: "Implementation constructor sets state".into(),
+                description: format!(
+                    "`{}` is deployed behind a proxy but sets storage at construction, which only \
+                     the implementation sees; the proxy starts with these variables unset. Move \
+                     the assignments into the initializer.",
+                    info.name()
+                ),
+                location: symbols.project.location(&loc),
+                extra_data: ExtraData {
+                    trace,
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+        }
+
+        // Anyone passing `_authorizeUpgrade` can point the proxy at their own code.
+        let authorize = info
+            .functions
+            .iter()
+            .copied()
+            .find(|&f| symbols.function(f).name == "_authorizeUpgrade");
+        if let Some(func) = authorize {
+            let def = symbols.function(func);
+            if def.body.is_some() && !guards.is_guarded(Some(context), func) {
+                findings.push(Finding {
+                    severity: Severity::High,
+                    confidence: match def.modifiers.is_empty() {
+                        true => Confidence::High,
+                        false => Confidence::Medium,
+                    },
+                    title: "Unprotected upgrade authorization".into(),
+                    description: format!(
+                        "`{}._authorizeUpgrade` does not check the caller, so anyone can upgrade \
+                         the proxy to an implementation of their choosing and take over its \
+                         storage and funds.",
+                        info.name()
+                    ),
+                    location: symbols.project.location(&def.loc),
+                    extra_data: ExtraData {
+                        trace: vec![step(func, "upgrade authorization".into(), &def.loc)],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                });
+            }
+        }
+
+        // OpenZeppelin upgradeable contracts must lock the implementation itself.
+        let initializers: Vec<FunctionId> = info
+            .functions
+            .iter()
+            .copied()
+            .filter(|&f| {
+                symbols.function(f).is_entry_point() && has_initializer_modifier(symbols, f)
+            })
+            .collect();
+        let disables = constructor.is_some_and(|f| {
+            analysis
+                .summary(Some(context), f)
+                .calls
+                .contains("_disableInitializers")
+        });
+        if !initializers.is_empty() && !disables {
+            findings.push(Finding {
+                severity: Severity::Medium,
+                confidence: Confidence::Medium,
+                title: "Implementation contract can be initialized".into(),
+                description: format!(
+                    "`{}` uses initializers but its constructor does not call \
+                     `_disableInitializers()`, so anyone can initialize the implementation \
+                     behind the proxy and act as its owner.",
+                    info.name()
+                ),
+                location: symbols.project.location(&info.def.loc),
+                ..Default::default()
+            });
+        }
+
+        for &func in &initializers {
+            let def = symbols.function(func);
+            let name = format!("{}.{}", info.name(), def.display_name());
+            for invocation in &def.modifiers {
+                let Some(modifier) = symbols.resolve_modifier(context, &invocation.name) else {
+                    continue;
+                };
+                let reason = match invocation.name.as_str() {
+                    "reinitializer"
+                        if !invocation
+                            .args
+                            .iter()
+                            .all(|v| is_fixed_version(symbols, info, v))
+                            && !guards.is_guarded(Some(context), func) =>
+                    {
+                        format!(
+                            "`{name}` takes its `reinitializer` version from the caller, so anyone \
+                             can run it again with a higher version."
+                        )
+                    }
+                    "initializer" | "reinitializer"
+                        if never_locks(symbols, context, modifier)
+                            && reported_modifiers.insert(modifier) =>
+                    {
+                        format!(
+                            "The `{}` modifier used by `{name}` checks an initialized flag but \
+                             never sets it, so the initializer can be called again.",
+                            invocation.name
+                        )
+                    }
+                    _ => continue,
+                };
+                findings.push(Finding {
+                    severity: Severity::High,
+                    confidence: Confidence::Medium,
+                    title: "Re-initializable initializer".into(),
+                    description: reason,
+                    location: symbols.project.location(&def.loc),
+                    extra_data: ExtraData {
+                        trace: vec![step(modifier, "initializer guard".into(), &invocation.loc)],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                });
+            }
+        }
+
+        // A base adding variables in an upgrade shifts every slot after it.
+        // `Initializable` is the most base contract and never grows.
+        for &base in &info.linearization[1..] {
+            let base_info = symbols.contract(base);
+            if base_info.name() == "Initializable" {
+                continue;
+            }
+            let stored = base_info.def.state_variables.iter().any(|v| v.is_stored());
+            let gap = base_info
+                .def
+                .state_variables
+                .iter()
+                .any(|v| v.name.starts_with("__gap"));
+            if stored && !gap && is_upgradeable(symbols, base_info) && reported_bases.insert(base) {
+                findings.push(Finding {
+                    severity: Severity::Low,
+                    confidence: Confidence::Medium,
+                    title: "Upgradeable base contract without storage gap".into(),
+                    description: format!(
+                        "`{}` is inherited by the upgradeable `{}` and declares state but no \
+                         `__gap` array, so adding a variable to it in an upgrade shifts the \
+                         storage of every contract deriving from it.",
+                        base_info.name(),
+                        info.name()
+                    ),
+                    location: symbols.project.location(&base_info.def.loc),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Project, ProjectBundle};
+
+    const INITIALIZABLE: &str = "abstract contract Initializable {
+            uint8 private _initialized;
+            modifier initializer() { require(_initialized == 0); _initialized = 1; _; }
+            modifier reinitializer(uint8 version) {
+                require(_initialized < version); _initialized = version; _;
+            }
+            function _disableInitializers() internal { _initialized = type(uint8).max; }
+        }
+        abstract contract UUPSUpgradeable is Initializable {
+            function _authorizeUpgrade(address) internal virtual;
+            function upgradeTo(address impl) external { _authorizeUpgrade(impl); }
+        }
+        abstract contract OwnableUpgradeable is Initializable {
+            address public owner;
+            uint256[49] private __gap;
+            modifier onlyOwner() { require(msg.sender == owner); _; }
+            function __Ownable_init() internal { owner = msg.sender; }
+        }
+        ";
+
+    fn findings(src: &str) -> Vec<(String, String)> {
+        let src = format!("{INITIALIZABLE}{src}");
+        let project = Project::load(&ProjectBundle::single("Test.sol", &src));
+        let symbols = SymbolTable::build(&project);
+        detect(&symbols)
+            .into_iter()
+            .map(|f| (f.title, f.description))
+            .collect()
+    }
+
+    #[test]
+    fn test_proxy_kinds() {
+        let project = Project::load(&ProjectBundle::single(
+            "Test.sol",
+            format!(
+                "{INITIALIZABLE}
+                 contract Vault is UUPSUpgradeable, OwnableUpgradeable {{
+                    function _authorizeUpgrade(address) internal override onlyOwner {{}}
+                 }}
+                 contract Proxy {{
+                    bytes32 internal constant _IMPLEMENTATION_SLOT = 0x01;
+                    modifier ifAdmin() {{ _; }}
+                    fallback() external payable {{
+                        assembly {{ let r := delegatecall(gas(), sload(0), 0, 0, 0, 0) }}
+                    }}
+                 }}
+                 contract Clone {{
+                    address impl;
+                    fallback() external {{ (bool ok, ) = impl.delegatecall(msg.data); require(ok); }}
+                 }}
+                 contract Token {{ uint256 supply; }}"
+            ),
+        ));
+        let symbols = SymbolTable::build(&project);
+        let kinds: Vec<(&str, Option<ProxyKind>)> = symbols
+            .contracts
+            .iter()
+            .skip(3)
+            .map(|info| (info.name(), proxy_kind(&symbols, info)))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("Vault", Some(ProxyKind::Uups)),
+                ("Proxy", Some(ProxyKind::Transparent)),
+                ("Clone", Some(ProxyKind::Generic)),
+                ("Token", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_upgradeable_implementations() {
+        let findings = findings(
+            "contract Safe is UUPSUpgradeable, OwnableUpgradeable {
+                uint256 public fee;
+                address immutable weth;
+                constructor(address w) { weth = w; _disableInitializers(); }
+                function initialize() external initializer { __Ownable_init(); fee = 10; }
+                function migrate() external reinitializer(2) { fee = 20; }
+                function _authorizeUpgrade(address) internal override onlyOwner {}
+             }
+             abstract contract FeesBase is Initializable {
+                uint256 public fee = 5;
+             }
+             contract Unsafe is UUPSUpgradeable, FeesBase, OwnableUpgradeable {
+                address public admin;
+                constructor() { admin = msg.sender; }
+                function initialize() external initializer { __Ownable_init(); }
+                function migrate(uint8 version) external reinitializer(version) { fee = 0; }
+                function _authorizeUpgrade(address) internal override {}
+             }",
+        );
+        let titles: Vec<&str> = findings.iter().map(|f| f.0.as_str()).collect();
+        assert_eq!(
+            titles,
+            [
+                "Implementation constructor sets state",
+                "Unprotected upgrade authorization",
+                "Implementation contract can be initialized",
+                "Re-initializable initializer",
+                "Upgradeable base contract without storage gap",
+            ]
+        );
+        assert!(findings.iter().all(|f| f.1.contains("Unsafe")));
+        assert!(findings[4].1.starts_with("`FeesBase`"));
+    }
+
+    #[test]
+    fn test_custom_initializer_never_locks() {
+        let findings = findings(
+            "contract Custom {
+                bool initialized;
+                address public owner;
+                constructor() { _disableInitializers(); }
+                modifier initializer() { require(!initialized); _; }
+                function _disableInitializers() internal { initialized = true; }
+                function initialize() external initializer { owner = msg.sender; }
+             }",
+        );
+        assert_eq!(
+            findings,
+            [(
+                "Re-initializable initializer".to_string(),
+                "The `initializer` modifier used by `Custom.initialize` checks an initialized \
+                 flag but never sets it, so the initializer can be called again."
+                    .to_string()
+            )]
+        );
+    }
+}
@@ -0,0 +1,456 @@
+//! Share inflation in tokenized vaults: a first depositor mints a single share,
+//! donates assets to inflate its price and makes later deposits round down to
+//! nothing. Vaults are recognised by their share math — `totalAssets` next to
+//! `convertToShares` or `previewDeposit` — whether or not they claim ERC4626.
+//!
+//! Either defense suffices: virtual shares and assets (an offset added to the
+//! supply and assets in the conversion, as in OpenZeppelin's ERC4626) or a
+//! minimum first deposit, including dead shares minted to a burn address. A
+//! minimum bounds the deposited amount or what derives from it, such as the
+//! shares minted; constants compared in modifiers are locks and the like.
+//! Vaults whose `totalAssets` reads only their own storage ignore donations
+//! and are not reported.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::ast::{ContractKind, FunctionKind};
+use crate::cfg::EdgeKind;
+use crate::detectors::erc::{self, callables, claim, Standard, ROUNDING};
+use crate::detectors::Detector;
+use crate::finding::{Confidence, Finding, Severity};
+use crate::ir::{BinaryOp, Constant, IrFunction, Op, Operand, UnaryOp};
+use crate::symbols::{ContractId, ContractInfo, FunctionId, SymbolTable};
+
+/// Conversions pricing new shares.
+const SHARE_CONVERSIONS: &[&str] = &["convertToShares", "previewDeposit"];
+
+/// Entry points taking deposits.
+const DEPOSITS: &[&str] = &["deposit", "mint"];
+
+#[derive(Debug, Clone, Default)]
+struct Summary {
+    /// Adds a constant to a value, as virtual shares and assets do.
+    offset: bool,
+    /// Mints dead shares.
+    dead_shares: bool,
+    /// Parameters whose value, or a value derived from it, must be at least
+    /// a non-zero constant.
+    bounded: BTreeSet<usize>,
+    /// Calls other contracts or reads an ether balance.
+    external: bool,
+}
+
+type Key = (Option<ContractId>, FunctionId);
+
+struct Analysis<'s, 'p> {
+    symbols: &'s SymbolTable<'p>,
+    summaries: HashMap<Key, Summary>,
+    active: HashSet<Key>,
+}
+
+/// Whether `operand` is a non-zero constant such as `1`, `10 ** DECIMALS` or
+/// `_decimalsOffset()`.
+fn is_constant(ir: &IrFunction, operand: &Operand) -> bool {
+    match operand {
+        Operand::Const(Constant::Number(number)) => number != "0",
+        Operand::Value(value) => match &ir.def(*value).op {
+            Op::StateConstant(_) => true,
+            Op::Copy(a) | Op::Conversion { value: a, .. } => is_constant(ir, a),
+            Op::Binary(BinaryOp::Pow | BinaryOp::Mul | BinaryOp::Add | BinaryOp::Shl, a, b) => {
+                is_constant(ir, a) && is_constant(ir, b)
+            }
+            Op::InternalCall { name, .. } => {
+                let lower = name.to_ascii_lowercase();
+                lower.contains("offset") || lower.contains("virtual")
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Parameters `operand` derives from.
+fn params(ir: &IrFunction, operand: &Operand) -> BTreeSet<usize> {
+    let mut params = BTreeSet::new();
+    let mut seen = HashSet::new();
+    let mut pending: Vec<&Operand> = vec![operand];
+    while let Some(operand) = pending.pop() {
+        let Some(value) = operand.value().filter(|&v| seen.insert(v)) else {
+            continue;
+        };
+        match &ir.def(value).op {
+            Op::Param(index) => {
+                params.insert(*index);
+            }
+            op => pending.extend(op.operands()),
+        }
+    }
+    params
+}
+
+/// Operands `condition` bounds from below by a non-zero constant when it must
+/// evaluate to `holds`: `x >= C` and `C < x` when it holds, `x < C` when not.
+fn lower_bounds<'i>(ir: &'i IrFunction, condition: &Operand, holds: bool) -> Vec<&'i Operand> {
+    let Some(value) = condition.value() else {
+        return Vec::new();
+    };
+    match &ir.def(value).op {
+        Op::Copy(inner) => lower_bounds(ir, inner, holds),
+        Op::Unary(UnaryOp::Not, inner) => lower_bounds(ir, inner, !holds),
+        // Both sides of a holding `&&`, or of a failing `||`, apply.
+        Op::Binary(BinaryOp::And, a, b) if holds => {
+            [lower_bounds(ir, a, true), lower_bounds(ir, b, true)].concat()
+        }
+        Op::Binary(BinaryOp::Or, a, b) if !holds => {
+            [lower_bounds(ir, a, false), lower_bounds(ir, b, false)].concat()
+        }
+        Op::Binary(op, a, b) => {
+            // As `x op C`.
+            let (x, op) = match (is_constant(ir, a), is_constant(ir, b)) {
+                (false, true) => (a, *op),
+                (true, false) => match op {
+                    BinaryOp::Lt => (b, BinaryOp::Gt),
+                    BinaryOp::Le => (b, BinaryOp::Ge),
+                    BinaryOp::Gt => (b, BinaryOp::Lt),
+                    BinaryOp::Ge => (b, BinaryOp::Le),
+                    _ => return Vec::new(),
+                },
+                _ => return Vec::new(),
+            };
+            match (op, holds) {
+                (BinaryOp::Ge | BinaryOp::Gt, true) | (BinaryOp::Lt | BinaryOp::Le, false) => {
+                    vec![x]
+                }
+                _ => Vec::new(),
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Whether `operand` is a fixed address such as `address(0xdead)`.
+fn is_address_constant(ir: &IrFunction, operand: &Operand) -> bool {
+    match operand {
+        Operand::Const(Constant::Address(_)) => true,
+        Operand::Value(value) => match &ir.def(*value).op {
+            Op::Conversion { ty, value } if ty.starts_with("address") => {
+                matches!(value, Operand::Const(_)) || is_address_constant(ir, value)
+            }
+            Op::Copy(a) => is_address_constant(ir, a),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+impl Analysis<'_, '_> {
+    fn summary(&mut self, context: Option<ContractId>, func: FunctionId) -> Summary {
+        let key = (context, func);
+        if let Some(summary) = self.summaries.get(&key) {
+            return summary.clone();
+        }
+        if !self.active.insert(key) {
+            return Summary::default();
+        }
+        let ir = IrFunction::build(self.symbols, context, func);
+        let mut summary = Summary::default();
+        for (block, inst) in ir.instructions() {
+            let origin = ir.cfg.node(block).origin;
+            let modifier = self.symbols.function(origin).kind == FunctionKind::Modifier;
+            match &inst.op {
+                Op::Binary(BinaryOp::Add, a, b) => {
+                    summary.offset |= is_constant(&ir, a) || is_constant(&ir, b);
+                }
+                Op::Builtin { name, args }
+                    if (name == "require" || name == "assert") && !modifier =>
+                {
+                    for bounded in args
+                        .first()
+                        .map_or(Vec::new(), |c| lower_bounds(&ir, c, true))
+                    {
+                        summary.bounded.extend(params(&ir, bounded));
+                    }
+                }
+                Op::ExternalCall { .. } | Op::Balance(_) => summary.external = true,
+                Op::InternalCall { callee, name, args }
+                | Op::LibraryCall {
+                    callee, name, args, ..
+                } => {
+                    // `_mint(address(0xdead), 1000)`: dead shares.
+                    if name.to_ascii_lowercase().contains("mint")
+                        && args.first().is_some_and(|a| is_address_constant(&ir, a))
+                    {
+                        summary.dead_shares = true;
+                    }
+                    if let Some(callee) = callee {
+                        let inner =
+                            self.summary(self.symbols.dispatch_context(context, *callee), *callee);
+                        summary.offset |= inner.offset;
+                        summary.dead_shares |= inner.dead_shares;
+                        summary.external |= inner.external;
+                        if !modifier {
+                            for index in inner.bounded {
+                                if let Some(arg) = args.get(index) {
+                                    summary.bounded.extend(params(&ir, arg));
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        // `if (assets < MIN_DEPOSIT) revert();`: a branch with an arm that cannot complete.
+        for block in ir.cfg.reverse_postorder() {
+            let Some(condition) = &ir.blocks[block].condition else {
+                continue;
+            };
+            let origin = ir.cfg.node(block).origin;
+            if self.symbols.function(origin).kind == FunctionKind::Modifier {
+                continue;
+            }
+            // The value the condition must have to continue.
+            let holds = ir
+                .cfg
+                .successors(block)
+                .find(|edge| !ir.cfg.can_reach(edge.to, ir.cfg.exit))
+                .map(|edge| edge.kind != EdgeKind::True);
+            for bounded in holds.map_or(Vec::new(), |holds| lower_bounds(&ir, condition, holds)) {
+                summary.bounded.extend(params(&ir, bounded));
+            }
+        }
+        self.active.remove(&key);
+        self.summaries.insert(key, summary.clone());
+        summary
+    }
+}
+
+/// Callable implementations of `names` in `info`.
+fn implementations(symbols: &SymbolTable, info: &ContractInfo, names: &[&str]) -> Vec<FunctionId> {
+    names
+        .iter()
+        .flat_map(|name| callables(symbols, info, name))
+        .filter_map(|c| c.function)
+        .collect()
+}
+
+/// First-depositor share inflation and rounding in the depositor's favour.
+pub struct VaultInflation;
+
+impl Detector for VaultInflation {
+    fn id(&self) -> &str {
+        "vault-inflation"
+    }
+
+    fn description(&self) -> &str {
+        "Vaults whose share price a first depositor can inflate"
+    }
+
+    fn cwe(&self) -> &[&str] {
+        &["CWE-682"]
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::High
+    }
+
+    fn run(&self, symbols: &SymbolTable) -> Vec<Finding> {
+        detect(symbols)
+    }
+}
+
+pub fn detect(symbols: &SymbolTable) -> Vec<Finding> {
+    let mut analysis = Analysis {
+        symbols,
+        summaries: HashMap::new(),
+        active: HashSet::new(),
+    };
+    let mut rounding = erc::Analysis::new(symbols);
+    let mut findings = Vec::new();
+    for info in &symbols.contracts {
+        if info.def.kind != ContractKind::Contract {
+            continue;
+        }
+        let conversions = implementations(symbols, info, SHARE_CONVERSIONS);
+        let total_assets = callables(symbols, info, "totalAssets");
+        if conversions.is_empty() || total_assets.is_empty() {
+            continue;
+        }
+        let name = info.name();
+        let mut summary = |func: FunctionId| analysis.summary(Some(info.id), func);
+        // A public `totalAssets` variable is storage, which donations do not change.
+        let donatable = total_assets
+            .iter()
+            .filter_map(|c| c.function)
+            .any(|func| summary(func).external);
+        let offset = conversions.iter().any(|&func| summary(func).offset);
+        // The deposited assets or minted shares: the unsigned parameters.
+        let minimum = implementations(symbols, info, DEPOSITS)
+            .into_iter()
+            .any(|func| {
+                let deposit = summary(func);
+                deposit.dead_shares
+                    || deposit.bounded.iter().any(|&index| {
+                        symbols.function(func).params[index]
+                            .type_name
+                            .starts_with("uint")
+                    })
+            });
+        if donatable && !offset && !minimum {
+            let def = symbols.function(conversions[0]);
+            findings.push(Finding {
+                severity: Severity::High,
+                confidence: Confidence::Medium,
+                title: "Vault share price can be inflated".into(),
+                description: format!(
+                    "`{name}` prices shares from `totalAssets()`, which counts assets sent to it \
+                     directly, and has neither virtual shares nor a minimum first deposit. The \
+                     first depositor can mint one share and donate assets to inflate its price, \
+                     so later deposits round down to few or no shares and the difference goes \
+                     to the attacker. Add a virtual offset to the supply and assets (as \
+                     OpenZeppelin's `_decimalsOffset` does), mint dead shares on the first \
+                     deposit, or require a minimum first deposit."
+                ),
+                location: symbols.project.location(&def.loc),
+                ..Default::default()
+            });
+        }
+
+        // Reported by `erc-conformance` for vaults that claim the standard.
+        if claim(symbols, info, Standard::Erc4626).is_some() {
+            continue;
+        }
+        for &(conversion, up) in ROUNDING {
+            for func in implementations(symbols, info, &[conversion]) {
+                if rounding.summary(Some(info.id), func).rounds_up() != Some(!up) {
+                    continue;
+                }
+                findings.push(Finding {
+                    severity: Severity::Medium,
+                    confidence: Confidence::Medium,
+                    title: "Vault rounding favors the caller".into(),
+                    description: format!(
+                        "`{name}.{conversion}` rounds {} in favour of the caller rather than the \
+                         vault; repeated small operations then extract value from other \
+                         depositors. Round {} instead.",
+                        if up { "down" } else { "up" },
+                        if up { "up" } else { "down" }
+                    ),
+                    location: symbols.project.location(&symbols.function(func).loc),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Project, ProjectBundle};
+
+    /// A vault with the given bodies of `totalAssets` and `convertToShares`,
+    /// and `deposit` checks.
+    fn vault(total_assets: &str, convert: &str, deposit: &str) -> String {
+        format!(
+            "interface IERC20 {{
+                function balanceOf(address) external view returns (uint256);
+                function transferFrom(address, address, uint256) external returns (bool);
+             }}
+             contract Vault {{
+                IERC20 asset;
+                uint256 public totalSupply;
+                uint256 internal deposited;
+                uint256 constant MIN_DEPOSIT = 1e6;
+                mapping(address => uint256) public balanceOf;
+                function totalAssets() public view returns (uint256) {{ {total_assets} }}
+                function convertToShares(uint256 assets) public view returns (uint256) {{ {convert} }}
+                function deposit(uint256 assets, address receiver) external returns (uint256 shares) {{
+                    {deposit}
+                    shares = convertToShares(assets);
+                    asset.transferFrom(msg.sender, address(this), assets);
+                    _mint(receiver, shares);
+                }}
+                function _mint(address to, uint256 shares) internal {{
+                    totalSupply += shares;
+                    balanceOf[to] += shares;
+                }}
+             }}"
+        )
+    }
+
+    fn titles(src: &str) -> Vec<String> {
+        let project = Project::load(&ProjectBundle::single("Test.sol", src));
+        let symbols = SymbolTable::build(&project);
+        detect(&symbols).into_iter().map(|f| f.title).collect()
+    }
+
+    #[test]
+    fn test_share_inflation() {
+        let balance = "return asset.balanceOf(address(this));";
+        let naive = "return totalSupply == 0 ? assets : assets * totalSupply / totalAssets();";
+        assert_eq!(
+            titles(&vault(balance, naive, "require(assets != 0);")),
+            ["Vault share price can be inflated"]
+        );
+        // Virtual shares and assets.
+        let offset = "return assets * (totalSupply + 1e3) / (totalAssets() + 1);";
+        assert_eq!(titles(&vault(balance, offset, "")), [] as [&str; 0]);
+        // Minimum first deposit, or dead shares.
+        let minimum = "if (totalSupply == 0) require(assets >= MIN_DEPOSIT);";
+        assert_eq!(titles(&vault(balance, naive, minimum)), [] as [&str; 0]);
+        let dead = "if (totalSupply == 0) _mint(address(0xdead), 1000);";
+        assert_eq!(titles(&vault(balance, naive, dead)), [] as [&str; 0]);
+        let shares = "require(convertToShares(assets) >= 1e3);";
+        assert_eq!(titles(&vault(balance, naive, shares)), [] as [&str; 0]);
+        let branch = "if (MIN_DEPOSIT > assets) revert();";
+        assert_eq!(titles(&vault(balance, naive, branch)), [] as [&str; 0]);
+        let both = "require(assets != 0 && assets >= MIN_DEPOSIT);";
+        assert_eq!(titles(&vault(balance, naive, both)), [] as [&str; 0]);
+        // A cap is no minimum.
+        for cap in [
+            "require(assets <= MIN_DEPOSIT);",
+            "if (assets > MIN_DEPOSIT) revert();",
+        ] {
+            assert_eq!(
+                titles(&vault(balance, naive, cap)),
+                ["Vault share price can be inflated"]
+            );
+        }
+        // Constants compared elsewhere, as reentrancy locks do, are no minimum.
+        let guarded = vault(balance, naive, "require(assets != 0);").replace(
+            "address receiver) external",
+            "address receiver) external nonReentrant",
+        ) + "
+             abstract contract ReentrancyGuard {
+                uint256 private locked = 1;
+                modifier nonReentrant() { require(locked == 1); locked = 2; _; locked = 1; }
+             }";
+        let guarded = guarded.replace("contract Vault {", "contract Vault is ReentrancyGuard {");
+        assert_eq!(titles(&guarded), ["Vault share price can be inflated"]);
+        // Internal accounting ignores donations.
+        assert_eq!(
+            titles(&vault("return deposited;", naive, "")),
+            [] as [&str; 0]
+        );
+    }
+
+    #[test]
+    fn test_vault_rounding() {
+        let findings = titles(
+            "library Math {
+                function mulDivUp(uint256 x, uint256 y, uint256 d) internal pure returns (uint256) { return (x * y + d - 1) / d; }
+             }
+             contract Pool {
+                using Math for uint256;
+                uint256 public totalSupply;
+                uint256 public totalAssets;
+                function convertToShares(uint256 assets) public view returns (uint256) {
+                    return assets.mulDivUp(totalSupply + 1, totalAssets + 1);
+                }
+             }",
+        );
+        assert_eq!(findings, ["Vault rounding favors the caller"]);
+    }
+}
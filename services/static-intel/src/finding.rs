@@ -0,0 +1,11 @@
+//! Findings reported by static-intel, in the model shared with the other
+//! engines (see `rukh-findings`).
+
+pub use rukh_findings::baseline::{self, Baseline};
+pub use rukh_findings::{
+    merge, sarif, Confidence, ExtraData, Finding, Severity, Span, Suppression, SuppressionKind,
+    TraceStep,
+};
+
+/// `source_engine` of static-intel findings.
+pub const SOURCE_ENGINE: &str = "static-intel";
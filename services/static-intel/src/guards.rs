@@ -0,0 +1,615 @@
+//! Access-control guard detection.
+//!
+//! A guard is a condition (`require`, `assert` or a branch) that compares the
+//! caller identity — `msg.sender` or `tx.origin` — against contract state:
+//! `msg.sender == owner`, `admins[msg.sender]`, `hasRole(ROLE, msg.sender)`.
+//! A flag keyed by the caller guards only when it must be set to proceed and
+//! the function does not set it itself; `require(!claimed[msg.sender])`
+//! prevents replays, not strangers.
+//! Checks performed inside called internal functions (`_checkOwner()`) count for
+//! the caller, with the callee's parameters substituted by the call arguments.
+//! Ordering comparisons such as `balances[msg.sender] >= amount` are not guards.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use solang_parser::pt::Loc;
+
+use crate::ast::{ContractKind, StateMutability, VariableMutability};
+use crate::cfg::EdgeKind;
+use crate::ir::{BinaryOp, EnvVar, IrFunction, Op, Operand, PathElem, UnaryOp};
+use crate::symbols::{ContractId, FunctionId, StateVarId, SymbolTable};
+
+/// What a value depends on, for guard purposes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Atom {
+    Sender,
+    Origin,
+    /// Parameter of the summarised function, substituted at call sites.
+    Param(usize),
+    Var(StateVarId),
+}
+
+type Atoms = BTreeSet<Atom>;
+
+/// A condition comparing `subject` (caller identity or a parameter standing for
+/// it) against the state variables `vars`, keyed by `keys` (`_roles[ROLE][...]`).
+#[derive(Debug, Clone)]
+struct Check {
+    subject: Atoms,
+    vars: BTreeSet<StateVarId>,
+    keys: Atoms,
+    /// For a flag keyed by the caller (`admins[msg.sender]`), whether the
+    /// condition holds when the flag is set rather than unset.
+    flag: Option<bool>,
+    loc: Loc,
+}
+
+impl Check {
+    fn merge(self, other: Check) -> Check {
+        Check {
+            subject: self.subject.union(&other.subject).copied().collect(),
+            vars: self.vars.union(&other.vars).copied().collect(),
+            keys: self.keys.union(&other.keys).copied().collect(),
+            flag: self.flag.zip(other.flag).map(|(a, b)| a || b),
+            loc: self.loc,
+        }
+    }
+
+    fn substitute(self, args: &[Atoms], loc: Loc) -> Check {
+        Check {
+            subject: substitute(&self.subject, args),
+            vars: self.vars,
+            keys: substitute(&self.keys, args),
+            loc,
+            ..self
+        }
+    }
+
+    /// Whether the check guards a function when its condition must be `holds`
+    /// to proceed; `writes` are the variables it writes with the caller they
+    /// are keyed by. `admins[a] = true` after `require(admins[msg.sender])` is
+    /// guarded, `claimed[msg.sender] = true` after `require(claimed[msg.sender])` not.
+    fn guards(&self, holds: bool, writes: &BTreeSet<(StateVarId, Atom)>) -> bool {
+        let written = self.vars.iter().any(|&var| {
+            self.subject
+                .iter()
+                .any(|&atom| writes.contains(&(var, atom)))
+        });
+        self.flag.is_none_or(|set| set == holds && !written)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Summary {
+    /// Dependencies of the returned values.
+    returns: Atoms,
+    /// Set when the function returns the outcome of a check (`hasRole`).
+    returns_check: Option<Check>,
+    /// Checks the function enforces.
+    checks: Vec<Check>,
+}
+
+/// An access-control check enforced on every path through a function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Guard {
+    /// State the caller is checked against (`owner`, `_roles`, ...).
+    pub vars: BTreeSet<StateVarId>,
+    /// Constants selecting the role checked for, such as `MINTER_ROLE` in
+    /// `hasRole(MINTER_ROLE, msg.sender)`.
+    pub roles: BTreeSet<StateVarId>,
+    /// The check uses `tx.origin` rather than `msg.sender`.
+    pub tx_origin: bool,
+    pub loc: Loc,
+}
+
+type Key = (Option<ContractId>, FunctionId);
+
+pub struct GuardAnalysis<'s, 'p> {
+    symbols: &'s SymbolTable<'p>,
+    summaries: HashMap<Key, Summary>,
+    active: HashSet<Key>,
+}
+
+impl<'s, 'p> GuardAnalysis<'s, 'p> {
+    pub fn new(symbols: &'s SymbolTable<'p>) -> Self {
+        Self {
+            symbols,
+            summaries: HashMap::new(),
+            active: HashSet::new(),
+        }
+    }
+
+    /// Checks of the caller identity made when `func` runs in `context`,
+    /// including those of its modifiers and of the internal functions it calls.
+    pub fn guards(&mut self, context: Option<ContractId>, func: FunctionId) -> Vec<Guard> {
+        let symbols = self.symbols;
+        self.summary(context, func)
+            .checks
+            .into_iter()
+            .filter_map(|check| {
+                let sender = check.subject.contains(&Atom::Sender);
+                let origin = check.subject.contains(&Atom::Origin);
+                let roles = state_vars(&check.keys)
+                    .into_iter()
+                    .filter(|&var| {
+                        symbols.state_variable(var).mutability != VariableMutability::Mutable
+                    })
+                    .collect();
+                (sender || origin).then_some(Guard {
+                    vars: check.vars,
+                    roles,
+                    tx_origin: origin && !sender,
+                    loc: check.loc,
+                })
+            })
+            .collect()
+    }
+
+    pub fn is_guarded(&mut self, context: Option<ContractId>, func: FunctionId) -> bool {
+        !self.guards(context, func).is_empty()
+    }
+
+    /// State variables checked against the caller anywhere in the project.
+    pub fn guard_variables(&mut self) -> BTreeSet<StateVarId> {
+        let symbols = self.symbols;
+        let mut vars = BTreeSet::new();
+        for info in &symbols.contracts {
+            if info.def.kind == ContractKind::Interface {
+                continue;
+            }
+            for &func in &info.functions {
+                for guard in self.guards(Some(info.id), func) {
+                    vars.extend(guard.vars);
+                }
+            }
+        }
+        vars
+    }
+
+    fn summary(&mut self, context: Option<ContractId>, func: FunctionId) -> Summary {
+        let key = (context, func);
+        if let Some(summary) = self.summaries.get(&key) {
+            return summary.clone();
+        }
+        if !self.active.insert(key) {
+            // Recursion: assume nothing about the recursive call.
+            return Summary::default();
+        }
+        let summary = self.compute(context, func);
+        self.active.remove(&key);
+        self.summaries.insert(key, summary.clone());
+        summary
+    }
+
+    fn compute(&mut self, context: Option<ContractId>, func: FunctionId) -> Summary {
+        let ir = IrFunction::build(self.symbols, context, func);
+        let mut deps: Vec<Atoms> = vec![Atoms::new(); ir.values.len()];
+        let mut checks: Vec<Option<Check>> = vec![None; ir.values.len()];
+
+        // Values only flow forward except through phis, so a few passes settle loops.
+        for _ in 0..4 {
+            let mut changed = false;
+            for (_, inst) in ir.instructions() {
+                let Some(dst) = inst.dst else {
+                    continue;
+                };
+                let (d, c) = self.transfer(context, &inst.op, inst.loc, &deps, &checks);
+                if d != deps[dst] {
+                    deps[dst] = d;
+                    changed = true;
+                }
+                if c.is_some() && checks[dst].is_none() {
+                    checks[dst] = c;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let mut writes = BTreeSet::new();
+        for (_, inst) in ir.instructions() {
+            if let Op::StorageWrite { var, path, .. } = &inst.op {
+                for elem in path {
+                    if let PathElem::Index(index) = elem {
+                        let callers = operand_deps(&deps, index).into_iter().filter(is_subject);
+                        writes.extend(callers.map(|atom| (*var, atom)));
+                    }
+                }
+            }
+        }
+        let check_of = |operand: &Operand, loc: Loc| {
+            operand
+                .value()
+                .and_then(|v| checks[v].clone())
+                .map(|check| Check { loc, ..check })
+        };
+        let mut summary = Summary::default();
+        for (_, inst) in ir.instructions() {
+            match &inst.op {
+                Op::Builtin { name, args } if name == "require" || name == "assert" => {
+                    if let Some(check) = args
+                        .first()
+                        .and_then(|a| check_of(a, inst.loc))
+                        .filter(|check| check.guards(true, &writes))
+                    {
+                        summary.checks.push(check);
+                    }
+                }
+                Op::InternalCall {
+                    callee: Some(callee),
+                    args,
+                    ..
+                }
+                | Op::LibraryCall {
+                    callee: Some(callee),
+                    args,
+                    ..
+                } => {
+                    let callee_context = self.symbols.dispatch_context(context, *callee);
+                    let inner = self.summary(callee_context, *callee);
+                    let args: Vec<Atoms> = args.iter().map(|a| operand_deps(&deps, a)).collect();
+                    for check in inner.checks {
+                        summary.checks.push(check.substitute(&args, inst.loc));
+                    }
+                }
+                Op::Return(values) => {
+                    for value in values {
+                        summary.returns.extend(operand_deps(&deps, value));
+                    }
+                    if let Some(check) = values.first().and_then(|v| check_of(v, inst.loc)) {
+                        summary.returns_check = Some(match summary.returns_check.take() {
+                            Some(existing) => existing.merge(check),
+                            None => check,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        // `if (msg.sender != owner) revert();`: a branch with an arm that cannot complete.
+        for block in ir.cfg.reverse_postorder() {
+            let Some(condition) = &ir.blocks[block].condition else {
+                continue;
+            };
+            // The value the condition must have to continue.
+            let holds = ir
+                .cfg
+                .successors(block)
+                .find(|edge| !ir.cfg.can_reach(edge.to, ir.cfg.exit))
+                .map(|edge| edge.kind != EdgeKind::True);
+            if let Some(check) = holds.and_then(|holds| {
+                check_of(condition, ir.cfg.node(block).loc)
+                    .filter(|check| check.guards(holds, &writes))
+            }) {
+                summary.checks.push(check);
+            }
+        }
+        summary
+    }
+
+    /// Dependencies and check of the value defined by `op`.
+    fn transfer(
+        &mut self,
+        context: Option<ContractId>,
+        op: &Op,
+        loc: Loc,
+        deps: &[Atoms],
+        checks: &[Option<Check>],
+    ) -> (Atoms, Option<Check>) {
+        let operand_check = |operand: &Operand| operand.value().and_then(|v| checks[v].clone());
+        let union = |operands: Vec<&Operand>| -> Atoms {
+            operands
+                .into_iter()
+                .flat_map(|o| operand_deps(deps, o))
+                .collect()
+        };
+        match op {
+            Op::Param(index) => (Atoms::from([Atom::Param(*index)]), None),
+            Op::Env(EnvVar::MsgSender) => (Atoms::from([Atom::Sender]), None),
+            Op::Env(EnvVar::TxOrigin) => (Atoms::from([Atom::Origin]), None),
+            Op::StateConstant(var) => (Atoms::from([Atom::Var(*var)]), None),
+            Op::StorageRead { var, path } => {
+                let mut atoms = Atoms::from([Atom::Var(*var)]);
+                let mut subject = Atoms::new();
+                let mut keys = Atoms::new();
+                for elem in path {
+                    if let PathElem::Index(index) = elem {
+                        let index = operand_deps(deps, index);
+                        subject.extend(index.iter().copied().filter(is_subject));
+                        keys.extend(index.iter().copied());
+                        atoms.extend(index);
+                    }
+                }
+                // `admins[msg.sender]`: reading state keyed by the caller.
+                let check = (!subject.is_empty()).then(|| Check {
+                    subject,
+                    vars: BTreeSet::from([*var]),
+                    keys,
+                    flag: Some(true),
+                    loc,
+                });
+                (atoms, check)
+            }
+            Op::Binary(op, a, b) => {
+                let (da, db) = (operand_deps(deps, a), operand_deps(deps, b));
+                let atoms = da.union(&db).copied().collect();
+                let check = match op {
+                    BinaryOp::Eq | BinaryOp::Ne => compare(&da, &db, loc),
+                    BinaryOp::And | BinaryOp::Or => match (operand_check(a), operand_check(b)) {
+                        (Some(x), Some(y)) => Some(x.merge(y)),
+                        (x, y) => x.or(y),
+                    },
+                    _ => None,
+                };
+                (atoms, check)
+            }
+            Op::Unary(UnaryOp::Not, a) => {
+                let check = operand_check(a).map(|check| Check {
+                    flag: check.flag.map(|set| !set),
+                    ..check
+                });
+                (operand_deps(deps, a), check)
+            }
+            Op::Copy(a) => (operand_deps(deps, a), operand_check(a)),
+            Op::Phi(incoming) => {
+                let atoms = union(incoming.iter().map(|(_, o)| o).collect());
+                let check = incoming.iter().find_map(|(_, o)| operand_check(o));
+                (atoms, check)
+            }
+            Op::InternalCall {
+                callee: Some(callee),
+                args,
+                ..
+            }
+            | Op::LibraryCall {
+                callee: Some(callee),
+                args,
+                ..
+            } => {
+                let callee_context = self.symbols.dispatch_context(context, *callee);
+                let inner = self.summary(callee_context, *callee);
+                let args: Vec<Atoms> = args.iter().map(|a| operand_deps(deps, a)).collect();
+                let check = inner
+                    .returns_check
+                    .map(|check| check.substitute(&args, loc));
+                (substitute(&inner.returns, &args), check)
+            }
+            // `registry.isAllowed(msg.sender)` against a stored registry.
+            Op::ExternalCall { target, args, .. } => {
+                let target = operand_deps(deps, target);
+                let args = union(args.iter().collect());
+                let atoms: Atoms = target.union(&args).copied().collect();
+                let subject: Atoms = args.iter().copied().filter(is_subject).collect();
+                let vars = state_vars(&target);
+                let check = (!subject.is_empty() && !vars.is_empty()).then(|| Check {
+                    subject,
+                    vars,
+                    keys: args.clone(),
+                    flag: None,
+                    loc,
+                });
+                (atoms, check)
+            }
+            other => (union(other.operands()), None),
+        }
+    }
+}
+
+/// Which functions of a contract each role may call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoleMap {
+    pub contract: String,
+    /// Role → entry points restricted to it. Roles are named after the role
+    /// constant (`MINTER_ROLE`) or, without one, the state the caller is checked
+    /// against (`owner`); `tx.origin` checks are suffixed with ` (tx.origin)`.
+    pub roles: BTreeMap<String, Vec<String>>,
+    /// State-changing entry points anyone can call.
+    pub unrestricted: Vec<String>,
+}
+
+/// Role → function matrix of every deployable contract.
+pub fn role_map(symbols: &SymbolTable) -> Vec<RoleMap> {
+    let mut analysis = GuardAnalysis::new(symbols);
+    let mut maps = Vec::new();
+    for info in &symbols.contracts {
+        if info.def.kind != ContractKind::Contract {
+            continue;
+        }
+        let mut map = RoleMap {
+            contract: info.name().to_string(),
+            roles: BTreeMap::new(),
+            unrestricted: Vec::new(),
+        };
+        for &func in &info.functions {
+            let def = symbols.function(func);
+            if !def.is_entry_point() {
+                continue;
+            }
+            let guards = analysis.guards(Some(info.id), func);
+            if guards.is_empty() {
+                if !matches!(
+                    def.mutability,
+                    StateMutability::View | StateMutability::Pure
+                ) {
+                    map.unrestricted.push(def.signature());
+                }
+                continue;
+            }
+            let mut roles = BTreeSet::new();
+            for guard in guards {
+                let vars = match guard.roles.is_empty() {
+                    true => guard.vars,
+                    false => guard.roles,
+                };
+                for var in vars {
+                    let name = &symbols.state_variable(var).name;
+                    roles.insert(match guard.tx_origin {
+                        true => format!("{name} (tx.origin)"),
+                        false => name.clone(),
+                    });
+                }
+            }
+            for role in roles {
+                map.roles.entry(role).or_default().push(def.signature());
+            }
+        }
+        maps.push(map);
+    }
+    maps
+}
+
+fn is_subject(atom: &Atom) -> bool {
+    matches!(atom, Atom::Sender | Atom::Origin | Atom::Param(_))
+}
+
+fn state_vars(atoms: &Atoms) -> BTreeSet<StateVarId> {
+    atoms
+        .iter()
+        .filter_map(|atom| match atom {
+            Atom::Var(var) => Some(*var),
+            _ => None,
+        })
+        .collect()
+}
+
+fn operand_deps(deps: &[Atoms], operand: &Operand) -> Atoms {
+    operand.value().map(|v| deps[v].clone()).unwrap_or_default()
+}
+
+/// `subject == state` in either order.
+fn compare(a: &Atoms, b: &Atoms, loc: Loc) -> Option<Check> {
+    let side = |subject: &Atoms, state: &Atoms| {
+        let subjects: Atoms = subject.iter().copied().filter(is_subject).collect();
+        let vars = state_vars(state);
+        let state_has_subject = state.iter().any(is_subject);
+        (!subjects.is_empty() && !vars.is_empty() && !state_has_subject).then_some(Check {
+            subject: subjects,
+            vars,
+            keys: Atoms::new(),
+            flag: None,
+            loc,
+        })
+    };
+    side(a, b).or_else(|| side(b, a))
+}
+
+/// Replaces parameter atoms of a callee by the atoms of the call arguments.
+fn substitute(atoms: &Atoms, args: &[Atoms]) -> Atoms {
+    atoms
+        .iter()
+        .flat_map(|atom| match atom {
+            Atom::Param(index) => args.get(*index).cloned().unwrap_or_default(),
+            other => Atoms::from([*other]),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Project, ProjectBundle};
+
+    fn guarded_functions(src: &str, contract: &str) -> Vec<(String, Vec<String>)> {
+        let project = Project::load(&ProjectBundle::single("Test.sol", src));
+        let symbols = SymbolTable::build(&project);
+        let info = symbols.contract_by_name(contract, None).unwrap();
+        let mut analysis = GuardAnalysis::new(&symbols);
+        let mut out = Vec::new();
+        for &func in &info.functions {
+            let mut vars: Vec<String> = analysis
+                .guards(Some(info.id), func)
+                .into_iter()
+                .flat_map(|g| g.vars)
+                .map(|v| symbols.state_variable(v).name.clone())
+                .collect();
+            vars.sort();
+            vars.dedup();
+            out.push((symbols.function(func).display_name().to_string(), vars));
+        }
+        out
+    }
+
+    #[test]
+    fn test_detects_guards() {
+        let guards = guarded_functions(
+            "abstract contract Ownable {
+                address private _owner;
+                modifier onlyOwner() { _checkOwner(); _; }
+                function owner() public view returns (address) { return _owner; }
+                function _checkOwner() internal view { if (owner() != _msgSender()) revert(); }
+                function _msgSender() internal view returns (address) { return msg.sender; }
+             }
+             contract A is Ownable {
+                mapping(bytes32 => mapping(address => bool)) roles;
+                mapping(address => uint) balances;
+                address admin;
+                function hasRole(bytes32 role, address account) public view returns (bool) {
+                    return roles[role][account];
+                }
+                function a() external onlyOwner {}
+                function b() external { require(hasRole(keccak256(\"MINTER\"), msg.sender)); }
+                function c(uint amount) external { require(balances[msg.sender] >= amount); }
+                function d() external { require(tx.origin == admin, \"no\"); }
+                function e() external { if (!hasRole(keccak256(\"MINTER\"), msg.sender)) revert(); }
+                mapping(address => bool) admins;
+                function addAdmin(address a) external { require(admins[msg.sender]); admins[a] = true; }
+             }",
+            "A",
+        );
+        let find = |name: &str| {
+            guards
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, vars)| vars.clone())
+                .unwrap()
+        };
+        assert_eq!(find("a"), ["_owner"]);
+        assert_eq!(find("b"), ["roles"]);
+        assert!(find("c").is_empty());
+        assert_eq!(find("d"), ["admin"]);
+        assert_eq!(find("e"), ["roles"]);
+        assert_eq!(find("addAdmin"), ["admins"]);
+        assert!(find("hasRole").is_empty());
+    }
+
+    #[test]
+    fn test_role_map() {
+        let project = Project::load(&ProjectBundle::single(
+            "Test.sol",
+            "contract Token {
+                bytes32 public constant MINTER_ROLE = keccak256(\"MINTER_ROLE\");
+                bytes32 public constant PAUSER_ROLE = keccak256(\"PAUSER_ROLE\");
+                mapping(bytes32 => mapping(address => bool)) private _roles;
+                address public owner;
+                modifier onlyRole(bytes32 role) { require(_roles[role][msg.sender]); _; }
+                modifier onlyOwner() { require(msg.sender == owner); _; }
+                function mint(address to) external onlyRole(MINTER_ROLE) {}
+                function pause() external onlyRole(PAUSER_ROLE) {}
+                function setOwner(address o) external onlyOwner { owner = o; }
+                function burn() external {}
+                mapping(address => bool) claimed;
+                function claim() external { require(!claimed[msg.sender]); claimed[msg.sender] = true; }
+                function total() external view returns (uint) { return 0; }
+             }",
+        ));
+        let symbols = SymbolTable::build(&project);
+        let maps = role_map(&symbols);
+        let roles: Vec<(&str, Vec<&str>)> = maps[0]
+            .roles
+            .iter()
+            .map(|(role, fs)| (role.as_str(), fs.iter().map(String::as_str).collect()))
+            .collect();
+        assert_eq!(
+            roles,
+            [
+                ("MINTER_ROLE", vec!["mint(address)"]),
+                ("PAUSER_ROLE", vec!["pause()"]),
+                ("owner", vec!["setOwner(address)"])
+            ]
+        );
+        assert_eq!(maps[0].unrestricted, ["burn()", "claim()"]);
+    }
+}
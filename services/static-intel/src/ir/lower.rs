@@ -0,0 +1,1379 @@
+//! Lowering of CFG nodes into IR instructions. Locals are referenced through
+//! [`Operand::Var`] here and only renamed into SSA values afterwards.
+
+use std::collections::{HashMap, HashSet};
+
+use solang_parser::pt::{self, CodeLocation, Expression, Loc, Statement};
+
+use super::{
+    element_type, path_type, BinaryOp, Block, CallKind, Constant, EnvVar, Instruction, IrFunction,
+    Local, LocalId, LocalKind, Op, Operand, PathElem, TransferKind, UnaryOp, ValueInfo,
+};
+use crate::ast::{ContractKind, DataLocation};
+use crate::cfg::{Cfg, Node, NodeKind};
+use crate::parser::convert_storage;
+use crate::symbols::{local_names, ContractId, Declaration, FunctionId, StateVarId, SymbolTable};
+use crate::visit::{self, Visitor};
+
+pub(super) fn lower<'p>(symbols: &SymbolTable<'p>, cfg: Cfg<'p>) -> IrFunction<'p> {
+    let mut lowerer = Lowerer {
+        symbols,
+        context: cfg.context,
+        function: cfg.function,
+        scope: cfg.function,
+        values: Vec::new(),
+        locals: Vec::new(),
+        local_ids: HashMap::new(),
+        local_names: HashMap::new(),
+        aliases: HashMap::new(),
+        current: Vec::new(),
+    };
+    let mut blocks = Vec::with_capacity(cfg.nodes.len());
+    for node in &cfg.nodes {
+        lowerer.scope = node.origin;
+        let condition = lowerer.node(node);
+        blocks.push(Block {
+            id: node.id,
+            instructions: std::mem::take(&mut lowerer.current),
+            condition,
+        });
+    }
+    IrFunction {
+        cfg,
+        blocks,
+        values: lowerer.values,
+        locals: lowerer.locals,
+    }
+}
+
+/// An assignable location.
+#[derive(Debug, Clone)]
+enum Place {
+    Storage(StateVarId, Vec<PathElem>),
+    Local(LocalId, Vec<PathElem>),
+}
+
+impl Place {
+    fn path_mut(&mut self) -> &mut Vec<PathElem> {
+        match self {
+            Place::Storage(_, path) | Place::Local(_, path) => path,
+        }
+    }
+}
+
+/// What `x.f(...)` resolves to through a `using` directive.
+enum UsingTarget {
+    Library(String, Option<FunctionId>),
+    Function(FunctionId),
+}
+
+struct Lowerer<'s, 'p> {
+    symbols: &'s SymbolTable<'p>,
+    context: Option<ContractId>,
+    function: FunctionId,
+    /// Function or modifier whose identifiers are being resolved.
+    scope: FunctionId,
+    values: Vec<ValueInfo>,
+    locals: Vec<Local>,
+    local_ids: HashMap<(FunctionId, String), LocalId>,
+    local_names: HashMap<FunctionId, HashSet<String>>,
+    /// `storage` pointer locals and the storage location they refer to.
+    aliases: HashMap<LocalId, (StateVarId, Vec<PathElem>)>,
+    current: Vec<Instruction>,
+}
+
+impl<'p> Lowerer<'_, 'p> {
+    /// Lowers one CFG node into `self.current`; returns the branch condition, if any.
+    fn node(&mut self, node: &Node<'p>) -> Option<Operand> {
+        let loc = node.loc;
+        match &node.kind {
+            NodeKind::Entry => {
+                let func = self.symbols.function(self.function);
+                for (index, param) in func.params.iter().enumerate() {
+                    if let Some(name) = &param.name {
+                        let local = self.local(name);
+                        self.define(local, Op::Param(index), param.loc);
+                    }
+                }
+                for param in &func.returns {
+                    if let Some(name) = &param.name {
+                        let local = self.local(name);
+                        self.define(
+                            local,
+                            Op::Copy(Operand::Const(Constant::Default)),
+                            param.loc,
+                        );
+                    }
+                }
+            }
+            NodeKind::Exit => {
+                let func = self.symbols.function(self.function);
+                if !func.returns.is_empty() && func.returns.iter().all(|p| p.name.is_some()) {
+                    let values = func
+                        .returns
+                        .iter()
+                        .filter_map(|p| p.name.as_deref())
+                        .map(|name| Operand::Var(self.local(name)))
+                        .collect();
+                    self.effect(Op::Return(values), loc);
+                }
+            }
+            NodeKind::Revert | NodeKind::Placeholder => {}
+            NodeKind::ModifierEntry {
+                modifier,
+                invocation,
+            } => {
+                // Arguments are expressions of the modified function.
+                self.scope = self.function;
+                let args: Vec<Operand> = invocation.args.iter().map(|a| self.expr(a)).collect();
+                self.scope = *modifier;
+                let params = &self.symbols.function(*modifier).params;
+                for (param, arg) in params.iter().zip(args) {
+                    if let Some(name) = &param.name {
+                        let local = self.local(name);
+                        self.define(local, Op::Copy(arg), invocation.loc);
+                    }
+                }
+            }
+            NodeKind::Expression(expr) => {
+                self.expr(expr);
+            }
+            NodeKind::VariableDefinition(decl, init) => self.variable_definition(decl, *init),
+            NodeKind::Condition(expr) => return Some(self.expr(expr)),
+            NodeKind::Return(expr) => {
+                let values = match expr {
+                    Some(expr) => self.tuple_items(expr),
+                    None => Vec::new(),
+                };
+                self.effect(Op::Return(values), loc);
+            }
+            NodeKind::Emit(expr) => {
+                let (event, args) = match expr {
+                    Expression::FunctionCall(_, callee, args) => {
+                        (callee.to_string(), args.iter().collect())
+                    }
+                    Expression::NamedFunctionCall(_, callee, args) => {
+                        (callee.to_string(), args.iter().map(|a| &a.expr).collect())
+                    }
+                    other => (other.to_string(), Vec::new()),
+                };
+                let args = self.exprs(args);
+                self.effect(Op::Emit { event, args }, loc);
+            }
+            NodeKind::RevertStatement(stmt) => {
+                let (error, args): (_, Vec<&Expression>) = match stmt {
+                    Statement::Revert(_, error, args) => (error, args.iter().collect()),
+                    Statement::RevertNamedArgs(_, error, args) => {
+                        (error, args.iter().map(|a| &a.expr).collect())
+                    }
+                    _ => return None,
+                };
+                let args = self.exprs(args);
+                let error = error.as_ref().map(ToString::to_string);
+                self.effect(Op::Revert { error, args }, loc);
+            }
+            NodeKind::Try(expr) => self.try_call(expr, loc),
+            NodeKind::Assembly(block) => {
+                let mut calls = Vec::new();
+                yul_block(block, &mut calls);
+                calls.sort();
+                calls.dedup();
+                self.effect(Op::Assembly { calls }, loc);
+            }
+        }
+        None
+    }
+
+    fn variable_definition(&mut self, decl: &pt::VariableDeclaration, init: Option<&Expression>) {
+        let Some(name) = &decl.name else {
+            if let Some(init) = init {
+                self.expr(init);
+            }
+            return;
+        };
+        let local = self.local(&name.name);
+        let storage = decl.storage.as_ref().map(convert_storage);
+        self.locals[local].ty = Some(decl.ty.to_string());
+        self.locals[local].storage = storage;
+        self.aliases.remove(&local);
+        let value = match init {
+            Some(init) => self.bind_pointer(local, init),
+            None => Operand::Const(Constant::Default),
+        };
+        self.define(local, Op::Copy(value), decl.loc);
+    }
+
+    /// Evaluates the initializer of `local`, recording a storage alias when the
+    /// local is a `storage` pointer to a state variable.
+    fn bind_pointer(&mut self, local: LocalId, init: &Expression) -> Operand {
+        if self.locals[local].storage == Some(DataLocation::Storage) {
+            if let Some(Place::Storage(var, path)) = self.place(init) {
+                self.aliases.insert(local, (var, path.clone()));
+                return self.read(Place::Storage(var, path), init.loc());
+            }
+        }
+        self.expr(init)
+    }
+
+    fn try_call(&mut self, expr: &Expression, loc: Loc) {
+        let result = self.expr(expr);
+        let Some(body) = &self.symbols.function(self.scope).body else {
+            return;
+        };
+        let mut finder = TryFinder {
+            expr,
+            returns: Vec::new(),
+            catches: Vec::new(),
+        };
+        visit::walk_statement(&mut finder, body);
+        let single = finder.returns.len() == 1;
+        for (index, param) in finder.returns.into_iter().enumerate() {
+            let Some((name, ty)) = param else {
+                continue;
+            };
+            let local = self.local(&name);
+            self.locals[local].ty = Some(ty);
+            let value = if single {
+                result.clone()
+            } else {
+                self.emit(Op::Extract(result.clone(), index), None, loc)
+            };
+            self.define(local, Op::Copy(value), loc);
+        }
+        for (clause, name, ty) in finder.catches {
+            let local = self.local(&name);
+            self.locals[local].ty = Some(ty);
+            let description = format!("catch {}", clause.as_deref().unwrap_or("data"));
+            self.define(
+                local,
+                Op::Opaque {
+                    description,
+                    args: Vec::new(),
+                },
+                loc,
+            );
+        }
+    }
+
+    fn tuple_items(&mut self, expr: &Expression) -> Vec<Operand> {
+        match strip_parens(expr) {
+            Expression::List(_, params) => params
+                .iter()
+                .map(|(_, param)| match param {
+                    Some(param) => self.expr(&param.ty),
+                    None => Operand::Const(Constant::Default),
+                })
+                .collect(),
+            expr => vec![self.expr(expr)],
+        }
+    }
+
+    fn exprs(&mut self, exprs: Vec<&Expression>) -> Vec<Operand> {
+        exprs.into_iter().map(|e| self.expr(e)).collect()
+    }
+
+    fn expr(&mut self, expr: &Expression) -> Operand {
+        let loc = expr.loc();
+        if let Some((op, left, right)) = binary(expr) {
+            let left = self.expr(left);
+            let right = self.expr(right);
+            let ty = self.binary_type(op, &left, &right);
+            return self.emit(Op::Binary(op, left, right), ty, loc);
+        }
+        if let Some((op, target, rhs)) = compound_assignment(expr) {
+            return self.compound(target, op, rhs, loc);
+        }
+        match expr {
+            Expression::BoolLiteral(_, b) => Operand::Const(Constant::Bool(*b)),
+            Expression::NumberLiteral(..) | Expression::RationalNumberLiteral(..) => {
+                Operand::Const(Constant::Number(expr.to_string()))
+            }
+            Expression::HexNumberLiteral(_, hex, _) => {
+                Operand::Const(Constant::Number(hex.clone()))
+            }
+            Expression::StringLiteral(parts) => Operand::Const(Constant::String(
+                parts.iter().map(|p| p.string.as_str()).collect(),
+            )),
+            Expression::HexLiteral(parts) => Operand::Const(Constant::Hex(
+                parts.iter().map(|p| p.hex.as_str()).collect(),
+            )),
+            Expression::AddressLiteral(_, address) => {
+                Operand::Const(Constant::Address(address.clone()))
+            }
+            Expression::Type(_, ty) => Operand::Const(Constant::Name(ty.to_string())),
+            Expression::Parenthesis(_, inner) => self.expr(inner),
+            Expression::Variable(ident) => self.identifier(&ident.name, loc),
+            Expression::MemberAccess(_, base, member) => self.member(expr, base, &member.name),
+            Expression::ArraySubscript(_, base, Some(index)) => match self.place(expr) {
+                Some(place) => self.read(place, loc),
+                None => {
+                    let ty = self.type_of(expr);
+                    let base = self.expr(base);
+                    let index = self.expr(index);
+                    self.emit(Op::Index(base, index), ty, loc)
+                }
+            },
+            // Array type such as `uint[]` in `new uint[](n)` or `abi.decode`.
+            Expression::ArraySubscript(_, _, None) => {
+                Operand::Const(Constant::Name(expr.to_string()))
+            }
+            Expression::ArraySlice(_, base, from, to) => {
+                let mut args = vec![self.expr(base)];
+                for bound in [from, to].into_iter().flatten() {
+                    args.push(self.expr(bound));
+                }
+                self.emit(
+                    Op::Opaque {
+                        description: "slice".into(),
+                        args,
+                    },
+                    None,
+                    loc,
+                )
+            }
+            Expression::FunctionCall(_, callee, args) => {
+                self.call(loc, callee, args.iter().collect())
+            }
+            Expression::NamedFunctionCall(_, callee, args) => {
+                self.call(loc, callee, args.iter().map(|a| &a.expr).collect())
+            }
+            // Call options on a function that is not called.
+            Expression::FunctionCallBlock(_, callee, _) => self.expr(callee),
+            Expression::New(_, inner) => self.new_contract(inner, loc),
+            Expression::Not(_, operand) => self.unary(UnaryOp::Not, operand, loc),
+            Expression::BitwiseNot(_, operand) => self.unary(UnaryOp::BitNot, operand, loc),
+            Expression::Negate(_, operand) => self.unary(UnaryOp::Neg, operand, loc),
+            Expression::UnaryPlus(_, operand) => self.expr(operand),
+            Expression::Delete(_, target) => {
+                match self.place(target) {
+                    Some(place) => self.write(place, Operand::Const(Constant::Default), loc),
+                    None => {
+                        self.expr(target);
+                    }
+                }
+                Operand::Const(Constant::Default)
+            }
+            Expression::PreIncrement(_, target) => self.increment(target, BinaryOp::Add, false),
+            Expression::PreDecrement(_, target) => self.increment(target, BinaryOp::Sub, false),
+            Expression::PostIncrement(_, target) => self.increment(target, BinaryOp::Add, true),
+            Expression::PostDecrement(_, target) => self.increment(target, BinaryOp::Sub, true),
+            Expression::ConditionalOperator(_, cond, then, otherwise) => {
+                let cond = self.expr(cond);
+                let then = self.expr(then);
+                let otherwise = self.expr(otherwise);
+                let ty = self.operand_type(&then);
+                self.emit(Op::Select(cond, then, otherwise), ty, loc)
+            }
+            Expression::Assign(_, target, value) => self.assign(target, value, loc),
+            Expression::List(..) => {
+                let items = self.tuple_items(expr);
+                self.emit(Op::Tuple(items), None, loc)
+            }
+            Expression::ArrayLiteral(_, items) => {
+                let items = self.exprs(items.iter().collect());
+                self.emit(Op::Tuple(items), None, loc)
+            }
+            _ => self.emit(
+                Op::Opaque {
+                    description: expr.to_string(),
+                    args: Vec::new(),
+                },
+                None,
+                loc,
+            ),
+        }
+    }
+
+    fn unary(&mut self, op: UnaryOp, operand: &Expression, loc: Loc) -> Operand {
+        let operand = self.expr(operand);
+        let ty = match op {
+            UnaryOp::Not => Some("bool".to_string()),
+            UnaryOp::BitNot | UnaryOp::Neg => self.operand_type(&operand),
+        };
+        self.emit(Op::Unary(op, operand), ty, loc)
+    }
+
+    fn identifier(&mut self, name: &str, loc: Loc) -> Operand {
+        if self.is_local(name) {
+            let local = self.local(name);
+            return match self.aliases.get(&local).cloned() {
+                Some((var, path)) => self.read(Place::Storage(var, path), loc),
+                None => Operand::Var(local),
+            };
+        }
+        match self.resolve(name) {
+            Some(Declaration::StateVariable(var)) => {
+                self.read(Place::Storage(var, Vec::new()), loc)
+            }
+            Some(Declaration::Builtin("this")) => self.env(EnvVar::This, loc),
+            Some(Declaration::Builtin("now")) => self.env(EnvVar::BlockTimestamp, loc),
+            Some(_) => Operand::Const(Constant::Name(name.to_string())),
+            None => self.emit(
+                Op::Opaque {
+                    description: name.to_string(),
+                    args: Vec::new(),
+                },
+                None,
+                loc,
+            ),
+        }
+    }
+
+    fn env(&mut self, var: EnvVar, loc: Loc) -> Operand {
+        self.emit(Op::Env(var), Some(var.type_name().to_string()), loc)
+    }
+
+    fn member(&mut self, expr: &Expression, base: &Expression, member: &str) -> Operand {
+        let loc = expr.loc();
+        if let Expression::Variable(ident) = strip_parens(base) {
+            if let Some(var) = EnvVar::from_member(&ident.name, member) {
+                if !self.is_local(&ident.name) {
+                    return self.env(var, loc);
+                }
+            }
+            if !self.is_local(&ident.name) {
+                if let Some(Declaration::Contract(_) | Declaration::Type(_)) =
+                    self.resolve(&ident.name)
+                {
+                    // Enum values, constants of other contracts and the like.
+                    return Operand::Const(Constant::Name(expr.to_string()));
+                }
+            }
+        }
+        let base_type = self.type_of(base);
+        let struct_field = base_type
+            .as_deref()
+            .is_some_and(|ty| self.symbols.field_type(ty, member).is_some());
+        if member == "balance" && !struct_field {
+            let base = self.expr(base);
+            return self.emit(Op::Balance(base), Some("uint256".into()), loc);
+        }
+        if let Some(place) = self.place(expr) {
+            return self.read(place, loc);
+        }
+        let ty = self.type_of(expr);
+        let base = self.expr(base);
+        self.emit(Op::Member(base, member.to_string()), ty, loc)
+    }
+
+    fn assign(&mut self, target: &Expression, value: &Expression, loc: Loc) -> Operand {
+        if let Expression::List(_, params) = strip_parens(target) {
+            let value = self.expr(value);
+            for (index, (_, param)) in params.iter().enumerate() {
+                let Some(param) = param else {
+                    continue;
+                };
+                let item = self.emit(Op::Extract(value.clone(), index), None, loc);
+                match &param.name {
+                    // `(uint a, uint b) = ...` declares new locals.
+                    Some(name) => {
+                        let local = self.local(&name.name);
+                        self.locals[local].ty = Some(param.ty.to_string());
+                        self.define(local, Op::Copy(item), param.loc);
+                    }
+                    None => {
+                        if let Some(place) = self.place(&param.ty) {
+                            self.write(place, item, loc);
+                        }
+                    }
+                }
+            }
+            return value;
+        }
+        // Re-pointing a storage pointer is not a storage write.
+        if let Expression::Variable(ident) = strip_parens(target) {
+            if self.is_local(&ident.name) {
+                let local = self.local(&ident.name);
+                if self.locals[local].storage == Some(DataLocation::Storage) {
+                    self.aliases.remove(&local);
+                    let value = self.bind_pointer(local, value);
+                    self.define(local, Op::Copy(value.clone()), loc);
+                    return value;
+                }
+            }
+        }
+        let value = self.expr(value);
+        match self.place(target) {
+            Some(place) => self.write(place, value.clone(), loc),
+            None => {
+                self.expr(target);
+            }
+        }
+        value
+    }
+
+    fn compound(
+        &mut self,
+        target: &Expression,
+        op: BinaryOp,
+        rhs: &Expression,
+        loc: Loc,
+    ) -> Operand {
+        match self.place(target) {
+            Some(place) => {
+                let old = self.read(place.clone(), loc);
+                let rhs = self.expr(rhs);
+                let ty = self.binary_type(op, &old, &rhs);
+                let new = self.emit(Op::Binary(op, old, rhs), ty, loc);
+                self.write(place, new.clone(), loc);
+                new
+            }
+            None => {
+                let old = self.expr(target);
+                let rhs = self.expr(rhs);
+                let ty = self.binary_type(op, &old, &rhs);
+                self.emit(Op::Binary(op, old, rhs), ty, loc)
+            }
+        }
+    }
+
+    fn increment(&mut self, target: &Expression, op: BinaryOp, post: bool) -> Operand {
+        let loc = target.loc();
+        let Some(place) = self.place(target) else {
+            return self.expr(target);
+        };
+        let old = self.read(place.clone(), loc);
+        let ty = self.operand_type(&old);
+        let one = Operand::Const(Constant::Number("1".into()));
+        let new = self.emit(Op::Binary(op, old.clone(), one), ty, loc);
+        self.write(place, new.clone(), loc);
+        if post {
+            old
+        } else {
+            new
+        }
+    }
+
+    fn place(&mut self, expr: &Expression) -> Option<Place> {
+        match expr {
+            Expression::Parenthesis(_, inner) => self.place(inner),
+            Expression::Variable(ident) => {
+                if self.is_local(&ident.name) {
+                    let local = self.local(&ident.name);
+                    return Some(match self.aliases.get(&local) {
+                        Some((var, path)) => Place::Storage(*var, path.clone()),
+                        None => Place::Local(local, Vec::new()),
+                    });
+                }
+                match self.resolve(&ident.name) {
+                    Some(Declaration::StateVariable(var)) => Some(Place::Storage(var, Vec::new())),
+                    _ => None,
+                }
+            }
+            Expression::ArraySubscript(_, base, Some(index)) => {
+                let mut place = self.place(base)?;
+                let index = self.expr(index);
+                place.path_mut().push(PathElem::Index(index));
+                Some(place)
+            }
+            Expression::MemberAccess(_, base, member) => {
+                let mut place = self.place(base)?;
+                place.path_mut().push(match member.name.as_str() {
+                    "length" => PathElem::Length,
+                    name => PathElem::Member(name.to_string()),
+                });
+                Some(place)
+            }
+            _ => None,
+        }
+    }
+
+    fn read(&mut self, place: Place, loc: Loc) -> Operand {
+        match place {
+            Place::Storage(var, path) => {
+                let declared = self.symbols.state_variable(var);
+                if !declared.is_stored() && path.is_empty() {
+                    return self.emit(
+                        Op::StateConstant(var),
+                        Some(declared.type_name.clone()),
+                        loc,
+                    );
+                }
+                let ty = path_type(self.symbols, Some(declared.type_name.clone()), &path);
+                self.emit(Op::StorageRead { var, path }, ty, loc)
+            }
+            Place::Local(local, path) => {
+                let mut value = Operand::Var(local);
+                let mut ty = self.locals[local].ty.clone();
+                for elem in path {
+                    ty = path_type(self.symbols, ty, std::slice::from_ref(&elem));
+                    let op = match elem {
+                        PathElem::Index(index) => Op::Index(value, index),
+                        PathElem::Member(member) => Op::Member(value, member),
+                        PathElem::Length => Op::Member(value, "length".into()),
+                        PathElem::Push | PathElem::Pop => continue,
+                    };
+                    value = self.emit(op, ty.clone(), loc);
+                }
+                value
+            }
+        }
+    }
+
+    fn write(&mut self, place: Place, value: Operand, loc: Loc) {
+        match place {
+            Place::Storage(var, path) => self.effect(Op::StorageWrite { var, path, value }, loc),
+            Place::Local(local, path) if path.is_empty() => {
+                self.define(local, Op::Copy(value), loc)
+            }
+            Place::Local(local, path) => self.define(
+                local,
+                Op::MemoryWrite {
+                    base: Operand::Var(local),
+                    path,
+                    value,
+                },
+                loc,
+            ),
+        }
+    }
+
+    /// Peels call options (`{value: v, gas: g}` and the legacy `.value(v)`/`.gas(g)`)
+    /// off a callee.
+    fn call_options<'e>(
+        &mut self,
+        mut callee: &'e Expression,
+        value: &mut Option<Operand>,
+        gas: &mut Option<Operand>,
+    ) -> &'e Expression {
+        loop {
+            match callee {
+                Expression::FunctionCallBlock(_, inner, block) => {
+                    if let Statement::Args(_, args) = block.as_ref() {
+                        for arg in args {
+                            let operand = self.expr(&arg.expr);
+                            match arg.name.name.as_str() {
+                                "value" => *value = Some(operand),
+                                "gas" => *gas = Some(operand),
+                                _ => {}
+                            }
+                        }
+                    }
+                    callee = inner;
+                }
+                Expression::FunctionCall(_, inner, args) if args.len() == 1 => match inner.as_ref()
+                {
+                    Expression::MemberAccess(_, base, option)
+                        if matches!(option.name.as_str(), "value" | "gas") =>
+                    {
+                        let operand = self.expr(&args[0]);
+                        match option.name.as_str() {
+                            "value" => *value = Some(operand),
+                            _ => *gas = Some(operand),
+                        }
+                        callee = base;
+                    }
+                    _ => return callee,
+                },
+                Expression::Parenthesis(_, inner) => callee = inner,
+                _ => return callee,
+            }
+        }
+    }
+
+    fn call(&mut self, loc: Loc, callee: &Expression, args: Vec<&Expression>) -> Operand {
+        let (mut value, mut gas) = (None, None);
+        let callee = self.call_options(callee, &mut value, &mut gas);
+        match callee {
+            Expression::Type(_, ty) => {
+                let args = self.exprs(args);
+                self.conversion(ty.to_string(), args, loc)
+            }
+            Expression::Variable(ident) => self.call_identifier(&ident.name, args, loc),
+            Expression::MemberAccess(_, base, member) => {
+                self.call_member(base, &member.name, args, value, gas, loc)
+            }
+            _ => {
+                let mut operands = vec![self.expr(callee)];
+                operands.extend(self.exprs(args));
+                self.emit(
+                    Op::Opaque {
+                        description: callee.to_string(),
+                        args: operands,
+                    },
+                    None,
+                    loc,
+                )
+            }
+        }
+    }
+
+    fn conversion(&mut self, ty: String, mut args: Vec<Operand>, loc: Loc) -> Operand {
+        if args.len() == 1 {
+            let value = args.remove(0);
+            self.emit(
+                Op::Conversion {
+                    ty: ty.clone(),
+                    value,
+                },
+                Some(ty),
+                loc,
+            )
+        } else {
+            // Struct constructors.
+            self.emit(Op::Tuple(args), Some(ty), loc)
+        }
+    }
+
+    fn call_identifier(&mut self, name: &str, args: Vec<&Expression>, loc: Loc) -> Operand {
+        let arity = args.len();
+        let declaration = if self.is_local(name) {
+            None
+        } else {
+            self.resolve(name)
+        };
+        let mut args = self.exprs(args);
+        match declaration {
+            Some(Declaration::Builtin("revert")) => {
+                self.effect(Op::Revert { error: None, args }, loc);
+                Operand::Const(Constant::Default)
+            }
+            Some(Declaration::Builtin("selfdestruct" | "suicide")) => {
+                let beneficiary = match args.is_empty() {
+                    true => Operand::Const(Constant::Default),
+                    false => args.remove(0),
+                };
+                self.effect(Op::Selfdestruct(beneficiary), loc);
+                Operand::Const(Constant::Default)
+            }
+            Some(Declaration::Builtin(builtin)) => self.emit(
+                Op::Builtin {
+                    name: builtin.to_string(),
+                    args,
+                },
+                builtin_type(builtin),
+                loc,
+            ),
+            Some(Declaration::Functions(candidates)) => {
+                let callee = match self.dispatch_context() {
+                    Some(context) => self
+                        .symbols
+                        .resolve_internal_call(
+                            context,
+                            self.scope.contract(),
+                            self.symbols.file_of(self.scope),
+                            name,
+                            arity,
+                        )
+                        .first()
+                        .copied(),
+                    None => candidates
+                        .into_iter()
+                        .find(|&f| self.symbols.function(f).params.len() == arity),
+                };
+                self.internal_call(callee, name, args, loc)
+            }
+            Some(Declaration::Contract(_) | Declaration::Type(_)) => {
+                self.conversion(name.to_string(), args, loc)
+            }
+            Some(Declaration::Event(_) | Declaration::Error(_)) => self.emit(
+                Op::Opaque {
+                    description: name.to_string(),
+                    args,
+                },
+                None,
+                loc,
+            ),
+            // Function pointers and unresolved names.
+            _ => self.internal_call(None, name, args, loc),
+        }
+    }
+
+    fn internal_call(
+        &mut self,
+        callee: Option<FunctionId>,
+        name: &str,
+        args: Vec<Operand>,
+        loc: Loc,
+    ) -> Operand {
+        let ty = callee.and_then(|f| match self.symbols.function(f).returns.as_slice() {
+            [single] => Some(single.type_name.clone()),
+            _ => None,
+        });
+        let op = Op::InternalCall {
+            callee,
+            name: name.to_string(),
+            args,
+        };
+        self.emit(op, ty, loc)
+    }
+
+    fn call_member(
+        &mut self,
+        base: &Expression,
+        member: &str,
+        args: Vec<&Expression>,
+        value: Option<Operand>,
+        gas: Option<Operand>,
+        loc: Loc,
+    ) -> Operand {
+        let arity = args.len();
+        if let Expression::Variable(ident) = strip_parens(base) {
+            if !self.is_local(&ident.name) {
+                match ident.name.as_str() {
+                    "super" => {
+                        let callee = self.dispatch_context().zip(self.scope.contract()).and_then(
+                            |(context, current)| {
+                                self.symbols.resolve_super(context, current, member, arity)
+                            },
+                        );
+                        let args = self.exprs(args);
+                        return self.internal_call(callee, member, args, loc);
+                    }
+                    "abi" => {
+                        let args = self.exprs(args);
+                        let name = format!("abi.{member}");
+                        let ty = builtin_type(&name);
+                        return self.emit(Op::Builtin { name, args }, ty, loc);
+                    }
+                    _ => {}
+                }
+                if let Some(Declaration::Contract(target)) = self.resolve(&ident.name) {
+                    return self.call_contract_member(target, member, args, loc);
+                }
+            }
+        }
+        if let Expression::Type(_, ty) = strip_parens(base) {
+            // `bytes.concat`, `string.concat`
+            let args = self.exprs(args);
+            let name = format!("{ty}.{member}");
+            return self.emit(Op::Builtin { name, args }, None, loc);
+        }
+        if let Some(kind) = CallKind::from_member(member).filter(|_| arity <= 1) {
+            let target = self.expr(base);
+            let data = self.exprs(args);
+            let op = Op::LowLevelCall {
+                kind,
+                target,
+                data,
+                value,
+                gas,
+            };
+            return self.emit(op, Some("(bool, bytes)".into()), loc);
+        }
+
+        let base_type = self.type_of(base);
+        if let Some(target) = self.using_for(base_type.as_deref(), member, arity + 1) {
+            let mut operands = vec![self.expr(base)];
+            operands.extend(self.exprs(args));
+            return match target {
+                UsingTarget::Library(library, callee) => {
+                    let ty =
+                        callee.and_then(|f| match self.symbols.function(f).returns.as_slice() {
+                            [single] => Some(single.type_name.clone()),
+                            _ => None,
+                        });
+                    let op = Op::LibraryCall {
+                        library,
+                        callee,
+                        name: member.to_string(),
+                        args: operands,
+                    };
+                    self.emit(op, ty, loc)
+                }
+                UsingTarget::Function(callee) => {
+                    self.internal_call(Some(callee), member, operands, loc)
+                }
+            };
+        }
+
+        match member {
+            "transfer" | "send" if arity == 1 => {
+                let target = self.expr(base);
+                let amount = self.exprs(args).remove(0);
+                let (kind, ty) = match member {
+                    "transfer" => (TransferKind::Transfer, None),
+                    _ => (TransferKind::Send, Some("bool".to_string())),
+                };
+                return self.emit(
+                    Op::Transfer {
+                        kind,
+                        target,
+                        amount,
+                    },
+                    ty,
+                    loc,
+                );
+            }
+            "push" | "pop" if arity <= 1 => {
+                if let Some(mut place) = self.place(base) {
+                    let value = match self.exprs(args).pop() {
+                        Some(value) => value,
+                        None => Operand::Const(Constant::Default),
+                    };
+                    place.path_mut().push(match member {
+                        "push" => PathElem::Push,
+                        _ => PathElem::Pop,
+                    });
+                    self.write(place, value, loc);
+                    return Operand::Const(Constant::Default);
+                }
+            }
+            _ => {}
+        }
+
+        let target = self.expr(base);
+        let args = self.exprs(args);
+        let op = Op::ExternalCall {
+            target,
+            name: member.to_string(),
+            args,
+            value,
+            gas,
+            interface: base_type,
+        };
+        self.emit(op, None, loc)
+    }
+
+    /// `L.f(...)` for a library, or `Base.f(...)` naming a base implementation.
+    fn call_contract_member(
+        &mut self,
+        target: ContractId,
+        member: &str,
+        args: Vec<&Expression>,
+        loc: Loc,
+    ) -> Operand {
+        let arity = args.len();
+        let info = self.symbols.contract(target);
+        let callee = info
+            .def
+            .functions
+            .iter()
+            .position(|f| f.name == member && f.params.len() == arity)
+            .map(|index| FunctionId::Function {
+                contract: target,
+                index,
+            });
+        let args = self.exprs(args);
+        if info.def.kind == ContractKind::Library {
+            let op = Op::LibraryCall {
+                library: info.name().to_string(),
+                callee,
+                name: member.to_string(),
+                args,
+            };
+            return self.emit(op, None, loc);
+        }
+        let inherited = self
+            .dispatch_context()
+            .is_some_and(|context| self.symbols.contract(context).inherits(target));
+        if inherited && callee.is_some() {
+            return self.internal_call(callee, member, args, loc);
+        }
+        let description = format!("{}.{member}", info.name());
+        self.emit(Op::Opaque { description, args }, None, loc)
+    }
+
+    /// The function a `using` directive in effect attaches to values of `ty` as `name`.
+    fn using_for(&self, ty: Option<&str>, name: &str, arity: usize) -> Option<UsingTarget> {
+        let file = self.symbols.file_of(self.scope);
+        let directives: Vec<_> = match self.dispatch_context() {
+            Some(context) => self.symbols.contract(context).using.clone(),
+            None => self
+                .symbols
+                .project
+                .unit(file)
+                .map(|unit| unit.using.iter().collect())
+                .unwrap_or_default(),
+        };
+        let normalize = |t: &str| t.trim_end_matches(" payable").to_string();
+        for directive in directives {
+            if let (Some(target), Some(ty)) = (&directive.target, ty) {
+                if normalize(target) != normalize(ty) {
+                    continue;
+                }
+            }
+            if let Some(library) = &directive.library {
+                let Some(info) = self.symbols.contract_by_name(library, Some(file)) else {
+                    continue;
+                };
+                if let Some(index) = info
+                    .def
+                    .functions
+                    .iter()
+                    .position(|f| f.name == name && f.params.len() == arity)
+                {
+                    let callee = FunctionId::Function {
+                        contract: info.id,
+                        index,
+                    };
+                    return Some(UsingTarget::Library(library.clone(), Some(callee)));
+                }
+                continue;
+            }
+            for function in &directive.functions {
+                let (library, function) = match function.rsplit_once('.') {
+                    Some((library, function)) => (Some(library), function),
+                    None => (None, function.as_str()),
+                };
+                if function != name {
+                    continue;
+                }
+                match library {
+                    Some(library) => {
+                        let info = self.symbols.contract_by_name(library, Some(file))?;
+                        let callee = info
+                            .def
+                            .functions
+                            .iter()
+                            .position(|f| f.name == name && f.params.len() == arity)
+                            .map(|index| FunctionId::Function {
+                                contract: info.id,
+                                index,
+                            });
+                        return Some(UsingTarget::Library(library.to_string(), callee));
+                    }
+                    None => {
+                        if let Some(Declaration::Functions(candidates)) =
+                            self.symbols.resolve_non_local(None, self.scope, function)
+                        {
+                            if let Some(&callee) = candidates
+                                .iter()
+                                .find(|&&f| self.symbols.function(f).params.len() == arity)
+                            {
+                                return Some(UsingTarget::Function(callee));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn new_contract(&mut self, inner: &Expression, loc: Loc) -> Operand {
+        let Expression::FunctionCall(_, callee, args) = inner else {
+            return self.expr(inner);
+        };
+        let (mut value, mut gas) = (None, None);
+        let callee = self.call_options(callee, &mut value, &mut gas);
+        let contract = callee.to_string();
+        let args = self.exprs(args.iter().collect());
+        let op = Op::New {
+            contract: contract.clone(),
+            args,
+            value,
+        };
+        self.emit(op, Some(contract), loc)
+    }
+
+    /// Static type of `expr` as written in the source, where cheaply known.
+    fn type_of(&mut self, expr: &Expression) -> Option<String> {
+        match expr {
+            Expression::Parenthesis(_, inner) => self.type_of(inner),
+            Expression::Variable(ident) => {
+                if self.is_local(&ident.name) {
+                    let local = self.local(&ident.name);
+                    return self.locals[local].ty.clone();
+                }
+                match self.resolve(&ident.name)? {
+                    Declaration::StateVariable(var) => {
+                        Some(self.symbols.state_variable(var).type_name.clone())
+                    }
+                    Declaration::Builtin("this") => self
+                        .dispatch_context()
+                        .map(|c| self.symbols.contract(c).name().to_string()),
+                    Declaration::Contract(_) | Declaration::Type(_) => Some(ident.name.clone()),
+                    _ => None,
+                }
+            }
+            Expression::FunctionCall(_, callee, _) => match callee.as_ref() {
+                Expression::Type(_, ty) => Some(ty.to_string()),
+                Expression::Variable(ident) if !self.is_local(&ident.name) => {
+                    match self.resolve(&ident.name)? {
+                        Declaration::Contract(_) | Declaration::Type(_) => Some(ident.name.clone()),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            },
+            Expression::ArraySubscript(_, base, Some(_)) => element_type(&self.type_of(base)?),
+            Expression::MemberAccess(_, base, member) => {
+                if let Expression::Variable(ident) = base.as_ref() {
+                    if let Some(var) = EnvVar::from_member(&ident.name, &member.name) {
+                        return Some(var.type_name().to_string());
+                    }
+                }
+                let base = self.type_of(base);
+                path_type(self.symbols, base, &[PathElem::Member(member.name.clone())])
+            }
+            _ => None,
+        }
+    }
+
+    fn operand_type(&self, operand: &Operand) -> Option<String> {
+        match operand {
+            Operand::Value(value) => self.values[*value].ty.clone(),
+            Operand::Var(local) => self.locals[*local].ty.clone(),
+            Operand::Const(_) => None,
+        }
+    }
+
+    fn binary_type(&self, op: BinaryOp, left: &Operand, right: &Operand) -> Option<String> {
+        if op.is_comparison() || matches!(op, BinaryOp::And | BinaryOp::Or) {
+            return Some("bool".into());
+        }
+        self.operand_type(left).or_else(|| match op {
+            BinaryOp::Shl | BinaryOp::Shr | BinaryOp::Pow => None,
+            _ => self.operand_type(right),
+        })
+    }
+
+    /// Contract whose effective members calls in `scope` dispatch to.
+    fn dispatch_context(&self) -> Option<ContractId> {
+        self.symbols.dispatch_context(self.context, self.scope)
+    }
+
+    fn resolve(&self, name: &str) -> Option<Declaration> {
+        self.symbols
+            .resolve_non_local(self.dispatch_context(), self.scope, name)
+    }
+
+    fn is_local(&mut self, name: &str) -> bool {
+        let symbols = self.symbols;
+        let scope = self.scope;
+        self.local_names
+            .entry(scope)
+            .or_insert_with(|| local_names(symbols.function(scope)))
+            .contains(name)
+    }
+
+    /// The local `name` of the current scope, created on first use.
+    fn local(&mut self, name: &str) -> LocalId {
+        let key = (self.scope, name.to_string());
+        if let Some(&id) = self.local_ids.get(&key) {
+            return id;
+        }
+        let func = self.symbols.function(self.scope);
+        let declared = |params: &[crate::ast::Parameter]| {
+            params
+                .iter()
+                .find(|p| p.name.as_deref() == Some(name))
+                .map(|p| (p.type_name.clone(), p.storage))
+        };
+        let (kind, ty, storage) = if let Some((ty, storage)) = declared(&func.params) {
+            (LocalKind::Param, Some(ty), storage)
+        } else if let Some((ty, storage)) = declared(&func.returns) {
+            (LocalKind::Return, Some(ty), storage)
+        } else {
+            (LocalKind::Local, None, None)
+        };
+        let id = self.locals.len();
+        self.locals.push(Local {
+            name: name.to_string(),
+            origin: self.scope,
+            kind,
+            ty,
+            storage,
+        });
+        self.local_ids.insert(key, id);
+        id
+    }
+
+    fn new_value(&mut self, local: Option<LocalId>, ty: Option<String>) -> usize {
+        self.values.push(ValueInfo {
+            local,
+            version: 0,
+            ty,
+            def: (0, 0),
+        });
+        self.values.len() - 1
+    }
+
+    /// Appends `op` defining a fresh temporary.
+    fn emit(&mut self, op: Op, ty: Option<String>, loc: Loc) -> Operand {
+        let dst = self.new_value(None, ty);
+        self.current.push(Instruction {
+            dst: Some(dst),
+            op,
+            loc,
+        });
+        Operand::Value(dst)
+    }
+
+    /// Appends `op` for its side effect only.
+    fn effect(&mut self, op: Op, loc: Loc) {
+        self.current.push(Instruction { dst: None, op, loc });
+    }
+
+    /// Appends `op` defining the next version of `local`.
+    fn define(&mut self, local: LocalId, op: Op, loc: Loc) {
+        let ty = self.locals[local].ty.clone();
+        let dst = self.new_value(Some(local), ty);
+        self.current.push(Instruction {
+            dst: Some(dst),
+            op,
+            loc,
+        });
+    }
+}
+
+/// Return parameters and catch variables of the `try` statement calling `expr`.
+struct TryFinder<'e> {
+    expr: &'e Expression,
+    returns: Vec<Option<(String, String)>>,
+    /// (clause identifier such as `Error`, variable name, type)
+    catches: Vec<(Option<String>, String, String)>,
+}
+
+impl Visitor for TryFinder<'_> {
+    fn visit_statement(&mut self, stmt: &Statement) -> bool {
+        if let Statement::Try(_, expr, returns, catches) = stmt {
+            if std::ptr::eq(expr, self.expr) {
+                if let Some((params, _)) = returns {
+                    self.returns = params
+                        .iter()
+                        .map(|(_, p)| {
+                            let p = p.as_ref()?;
+                            Some((p.name.as_ref()?.name.clone(), p.ty.to_string()))
+                        })
+                        .collect();
+                }
+                for clause in catches {
+                    let (clause, param) = match clause {
+                        pt::CatchClause::Simple(_, param, _) => (None, param.as_ref()),
+                        pt::CatchClause::Named(_, ident, param, _) => {
+                            (Some(ident.name.clone()), Some(param))
+                        }
+                    };
+                    if let Some(param) = param {
+                        if let Some(name) = &param.name {
+                            self.catches
+                                .push((clause, name.name.clone(), param.ty.to_string()));
+                        }
+                    }
+                }
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn strip_parens(mut expr: &Expression) -> &Expression {
+    while let Expression::Parenthesis(_, inner) = expr {
+        expr = inner;
+    }
+    expr
+}
+
+fn binary(expr: &Expression) -> Option<(BinaryOp, &Expression, &Expression)> {
+    let (op, left, right) = match expr {
+        Expression::Add(_, l, r) => (BinaryOp::Add, l, r),
+        Expression::Subtract(_, l, r) => (BinaryOp::Sub, l, r),
+        Expression::Multiply(_, l, r) => (BinaryOp::Mul, l, r),
+        Expression::Divide(_, l, r) => (BinaryOp::Div, l, r),
+        Expression::Modulo(_, l, r) => (BinaryOp::Mod, l, r),
+        Expression::Power(_, l, r) => (BinaryOp::Pow, l, r),
+        Expression::ShiftLeft(_, l, r) => (BinaryOp::Shl, l, r),
+        Expression::ShiftRight(_, l, r) => (BinaryOp::Shr, l, r),
+        Expression::BitwiseAnd(_, l, r) => (BinaryOp::BitAnd, l, r),
+        Expression::BitwiseOr(_, l, r) => (BinaryOp::BitOr, l, r),
+        Expression::BitwiseXor(_, l, r) => (BinaryOp::BitXor, l, r),
+        Expression::And(_, l, r) => (BinaryOp::And, l, r),
+        Expression::Or(_, l, r) => (BinaryOp::Or, l, r),
+        Expression::Equal(_, l, r) => (BinaryOp::Eq, l, r),
+        Expression::NotEqual(_, l, r) => (BinaryOp::Ne, l, r),
+        Expression::Less(_, l, r) => (BinaryOp::Lt, l, r),
+        Expression::LessEqual(_, l, r) => (BinaryOp::Le, l, r),
+        Expression::More(_, l, r) => (BinaryOp::Gt, l, r),
+        Expression::MoreEqual(_, l, r) => (BinaryOp::Ge, l, r),
+        _ => return None,
+    };
+    Some((op, left, right))
+}
+
+fn compound_assignment(expr: &Expression) -> Option<(BinaryOp, &Expression, &Expression)> {
+    let (op, target, value) = match expr {
+        Expression::AssignAdd(_, l, r) => (BinaryOp::Add, l, r),
+        Expression::AssignSubtract(_, l, r) => (BinaryOp::Sub, l, r),
+        Expression::AssignMultiply(_, l, r) => (BinaryOp::Mul, l, r),
+        Expression::AssignDivide(_, l, r) => (BinaryOp::Div, l, r),
+        Expression::AssignModulo(_, l, r) => (BinaryOp::Mod, l, r),
+        Expression::AssignShiftLeft(_, l, r) => (BinaryOp::Shl, l, r),
+        Expression::AssignShiftRight(_, l, r) => (BinaryOp::Shr, l, r),
+        Expression::AssignAnd(_, l, r) => (BinaryOp::BitAnd, l, r),
+        Expression::AssignOr(_, l, r) => (BinaryOp::BitOr, l, r),
+        Expression::AssignXor(_, l, r) => (BinaryOp::BitXor, l, r),
+        _ => return None,
+    };
+    Some((op, target, value))
+}
+
+fn builtin_type(name: &str) -> Option<String> {
+    let ty = match name {
+        "keccak256" | "sha256" | "blockhash" | "blobhash" => "bytes32",
+        "ripemd160" => "bytes20",
+        "ecrecover" => "address",
+        "gasleft" | "addmod" | "mulmod" => "uint256",
+        "require" | "assert" => return None,
+        n if n.starts_with("abi.encode") => "bytes",
+        _ => return None,
+    };
+    Some(ty.to_string())
+}
+
+fn yul_block(block: &pt::YulBlock, calls: &mut Vec<String>) {
+    for stmt in &block.statements {
+        yul_statement(stmt, calls);
+    }
+}
+
+fn yul_statement(stmt: &pt::YulStatement, calls: &mut Vec<String>) {
+    match stmt {
+        pt::YulStatement::Assign(_, targets, value) => {
+            for expr in targets.iter().chain([value]) {
+                yul_expression(expr, calls);
+            }
+        }
+        pt::YulStatement::VariableDeclaration(_, _, value) => {
+            if let Some(value) = value {
+                yul_expression(value, calls);
+            }
+        }
+        pt::YulStatement::If(_, cond, block) => {
+            yul_expression(cond, calls);
+            yul_block(block, calls);
+        }
+        pt::YulStatement::For(f) => {
+            yul_block(&f.init_block, calls);
+            yul_expression(&f.condition, calls);
+            yul_block(&f.post_block, calls);
+            yul_block(&f.execution_block, calls);
+        }
+        pt::YulStatement::Switch(switch) => {
+            yul_expression(&switch.condition, calls);
+            for case in switch.cases.iter().chain(&switch.default) {
+                match case {
+                    pt::YulSwitchOptions::Case(_, value, block) => {
+                        yul_expression(value, calls);
+                        yul_block(block, calls);
+                    }
+                    pt::YulSwitchOptions::Default(_, block) => yul_block(block, calls),
+                }
+            }
+        }
+        pt::YulStatement::Block(block) => yul_block(block, calls),
+        pt::YulStatement::FunctionDefinition(def) => yul_block(&def.body, calls),
+        pt::YulStatement::FunctionCall(call) => yul_call(call, calls),
+        pt::YulStatement::Leave(_)
+        | pt::YulStatement::Break(_)
+        | pt::YulStatement::Continue(_)
+        | pt::YulStatement::Error(_) => {}
+    }
+}
+
+fn yul_expression(expr: &pt::YulExpression, calls: &mut Vec<String>) {
+    match expr {
+        pt::YulExpression::FunctionCall(call) => yul_call(call, calls),
+        pt::YulExpression::SuffixAccess(_, base, _) => yul_expression(base, calls),
+        _ => {}
+    }
+}
+
+fn yul_call(call: &pt::YulFunctionCall, calls: &mut Vec<String>) {
+    calls.push(call.id.name.clone());
+    for arg in &call.arguments {
+        yul_expression(arg, calls);
+    }
+}
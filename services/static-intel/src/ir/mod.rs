@@ -0,0 +1,978 @@
+//! SSA intermediate representation of function bodies.
+//!
+//! Bodies are lowered from the solang AST into a small instruction set in the
+//! spirit of SlithIR: storage reads and writes, external calls, low-level calls,
+//! ETH transfers and event emissions are distinct instructions, so detectors
+//! match a handful of operations instead of the whole expression grammar.
+//! Blocks correspond one-to-one with the nodes of the function's [`Cfg`]
+//! (modifiers included), and locals are in SSA form: every assignment defines a
+//! new value and joins merge them with phi instructions. State variables are not
+//! renamed; every access is an explicit storage instruction.
+
+mod lower;
+mod ssa;
+
+use std::fmt::{self, Write};
+
+use solang_parser::pt::Loc;
+
+use crate::ast::DataLocation;
+use crate::cfg::{Cfg, NodeId};
+use crate::symbols::{ContractId, FunctionId, StateVarId, SymbolTable};
+
+/// Blocks are indexed like the CFG nodes they were lowered from.
+pub type BlockId = NodeId;
+/// Index into [`IrFunction::values`].
+pub type ValueId = usize;
+/// Index into [`IrFunction::locals`].
+pub type LocalId = usize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Constant {
+    Bool(bool),
+    Number(String),
+    String(String),
+    Hex(String),
+    Address(String),
+    /// Zero value of the target type: `delete x`, uninitialised locals.
+    Default,
+    /// A type, contract or function name used as a value (`E.A`, `type(T)`).
+    Name(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operand {
+    Value(ValueId),
+    Const(Constant),
+    /// A local before SSA renaming; never present in a built [`IrFunction`].
+    Var(LocalId),
+}
+
+impl Operand {
+    pub fn value(&self) -> Option<ValueId> {
+        match self {
+            Operand::Value(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+    Shl,
+    Shr,
+    BitAnd,
+    BitOr,
+    BitXor,
+    And,
+    Or,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl BinaryOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Mod => "%",
+            BinaryOp::Pow => "**",
+            BinaryOp::Shl => "<<",
+            BinaryOp::Shr => ">>",
+            BinaryOp::BitAnd => "&",
+            BinaryOp::BitOr => "|",
+            BinaryOp::BitXor => "^",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+        }
+    }
+
+    /// Operations that can overflow or underflow.
+    pub fn is_arithmetic(self) -> bool {
+        matches!(
+            self,
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Pow
+        )
+    }
+
+    pub fn is_comparison(self) -> bool {
+        matches!(
+            self,
+            BinaryOp::Eq | BinaryOp::Ne | BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnaryOp {
+    Not,
+    BitNot,
+    Neg,
+}
+
+/// Transaction and block context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EnvVar {
+    MsgSender,
+    MsgValue,
+    MsgData,
+    MsgSig,
+    TxOrigin,
+    TxGasPrice,
+    BlockTimestamp,
+    BlockNumber,
+    BlockCoinbase,
+    BlockPrevrandao,
+    BlockGasLimit,
+    BlockBaseFee,
+    BlockChainId,
+    This,
+}
+
+impl EnvVar {
+    pub fn from_member(base: &str, member: &str) -> Option<Self> {
+        Some(match (base, member) {
+            ("msg", "sender") => EnvVar::MsgSender,
+            ("msg", "value") => EnvVar::MsgValue,
+            ("msg", "data") => EnvVar::MsgData,
+            ("msg", "sig") => EnvVar::MsgSig,
+            ("tx", "origin") => EnvVar::TxOrigin,
+            ("tx", "gasprice") => EnvVar::TxGasPrice,
+            ("block", "timestamp") => EnvVar::BlockTimestamp,
+            ("block", "number") => EnvVar::BlockNumber,
+            ("block", "coinbase") => EnvVar::BlockCoinbase,
+            ("block", "difficulty" | "prevrandao") => EnvVar::BlockPrevrandao,
+            ("block", "gaslimit") => EnvVar::BlockGasLimit,
+            ("block", "basefee") => EnvVar::BlockBaseFee,
+            ("block", "chainid") => EnvVar::BlockChainId,
+            _ => return None,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EnvVar::MsgSender => "msg.sender",
+            EnvVar::MsgValue => "msg.value",
+            EnvVar::MsgData => "msg.data",
+            EnvVar::MsgSig => "msg.sig",
+            EnvVar::TxOrigin => "tx.origin",
+            EnvVar::TxGasPrice => "tx.gasprice",
+            EnvVar::BlockTimestamp => "block.timestamp",
+            EnvVar::BlockNumber => "block.number",
+            EnvVar::BlockCoinbase => "block.coinbase",
+            EnvVar::BlockPrevrandao => "block.prevrandao",
+            EnvVar::BlockGasLimit => "block.gaslimit",
+            EnvVar::BlockBaseFee => "block.basefee",
+            EnvVar::BlockChainId => "block.chainid",
+            EnvVar::This => "this",
+        }
+    }
+
+    pub fn type_name(self) -> &'static str {
+        match self {
+            EnvVar::MsgSender | EnvVar::TxOrigin | EnvVar::BlockCoinbase | EnvVar::This => {
+                "address"
+            }
+            EnvVar::MsgData => "bytes",
+            EnvVar::MsgSig => "bytes4",
+            _ => "uint256",
+        }
+    }
+}
+
+/// Step from a variable to the accessed element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathElem {
+    Index(Operand),
+    Member(String),
+    Length,
+    /// `push` on the array the path leads to; only in writes.
+    Push,
+    /// `pop` on the array the path leads to; only in writes.
+    Pop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CallKind {
+    Call,
+    DelegateCall,
+    StaticCall,
+    CallCode,
+}
+
+impl CallKind {
+    pub fn from_member(member: &str) -> Option<Self> {
+        Some(match member {
+            "call" => CallKind::Call,
+            "delegatecall" => CallKind::DelegateCall,
+            "staticcall" => CallKind::StaticCall,
+            "callcode" => CallKind::CallCode,
+            _ => return None,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CallKind::Call => "call",
+            CallKind::DelegateCall => "delegatecall",
+            CallKind::StaticCall => "staticcall",
+            CallKind::CallCode => "callcode",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransferKind {
+    Transfer,
+    Send,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op {
+    /// `i`-th parameter of the analysed function; inlined modifier parameters are
+    /// copies of the invocation arguments.
+    Param(usize),
+    Copy(Operand),
+    /// One incoming value per predecessor block.
+    Phi(Vec<(BlockId, Operand)>),
+    Binary(BinaryOp, Operand, Operand),
+    Unary(UnaryOp, Operand),
+    /// `cond ? a : b`
+    Select(Operand, Operand, Operand),
+    /// Tuple, struct or array literal.
+    Tuple(Vec<Operand>),
+    /// Component of a tuple (multiple return values, destructuring).
+    Extract(Operand, usize),
+    Env(EnvVar),
+    StorageRead {
+        var: StateVarId,
+        path: Vec<PathElem>,
+    },
+    StorageWrite {
+        var: StateVarId,
+        path: Vec<PathElem>,
+        value: Operand,
+    },
+    /// Read of a `constant` or `immutable` state variable.
+    StateConstant(StateVarId),
+    Member(Operand, String),
+    Index(Operand, Operand),
+    /// Element update of a memory or calldata local; defines the local's next version.
+    MemoryWrite {
+        base: Operand,
+        path: Vec<PathElem>,
+        value: Operand,
+    },
+    /// `addr.balance`
+    Balance(Operand),
+    /// Explicit conversion such as `uint8(x)`, `address(x)` or `IERC20(x)`.
+    Conversion {
+        ty: String,
+        value: Operand,
+    },
+    /// Call of a contract or free function; `callee` is `None` when unresolved
+    /// (e.g. through a function pointer).
+    InternalCall {
+        callee: Option<FunctionId>,
+        name: String,
+        args: Vec<Operand>,
+    },
+    /// Library call, including `using L for T` calls with the receiver as first argument.
+    LibraryCall {
+        library: String,
+        callee: Option<FunctionId>,
+        name: String,
+        args: Vec<Operand>,
+    },
+    /// High-level call through a contract or interface type, or `this.f()`.
+    ExternalCall {
+        target: Operand,
+        name: String,
+        args: Vec<Operand>,
+        value: Option<Operand>,
+        gas: Option<Operand>,
+        /// Static type of the target, when known.
+        interface: Option<String>,
+    },
+    LowLevelCall {
+        kind: CallKind,
+        target: Operand,
+        data: Vec<Operand>,
+        value: Option<Operand>,
+        gas: Option<Operand>,
+    },
+    Transfer {
+        kind: TransferKind,
+        target: Operand,
+        amount: Operand,
+    },
+    New {
+        contract: String,
+        args: Vec<Operand>,
+        value: Option<Operand>,
+    },
+    /// Global function such as `require`, `keccak256`, `ecrecover` or `abi.encode`.
+    Builtin {
+        name: String,
+        args: Vec<Operand>,
+    },
+    Selfdestruct(Operand),
+    Emit {
+        event: String,
+        args: Vec<Operand>,
+    },
+    /// `revert(...)` or `revert Error(...)`.
+    Revert {
+        error: Option<String>,
+        args: Vec<Operand>,
+    },
+    Return(Vec<Operand>),
+    /// Inline assembly, summarised by the Yul builtins it calls.
+    Assembly {
+        calls: Vec<String>,
+    },
+    /// Anything the lowering does not model.
+    Opaque {
+        description: String,
+        args: Vec<Operand>,
+    },
+}
+
+/// Operands of an op, in evaluation order; shared by the `&` and `&mut` accessors.
+macro_rules! collect_operands {
+    ($op:expr, $iter:ident) => {
+        match $op {
+            Op::Param(_) | Op::Env(_) | Op::StateConstant(_) | Op::Assembly { .. } => Vec::new(),
+            Op::Copy(a)
+            | Op::Unary(_, a)
+            | Op::Extract(a, _)
+            | Op::Member(a, _)
+            | Op::Balance(a)
+            | Op::Conversion { value: a, .. }
+            | Op::Selfdestruct(a) => vec![a],
+            Op::Phi(incoming) => incoming.$iter().map(|(_, o)| o).collect(),
+            Op::Binary(_, a, b) | Op::Index(a, b) => vec![a, b],
+            Op::Select(c, a, b) => vec![c, a, b],
+            Op::Tuple(args)
+            | Op::InternalCall { args, .. }
+            | Op::LibraryCall { args, .. }
+            | Op::Builtin { args, .. }
+            | Op::Emit { args, .. }
+            | Op::Revert { args, .. }
+            | Op::Return(args)
+            | Op::Opaque { args, .. } => args.$iter().collect(),
+            Op::StorageRead { path, .. } => path
+                .$iter()
+                .filter_map(|elem| match elem {
+                    PathElem::Index(index) => Some(index),
+                    _ => None,
+                })
+                .collect(),
+            Op::StorageWrite { path, value, .. } => path
+                .$iter()
+                .filter_map(|elem| match elem {
+                    PathElem::Index(index) => Some(index),
+                    _ => None,
+                })
+                .chain([value])
+                .collect(),
+            Op::MemoryWrite { base, path, value } => std::iter::once(base)
+                .chain(path.$iter().filter_map(|elem| match elem {
+                    PathElem::Index(index) => Some(index),
+                    _ => None,
+                }))
+                .chain([value])
+                .collect(),
+            Op::ExternalCall {
+                target,
+                args,
+                value,
+                gas,
+                ..
+            } => std::iter::once(target)
+                .chain(args.$iter())
+                .chain(value.$iter())
+                .chain(gas.$iter())
+                .collect(),
+            Op::LowLevelCall {
+                target,
+                data,
+                value,
+                gas,
+                ..
+            } => std::iter::once(target)
+                .chain(data.$iter())
+                .chain(value.$iter())
+                .chain(gas.$iter())
+                .collect(),
+            Op::Transfer { target, amount, .. } => vec![target, amount],
+            Op::New { args, value, .. } => args.$iter().chain(value.$iter()).collect(),
+        }
+    };
+}
+
+impl Op {
+    pub fn operands(&self) -> Vec<&Operand> {
+        collect_operands!(self, iter)
+    }
+
+    pub(crate) fn operands_mut(&mut self) -> Vec<&mut Operand> {
+        collect_operands!(self, iter_mut)
+    }
+
+    /// Calls that hand control to code outside the contract.
+    pub fn is_external_call(&self) -> bool {
+        matches!(
+            self,
+            Op::ExternalCall { .. }
+                | Op::LowLevelCall { .. }
+                | Op::Transfer { .. }
+                | Op::New { .. }
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instruction {
+    pub dst: Option<ValueId>,
+    pub op: Op,
+    pub loc: Loc,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Block {
+    pub id: BlockId,
+    pub instructions: Vec<Instruction>,
+    /// Branch value of a condition node; successors follow the CFG's `True`/`False` edges.
+    pub condition: Option<Operand>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LocalKind {
+    Param,
+    Return,
+    Local,
+}
+
+/// A source-level local variable; its SSA versions are values pointing back here.
+#[derive(Debug, Clone)]
+pub struct Local {
+    pub name: String,
+    /// Function or modifier declaring the variable.
+    pub origin: FunctionId,
+    pub kind: LocalKind,
+    pub ty: Option<String>,
+    pub storage: Option<DataLocation>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ValueInfo {
+    /// Local this value is a version of; `None` for temporaries.
+    pub local: Option<LocalId>,
+    pub version: usize,
+    pub ty: Option<String>,
+    /// Block and instruction index of the definition.
+    pub def: (BlockId, usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct IrFunction<'p> {
+    pub cfg: Cfg<'p>,
+    pub blocks: Vec<Block>,
+    pub values: Vec<ValueInfo>,
+    pub locals: Vec<Local>,
+}
+
+impl<'p> IrFunction<'p> {
+    /// Lowers `func` as executed in `context` (see [`Cfg::build`]) into SSA form.
+    pub fn build(symbols: &SymbolTable<'p>, context: Option<ContractId>, func: FunctionId) -> Self {
+        let cfg = Cfg::build(symbols, context, func);
+        let mut ir = lower::lower(symbols, cfg);
+        ssa::construct(&mut ir);
+        ir
+    }
+
+    /// Instructions of reachable blocks in reverse post-order.
+    pub fn instructions(&self) -> impl Iterator<Item = (BlockId, &Instruction)> {
+        self.cfg
+            .reverse_postorder()
+            .into_iter()
+            .flat_map(move |b| self.blocks[b].instructions.iter().map(move |i| (b, i)))
+    }
+
+    /// Defining instruction of `value`.
+    pub fn def(&self, value: ValueId) -> &Instruction {
+        let (block, index) = self.values[value].def;
+        &self.blocks[block].instructions[index]
+    }
+
+    /// Source-level local of `value`, if it is a version of one.
+    pub fn local(&self, value: ValueId) -> Option<&Local> {
+        self.values[value].local.map(|l| &self.locals[l])
+    }
+
+    /// `amount_1` for versions of locals, `t7` for temporaries.
+    pub fn value_name(&self, value: ValueId) -> String {
+        match self.local(value) {
+            Some(local) => format!("{}_{}", local.name, self.values[value].version),
+            None => format!("t{value}"),
+        }
+    }
+
+    /// SlithIR-like listing of the reachable blocks, for debugging and tests.
+    pub fn to_text(&self, symbols: &SymbolTable) -> String {
+        let mut out = String::new();
+        for block in self.cfg.reverse_postorder() {
+            let node = self.cfg.node(block);
+            let _ = writeln!(out, "n{block}: {}", node.label(symbols));
+            for inst in &self.blocks[block].instructions {
+                let op = self.op_text(symbols, &inst.op);
+                let _ = match inst.dst {
+                    Some(dst) => writeln!(out, "  {} = {op}", self.value_name(dst)),
+                    None => writeln!(out, "  {op}"),
+                };
+            }
+            if let Some(cond) = &self.blocks[block].condition {
+                let _ = writeln!(out, "  BRANCH {}", self.operand_text(cond));
+            }
+        }
+        out
+    }
+
+    fn operand_text(&self, operand: &Operand) -> String {
+        match operand {
+            Operand::Value(value) => self.value_name(*value),
+            Operand::Const(constant) => constant.to_string(),
+            Operand::Var(local) => format!("{}?", self.locals[*local].name),
+        }
+    }
+
+    fn list_text(&self, operands: &[Operand]) -> String {
+        let items: Vec<String> = operands.iter().map(|o| self.operand_text(o)).collect();
+        items.join(", ")
+    }
+
+    fn path_text(&self, symbols: &SymbolTable, var: StateVarId, path: &[PathElem]) -> String {
+        let mut out = symbols.state_variable(var).name.clone();
+        for elem in path {
+            match elem {
+                PathElem::Index(index) => {
+                    let _ = write!(out, "[{}]", self.operand_text(index));
+                }
+                PathElem::Member(member) => {
+                    let _ = write!(out, ".{member}");
+                }
+                PathElem::Length => out.push_str(".length"),
+                PathElem::Push => out.push_str(".push"),
+                PathElem::Pop => out.push_str(".pop"),
+            }
+        }
+        out
+    }
+
+    fn options_text(&self, value: &Option<Operand>, gas: &Option<Operand>) -> String {
+        let options: Vec<String> = [("value", value), ("gas", gas)]
+            .into_iter()
+            .filter_map(|(name, o)| {
+                o.as_ref()
+                    .map(|o| format!("{name}: {}", self.operand_text(o)))
+            })
+            .collect();
+        if options.is_empty() {
+            String::new()
+        } else {
+            format!("{{{}}}", options.join(", "))
+        }
+    }
+
+    fn op_text(&self, symbols: &SymbolTable, op: &Op) -> String {
+        let o = |operand: &Operand| self.operand_text(operand);
+        match op {
+            Op::Param(index) => format!("PARAM {index}"),
+            Op::Copy(a) => o(a),
+            Op::Phi(incoming) => {
+                let items: Vec<String> = incoming
+                    .iter()
+                    .map(|(block, a)| format!("n{block}: {}", o(a)))
+                    .collect();
+                format!("PHI({})", items.join(", "))
+            }
+            Op::Binary(op, a, b) => format!("{} {} {}", o(a), op.symbol(), o(b)),
+            Op::Unary(op, a) => {
+                let symbol = match op {
+                    UnaryOp::Not => "!",
+                    UnaryOp::BitNot => "~",
+                    UnaryOp::Neg => "-",
+                };
+                format!("{symbol}{}", o(a))
+            }
+            Op::Select(c, a, b) => format!("{} ? {} : {}", o(c), o(a), o(b)),
+            Op::Tuple(items) => format!("({})", self.list_text(items)),
+            Op::Extract(a, index) => format!("EXTRACT {}, {index}", o(a)),
+            Op::Env(var) => var.name().to_string(),
+            Op::StorageRead { var, path } => {
+                format!("STORAGE_READ {}", self.path_text(symbols, *var, path))
+            }
+            Op::StorageWrite { var, path, value } => format!(
+                "STORAGE_WRITE {} = {}",
+                self.path_text(symbols, *var, path),
+                o(value)
+            ),
+            Op::StateConstant(var) => {
+                format!("CONSTANT {}", symbols.state_variable(*var).name)
+            }
+            Op::Member(a, member) => format!("{}.{member}", o(a)),
+            Op::Index(a, index) => format!("{}[{}]", o(a), o(index)),
+            Op::MemoryWrite { base, path, value } => {
+                let mut target = o(base);
+                for elem in path {
+                    match elem {
+                        PathElem::Index(index) => {
+                            let _ = write!(target, "[{}]", o(index));
+                        }
+                        PathElem::Member(member) => {
+                            let _ = write!(target, ".{member}");
+                        }
+                        PathElem::Length => target.push_str(".length"),
+                        PathElem::Push => target.push_str(".push"),
+                        PathElem::Pop => target.push_str(".pop"),
+                    }
+                }
+                format!("MEMORY_WRITE {target} = {}", o(value))
+            }
+            Op::Balance(a) => format!("BALANCE {}", o(a)),
+            Op::Conversion { ty, value } => format!("CONVERT {} TO {ty}", o(value)),
+            Op::InternalCall { callee, name, args } => {
+                let name = match callee {
+                    Some(callee) => symbols.qualified_name(*callee),
+                    None => name.clone(),
+                };
+                format!("INTERNAL_CALL {name}({})", self.list_text(args))
+            }
+            Op::LibraryCall {
+                library,
+                name,
+                args,
+                ..
+            } => format!("LIBRARY_CALL {library}.{name}({})", self.list_text(args)),
+            Op::ExternalCall {
+                target,
+                name,
+                args,
+                value,
+                gas,
+                ..
+            } => format!(
+                "EXTERNAL_CALL {}.{name}{}({})",
+                o(target),
+                self.options_text(value, gas),
+                self.list_text(args)
+            ),
+            Op::LowLevelCall {
+                kind,
+                target,
+                data,
+                value,
+                gas,
+            } => format!(
+                "LOW_LEVEL_CALL {}.{}{}({})",
+                o(target),
+                kind.name(),
+                self.options_text(value, gas),
+                self.list_text(data)
+            ),
+            Op::Transfer {
+                kind,
+                target,
+                amount,
+            } => {
+                let name = match kind {
+                    TransferKind::Transfer => "TRANSFER",
+                    TransferKind::Send => "SEND",
+                };
+                format!("{name} {} {}", o(target), o(amount))
+            }
+            Op::New {
+                contract,
+                args,
+                value,
+            } => format!(
+                "NEW {contract}{}({})",
+                self.options_text(value, &None),
+                self.list_text(args)
+            ),
+            Op::Builtin { name, args } => format!("BUILTIN {name}({})", self.list_text(args)),
+            Op::Selfdestruct(a) => format!("SELFDESTRUCT {}", o(a)),
+            Op::Emit { event, args } => format!("EMIT {event}({})", self.list_text(args)),
+            Op::Revert { error, args } => format!(
+                "REVERT {}({})",
+                error.as_deref().unwrap_or(""),
+                self.list_text(args)
+            ),
+            Op::Return(values) => format!("RETURN {}", self.list_text(values)),
+            Op::Assembly { calls } => format!("ASSEMBLY {{{}}}", calls.join(", ")),
+            Op::Opaque { description, args } => {
+                format!("OPAQUE {description}({})", self.list_text(args))
+            }
+        }
+    }
+}
+
+/// Element type of a mapping or array type name.
+pub fn element_type(ty: &str) -> Option<String> {
+    if let Some(inner) = ty
+        .strip_prefix("mapping(")
+        .and_then(|t| t.strip_suffix(')'))
+    {
+        return inner.split_once("=>").map(|(_, v)| v.trim().to_string());
+    }
+    if ty.ends_with(']') {
+        return ty.rfind('[').map(|i| ty[..i].to_string());
+    }
+    None
+}
+
+/// Type reached by following `path` from a value of type `ty`.
+pub fn path_type(
+    symbols: &SymbolTable,
+    mut ty: Option<String>,
+    path: &[PathElem],
+) -> Option<String> {
+    for elem in path {
+        ty = match elem {
+            PathElem::Index(_) => element_type(ty.as_deref()?),
+            PathElem::Member(member) => symbols.field_type(ty.as_deref()?, member),
+            PathElem::Length => Some("uint256".into()),
+            PathElem::Push | PathElem::Pop => None,
+        };
+    }
+    ty
+}
+
+impl fmt::Display for Constant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Constant::Bool(b) => write!(f, "{b}"),
+            Constant::Number(n) => write!(f, "{n}"),
+            Constant::String(s) => write!(f, "{s:?}"),
+            Constant::Hex(h) => write!(f, "hex\"{h}\""),
+            Constant::Address(a) => write!(f, "{a}"),
+            Constant::Default => write!(f, "DEFAULT"),
+            Constant::Name(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Project, ProjectBundle};
+
+    fn project(src: &str) -> Project {
+        Project::load(&ProjectBundle::single("Test.sol", src))
+    }
+
+    fn build<'p>(symbols: &SymbolTable<'p>, contract: &str, func: &str) -> IrFunction<'p> {
+        let info = symbols.contract_by_name(contract, None).unwrap();
+        let id = info
+            .functions
+            .iter()
+            .copied()
+            .find(|&f| symbols.function(f).name == func)
+            .unwrap();
+        IrFunction::build(symbols, Some(info.id), id)
+    }
+
+    fn ops<'a>(ir: &'a IrFunction) -> Vec<&'a Op> {
+        ir.instructions().map(|(_, inst)| &inst.op).collect()
+    }
+
+    fn assert_renamed(ir: &IrFunction) {
+        for (_, inst) in ir.instructions() {
+            assert!(
+                inst.op
+                    .operands()
+                    .iter()
+                    .all(|o| !matches!(o, Operand::Var(_))),
+                "{inst:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_lower_vault_deposit() {
+        let source = include_str!("../../../../integrations/foundry/src/ReentrancyVault.sol");
+        let project = project(source);
+        let symbols = SymbolTable::build(&project);
+        let ir = build(&symbols, "ReentrancyVault", "deposit");
+        let text = ir.to_text(&symbols);
+        assert!(text.contains(
+            "n4: balances[msg.sender] += msg.value
+  t3 = msg.sender
+  t4 = STORAGE_READ balances[t3]
+  t5 = msg.value
+  t6 = t4 + t5
+  STORAGE_WRITE balances[t3] = t6
+"
+        ));
+        assert!(text.contains("EMIT Deposit(t7, t8)"), "{text}");
+    }
+
+    #[test]
+    fn test_lower_vault_withdraw() {
+        let source = include_str!("../../../../integrations/foundry/src/ReentrancyVault.sol");
+        let project = project(source);
+        let symbols = SymbolTable::build(&project);
+        let ir = build(&symbols, "ReentrancyVault", "withdraw");
+        assert_renamed(&ir);
+
+        let (call_block, call) = ir
+            .instructions()
+            .find(|(_, inst)| matches!(inst.op, Op::LowLevelCall { .. }))
+            .unwrap();
+        let Op::LowLevelCall {
+            kind: CallKind::Call,
+            target: Operand::Value(target),
+            value: Some(Operand::Value(amount)),
+            ..
+        } = &call.op
+        else {
+            panic!("{call:?}");
+        };
+        assert_eq!(ir.def(*target).op, Op::Env(EnvVar::MsgSender));
+        assert_eq!(ir.def(*amount).op, Op::Param(0));
+        assert_eq!(ir.local(*amount).unwrap().name, "amount");
+
+        let (write_block, _) = ir
+            .instructions()
+            .find(|(_, inst)| matches!(inst.op, Op::StorageWrite { .. }))
+            .unwrap();
+        assert!(ir.cfg.can_reach(call_block, write_block));
+    }
+
+    #[test]
+    fn test_loop_phis() {
+        let project = project(
+            "contract A { function f(uint n) public pure returns (uint s) {
+                for (uint i = 0; i < n; i++) { s += i; }
+            } }",
+        );
+        let symbols = SymbolTable::build(&project);
+        let ir = build(&symbols, "A", "f");
+        assert_renamed(&ir);
+        let phis: Vec<(String, usize)> = ir
+            .instructions()
+            .filter_map(|(_, inst)| match &inst.op {
+                Op::Phi(incoming) => Some((ir.value_name(inst.dst?), incoming.len())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(phis, [("i_1".to_string(), 2), ("s_1".to_string(), 2)]);
+
+        // The returned value is the loop-carried version of `s`.
+        let Some(Op::Return(values)) = ops(&ir).into_iter().find(|op| matches!(op, Op::Return(_)))
+        else {
+            panic!("no return");
+        };
+        assert_eq!(ir.value_name(values[0].value().unwrap()), "s_1");
+    }
+
+    #[test]
+    fn test_call_classification() {
+        let project = project(
+            "library SafeMath { function add(uint a, uint b) internal pure returns (uint) { return a + b; } }
+             interface IERC20 { function transfer(address, uint) external returns (bool); }
+             contract A {
+                using SafeMath for uint256;
+                struct S { uint balance; address owner; }
+                mapping(address => mapping(uint => S)) users;
+                uint[] arr;
+                IERC20 token;
+                event E(uint8);
+                modifier only(address who) { require(msg.sender == who); _; }
+                function f(uint a, address to) public only(to) returns (bool ok) {
+                    S storage s = users[msg.sender][a];
+                    s.balance += a.add(1);
+                    arr.push(a);
+                    (ok, ) = to.call{value: a}(abi.encodeWithSignature(\"g()\"));
+                    token.transfer(to, s.balance);
+                    payable(to).transfer(1);
+                    emit E(uint8(a));
+                    this.g();
+                    assembly { sstore(0, 1) }
+                    selfdestruct(payable(to));
+                }
+                function g() external {}
+             }",
+        );
+        let symbols = SymbolTable::build(&project);
+        let ir = build(&symbols, "A", "f");
+        assert_renamed(&ir);
+        let ops = ops(&ir);
+        let find = |pred: &dyn Fn(&Op) -> bool| ops.iter().copied().find(|op| pred(op));
+
+        // The modifier argument flows into its parameter.
+        let who = ir.locals.iter().position(|l| l.name == "who").unwrap();
+        assert!(ir.values.iter().any(|v| v.local == Some(who)));
+
+        let Some(Op::StorageWrite { path, .. }) =
+            find(&|op| matches!(op, Op::StorageWrite { path, .. } if path.len() == 3))
+        else {
+            panic!("no write through the storage pointer");
+        };
+        assert!(matches!(
+            path.as_slice(),
+            [PathElem::Index(_), PathElem::Index(_), PathElem::Member(m)] if m == "balance"
+        ));
+        assert!(find(
+            &|op| matches!(op, Op::LibraryCall { library, name, args, .. }
+            if library == "SafeMath" && name == "add" && args.len() == 2)
+        )
+        .is_some());
+        assert!(find(&|op| matches!(op, Op::StorageWrite { path, .. }
+            if path == &[PathElem::Push]))
+        .is_some());
+        assert!(find(
+            &|op| matches!(op, Op::ExternalCall { name, interface: Some(i), .. }
+            if name == "transfer" && i == "IERC20")
+        )
+        .is_some());
+        assert!(find(
+            &|op| matches!(op, Op::ExternalCall { name, target: Operand::Value(t), .. }
+            if name == "g" && ir.def(*t).op == Op::Env(EnvVar::This))
+        )
+        .is_some());
+        assert!(find(&|op| matches!(
+            op,
+            Op::Transfer {
+                kind: TransferKind::Transfer,
+                ..
+            }
+        ))
+        .is_some());
+        assert!(find(&|op| matches!(op, Op::Conversion { ty, .. } if ty == "uint8")).is_some());
+        assert!(find(&|op| matches!(op, Op::Emit { event, .. } if event == "E")).is_some());
+        assert!(find(&|op| matches!(op, Op::Assembly { calls } if calls == &["sstore"])).is_some());
+        assert!(find(&|op| matches!(op, Op::Selfdestruct(_))).is_some());
+        let external = ops.iter().filter(|op| op.is_external_call()).count();
+        assert_eq!(external, 4);
+    }
+}
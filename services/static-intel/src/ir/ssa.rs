@@ -0,0 +1,296 @@
+//! SSA construction over lowered blocks: dominators (Cooper, Harvey & Kennedy),
+//! phi placement on iterated dominance frontiers, renaming of locals and removal
+//! of phis nothing reads.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use solang_parser::pt::Loc;
+
+use super::{BlockId, Constant, Instruction, IrFunction, LocalId, Op, Operand, ValueInfo};
+
+pub(super) fn construct(ir: &mut IrFunction) {
+    let count = ir.blocks.len();
+    let rpo = ir.cfg.reverse_postorder();
+    let mut order = vec![usize::MAX; count];
+    for (position, &block) in rpo.iter().enumerate() {
+        order[block] = position;
+    }
+    let reachable = |block: BlockId| order[block] != usize::MAX;
+
+    // Dead code (e.g. after a `revert`) has no reaching definitions to rename.
+    for block in (0..count).filter(|&b| !reachable(b)) {
+        ir.blocks[block].instructions.clear();
+        ir.blocks[block].condition = None;
+    }
+
+    let preds: Vec<Vec<BlockId>> = (0..count)
+        .map(|block| {
+            let set: BTreeSet<BlockId> = ir
+                .cfg
+                .predecessors(block)
+                .map(|e| e.from)
+                .filter(|&p| reachable(p))
+                .collect();
+            set.into_iter().collect()
+        })
+        .collect();
+    let idom = dominators(ir.cfg.entry, &rpo, &order, &preds);
+    let frontiers = dominance_frontiers(&rpo, &preds, &idom);
+    place_phis(ir, &frontiers, &preds);
+    rename(ir, &rpo, &idom);
+    prune_phis(ir);
+    compact(ir, &rpo);
+}
+
+fn dominators(
+    entry: BlockId,
+    rpo: &[BlockId],
+    order: &[usize],
+    preds: &[Vec<BlockId>],
+) -> Vec<Option<BlockId>> {
+    let mut idom = vec![None; order.len()];
+    idom[entry] = Some(entry);
+    let intersect = |idom: &[Option<BlockId>], mut a: BlockId, mut b: BlockId| {
+        while a != b {
+            while order[a] > order[b] {
+                a = idom[a].expect("processed block has a dominator");
+            }
+            while order[b] > order[a] {
+                b = idom[b].expect("processed block has a dominator");
+            }
+        }
+        a
+    };
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &block in rpo.iter().skip(1) {
+            let mut new = None;
+            for &pred in &preds[block] {
+                if idom[pred].is_some() {
+                    new = Some(match new {
+                        None => pred,
+                        Some(current) => intersect(&idom, pred, current),
+                    });
+                }
+            }
+            if idom[block] != new {
+                idom[block] = new;
+                changed = true;
+            }
+        }
+    }
+    idom
+}
+
+fn dominance_frontiers(
+    rpo: &[BlockId],
+    preds: &[Vec<BlockId>],
+    idom: &[Option<BlockId>],
+) -> Vec<BTreeSet<BlockId>> {
+    let mut frontiers = vec![BTreeSet::new(); idom.len()];
+    for &block in rpo {
+        if preds[block].len() < 2 {
+            continue;
+        }
+        let Some(dominator) = idom[block] else {
+            continue;
+        };
+        for &pred in &preds[block] {
+            let mut runner = pred;
+            while runner != dominator {
+                frontiers[runner].insert(block);
+                match idom[runner] {
+                    Some(next) if next != runner => runner = next,
+                    _ => break,
+                }
+            }
+        }
+    }
+    frontiers
+}
+
+fn place_phis(ir: &mut IrFunction, frontiers: &[BTreeSet<BlockId>], preds: &[Vec<BlockId>]) {
+    let mut defs: BTreeMap<LocalId, BTreeSet<BlockId>> = BTreeMap::new();
+    for block in &ir.blocks {
+        for inst in &block.instructions {
+            if let Some(local) = inst.dst.and_then(|dst| ir.values[dst].local) {
+                defs.entry(local).or_default().insert(block.id);
+            }
+        }
+    }
+    for (local, blocks) in defs {
+        let mut worklist: Vec<BlockId> = blocks.iter().copied().collect();
+        let mut placed = BTreeSet::new();
+        while let Some(block) = worklist.pop() {
+            for &frontier in &frontiers[block] {
+                if !placed.insert(frontier) {
+                    continue;
+                }
+                ir.values.push(ValueInfo {
+                    local: Some(local),
+                    version: 0,
+                    ty: ir.locals[local].ty.clone(),
+                    def: (frontier, 0),
+                });
+                let incoming = preds[frontier]
+                    .iter()
+                    .map(|&pred| (pred, Operand::Var(local)))
+                    .collect();
+                ir.blocks[frontier].instructions.insert(
+                    0,
+                    Instruction {
+                        dst: Some(ir.values.len() - 1),
+                        op: Op::Phi(incoming),
+                        loc: Loc::Implicit,
+                    },
+                );
+                if !blocks.contains(&frontier) {
+                    worklist.push(frontier);
+                }
+            }
+        }
+    }
+}
+
+enum Visit {
+    Enter(BlockId),
+    /// Leaves a block, popping the locals it defined.
+    Exit(Vec<LocalId>),
+}
+
+fn rename(ir: &mut IrFunction, rpo: &[BlockId], idom: &[Option<BlockId>]) {
+    let mut children: Vec<Vec<BlockId>> = vec![Vec::new(); idom.len()];
+    for &block in rpo.iter().skip(1) {
+        if let Some(parent) = idom[block] {
+            children[parent].push(block);
+        }
+    }
+    let mut stacks: Vec<Vec<usize>> = vec![Vec::new(); ir.locals.len()];
+    let current = |stacks: &[Vec<usize>], local: LocalId| match stacks[local].last() {
+        Some(&value) => Operand::Value(value),
+        // Read before any definition on this path: the zero value.
+        None => Operand::Const(Constant::Default),
+    };
+
+    let mut visits = vec![Visit::Enter(ir.cfg.entry)];
+    while let Some(visit) = visits.pop() {
+        let block = match visit {
+            Visit::Enter(block) => block,
+            Visit::Exit(defined) => {
+                for local in defined {
+                    stacks[local].pop();
+                }
+                continue;
+            }
+        };
+        let mut defined = Vec::new();
+        for inst in &mut ir.blocks[block].instructions {
+            if !matches!(inst.op, Op::Phi(_)) {
+                for operand in inst.op.operands_mut() {
+                    if let Operand::Var(local) = *operand {
+                        *operand = current(&stacks, local);
+                    }
+                }
+            }
+            if let Some(dst) = inst.dst {
+                if let Some(local) = ir.values[dst].local {
+                    stacks[local].push(dst);
+                    defined.push(local);
+                }
+            }
+        }
+        if let Some(Operand::Var(local)) = ir.blocks[block].condition {
+            ir.blocks[block].condition = Some(current(&stacks, local));
+        }
+
+        let successors: BTreeSet<BlockId> = ir.cfg.successors(block).map(|e| e.to).collect();
+        for succ in successors {
+            for inst in &mut ir.blocks[succ].instructions {
+                let Op::Phi(incoming) = &mut inst.op else {
+                    break;
+                };
+                for (pred, operand) in incoming.iter_mut() {
+                    if *pred == block {
+                        if let Operand::Var(local) = *operand {
+                            *operand = current(&stacks, local);
+                        }
+                    }
+                }
+            }
+        }
+
+        visits.push(Visit::Exit(defined));
+        for &child in children[block].iter().rev() {
+            visits.push(Visit::Enter(child));
+        }
+    }
+}
+
+/// Removes phis whose value is never read (e.g. at the revert block).
+fn prune_phis(ir: &mut IrFunction) {
+    loop {
+        let mut used = vec![false; ir.values.len()];
+        for block in &ir.blocks {
+            for inst in &block.instructions {
+                for operand in inst.op.operands() {
+                    if let Some(value) = operand.value().filter(|&v| Some(v) != inst.dst) {
+                        used[value] = true;
+                    }
+                }
+            }
+            if let Some(value) = block.condition.as_ref().and_then(Operand::value) {
+                used[value] = true;
+            }
+        }
+        let mut removed = false;
+        for block in &mut ir.blocks {
+            block.instructions.retain(|inst| {
+                let dead = matches!(inst.op, Op::Phi(_)) && inst.dst.is_some_and(|v| !used[v]);
+                removed |= dead;
+                !dead
+            });
+        }
+        if !removed {
+            return;
+        }
+    }
+}
+
+/// Renumbers values densely in reverse post-order, dropping those whose
+/// definitions were removed, and records versions and definition sites.
+fn compact(ir: &mut IrFunction, rpo: &[BlockId]) {
+    let mut remap = vec![None; ir.values.len()];
+    let mut values = Vec::new();
+    let mut versions = vec![0; ir.locals.len()];
+    for &block in rpo {
+        for (index, inst) in ir.blocks[block].instructions.iter().enumerate() {
+            let Some(dst) = inst.dst else {
+                continue;
+            };
+            let mut info = ir.values[dst].clone();
+            if let Some(local) = info.local {
+                info.version = versions[local];
+                versions[local] += 1;
+            }
+            info.def = (block, index);
+            remap[dst] = Some(values.len());
+            values.push(info);
+        }
+    }
+    let map = |operand: &mut Operand| {
+        if let Operand::Value(value) = operand {
+            *value = remap[*value].expect("operand defined in a reachable block");
+        }
+    };
+    for block in &mut ir.blocks {
+        for inst in &mut block.instructions {
+            inst.dst = inst.dst.and_then(|dst| remap[dst]);
+            inst.op.operands_mut().into_iter().for_each(map);
+        }
+        if let Some(condition) = &mut block.condition {
+            map(condition);
+        }
+    }
+    ir.values = values;
+}
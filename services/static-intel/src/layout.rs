@@ -0,0 +1,752 @@
+//! Storage layout of deployable contracts, as `forge inspect <C> storageLayout`
+//! reports it, and its compatibility with the layout of the version it upgrades.
+//!
+//! Variables are laid out in inheritance order, most base contract first, and
+//! packed right to left into 32-byte slots while they fit. Structs and static
+//! arrays start a fresh slot and so does whatever follows them; mappings,
+//! dynamic arrays, `string` and `bytes` take one slot for their length or seed.
+//!
+//! An upgrade keeps the proxy's storage, so every variable of the deployed
+//! version must stay where it was with a type of the same encoding. New
+//! variables may only be appended or carved out of a `__gap` array.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use solang_parser::pt::{self, Expression};
+
+use crate::ast::{ContractKind, EnumDef, StructDef, TypeDef};
+use crate::detectors::{upgradeability, Detector};
+use crate::finding::{Confidence, ExtraData, Finding, Severity};
+use crate::source::Location;
+use crate::symbols::{ContractId, SymbolTable};
+
+/// Check name of upgrade-compatibility findings.
+pub const CHECK_NAME: &str = "storage-layout";
+
+const SLOT_BYTES: u64 = 32;
+
+/// A state variable's place in storage.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageEntry {
+    pub label: String,
+    /// Contract declaring the variable.
+    pub contract: String,
+    pub slot: u64,
+    /// Byte offset within the slot, counted from its least significant end.
+    pub offset: u64,
+    /// Bytes occupied; whole slots for structs and static arrays.
+    pub bytes: u64,
+    /// Type as written.
+    #[serde(rename = "type")]
+    pub type_name: String,
+    /// Type as stored: contracts as `address`, enums and user-defined value
+    /// types as their underlying type, structs as their fields. Variables of
+    /// equal canonical types read each other's data correctly.
+    pub canonical: String,
+    pub location: Option<Location>,
+}
+
+impl StorageEntry {
+    /// `__gap` arrays reserved for variables added by later versions.
+    pub fn is_gap(&self) -> bool {
+        self.label.starts_with("__gap")
+    }
+
+    /// Byte range in storage, treating slots as consecutive 32-byte words.
+    fn range(&self) -> std::ops::Range<u128> {
+        let start = self.slot as u128 * SLOT_BYTES as u128 + self.offset as u128;
+        start..start + self.bytes as u128
+    }
+
+    fn overlaps(&self, other: &StorageEntry) -> bool {
+        let (a, b) = (self.range(), other.range());
+        a.start < b.end && b.start < a.end
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageLayout {
+    pub contract: String,
+    pub file: String,
+    pub location: Option<Location>,
+    pub entries: Vec<StorageEntry>,
+}
+
+impl StorageLayout {
+    pub fn compute(symbols: &SymbolTable, contract: ContractId) -> Self {
+        let info = symbols.contract(contract);
+        let mut types = Types {
+            symbols,
+            structs: Vec::new(),
+        };
+        let mut packer = Packer::default();
+        let mut entries = Vec::new();
+        for &var in &info.state_variables {
+            let def = symbols.state_variable(var);
+            if !def.is_stored() {
+                continue;
+            }
+            let ty = types.resolve(var.contract, &def.ty);
+            let (slot, offset) = packer.place(&ty);
+            entries.push(StorageEntry {
+                label: def.name.clone(),
+                contract: symbols.contract(var.contract).name().to_string(),
+                slot,
+                offset,
+                bytes: ty.bytes,
+                type_name: def.type_name.clone(),
+                canonical: ty.canonical,
+                location: symbols.project.location(&def.loc),
+            });
+        }
+        Self {
+            contract: info.name().to_string(),
+            file: info.unit.path.clone(),
+            location: symbols.project.location(&info.def.loc),
+            entries,
+        }
+    }
+
+    /// The variable `label` declared in `contract`, or in any contract when
+    /// it moved between them.
+    fn find(&self, contract: &str, label: &str) -> Option<&StorageEntry> {
+        self.entries
+            .iter()
+            .find(|e| e.contract == contract && e.label == label)
+            .or_else(|| {
+                self.entries
+                    .iter()
+                    .find(|e| e.label == label && !e.is_gap())
+            })
+    }
+}
+
+/// Layouts of every deployable contract.
+pub fn layouts(symbols: &SymbolTable) -> Vec<StorageLayout> {
+    symbols
+        .contracts
+        .iter()
+        .filter(|info| info.def.kind == ContractKind::Contract)
+        .map(|info| StorageLayout::compute(symbols, info.id))
+        .collect()
+}
+
+/// Storage footprint of a type.
+#[derive(Debug, Clone)]
+struct TypeLayout {
+    bytes: u64,
+    /// Starts a fresh slot and ends its last one (structs, static arrays).
+    whole_slots: bool,
+    canonical: String,
+}
+
+impl TypeLayout {
+    fn value(bytes: u64, canonical: impl Into<String>) -> Self {
+        Self {
+            bytes,
+            whole_slots: false,
+            canonical: canonical.into(),
+        }
+    }
+}
+
+/// Assigns consecutive items their slot and offset.
+#[derive(Debug, Default)]
+struct Packer {
+    slot: u64,
+    offset: u64,
+}
+
+impl Packer {
+    fn place(&mut self, ty: &TypeLayout) -> (u64, u64) {
+        if self.offset > 0 && (ty.whole_slots || self.offset + ty.bytes > SLOT_BYTES) {
+            self.slot += 1;
+            self.offset = 0;
+        }
+        let place = (self.slot, self.offset);
+        if ty.whole_slots || ty.bytes >= SLOT_BYTES {
+            self.slot += ty.bytes.div_ceil(SLOT_BYTES);
+        } else {
+            self.offset += ty.bytes;
+        }
+        place
+    }
+
+    /// Slots used so far.
+    fn slots(&self) -> u64 {
+        self.slot + u64::from(self.offset > 0)
+    }
+}
+
+enum Named<'p> {
+    Struct(&'p StructDef),
+    Enum(&'p EnumDef),
+    Value(&'p TypeDef),
+    Contract,
+}
+
+struct Types<'s, 'p> {
+    symbols: &'s SymbolTable<'p>,
+    /// Structs being laid out, to stop at recursive definitions.
+    structs: Vec<String>,
+}
+
+impl<'p> Types<'_, 'p> {
+    /// The user-defined type `name` as seen from `scope`: its own and inherited
+    /// definitions first, then file-level ones, then those of any contract.
+    fn named(&self, scope: ContractId, name: &str) -> Option<Named<'p>> {
+        let symbols = self.symbols;
+        let find = |structs: &'p [StructDef], enums: &'p [EnumDef], types: &'p [TypeDef]| {
+            if let Some(def) = structs.iter().find(|s| s.name == name) {
+                Some(Named::Struct(def))
+            } else if let Some(def) = enums.iter().find(|e| e.name == name) {
+                Some(Named::Enum(def))
+            } else {
+                types.iter().find(|t| t.name == name).map(Named::Value)
+            }
+        };
+        let inherited = symbols.contract(scope).linearization.iter().map(|&c| {
+            let def = symbols.contract(c).def;
+            (&def.structs, &def.enums, &def.types)
+        });
+        let units = symbols.project.units.iter();
+        let file_level = units.clone().map(|u| (&u.structs, &u.enums, &u.types));
+        let nested = units
+            .flat_map(|u| &u.contracts)
+            .map(|c| (&c.structs, &c.enums, &c.types));
+        inherited
+            .chain(file_level)
+            .chain(nested)
+            .find_map(|(structs, enums, types)| find(structs, enums, types))
+            .or_else(|| {
+                symbols
+                    .contract_by_name(name, None)
+                    .map(|_| Named::Contract)
+            })
+    }
+
+    fn resolve(&mut self, scope: ContractId, ty: &Expression) -> TypeLayout {
+        match ty {
+            Expression::Type(_, ty) => self.elementary(scope, ty),
+            Expression::Parenthesis(_, inner) => self.resolve(scope, inner),
+            Expression::ArraySubscript(_, element, None) => {
+                let element = self.resolve(scope, element);
+                TypeLayout::value(SLOT_BYTES, format!("{}[]", element.canonical))
+            }
+            Expression::ArraySubscript(_, element, Some(length)) => {
+                let element = self.resolve(scope, element);
+                let length = constant(self.symbols, scope, length);
+                let count = length.unwrap_or(1);
+                let slots = if !element.whole_slots && element.bytes <= SLOT_BYTES / 2 {
+                    count.div_ceil(SLOT_BYTES / element.bytes.max(1))
+                } else {
+                    count.saturating_mul(element.bytes.div_ceil(SLOT_BYTES))
+                };
+                TypeLayout {
+                    bytes: slots.saturating_mul(SLOT_BYTES),
+                    whole_slots: true,
+                    canonical: match length {
+                        Some(length) => format!("{}[{length}]", element.canonical),
+                        None => format!("{}[?]", element.canonical),
+                    },
+                }
+            }
+            Expression::Variable(ident) | Expression::MemberAccess(_, _, ident) => {
+                self.user_defined(scope, &ident.name)
+            }
+            other => TypeLayout::value(SLOT_BYTES, other.to_string()),
+        }
+    }
+
+    fn elementary(&mut self, scope: ContractId, ty: &pt::Type) -> TypeLayout {
+        match ty {
+            pt::Type::Address | pt::Type::AddressPayable | pt::Type::Payable => {
+                TypeLayout::value(20, "address")
+            }
+            pt::Type::Bool => TypeLayout::value(1, "bool"),
+            pt::Type::Int(bits) => TypeLayout::value(u64::from(*bits) / 8, format!("int{bits}")),
+            pt::Type::Uint(bits) => TypeLayout::value(u64::from(*bits) / 8, format!("uint{bits}")),
+            pt::Type::Bytes(n) => TypeLayout::value(u64::from(*n), format!("bytes{n}")),
+            pt::Type::String => TypeLayout::value(SLOT_BYTES, "string"),
+            pt::Type::DynamicBytes => TypeLayout::value(SLOT_BYTES, "bytes"),
+            pt::Type::Rational => TypeLayout::value(SLOT_BYTES, "fixed"),
+            pt::Type::Mapping { key, value, .. } => {
+                let key = self.resolve(scope, key).canonical;
+                let value = self.resolve(scope, value).canonical;
+                TypeLayout::value(SLOT_BYTES, format!("mapping({key} => {value})"))
+            }
+            pt::Type::Function { attributes, .. } => {
+                let external = attributes.iter().any(|a| {
+                    matches!(
+                        a,
+                        pt::FunctionAttribute::Visibility(pt::Visibility::External(_))
+                    )
+                });
+                match external {
+                    true => TypeLayout::value(24, "function external"),
+                    false => TypeLayout::value(8, "function internal"),
+                }
+            }
+        }
+    }
+
+    fn user_defined(&mut self, scope: ContractId, name: &str) -> TypeLayout {
+        match self.named(scope, name) {
+            Some(Named::Contract) => TypeLayout::value(20, "address"),
+            Some(Named::Enum(def)) => {
+                let max = def.values.len().saturating_sub(1) as u64;
+                let bytes = u64::from(u64::BITS - max.leading_zeros())
+                    .div_ceil(8)
+                    .max(1);
+                TypeLayout::value(bytes, format!("uint{}", bytes * 8))
+            }
+            Some(Named::Value(def)) => self.resolve(scope, &def.ty),
+            Some(Named::Struct(def)) if self.structs.contains(&def.name) => TypeLayout {
+                bytes: SLOT_BYTES,
+                whole_slots: true,
+                canonical: format!("struct {}", def.name),
+            },
+            Some(Named::Struct(def)) => {
+                self.structs.push(def.name.clone());
+                let mut packer = Packer::default();
+                let mut fields = Vec::new();
+                for field in &def.fields {
+                    let ty = self.resolve(scope, &field.ty);
+                    packer.place(&ty);
+                    fields.push(ty.canonical);
+                }
+                self.structs.pop();
+                TypeLayout {
+                    bytes: packer.slots().max(1) * SLOT_BYTES,
+                    whole_slots: true,
+                    canonical: format!("struct({})", fields.join(",")),
+                }
+            }
+            None => TypeLayout::value(SLOT_BYTES, name),
+        }
+    }
+}
+
+/// Value of an array length: literals, constants and arithmetic on them.
+fn constant(symbols: &SymbolTable, scope: ContractId, expr: &Expression) -> Option<u64> {
+    let eval = |e: &Expression| constant(symbols, scope, e);
+    match expr {
+        Expression::NumberLiteral(_, digits, exponent, _) => {
+            let value: u64 = digits.replace('_', "").parse().ok()?;
+            match exponent.as_str() {
+                "" => Some(value),
+                exponent => value.checked_mul(10u64.checked_pow(exponent.parse().ok()?)?),
+            }
+        }
+        Expression::HexNumberLiteral(_, hex, _) => {
+            u64::from_str_radix(&hex.trim_start_matches("0x").replace('_', ""), 16).ok()
+        }
+        Expression::Parenthesis(_, inner) => eval(inner),
+        Expression::Add(_, a, b) => eval(a)?.checked_add(eval(b)?),
+        Expression::Subtract(_, a, b) => eval(a)?.checked_sub(eval(b)?),
+        Expression::Multiply(_, a, b) => eval(a)?.checked_mul(eval(b)?),
+        Expression::Power(_, a, b) => eval(a)?.checked_pow(eval(b)?.try_into().ok()?),
+        Expression::Variable(ident) => symbols
+            .contract(scope)
+            .state_variables
+            .iter()
+            .map(|&var| symbols.state_variable(var))
+            .find(|def| def.name == ident.name && !def.is_stored())
+            .and_then(|def| eval(def.initializer.as_ref()?)),
+        _ => None,
+    }
+}
+
+fn finding(
+    severity: Severity,
+    title: &str,
+    description: String,
+    entry: &StorageEntry,
+    location: Option<Location>,
+) -> Finding {
+    Finding {
+        severity,
+        confidence: Confidence::High,
+        title: title.into(),
+        description,
+        location,
+        extra_data: ExtraData {
+            anchor: Some(format!("{}.{}", entry.contract, entry.label)),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn place(entry: &StorageEntry) -> String {
+    match entry.offset {
+        0 => format!("slot {}", entry.slot),
+        offset => format!("slot {} offset {offset}", entry.slot),
+    }
+}
+
+/// Changes from `old` to `new` that corrupt the storage of a proxy upgraded
+/// from one to the other. Findings point into the new version; those about
+/// variables that no longer exist point at the new contract.
+pub fn compare(old: &StorageLayout, new: &StorageLayout) -> Vec<Finding> {
+    let name = &new.contract;
+    let mut findings = Vec::new();
+    // New variables accounted for as renames.
+    let mut renamed = HashSet::new();
+    for before in &old.entries {
+        let Some(after) = new.find(&before.contract, &before.label) else {
+            let at_place = new.entries.iter().find(|e| {
+                (e.slot, e.offset) == (before.slot, before.offset)
+                    && old.find(&e.contract, &e.label).is_none()
+            });
+            match at_place {
+                Some(after) if after.canonical == before.canonical => {
+                    renamed.insert((&after.contract, &after.label));
+                }
+                _ if before.is_gap() => findings.push(finding(
+                    Severity::High,
+                    "Storage gap removed",
+                    format!(
+                        "`{}.{}` reserved {} slots from {} and is gone from `{name}`, so the \
+                         variables of every contract after it moved.",
+                        before.contract,
+                        before.label,
+                        before.bytes / SLOT_BYTES,
+                        place(before)
+                    ),
+                    before,
+                    new.location.clone(),
+                )),
+                _ => findings.push(finding(
+                    Severity::Medium,
+                    "State variable removed",
+                    format!(
+                        "`{}.{}` ({}) at {} was removed from `{name}`. Its old value stays in \
+                         the proxy's storage and is read by whatever is declared there next.",
+                        before.contract,
+                        before.label,
+                        before.type_name,
+                        place(before)
+                    ),
+                    before,
+                    new.location.clone(),
+                )),
+            }
+            continue;
+        };
+        if before.is_gap() {
+            // Gaps shrink as variables take their place; what moved is reported below.
+            continue;
+        }
+        if (after.slot, after.offset) != (before.slot, before.offset) {
+            findings.push(finding(
+                Severity::High,
+                "State variable moved",
+                format!(
+                    "`{}.{}` moved from {} to {} in `{name}`, so after the upgrade it reads \
+                     another variable's data.",
+                    after.contract,
+                    after.label,
+                    place(before),
+                    place(after)
+                ),
+                after,
+                after.location.clone().or_else(|| new.location.clone()),
+            ));
+        } else if after.canonical != before.canonical {
+            let resized = after.bytes != before.bytes;
+            findings.push(finding(
+                match resized {
+                    true => Severity::High,
+                    false => Severity::Medium,
+                },
+                "State variable type changed",
+                format!(
+                    "`{}.{}` changed from `{}` to `{}` in `{name}`; the value stored by the \
+                     previous version {}.",
+                    after.contract,
+                    after.label,
+                    before.type_name,
+                    after.type_name,
+                    match resized {
+                        true => "no longer fits the variable and corrupts its neighbours",
+                        false => "is decoded differently",
+                    }
+                ),
+                after,
+                after.location.clone().or_else(|| new.location.clone()),
+            ));
+        }
+    }
+    for after in &new.entries {
+        if after.is_gap()
+            || renamed.contains(&(&after.contract, &after.label))
+            || old.find(&after.contract, &after.label).is_some()
+        {
+            continue;
+        }
+        // Taking the place of a gap or appending is fine.
+        let Some(displaced) = old
+            .entries
+            .iter()
+            .find(|e| !e.is_gap() && e.overlaps(after))
+        else {
+            continue;
+        };
+        let in_base = after.contract != new.contract;
+        findings.push(finding(
+            Severity::High,
+            match in_base {
+                true => "State variable inserted in base contract",
+                false => "State variable inserted before existing state",
+            },
+            format!(
+                "`{}.{}` was inserted at {}, which `{}.{}` occupied in the previous version of \
+                 `{name}`; it and every variable after it now read the wrong data.{}",
+                after.contract,
+                after.label,
+                place(after),
+                displaced.contract,
+                displaced.label,
+                match in_base {
+                    true => " Declare it in place of part of the base contract's `__gap` instead.",
+                    false => " Append it after the existing variables instead.",
+                }
+            ),
+            after,
+            after.location.clone().or_else(|| new.location.clone()),
+        ));
+    }
+    findings
+}
+
+/// [`compare`]s the contracts of a previous version with their counterparts
+/// in `new`: `contract` when given, renamed from `previous_contract`, or else
+/// every upgradeable contract present in both under the same name.
+pub fn check_upgrade(
+    old: &SymbolTable,
+    new: &SymbolTable,
+    contract: Option<&str>,
+    previous_contract: Option<&str>,
+) -> Vec<Finding> {
+    let deployable = |symbols: &SymbolTable, name: &str| {
+        symbols
+            .contracts
+            .iter()
+            .find(|info| info.name() == name && info.def.kind == ContractKind::Contract)
+            .map(|info| info.id)
+    };
+    let pairs: Vec<(ContractId, ContractId)> = match contract {
+        Some(name) => deployable(old, previous_contract.unwrap_or(name))
+            .zip(deployable(new, name))
+            .into_iter()
+            .collect(),
+        None => new
+            .contracts
+            .iter()
+            .filter(|info| {
+                info.def.kind == ContractKind::Contract && upgradeability::is_upgradeable(new, info)
+            })
+            .filter_map(|info| Some((deployable(old, info.name())?, info.id)))
+            .collect(),
+    };
+    pairs
+        .into_iter()
+        .flat_map(|(before, after)| {
+            compare(
+                &StorageLayout::compute(old, before),
+                &StorageLayout::compute(new, after),
+            )
+        })
+        .collect()
+}
+
+/// [`check_upgrade`] against the version a task upgrades. Registered without
+/// one, when it reports nothing; tasks with a previous version run it in
+/// place of the registered one (see [`Registry::run_with`]).
+///
+/// [`Registry::run_with`]: crate::detectors::Registry::run_with
+#[derive(Default)]
+pub struct StorageLayoutCheck<'s, 'p> {
+    previous: Option<&'s SymbolTable<'p>>,
+    contract: Option<&'s str>,
+    previous_contract: Option<&'s str>,
+}
+
+impl<'s, 'p> StorageLayoutCheck<'s, 'p> {
+    /// Checks `contract`, named `previous_contract` in `previous`, or all
+    /// upgradeable contracts (see [`check_upgrade`]).
+    pub fn upgrading(
+        previous: &'s SymbolTable<'p>,
+        contract: Option<&'s str>,
+        previous_contract: Option<&'s str>,
+    ) -> Self {
+        Self {
+            previous: Some(previous),
+            contract,
+            previous_contract,
+        }
+    }
+}
+
+impl Detector for StorageLayoutCheck<'_, '_> {
+    fn id(&self) -> &str {
+        CHECK_NAME
+    }
+
+    fn description(&self) -> &str {
+        "Storage layout changes that corrupt a proxy's state on upgrade"
+    }
+
+    fn cwe(&self) -> &[&str] {
+        &["CWE-843"]
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::High
+    }
+
+    fn run(&self, symbols: &SymbolTable) -> Vec<Finding> {
+        match self.previous {
+            Some(previous) => {
+                check_upgrade(previous, symbols, self.contract, self.previous_contract)
+            }
+            None => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Project, ProjectBundle};
+
+    fn layout(src: &str, contract: &str) -> Vec<(String, u64, u64, u64)> {
+        let project = Project::load(&ProjectBundle::single("Test.sol", src));
+        let symbols = SymbolTable::build(&project);
+        let id = symbols.contract_by_name(contract, None).unwrap().id;
+        StorageLayout::compute(&symbols, id)
+            .entries
+            .into_iter()
+            .map(|e| (e.label, e.slot, e.offset, e.bytes))
+            .collect()
+    }
+
+    #[test]
+    fn test_compute_layout() {
+        let entries = layout(
+            "type Price is uint128;
+             interface IERC20 {}
+             abstract contract Base {
+                address owner;
+                bool paused;
+                uint256[2] private __gap;
+             }
+             contract Vault is Base {
+                enum Status { Open, Closed }
+                struct Position { uint128 size; uint64 opened; Status status; address trader; }
+                uint256 constant SLOTS = 3;
+                IERC20 immutable token;
+                Price price;
+                Status status;
+                uint16[SLOTS * 10 + 1] counts;
+                mapping(address => Position) positions;
+                Position last;
+                uint8 flag;
+                string name;
+             }",
+            "Vault",
+        );
+        let expected = [
+            ("owner", 0, 0, 20),
+            ("paused", 0, 20, 1),
+            ("__gap", 1, 0, 64),
+            ("price", 3, 0, 16),
+            ("status", 3, 16, 1),
+            ("counts", 4, 0, 64),
+            ("positions", 6, 0, 32),
+            ("last", 7, 0, 64),
+            ("flag", 9, 0, 1),
+            ("name", 10, 0, 32),
+        ];
+        assert_eq!(
+            entries,
+            expected.map(|(label, slot, offset, bytes)| (label.to_string(), slot, offset, bytes))
+        );
+    }
+
+    fn compare_versions(old: &str, new: &str) -> Vec<(String, String)> {
+        let old_project = Project::load(&ProjectBundle::single("Test.sol", old));
+        let new_project = Project::load(&ProjectBundle::single("Test.sol", new));
+        let old = SymbolTable::build(&old_project);
+        let new = SymbolTable::build(&new_project);
+        check_upgrade(&old, &new, Some("Vault"), None)
+            .into_iter()
+            .map(|f| (f.title, f.extra_data.anchor.unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_compatible_upgrade() {
+        let findings = compare_versions(
+            "interface IERC20 {}
+             abstract contract Base { address owner; uint256[10] __gap; }
+             contract Vault is Base { address token; uint256 total; }",
+            "interface IERC20 {}
+             abstract contract Base { address owner; uint96 fee; uint256[10] __gap; }
+             contract Vault is Base { IERC20 asset; uint256 total; mapping(address => uint256) shares; }",
+        );
+        // `fee` packs next to `owner`; `token` is renamed to `asset` with the same encoding.
+        assert_eq!(findings, []);
+    }
+
+    #[test]
+    fn test_incompatible_upgrade() {
+        let expect = |findings: Vec<(String, String)>, expected: &[(&str, &str)]| {
+            let expected: Vec<(String, String)> = expected
+                .iter()
+                .map(|(title, anchor)| (title.to_string(), anchor.to_string()))
+                .collect();
+            assert_eq!(findings, expected);
+        };
+        expect(
+            compare_versions(
+                "abstract contract Base { address owner; uint256[2] __gap; }
+                 contract Vault is Base { uint256 total; }",
+                "abstract contract Base { address owner; }
+                 contract Vault is Base { uint256 total; }",
+            ),
+            &[
+                ("Storage gap removed", "Base.__gap"),
+                ("State variable moved", "Vault.total"),
+            ],
+        );
+        expect(
+            compare_versions(
+                "abstract contract Base { address owner; }
+                 contract Vault is Base { uint256 total; }",
+                "abstract contract Base { address owner; address pendingOwner; }
+                 contract Vault is Base { uint256 total; }",
+            ),
+            &[
+                ("State variable moved", "Vault.total"),
+                (
+                    "State variable inserted in base contract",
+                    "Base.pendingOwner",
+                ),
+            ],
+        );
+        expect(
+            compare_versions(
+                "contract Vault { uint128 total; uint128 cap; address admin; bool paused; }",
+                "contract Vault { uint128 total; int128 cap; bool paused; }",
+            ),
+            &[
+                ("State variable type changed", "Vault.cap"),
+                ("State variable removed", "Vault.admin"),
+                ("State variable moved", "Vault.paused"),
+            ],
+        );
+    }
+}
@@ -0,0 +1,27 @@
+/*!
+ * RUKH Static Intelligence
+ * Author: Volodymyr Stetsenko (Zero2Auditor)
+ *
+ * Solidity source analysis shared by the `static-intel` service binary and the
+ * `rukh-static` command line tool.
+ */
+
+pub mod ast;
+pub mod callgraph;
+pub mod cfg;
+pub mod cli;
+pub mod detectors;
+pub mod finding;
+pub mod guards;
+pub mod ir;
+pub mod layout;
+pub mod parser;
+pub mod project;
+pub mod rules;
+pub mod source;
+pub mod suppress;
+pub mod symbols;
+pub mod taint;
+pub mod task;
+pub mod visit;
+pub mod worker;
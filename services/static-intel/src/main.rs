@@ -3,19 +3,22 @@
  * Author: Volodymyr Stetsenko (Zero2Auditor)
  */
 
-use serde::{Deserialize, Serialize};
+use static_intel::detectors::Registry;
+use static_intel::worker::{Worker, WorkerConfig};
+use tracing_subscriber::EnvFilter;
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Vulnerability {
-    severity: String,
-    title: String,
-    description: String,
-}
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .init();
+    tracing::info!(
+        "RUKH Static Intelligence Service v{}",
+        env!("CARGO_PKG_VERSION")
+    );
 
-fn main() {
-    println!("RUKH Static Intelligence Service v0.1.0");
-    println!("Author: Volodymyr Stetsenko (Zero2Auditor)");
-    println!("Service ready. Waiting for analysis jobs...");
+    let worker = Worker::connect(WorkerConfig::from_env()?, Registry::builtin()).await?;
+    worker.run().await
 }
 
 #[cfg(test)]
@@ -25,4 +28,3 @@ mod tests {
         assert_eq!(2 + 2, 4);
     }
 }
-
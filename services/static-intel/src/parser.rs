@@ -0,0 +1,586 @@
+//! Parsing of Solidity sources into the owned [`ast`](crate::ast) model.
+
+use serde::{Deserialize, Serialize};
+use solang_parser::diagnostics::{self, Level};
+use solang_parser::pt::{self, CodeLocation, Loc};
+
+use crate::ast::*;
+use crate::source::{Location, SourceFile};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+/// A problem found while loading sources. Never fatal for the job as a whole.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub level: DiagnosticLevel,
+    pub message: String,
+    pub location: Option<Location>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, location: Option<Location>) -> Self {
+        Self {
+            level: DiagnosticLevel::Error,
+            message: message.into(),
+            location,
+        }
+    }
+
+    pub fn warning(message: impl Into<String>, location: Option<Location>) -> Self {
+        Self {
+            level: DiagnosticLevel::Warning,
+            message: message.into(),
+            location,
+        }
+    }
+}
+
+/// Result of parsing one file: the unit is `None` if the parser could not recover.
+#[derive(Debug, Clone)]
+pub struct ParseOutput {
+    pub unit: Option<SourceUnit>,
+    pub comments: Vec<pt::Comment>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+pub fn parse_file(file: &SourceFile) -> ParseOutput {
+    match solang_parser::parse(&file.content, file.id) {
+        Ok((tree, comments)) => ParseOutput {
+            unit: Some(build_unit(file, &tree)),
+            comments,
+            diagnostics: Vec::new(),
+        },
+        Err(errors) => ParseOutput {
+            unit: None,
+            comments: Vec::new(),
+            diagnostics: errors.iter().map(|d| convert_diagnostic(file, d)).collect(),
+        },
+    }
+}
+
+fn convert_diagnostic(file: &SourceFile, diag: &diagnostics::Diagnostic) -> Diagnostic {
+    let level = match diag.level {
+        Level::Error => DiagnosticLevel::Error,
+        Level::Warning => DiagnosticLevel::Warning,
+        Level::Info | Level::Debug => DiagnosticLevel::Info,
+    };
+    let location = match diag.loc {
+        Loc::File(_, start, end) => Some(file.location(start, end)),
+        _ => None,
+    };
+    Diagnostic {
+        level,
+        message: diag.message.clone(),
+        location,
+    }
+}
+
+fn build_unit(file: &SourceFile, tree: &pt::SourceUnit) -> SourceUnit {
+    let mut unit = SourceUnit {
+        file: file.id,
+        path: file.path.clone(),
+        pragmas: Vec::new(),
+        imports: Vec::new(),
+        contracts: Vec::new(),
+        functions: Vec::new(),
+        structs: Vec::new(),
+        enums: Vec::new(),
+        types: Vec::new(),
+        events: Vec::new(),
+        errors: Vec::new(),
+        using: Vec::new(),
+    };
+
+    for part in &tree.0 {
+        match part {
+            pt::SourceUnitPart::PragmaDirective(pragma) => unit.pragmas.push(build_pragma(pragma)),
+            pt::SourceUnitPart::ImportDirective(import) => unit.imports.push(build_import(import)),
+            pt::SourceUnitPart::ContractDefinition(contract) => {
+                unit.contracts.push(build_contract(contract))
+            }
+            pt::SourceUnitPart::FunctionDefinition(func) => unit
+                .functions
+                .push(build_function(func, Visibility::Internal)),
+            pt::SourceUnitPart::StructDefinition(def) => unit.structs.push(build_struct(def)),
+            pt::SourceUnitPart::EnumDefinition(def) => unit.enums.push(build_enum(def)),
+            pt::SourceUnitPart::EventDefinition(def) => unit.events.push(build_event(def)),
+            pt::SourceUnitPart::ErrorDefinition(def) => unit.errors.push(build_error(def)),
+            pt::SourceUnitPart::Using(using) => unit.using.push(build_using(using)),
+            pt::SourceUnitPart::TypeDefinition(def) => unit.types.push(build_type(def)),
+            pt::SourceUnitPart::VariableDefinition(_)
+            | pt::SourceUnitPart::Annotation(_)
+            | pt::SourceUnitPart::StraySemicolon(_) => {}
+        }
+    }
+    unit
+}
+
+fn build_pragma(pragma: &pt::PragmaDirective) -> Pragma {
+    match pragma {
+        pt::PragmaDirective::Identifier(loc, name, value) => Pragma {
+            name: name.as_ref().map(|n| n.name.clone()).unwrap_or_default(),
+            value: value.as_ref().map(|v| v.name.clone()).unwrap_or_default(),
+            loc: *loc,
+        },
+        pt::PragmaDirective::StringLiteral(loc, name, value) => Pragma {
+            name: name.name.clone(),
+            value: value.string.clone(),
+            loc: *loc,
+        },
+        pt::PragmaDirective::Version(loc, name, comparators) => Pragma {
+            name: name.name.clone(),
+            value: comparators
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" "),
+            loc: *loc,
+        },
+    }
+}
+
+fn build_import(import: &pt::Import) -> Import {
+    let path = match import {
+        pt::Import::Plain(path, _)
+        | pt::Import::GlobalSymbol(path, _, _)
+        | pt::Import::Rename(path, _, _) => match path {
+            pt::ImportPath::Filename(literal) => literal.string.clone(),
+            pt::ImportPath::Path(path) => path.to_string(),
+        },
+    };
+    let (alias, symbols) = match import {
+        pt::Import::Plain(..) => (None, Vec::new()),
+        pt::Import::GlobalSymbol(_, alias, _) => (Some(alias.name.clone()), Vec::new()),
+        pt::Import::Rename(_, symbols, _) => (
+            None,
+            symbols
+                .iter()
+                .map(|(name, alias)| (name.name.clone(), alias.as_ref().map(|a| a.name.clone())))
+                .collect(),
+        ),
+    };
+    Import {
+        path,
+        alias,
+        symbols,
+        resolved: None,
+        loc: import.loc(),
+    }
+}
+
+fn build_contract(def: &pt::ContractDefinition) -> Contract {
+    let kind = match def.ty {
+        pt::ContractTy::Contract(_) => ContractKind::Contract,
+        pt::ContractTy::Abstract(_) => ContractKind::Abstract,
+        pt::ContractTy::Interface(_) => ContractKind::Interface,
+        pt::ContractTy::Library(_) => ContractKind::Library,
+    };
+    let default_visibility = if kind == ContractKind::Interface {
+        Visibility::External
+    } else {
+        Visibility::Public
+    };
+
+    let mut contract = Contract {
+        name: ident_name(&def.name),
+        kind,
+        bases: def
+            .base
+            .iter()
+            .map(|base| InheritanceSpecifier {
+                name: base.name.to_string(),
+                args: base.args.clone(),
+                loc: base.loc,
+            })
+            .collect(),
+        state_variables: Vec::new(),
+        functions: Vec::new(),
+        modifiers: Vec::new(),
+        events: Vec::new(),
+        errors: Vec::new(),
+        structs: Vec::new(),
+        enums: Vec::new(),
+        types: Vec::new(),
+        using: Vec::new(),
+        loc: def.loc,
+    };
+
+    for part in &def.parts {
+        match part {
+            pt::ContractPart::VariableDefinition(var) => {
+                contract.state_variables.push(build_state_variable(var))
+            }
+            pt::ContractPart::FunctionDefinition(func) => {
+                let func = build_function(func, default_visibility);
+                if func.kind == FunctionKind::Modifier {
+                    contract.modifiers.push(func);
+                } else {
+                    contract.functions.push(func);
+                }
+            }
+            pt::ContractPart::EventDefinition(def) => contract.events.push(build_event(def)),
+            pt::ContractPart::ErrorDefinition(def) => contract.errors.push(build_error(def)),
+            pt::ContractPart::StructDefinition(def) => contract.structs.push(build_struct(def)),
+            pt::ContractPart::EnumDefinition(def) => contract.enums.push(build_enum(def)),
+            pt::ContractPart::Using(using) => contract.using.push(build_using(using)),
+            pt::ContractPart::TypeDefinition(def) => contract.types.push(build_type(def)),
+            pt::ContractPart::Annotation(_) | pt::ContractPart::StraySemicolon(_) => {}
+        }
+    }
+    contract
+}
+
+fn build_state_variable(var: &pt::VariableDefinition) -> StateVariable {
+    let mut visibility = Visibility::Internal;
+    let mut mutability = VariableMutability::Mutable;
+    for attr in &var.attrs {
+        match attr {
+            pt::VariableAttribute::Visibility(v) => visibility = convert_visibility(v),
+            pt::VariableAttribute::Constant(_) => mutability = VariableMutability::Constant,
+            pt::VariableAttribute::Immutable(_) => mutability = VariableMutability::Immutable,
+            pt::VariableAttribute::Override(..) | pt::VariableAttribute::StorageType(_) => {}
+        }
+    }
+    StateVariable {
+        name: ident_name(&var.name),
+        ty: var.ty.clone(),
+        type_name: var.ty.to_string(),
+        visibility,
+        mutability,
+        initializer: var.initializer.clone(),
+        loc: var.loc,
+    }
+}
+
+fn build_function(def: &pt::FunctionDefinition, default_visibility: Visibility) -> Function {
+    let kind = match def.ty {
+        pt::FunctionTy::Constructor => FunctionKind::Constructor,
+        pt::FunctionTy::Function => FunctionKind::Function,
+        pt::FunctionTy::Fallback => FunctionKind::Fallback,
+        pt::FunctionTy::Receive => FunctionKind::Receive,
+        pt::FunctionTy::Modifier => FunctionKind::Modifier,
+    };
+    let mut visibility = match kind {
+        FunctionKind::Modifier => Visibility::Internal,
+        FunctionKind::Fallback | FunctionKind::Receive => Visibility::External,
+        _ => default_visibility,
+    };
+    let mut mutability = StateMutability::NonPayable;
+    let mut is_virtual = false;
+    let mut overrides = None;
+    let mut modifiers = Vec::new();
+
+    for attr in &def.attributes {
+        match attr {
+            pt::FunctionAttribute::Visibility(v) => visibility = convert_visibility(v),
+            pt::FunctionAttribute::Mutability(m) => {
+                mutability = match m {
+                    pt::Mutability::Pure(_) => StateMutability::Pure,
+                    pt::Mutability::View(_) | pt::Mutability::Constant(_) => StateMutability::View,
+                    pt::Mutability::Payable(_) => StateMutability::Payable,
+                }
+            }
+            pt::FunctionAttribute::Virtual(_) => is_virtual = true,
+            pt::FunctionAttribute::Override(_, bases) => {
+                overrides = Some(bases.iter().map(ToString::to_string).collect())
+            }
+            pt::FunctionAttribute::BaseOrModifier(loc, base) => {
+                modifiers.push(ModifierInvocation {
+                    name: base.name.to_string(),
+                    args: base.args.clone().unwrap_or_default(),
+                    loc: *loc,
+                })
+            }
+            pt::FunctionAttribute::Immutable(_) | pt::FunctionAttribute::Error(_) => {}
+        }
+    }
+
+    Function {
+        name: ident_name(&def.name),
+        kind,
+        visibility,
+        mutability,
+        is_virtual,
+        overrides,
+        params: build_params(&def.params),
+        returns: build_params(&def.returns),
+        modifiers,
+        body: def.body.clone(),
+        loc: def.loc,
+    }
+}
+
+pub(crate) fn build_params(params: &pt::ParameterList) -> Vec<Parameter> {
+    params
+        .iter()
+        .filter_map(|(_, param)| param.as_ref())
+        .map(|param| Parameter {
+            name: param.name.as_ref().map(|n| n.name.clone()),
+            ty: param.ty.clone(),
+            type_name: param.ty.to_string(),
+            storage: param.storage.as_ref().map(convert_storage),
+            loc: param.loc,
+        })
+        .collect()
+}
+
+fn build_event(def: &pt::EventDefinition) -> EventDef {
+    EventDef {
+        name: ident_name(&def.name),
+        params: def
+            .fields
+            .iter()
+            .map(|field| EventParam {
+                name: field.name.as_ref().map(|n| n.name.clone()),
+                type_name: field.ty.to_string(),
+                indexed: field.indexed,
+            })
+            .collect(),
+        anonymous: def.anonymous,
+        loc: def.loc,
+    }
+}
+
+fn build_error(def: &pt::ErrorDefinition) -> ErrorDef {
+    ErrorDef {
+        name: ident_name(&def.name),
+        params: def
+            .fields
+            .iter()
+            .map(|field| Parameter {
+                name: field.name.as_ref().map(|n| n.name.clone()),
+                ty: field.ty.clone(),
+                type_name: field.ty.to_string(),
+                storage: None,
+                loc: field.loc,
+            })
+            .collect(),
+        loc: def.loc,
+    }
+}
+
+fn build_struct(def: &pt::StructDefinition) -> StructDef {
+    StructDef {
+        name: ident_name(&def.name),
+        fields: def
+            .fields
+            .iter()
+            .map(|field| Parameter {
+                name: field.name.as_ref().map(|n| n.name.clone()),
+                ty: field.ty.clone(),
+                type_name: field.ty.to_string(),
+                storage: field.storage.as_ref().map(convert_storage),
+                loc: field.loc,
+            })
+            .collect(),
+        loc: def.loc,
+    }
+}
+
+fn build_type(def: &pt::TypeDefinition) -> TypeDef {
+    TypeDef {
+        name: def.name.name.clone(),
+        ty: def.ty.clone(),
+        loc: def.loc,
+    }
+}
+
+fn build_enum(def: &pt::EnumDefinition) -> EnumDef {
+    EnumDef {
+        name: ident_name(&def.name),
+        values: def
+            .values
+            .iter()
+            .flatten()
+            .map(|v| v.name.clone())
+            .collect(),
+        loc: def.loc,
+    }
+}
+
+fn build_using(using: &pt::Using) -> UsingDirective {
+    let (library, functions) = match &using.list {
+        pt::UsingList::Library(path) => (Some(path.to_string()), Vec::new()),
+        pt::UsingList::Functions(funcs) => {
+            (None, funcs.iter().map(|f| f.path.to_string()).collect())
+        }
+        pt::UsingList::Error => (None, Vec::new()),
+    };
+    UsingDirective {
+        library,
+        functions,
+        target: using.ty.as_ref().map(ToString::to_string),
+        global: using.global.is_some(),
+        loc: using.loc,
+    }
+}
+
+fn convert_visibility(v: &pt::Visibility) -> Visibility {
+    match v {
+        pt::Visibility::External(_) => Visibility::External,
+        pt::Visibility::Public(_) => Visibility::Public,
+        pt::Visibility::Internal(_) => Visibility::Internal,
+        pt::Visibility::Private(_) => Visibility::Private,
+    }
+}
+
+pub(crate) fn convert_storage(storage: &pt::StorageLocation) -> DataLocation {
+    match storage {
+        pt::StorageLocation::Memory(_) => DataLocation::Memory,
+        pt::StorageLocation::Storage(_) => DataLocation::Storage,
+        pt::StorageLocation::Calldata(_) => DataLocation::Calldata,
+    }
+}
+
+fn ident_name(ident: &Option<pt::Identifier>) -> String {
+    ident.as_ref().map(|i| i.name.clone()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = r#"
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import "./IERC20.sol";
+import {Ownable as Owned} from "@openzeppelin/contracts/access/Ownable.sol";
+
+error Unauthorized(address caller);
+
+contract Token is IERC20, Owned {
+    uint256 public constant DECIMALS = 18;
+    mapping(address => uint256) balances;
+    address immutable treasury;
+
+    event Minted(address indexed to, uint256 amount);
+    error InsufficientBalance(uint256 available, uint256 required);
+
+    modifier onlyTreasury() {
+        require(msg.sender == treasury);
+        _;
+    }
+
+    constructor(address t) Owned(msg.sender) {
+        treasury = t;
+    }
+
+    function mint(address to, uint amount) external onlyOwner onlyTreasury {
+        balances[to] += amount;
+        emit Minted(to, amount);
+    }
+
+    function balanceOf(address who) public view virtual override returns (uint256) {
+        return balances[who];
+    }
+
+    receive() external payable {}
+}
+"#;
+
+    fn parse(src: &str) -> ParseOutput {
+        parse_file(&SourceFile::new(0, "Token.sol", src))
+    }
+
+    #[test]
+    fn test_parse_declarations() {
+        let output = parse(TOKEN);
+        assert!(output.diagnostics.is_empty());
+        let unit = output.unit.unwrap();
+
+        assert_eq!(unit.solidity_pragma(), Some("^0.8.20"));
+        assert_eq!(unit.imports.len(), 2);
+        assert_eq!(
+            unit.imports[1].path,
+            "@openzeppelin/contracts/access/Ownable.sol"
+        );
+        assert_eq!(
+            unit.imports[1].symbols,
+            vec![("Ownable".into(), Some("Owned".into()))]
+        );
+        assert_eq!(unit.errors[0].name, "Unauthorized");
+
+        let token = unit.contract("Token").unwrap();
+        assert_eq!(token.kind, ContractKind::Contract);
+        let bases: Vec<&str> = token.bases.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(bases, ["IERC20", "Owned"]);
+        assert_eq!(token.state_variables.len(), 3);
+        assert!(!token.state_variable("DECIMALS").unwrap().is_stored());
+        assert_eq!(
+            token.state_variable("treasury").unwrap().mutability,
+            VariableMutability::Immutable
+        );
+        assert_eq!(
+            token.state_variable("balances").unwrap().visibility,
+            Visibility::Internal
+        );
+        assert_eq!(token.events[0].params.len(), 2);
+        assert!(token.events[0].params[0].indexed);
+        assert_eq!(token.errors[0].params.len(), 2);
+        assert_eq!(token.modifiers.len(), 1);
+        assert_eq!(token.functions.len(), 4);
+    }
+
+    #[test]
+    fn test_parse_function_attributes() {
+        let unit = parse(TOKEN).unit.unwrap();
+        let token = unit.contract("Token").unwrap();
+
+        let mint = token.function("mint").unwrap();
+        assert_eq!(mint.signature(), "mint(address,uint256)");
+        assert_eq!(mint.visibility, Visibility::External);
+        assert!(mint.has_modifier("onlyOwner") && mint.has_modifier("onlyTreasury"));
+        assert!(mint.is_entry_point());
+
+        let balance_of = token.function("balanceOf").unwrap();
+        assert!(balance_of.is_view() && balance_of.is_virtual);
+        assert_eq!(balance_of.overrides, Some(Vec::new()));
+        assert_eq!(balance_of.returns[0].type_name, "uint256");
+
+        let constructor = token.constructor().unwrap();
+        assert_eq!(constructor.modifiers[0].name, "Owned");
+        assert_eq!(constructor.modifiers[0].args.len(), 1);
+
+        let receive = token
+            .functions
+            .iter()
+            .find(|f| f.kind == FunctionKind::Receive)
+            .unwrap();
+        assert_eq!(receive.mutability, StateMutability::Payable);
+        assert_eq!(receive.display_name(), "receive");
+    }
+
+    #[test]
+    fn test_min_solidity_version() {
+        let version = |pragma: &str| {
+            let src = format!("pragma solidity {pragma};\ncontract A {{}}\n");
+            parse(&src).unit.unwrap().min_solidity_version()
+        };
+        assert_eq!(version("^0.8.20"), Some((0, 8, 20)));
+        assert_eq!(version(">=0.6.2 <0.9.0"), Some((0, 6, 2)));
+        assert_eq!(version("0.7.6 || ^0.8.0"), Some((0, 7, 6)));
+        assert_eq!(version("=0.5.17"), Some((0, 5, 17)));
+        assert!(!parse("pragma solidity ^0.7.0;\ncontract A {}\n")
+            .unit
+            .unwrap()
+            .has_checked_arithmetic());
+    }
+
+    #[test]
+    fn test_parse_error_reports_location() {
+        let output = parse("pragma solidity ^0.8.0;\ncontract A {\n  function f() public {\n    uint x = ;\n  }\n}\n");
+        assert!(output.unit.is_none());
+        let diag = &output.diagnostics[0];
+        assert_eq!(diag.level, DiagnosticLevel::Error);
+        let location = diag.location.as_ref().unwrap();
+        assert_eq!((location.file.as_str(), location.line), ("Token.sol", 4));
+        assert!(location.column > 1);
+    }
+}
@@ -0,0 +1,625 @@
+//! Project-level model: a bundle of source units with imports resolved offline.
+//!
+//! Bundles come either as a JSON map of path to content or as a (gzipped) tarball
+//! of a Foundry/Hardhat repository. Import paths are resolved the way solc does it:
+//! relative imports against the importing file, everything else through
+//! remappings, with a `node_modules/` fallback for Hardhat layouts.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Read};
+use std::str::FromStr;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use solang_parser::pt;
+use thiserror::Error;
+
+use crate::ast::{Contract, SourceUnit};
+use crate::parser::{self, Diagnostic};
+use crate::source::{FileId, Location, SourceMap};
+
+const REMAPPINGS_FILE: &str = "remappings.txt";
+
+/// Files at the root of a Solidity project.
+const PROJECT_MARKERS: &[&str] = &[
+    "foundry.toml",
+    REMAPPINGS_FILE,
+    "package.json",
+    "hardhat.config.js",
+    "hardhat.config.ts",
+    "truffle-config.js",
+];
+
+/// Most bytes an archive may unpack to, skipped entries included.
+const MAX_ARCHIVE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Most bytes of a single source file in an archive.
+const MAX_FILE_BYTES: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Error)]
+pub enum BundleError {
+    #[error("invalid base64 archive: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("failed to read archive: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid remapping `{0}`")]
+    Remapping(String),
+    #[error("archive too large: {0}")]
+    TooLarge(String),
+}
+
+/// Reader failing with [`io::ErrorKind::FileTooLarge`] once more than `remaining`
+/// bytes have been read, so a decompression bomb stops at the limit.
+struct Limited<R> {
+    inner: R,
+    remaining: u64,
+}
+
+impl<R: Read> Read for Limited<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.remaining = self.remaining.checked_sub(read as u64).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::FileTooLarge,
+                "archive exceeds the size limit",
+            )
+        })?;
+        Ok(read)
+    }
+}
+
+/// `[context:]prefix=target`, as accepted by solc and written in `remappings.txt`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Remapping {
+    pub context: Option<String>,
+    pub prefix: String,
+    pub target: String,
+}
+
+impl FromStr for Remapping {
+    type Err = BundleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (lhs, target) = s
+            .trim()
+            .split_once('=')
+            .ok_or_else(|| BundleError::Remapping(s.to_string()))?;
+        let (context, prefix) = match lhs.split_once(':') {
+            Some((context, prefix)) => (Some(context.to_string()), prefix),
+            None => (None, lhs),
+        };
+        if prefix.is_empty() {
+            return Err(BundleError::Remapping(s.to_string()));
+        }
+        Ok(Remapping {
+            context: context.filter(|c| !c.is_empty()),
+            prefix: prefix.to_string(),
+            target: target.to_string(),
+        })
+    }
+}
+
+/// Raw project sources before parsing.
+#[derive(Debug, Clone, Default)]
+pub struct ProjectBundle {
+    pub sources: BTreeMap<String, String>,
+    pub remappings: Vec<Remapping>,
+}
+
+impl ProjectBundle {
+    pub fn single(path: impl Into<String>, content: impl Into<String>) -> Self {
+        let mut sources = BTreeMap::new();
+        sources.insert(path.into(), content.into());
+        Self {
+            sources,
+            remappings: Vec::new(),
+        }
+    }
+
+    /// Builds a bundle from a path → content map. A `remappings.txt` entry in the
+    /// map is honoured after the explicitly given remappings.
+    pub fn from_map(
+        sources: BTreeMap<String, String>,
+        remappings: &[String],
+    ) -> Result<Self, BundleError> {
+        let mut bundle = Self::default();
+        for remapping in remappings {
+            bundle.remappings.push(remapping.parse()?);
+        }
+        for (path, content) in sources {
+            bundle.insert(&path, content)?;
+        }
+        Ok(bundle)
+    }
+
+    /// Builds a bundle from a `.tar` or `.tar.gz` archive. A top-level
+    /// directory wrapping the project (as produced by `git archive` or GitHub
+    /// downloads) is stripped: one holding the project's configuration, or the
+    /// only directory of a `git archive`. Others, such as `src/`, are kept.
+    pub fn from_archive(bytes: &[u8]) -> Result<Self, BundleError> {
+        Self::from_archive_limited(bytes, MAX_ARCHIVE_BYTES, MAX_FILE_BYTES)
+    }
+
+    fn from_archive_limited(
+        bytes: &[u8],
+        max_total: u64,
+        max_file: u64,
+    ) -> Result<Self, BundleError> {
+        let too_large = |error: io::Error| match error.kind() {
+            io::ErrorKind::FileTooLarge => {
+                BundleError::TooLarge(format!("unpacks to more than {max_total} bytes"))
+            }
+            _ => BundleError::Io(error),
+        };
+        let reader: Box<dyn Read + '_> = if bytes.starts_with(&[0x1f, 0x8b]) {
+            Box::new(flate2::read::GzDecoder::new(bytes))
+        } else {
+            Box::new(bytes)
+        };
+        let mut archive = tar::Archive::new(Limited {
+            inner: reader,
+            remaining: max_total,
+        });
+
+        let mut entries = Vec::new();
+        let mut files = Vec::new();
+        let mut git_archive = false;
+        for entry in archive.entries().map_err(too_large)? {
+            let mut entry = entry.map_err(too_large)?;
+            // `git archive` starts with a global header carrying the commit id.
+            git_archive |= entry.header().entry_type() == tar::EntryType::XGlobalHeader;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let path = normalize_path(&entry.path()?.to_string_lossy());
+            files.push(path.clone());
+            if !(path.ends_with(".sol") || path.ends_with(REMAPPINGS_FILE)) {
+                continue;
+            }
+            let mut content = String::new();
+            entry
+                .by_ref()
+                .take(max_file + 1)
+                .read_to_string(&mut content)
+                .map_err(too_large)?;
+            if content.len() as u64 > max_file {
+                return Err(BundleError::TooLarge(format!(
+                    "`{path}` is larger than {max_file} bytes"
+                )));
+            }
+            entries.push((path, content));
+        }
+
+        let root = common_root(files.iter().map(String::as_str)).filter(|root| {
+            git_archive
+                || PROJECT_MARKERS
+                    .iter()
+                    .any(|marker| files.contains(&format!("{root}/{marker}")))
+        });
+        let mut bundle = Self::default();
+        for (path, content) in entries {
+            let path = match &root {
+                Some(root) => path[root.len() + 1..].to_string(),
+                None => path,
+            };
+            bundle.insert(&path, content)?;
+        }
+        Ok(bundle)
+    }
+
+    /// Decodes a base64 encoded archive, as carried in task payloads.
+    pub fn from_base64_archive(encoded: &str) -> Result<Self, BundleError> {
+        let bytes = base64::engine::general_purpose::STANDARD.decode(encoded.trim())?;
+        Self::from_archive(&bytes)
+    }
+
+    fn insert(&mut self, path: &str, content: String) -> Result<(), BundleError> {
+        let path = normalize_path(path);
+        if path == REMAPPINGS_FILE {
+            for line in content.lines().map(str::trim) {
+                if !line.is_empty() && !line.starts_with('#') {
+                    self.remappings.push(line.parse()?);
+                }
+            }
+        } else if path.ends_with(".sol") {
+            self.sources.insert(path, content);
+        }
+        Ok(())
+    }
+}
+
+/// A fully loaded project: every file parsed, imports resolved to file ids.
+#[derive(Debug, Clone, Default)]
+pub struct Project {
+    pub sources: SourceMap,
+    /// Successfully parsed units, ordered by file id.
+    pub units: Vec<SourceUnit>,
+    pub comments: HashMap<FileId, Vec<pt::Comment>>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Project {
+    pub fn load(bundle: &ProjectBundle) -> Self {
+        let mut project = Project::default();
+        for (path, content) in &bundle.sources {
+            project.sources.add(path.as_str(), content.as_str());
+        }
+
+        let by_path: HashMap<&str, FileId> = project
+            .sources
+            .files()
+            .iter()
+            .map(|file| (file.path.as_str(), file.id))
+            .collect();
+
+        for file in project.sources.files() {
+            let output = parser::parse_file(file);
+            project.diagnostics.extend(output.diagnostics);
+            let Some(mut unit) = output.unit else {
+                continue;
+            };
+            for import in &mut unit.imports {
+                import.resolved =
+                    resolve_import(&file.path, &import.path, &bundle.remappings, |p| {
+                        by_path.contains_key(p)
+                    })
+                    .map(|p| by_path[p.as_str()]);
+                if import.resolved.is_none() {
+                    project.diagnostics.push(Diagnostic::warning(
+                        format!("unresolved import \"{}\"", import.path),
+                        project.sources.location(&import.loc),
+                    ));
+                }
+            }
+            project.comments.insert(file.id, output.comments);
+            project.units.push(unit);
+        }
+        project
+    }
+
+    pub fn unit(&self, file: FileId) -> Option<&SourceUnit> {
+        self.units
+            .binary_search_by_key(&file, |unit| unit.file)
+            .ok()
+            .map(|idx| &self.units[idx])
+    }
+
+    pub fn contracts(&self) -> impl Iterator<Item = (&SourceUnit, &Contract)> {
+        self.units
+            .iter()
+            .flat_map(|unit| unit.contracts.iter().map(move |c| (unit, c)))
+    }
+
+    /// Looks a contract up by name, preferring the one visible from `from` through
+    /// its (transitive) imports when several files declare the same name.
+    pub fn find_contract(
+        &self,
+        name: &str,
+        from: Option<FileId>,
+    ) -> Option<(&SourceUnit, &Contract)> {
+        if let Some(from) = from {
+            for file in self.visible_files(from) {
+                if let Some(unit) = self.unit(file) {
+                    if let Some(contract) = unit.contract(name) {
+                        return Some((unit, contract));
+                    }
+                }
+            }
+        }
+        self.contracts().find(|(_, c)| c.name == name)
+    }
+
+    /// `file` followed by everything it transitively imports, breadth first.
+    pub fn visible_files(&self, file: FileId) -> Vec<FileId> {
+        let mut seen = vec![file];
+        let mut idx = 0;
+        while idx < seen.len() {
+            if let Some(unit) = self.unit(seen[idx]) {
+                for imported in unit.imports.iter().filter_map(|i| i.resolved) {
+                    if !seen.contains(&imported) {
+                        seen.push(imported);
+                    }
+                }
+            }
+            idx += 1;
+        }
+        seen
+    }
+
+    pub fn location(&self, loc: &pt::Loc) -> Option<Location> {
+        self.sources.location(loc)
+    }
+}
+
+/// Resolves an import path to a bundle path, or `None` if no candidate exists.
+pub fn resolve_import(
+    importer: &str,
+    import: &str,
+    remappings: &[Remapping],
+    exists: impl Fn(&str) -> bool,
+) -> Option<String> {
+    let mut candidates = Vec::new();
+    if import.starts_with("./") || import.starts_with("../") {
+        let dir = importer.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+        candidates.push(normalize_path(&format!("{dir}/{import}")));
+    } else {
+        if let Some(remapped) = apply_remappings(importer, import, remappings) {
+            candidates.push(normalize_path(&remapped));
+        }
+        candidates.push(normalize_path(import));
+        candidates.push(normalize_path(&format!("node_modules/{import}")));
+    }
+    candidates.into_iter().find(|c| exists(c))
+}
+
+/// Applies the longest matching remapping, preferring the longest context (solc rules).
+fn apply_remappings(importer: &str, import: &str, remappings: &[Remapping]) -> Option<String> {
+    remappings
+        .iter()
+        .filter(|r| {
+            r.context
+                .as_deref()
+                .is_none_or(|ctx| importer.starts_with(ctx))
+        })
+        .filter(|r| import.starts_with(&r.prefix))
+        .max_by_key(|r| (r.context.as_deref().map_or(0, str::len), r.prefix.len()))
+        .map(|r| format!("{}{}", r.target, &import[r.prefix.len()..]))
+}
+
+/// Top-level directories of dependencies, tests and scripts: loaded to
+/// resolve imports, but not part of the project proper.
+pub const DEPENDENCY_DIRS: &[&str] = &["lib", "node_modules", "test", "script"];
+
+/// Whether the bundle path `path` lies in one of the [`DEPENDENCY_DIRS`].
+pub fn is_dependency(path: &str) -> bool {
+    path.split('/')
+        .next()
+        .is_some_and(|dir| DEPENDENCY_DIRS.contains(&dir))
+}
+
+/// Collapses `.`/`..` segments and duplicate separators; strips leading `./`.
+pub fn normalize_path(path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                if parts.last().is_some_and(|p| *p != "..") {
+                    parts.pop();
+                } else {
+                    parts.push("..");
+                }
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+fn common_root<'a>(mut paths: impl Iterator<Item = &'a str>) -> Option<String> {
+    let first = paths.next()?;
+    let (root, _) = first.split_once('/')?;
+    let prefix = format!("{root}/");
+    paths
+        .all(|p| p.starts_with(&prefix))
+        .then(|| root.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OWNABLE: &str = r#"
+pragma solidity ^0.8.20;
+abstract contract Ownable {
+    address private _owner;
+    modifier onlyOwner() { require(msg.sender == _owner); _; }
+}
+"#;
+
+    const VAULT: &str = r#"
+pragma solidity ^0.8.20;
+import {Ownable} from "@openzeppelin/contracts/access/Ownable.sol";
+import "./interfaces/IVault.sol";
+import "forge-std/Test.sol";
+contract Vault is Ownable, IVault {
+    function sweep() external onlyOwner {}
+}
+"#;
+
+    const IVAULT: &str =
+        "pragma solidity ^0.8.20;\ninterface IVault { function sweep() external; }\n";
+
+    fn bundle() -> ProjectBundle {
+        let mut sources = BTreeMap::new();
+        sources.insert("src/Vault.sol".to_string(), VAULT.to_string());
+        sources.insert("src/interfaces/IVault.sol".to_string(), IVAULT.to_string());
+        sources.insert(
+            "lib/openzeppelin-contracts/contracts/access/Ownable.sol".to_string(),
+            OWNABLE.to_string(),
+        );
+        sources.insert(
+            "remappings.txt".to_string(),
+            "@openzeppelin/=lib/openzeppelin-contracts/\n".to_string(),
+        );
+        ProjectBundle::from_map(sources, &[]).unwrap()
+    }
+
+    #[test]
+    fn test_parse_remapping() {
+        let r: Remapping = "src:@oz/=lib/oz/".parse().unwrap();
+        assert_eq!(r.context.as_deref(), Some("src"));
+        assert_eq!((r.prefix.as_str(), r.target.as_str()), ("@oz/", "lib/oz/"));
+        assert!("no-equals-sign".parse::<Remapping>().is_err());
+    }
+
+    #[test]
+    fn test_resolve_import() {
+        let remappings = vec![
+            "@oz/=lib/oz/".parse().unwrap(),
+            "@oz/token/=lib/oz-token/".parse().unwrap(),
+            "test:@oz/=lib/oz-test/".parse().unwrap(),
+        ];
+        let exists = |_: &str| true;
+        let resolve = |importer, import| resolve_import(importer, import, &remappings, exists);
+        assert_eq!(
+            resolve("src/a/A.sol", "../B.sol").as_deref(),
+            Some("src/B.sol")
+        );
+        assert_eq!(
+            resolve("src/A.sol", "@oz/X.sol").as_deref(),
+            Some("lib/oz/X.sol")
+        );
+        assert_eq!(
+            resolve("src/A.sol", "@oz/token/T.sol").as_deref(),
+            Some("lib/oz-token/T.sol")
+        );
+        assert_eq!(
+            resolve("test/A.t.sol", "@oz/X.sol").as_deref(),
+            Some("lib/oz-test/X.sol")
+        );
+        let only_node_modules = |p: &str| p.starts_with("node_modules/");
+        assert_eq!(
+            resolve_import("contracts/A.sol", "@oz/X.sol", &[], only_node_modules).as_deref(),
+            Some("node_modules/@oz/X.sol")
+        );
+    }
+
+    #[test]
+    fn test_load_project_resolves_imports() {
+        let project = Project::load(&bundle());
+        assert_eq!(project.units.len(), 3);
+
+        let (unit, vault) = project.find_contract("Vault", None).unwrap();
+        assert_eq!(unit.path, "src/Vault.sol");
+        assert_eq!(vault.bases.len(), 2);
+        let resolved: Vec<Option<&str>> = unit
+            .imports
+            .iter()
+            .map(|i| {
+                i.resolved
+                    .map(|f| project.sources.get(f).unwrap().path.as_str())
+            })
+            .collect();
+        assert_eq!(
+            resolved,
+            [
+                Some("lib/openzeppelin-contracts/contracts/access/Ownable.sol"),
+                Some("src/interfaces/IVault.sol"),
+                None
+            ]
+        );
+
+        // The missing forge-std import is reported, not fatal.
+        assert_eq!(project.diagnostics.len(), 1);
+        let location = project.diagnostics[0].location.as_ref().unwrap();
+        assert_eq!(
+            (location.file.as_str(), location.line),
+            ("src/Vault.sol", 5)
+        );
+
+        let (owner_unit, _) = project.find_contract("Ownable", Some(unit.file)).unwrap();
+        assert!(owner_unit.path.starts_with("lib/openzeppelin-contracts"));
+    }
+
+    #[test]
+    fn test_bundle_from_archive() {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, content) in [
+            ("repo-main/src/Vault.sol", VAULT),
+            ("repo-main/src/interfaces/IVault.sol", IVAULT),
+            ("repo-main/README.md", "# vault"),
+            (
+                "repo-main/remappings.txt",
+                "forge-std/=lib/forge-std/src/\n",
+            ),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        let tarball = builder.into_inner().unwrap();
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        std::io::Write::write_all(&mut gz, &tarball).unwrap();
+        let encoded = base64::engine::general_purpose::STANDARD.encode(gz.finish().unwrap());
+
+        let bundle = ProjectBundle::from_base64_archive(&encoded).unwrap();
+        let paths: Vec<&str> = bundle.sources.keys().map(String::as_str).collect();
+        assert_eq!(paths, ["src/Vault.sol", "src/interfaces/IVault.sol"]);
+        assert_eq!(bundle.remappings[0].target, "lib/forge-std/src/");
+    }
+
+    fn tarball(global_header: bool, files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        if global_header {
+            let comment = "52 comment=0123456789abcdef0123456789abcdef01234567\n";
+            let mut header = tar::Header::new_ustar();
+            header.set_entry_type(tar::EntryType::XGlobalHeader);
+            header.set_size(comment.len() as u64);
+            builder
+                .append_data(&mut header, "pax_global_header", comment.as_bytes())
+                .unwrap();
+        }
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_archive_keeps_source_directory() {
+        let paths = |bundle: ProjectBundle| bundle.sources.into_keys().collect::<Vec<_>>();
+        let files = [
+            ("src/Vault.sol", VAULT),
+            ("src/interfaces/IVault.sol", IVAULT),
+        ];
+        let bundle = ProjectBundle::from_archive(&tarball(false, &files)).unwrap();
+        assert_eq!(
+            paths(bundle),
+            ["src/Vault.sol", "src/interfaces/IVault.sol"]
+        );
+        // `git archive --prefix=vault/` of a project without configuration.
+        let bundle = ProjectBundle::from_archive(&tarball(
+            true,
+            &[("vault/Vault.sol", VAULT), ("vault/IVault.sol", IVAULT)],
+        ))
+        .unwrap();
+        assert_eq!(paths(bundle), ["IVault.sol", "Vault.sol"]);
+    }
+
+    #[test]
+    fn test_archive_size_limits() {
+        let source = format!("contract Big {{}}\n{}", "//".repeat(2048));
+        let mut builder = tar::Builder::new(Vec::new());
+        for path in ["src/Big.sol", "docs/blob.bin"] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(source.len() as u64);
+            header.set_mode(0o644);
+            builder
+                .append_data(&mut header, path, source.as_bytes())
+                .unwrap();
+        }
+        let tarball = builder.into_inner().unwrap();
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+        std::io::Write::write_all(&mut gz, &tarball).unwrap();
+        let compressed = gz.finish().unwrap();
+
+        assert!(ProjectBundle::from_archive_limited(&compressed, 1 << 20, 1 << 20).is_ok());
+        let file = ProjectBundle::from_archive_limited(&compressed, 1 << 20, 1024);
+        assert!(
+            matches!(&file, Err(BundleError::TooLarge(m)) if m.contains("src/Big.sol")),
+            "{file:?}"
+        );
+        // Skipped entries count towards the total.
+        let total = ProjectBundle::from_archive_limited(&compressed, 6 * 1024, 1 << 20);
+        assert!(matches!(total, Err(BundleError::TooLarge(_))), "{total:?}");
+    }
+}
@@ -0,0 +1,500 @@
+//! Project-specific checks written as data instead of Rust. A rule selects
+//! functions with `match` and reports each of them, or, when it has a `require`
+//! block, each selected function that does not satisfy it:
+//!
+//! ```yaml
+//! rules:
+//!   - id: supply-change-emits-transfer
+//!     title: totalSupply changed without a Transfer event
+//!     severity: medium
+//!     match:
+//!       writes: totalSupply
+//!     require:
+//!       emits: Transfer
+//! ```
+//!
+//! All conditions of a block must hold and `not` negates a nested block.
+//! `writes`, `reads`, `emits` and `calls` look through inlined modifiers and
+//! internal calls. Rules load from `.yaml`, `.yml` and `.json` files or come
+//! with the job, and run as ordinary [`Detector`]s.
+//!
+//! YAML files are read by a small parser ([`yaml`]) that covers the syntax
+//! above: block mappings and sequences, plain and quoted scalars, flow
+//! sequences such as `[Transfer, Mint]`, `|`/`>` block scalars and comments.
+//! Flow mappings such as `{writes: totalSupply}` (write an indented block;
+//! only the empty `{}` is accepted), anchors, aliases, tags and multi-document
+//! files are rejected with an error naming the feature.
+
+pub mod yaml;
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize};
+use solang_parser::pt::CodeLocation;
+use thiserror::Error;
+
+use crate::ast::ContractKind;
+use crate::detectors::Detector;
+use crate::finding::{Confidence, Finding, Severity};
+use crate::ir::{IrFunction, Op};
+use crate::symbols::{ContractId, FunctionId, SymbolTable};
+
+#[derive(Debug, Error)]
+pub enum RuleError {
+    #[error("failed to read {0}: {1}")]
+    Io(String, std::io::Error),
+    #[error("{0}: {1}")]
+    Yaml(String, yaml::YamlError),
+    #[error("{0}: {1}")]
+    Format(String, serde_json::Error),
+    #[error("rule `{0}`: {1}")]
+    Invalid(String, String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default = "default_level")]
+    pub severity: String,
+    #[serde(default = "default_level")]
+    pub confidence: String,
+    /// Functions the rule applies to; every function with a body when empty.
+    #[serde(default, rename = "match")]
+    pub selector: Condition,
+    /// What selected functions must satisfy.
+    #[serde(default)]
+    pub require: Option<Condition>,
+}
+
+fn default_level() -> String {
+    "medium".into()
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Condition {
+    /// Regex on the contract name.
+    pub contract: Option<String>,
+    /// Regex on the function name.
+    pub function: Option<String>,
+    /// Callable from outside: public or external, fallback or receive.
+    pub entry_point: Option<bool>,
+    /// Declared `view` or `pure`.
+    pub view: Option<bool>,
+    #[serde(deserialize_with = "one_or_many")]
+    pub modifiers: Vec<String>,
+    /// State variables written.
+    #[serde(deserialize_with = "one_or_many")]
+    pub writes: Vec<String>,
+    #[serde(deserialize_with = "one_or_many")]
+    pub reads: Vec<String>,
+    /// Events emitted.
+    #[serde(deserialize_with = "one_or_many")]
+    pub emits: Vec<String>,
+    /// Functions called, by name; external calls also match `Interface.name`.
+    #[serde(deserialize_with = "one_or_many")]
+    pub calls: Vec<String>,
+    /// Regex on the function's source text.
+    pub pattern: Option<String>,
+    pub not: Option<Box<Condition>>,
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => vec![value],
+        OneOrMany::Many(values) => values,
+    })
+}
+
+impl Condition {
+    fn regexes(&self) -> impl Iterator<Item = &String> {
+        let nested: Box<dyn Iterator<Item = &String>> = match &self.not {
+            Some(not) => Box::new(not.regexes()),
+            None => Box::new(std::iter::empty()),
+        };
+        [&self.contract, &self.function, &self.pattern]
+            .into_iter()
+            .flatten()
+            .chain(nested)
+    }
+}
+
+impl Rule {
+    /// Checks levels and regexes, so evaluation cannot fail.
+    pub fn validate(&self) -> Result<(), RuleError> {
+        let invalid = |message: String| RuleError::Invalid(self.id.clone(), message);
+        if self.id.is_empty() {
+            return Err(invalid("empty id".into()));
+        }
+        if self.severity.parse::<Severity>().is_err() {
+            return Err(invalid(format!("unknown severity `{}`", self.severity)));
+        }
+        if self.confidence.parse::<Confidence>().is_err() {
+            return Err(invalid(format!("unknown confidence `{}`", self.confidence)));
+        }
+        for regex in self
+            .selector
+            .regexes()
+            .chain(self.require.iter().flat_map(|r| r.regexes()))
+        {
+            Regex::new(regex).map_err(|err| invalid(err.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+impl Detector for Rule {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn description(&self) -> &str {
+        &self.title
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity.parse().unwrap_or_default()
+    }
+
+    fn run(&self, symbols: &SymbolTable) -> Vec<Finding> {
+        evaluate(std::slice::from_ref(self), symbols)
+    }
+}
+
+/// Rules in a YAML document: a `rules:` list, a bare list or a single rule.
+pub fn parse_yaml(src: &str, path: &str) -> Result<Vec<Rule>, RuleError> {
+    let value = yaml::parse(src).map_err(|err| RuleError::Yaml(path.to_string(), err))?;
+    from_value(value, path)
+}
+
+pub fn parse_json(src: &str, path: &str) -> Result<Vec<Rule>, RuleError> {
+    let value =
+        serde_json::from_str(src).map_err(|err| RuleError::Format(path.to_string(), err))?;
+    from_value(value, path)
+}
+
+fn from_value(mut value: serde_json::Value, path: &str) -> Result<Vec<Rule>, RuleError> {
+    if let Some(rules) = value.get_mut("rules") {
+        value = rules.take();
+    }
+    if value.is_object() {
+        value = serde_json::Value::Array(vec![value]);
+    }
+    let rules: Vec<Rule> =
+        serde_json::from_value(value).map_err(|err| RuleError::Format(path.to_string(), err))?;
+    for rule in &rules {
+        rule.validate()?;
+    }
+    Ok(rules)
+}
+
+/// Rules from the `.yaml`, `.yml` and `.json` files in `dir`, in file name order.
+pub fn load_dir(dir: &Path) -> Result<Vec<Rule>, RuleError> {
+    let io = |err| RuleError::Io(dir.display().to_string(), err);
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .map_err(io)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()
+        .map_err(io)?;
+    paths.sort();
+    let mut rules = Vec::new();
+    for path in paths {
+        let name = path.display().to_string();
+        let parse = match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml" | "yml") => parse_yaml,
+            Some("json") => parse_json,
+            _ => continue,
+        };
+        let src = std::fs::read_to_string(&path).map_err(|err| RuleError::Io(name.clone(), err))?;
+        rules.extend(parse(&src, &name)?);
+    }
+    Ok(rules)
+}
+
+/// What a function does, including inlined modifiers and internal callees.
+#[derive(Debug, Clone, Default)]
+struct Facts {
+    writes: BTreeSet<String>,
+    reads: BTreeSet<String>,
+    emits: BTreeSet<String>,
+    calls: BTreeSet<String>,
+}
+
+type Key = (Option<ContractId>, FunctionId);
+
+struct Evaluator<'s, 'p> {
+    symbols: &'s SymbolTable<'p>,
+    facts: HashMap<Key, Facts>,
+    active: HashSet<Key>,
+    /// Compiled `contract`, `function` and `pattern` regexes.
+    regexes: HashMap<String, Regex>,
+}
+
+impl Evaluator<'_, '_> {
+    fn facts(&mut self, context: Option<ContractId>, func: FunctionId) -> Facts {
+        let key = (context, func);
+        if let Some(facts) = self.facts.get(&key) {
+            return facts.clone();
+        }
+        if !self.active.insert(key) {
+            return Facts::default();
+        }
+        let facts = self.compute(context, func);
+        self.active.remove(&key);
+        self.facts.insert(key, facts.clone());
+        facts
+    }
+
+    fn compute(&mut self, context: Option<ContractId>, func: FunctionId) -> Facts {
+        let ir = IrFunction::build(self.symbols, context, func);
+        let mut facts = Facts::default();
+        let var_name = |var| self.symbols.state_variable(var).name.clone();
+        for (_, inst) in ir.instructions() {
+            match &inst.op {
+                Op::StorageWrite { var, .. } => {
+                    facts.writes.insert(var_name(*var));
+                }
+                Op::StorageRead { var, .. } | Op::StateConstant(var) => {
+                    facts.reads.insert(var_name(*var));
+                }
+                Op::Emit { event, .. } => {
+                    facts.emits.insert(event.clone());
+                }
+                Op::ExternalCall {
+                    name, interface, ..
+                } => {
+                    facts.calls.insert(name.clone());
+                    if let Some(interface) = interface {
+                        facts.calls.insert(format!("{interface}.{name}"));
+                    }
+                }
+                Op::InternalCall { name, .. } | Op::LibraryCall { name, .. } => {
+                    facts.calls.insert(name.clone());
+                }
+                _ => {}
+            }
+            if let Op::InternalCall {
+                callee: Some(callee),
+                ..
+            }
+            | Op::LibraryCall {
+                callee: Some(callee),
+                ..
+            } = &inst.op
+            {
+                let inner = self.facts(self.symbols.dispatch_context(context, *callee), *callee);
+                facts.writes.extend(inner.writes);
+                facts.reads.extend(inner.reads);
+                facts.emits.extend(inner.emits);
+                facts.calls.extend(inner.calls);
+            }
+        }
+        facts
+    }
+
+    /// Whether `text` matches `regex`, if any; rules are validated, so it compiles.
+    fn matches(&mut self, regex: &Option<String>, text: &str) -> bool {
+        let Some(regex) = regex else {
+            return true;
+        };
+        if !self.regexes.contains_key(regex) {
+            match Regex::new(regex) {
+                Ok(compiled) => self.regexes.insert(regex.clone(), compiled),
+                Err(_) => return false,
+            };
+        }
+        self.regexes[regex].is_match(text)
+    }
+
+    fn holds(&mut self, condition: &Condition, context: ContractId, func: FunctionId) -> bool {
+        let symbols = self.symbols;
+        let def = symbols.function(func);
+        if !self.matches(&condition.contract, symbols.contract(context).name())
+            || !self.matches(&condition.function, def.display_name())
+            || condition
+                .entry_point
+                .is_some_and(|e| e != def.is_entry_point())
+            || condition.view.is_some_and(|v| v != def.is_view())
+            || !condition.modifiers.iter().all(|m| def.has_modifier(m))
+        {
+            return false;
+        }
+        if condition.pattern.is_some() {
+            let text = def
+                .body
+                .as_ref()
+                .and_then(|body| {
+                    symbols
+                        .project
+                        .sources
+                        .snippet(&def.loc.with_end_from(&body.loc()))
+                })
+                .unwrap_or_default();
+            if !self.matches(&condition.pattern, text) {
+                return false;
+            }
+        }
+        let all = |names: &[String], set: &BTreeSet<String>| names.iter().all(|n| set.contains(n));
+        if !(condition.writes.is_empty()
+            && condition.reads.is_empty()
+            && condition.emits.is_empty()
+            && condition.calls.is_empty())
+        {
+            let facts = self.facts(Some(context), func);
+            if !all(&condition.writes, &facts.writes)
+                || !all(&condition.reads, &facts.reads)
+                || !all(&condition.emits, &facts.emits)
+                || !all(&condition.calls, &facts.calls)
+            {
+                return false;
+            }
+        }
+        match &condition.not {
+            Some(not) => !self.holds(not, context, func),
+            None => true,
+        }
+    }
+}
+
+/// Findings of `rules`, which must be valid, sharing the per-function facts.
+pub fn evaluate(rules: &[Rule], symbols: &SymbolTable) -> Vec<Finding> {
+    let mut evaluator = Evaluator {
+        symbols,
+        facts: HashMap::new(),
+        active: HashSet::new(),
+        regexes: HashMap::new(),
+    };
+    let mut findings = Vec::new();
+    for rule in rules {
+        let mut reported = HashSet::new();
+        for info in &symbols.contracts {
+            if info.def.kind == ContractKind::Interface {
+                continue;
+            }
+            for &func in &info.functions {
+                let def = symbols.function(func);
+                if def.body.is_none()
+                    || !evaluator.holds(&rule.selector, info.id, func)
+                    || rule
+                        .require
+                        .as_ref()
+                        .is_some_and(|require| evaluator.holds(require, info.id, func))
+                    || !reported.insert(func)
+                {
+                    continue;
+                }
+                let name = format!("{}.{}", info.name(), def.display_name());
+                let description = match &rule.description {
+                    Some(description) => format!("`{name}`: {}", description.trim()),
+                    None => format!("`{name}` violates rule `{}`.", rule.id),
+                };
+                findings.push(Finding {
+                    check_name: rule.id.clone(),
+                    severity: rule.severity.parse().unwrap_or_default(),
+                    confidence: rule.confidence.parse().unwrap_or_default(),
+                    title: rule.title.clone(),
+                    description,
+                    location: symbols.project.location(&def.loc),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Project, ProjectBundle};
+
+    const TOKEN: &str = "contract Token {
+        uint256 totalSupply;
+        mapping(address => uint256) balances;
+        event Transfer(address from, address to, uint256 amount);
+        function mint(address to, uint256 amount) external {
+            _mint(to, amount);
+        }
+        function burn(uint256 amount) external {
+            totalSupply -= amount;
+            balances[msg.sender] -= amount;
+        }
+        function stamp() external view returns (uint256) { return block.timestamp; }
+        function _mint(address to, uint256 amount) internal {
+            totalSupply += amount;
+            balances[to] += amount;
+            emit Transfer(address(0), to, amount);
+        }
+    }";
+
+    fn run(rules: &str) -> Vec<(String, usize)> {
+        let rules = parse_yaml(rules, "rules.yaml").unwrap();
+        let project = Project::load(&ProjectBundle::single("Token.sol", TOKEN));
+        let symbols = SymbolTable::build(&project);
+        evaluate(&rules, &symbols)
+            .into_iter()
+            .map(|f| (f.description, f.location.unwrap().line))
+            .collect()
+    }
+
+    #[test]
+    fn test_require_rule() {
+        let findings = run("
+rules:
+  - id: supply-emits-transfer
+    title: totalSupply changed without Transfer
+    match:
+      entry_point: true
+      writes: totalSupply
+    require:
+      emits: Transfer
+");
+        assert_eq!(
+            findings,
+            [(
+                "`Token.burn` violates rule `supply-emits-transfer`.".to_string(),
+                8
+            )]
+        );
+    }
+
+    #[test]
+    fn test_pattern_rule() {
+        let findings = run("
+- id: no-timestamp
+  title: Timestamp dependence
+  description: Miners can skew block.timestamp.
+  match:
+    pattern: 'block\\.timestamp'
+    not:
+      function: '^_'
+");
+        assert_eq!(
+            findings,
+            [(
+                "`Token.stamp`: Miners can skew block.timestamp.".to_string(),
+                12
+            )]
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let err = parse_yaml("id: x\ntitle: t\nseverity: severe\n", "r.yaml").unwrap_err();
+        assert_eq!(err.to_string(), "rule `x`: unknown severity `severe`");
+        assert!(parse_yaml("id: x\ntitle: t\nmatch:\n  function: '('\n", "r.yaml").is_err());
+        assert!(parse_yaml("id: x\ntitle: t\nmatch:\n  writez: a\n", "r.yaml").is_err());
+    }
+}
@@ -0,0 +1,422 @@
+//! The subset of YAML that rule files use, read into a JSON value: block
+//! mappings and sequences, plain and quoted scalars, flow sequences of scalars,
+//! `|` and `>` block scalars, and comments. Anchors, aliases, tags, flow
+//! mappings other than `{}` and multi-document streams are rejected with an
+//! error naming the feature rather than read as strings.
+
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("line {line}: {message}")]
+pub struct YamlError {
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    /// Content without indentation and trailing comment.
+    text: &'a str,
+    /// Content without indentation, as written (for block scalars).
+    raw: &'a str,
+}
+
+struct Parser<'a> {
+    lines: Vec<Line<'a>>,
+    pos: usize,
+}
+
+pub fn parse(src: &str) -> Result<Value, YamlError> {
+    let lines = src
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let raw = line.trim_start();
+            Line {
+                number: index + 1,
+                indent: line.len() - raw.len(),
+                text: strip_comment(raw).trim_end(),
+                raw: raw.trim_end(),
+            }
+        })
+        .collect();
+    let mut parser = Parser { lines, pos: 0 };
+    parser.skip_blank();
+    if parser.peek().is_some_and(|l| l.text == "---") {
+        parser.pos += 1;
+        parser.skip_blank();
+    }
+    let value = match parser.peek() {
+        Some(line) => parser.block(line.indent)?,
+        None => Value::Null,
+    };
+    parser.skip_blank();
+    match parser.peek() {
+        Some(line) if line.text == "---" => Err(error(
+            &line,
+            "multi-document files are not supported; put all rules in one document",
+        )),
+        Some(line) => Err(error(&line, "unexpected content")),
+        None => Ok(value),
+    }
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Line<'a>> {
+        self.lines.get(self.pos).copied()
+    }
+
+    fn skip_blank(&mut self) {
+        while self.peek().is_some_and(|l| l.text.is_empty()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Mapping or sequence whose entries start at column `indent`.
+    fn block(&mut self, indent: usize) -> Result<Value, YamlError> {
+        match self.peek() {
+            Some(line) if is_item(line.text) => self.sequence(indent),
+            _ => self.mapping(indent),
+        }
+    }
+
+    fn sequence(&mut self, indent: usize) -> Result<Value, YamlError> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank();
+            let Some(line) = self.peek() else {
+                break;
+            };
+            if line.indent < indent || !is_item(line.text) {
+                break;
+            }
+            if line.indent > indent {
+                return Err(error(&line, "unexpected indentation"));
+            }
+            let rest = line.text[1..].trim_start();
+            if rest.is_empty() {
+                self.pos += 1;
+                items.push(self.nested(indent)?);
+            } else if is_item(rest) || split_key(rest).is_some() {
+                // `- key: value` opens a mapping (or `- - x` a sequence) whose
+                // entries are aligned with `key`.
+                let offset = line.text.len() - rest.len();
+                self.lines[self.pos] = Line {
+                    indent: indent + offset,
+                    text: rest,
+                    raw: &line.raw[offset..],
+                    ..line
+                };
+                items.push(self.block(indent + offset)?);
+            } else {
+                self.pos += 1;
+                items.push(self.value(&line, rest, indent)?);
+            }
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn mapping(&mut self, indent: usize) -> Result<Value, YamlError> {
+        let mut map = Map::new();
+        loop {
+            self.skip_blank();
+            let Some(line) = self.peek() else {
+                break;
+            };
+            if line.indent < indent
+                || (line.indent == indent && is_item(line.text))
+                || line.text == "---"
+            {
+                break;
+            }
+            if line.indent > indent {
+                return Err(error(&line, "unexpected indentation"));
+            }
+            let (key, rest) =
+                split_key(line.text).ok_or_else(|| error(&line, "expected `key: value`"))?;
+            self.pos += 1;
+            let value = if rest.is_empty() {
+                self.skip_blank();
+                match self.peek() {
+                    // A sequence may sit at the same indentation as its key.
+                    Some(next) if next.indent == indent && is_item(next.text) => {
+                        self.sequence(indent)?
+                    }
+                    _ => self.nested(indent)?,
+                }
+            } else {
+                self.value(&line, rest, indent)?
+            };
+            map.insert(unquote(key), value);
+        }
+        Ok(Value::Object(map))
+    }
+
+    /// Block indented deeper than `indent`, or null if there is none.
+    fn nested(&mut self, indent: usize) -> Result<Value, YamlError> {
+        self.skip_blank();
+        match self.peek() {
+            Some(next) if next.indent > indent => self.block(next.indent),
+            _ => Ok(Value::Null),
+        }
+    }
+
+    /// Inline value of an entry at `indent`, reading following lines for block scalars.
+    fn value(&mut self, line: &Line, text: &str, indent: usize) -> Result<Value, YamlError> {
+        match text {
+            "|" | "|-" | "|+" | ">" | ">-" | ">+" => {
+                Ok(Value::String(self.block_scalar(text, indent)))
+            }
+            _ => scalar(text).map_err(|message| error(line, message)),
+        }
+    }
+
+    fn block_scalar(&mut self, header: &str, indent: usize) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        let mut base = None;
+        while let Some(line) = self.peek() {
+            if line.raw.is_empty() {
+                lines.push(String::new());
+            } else if line.indent <= indent {
+                break;
+            } else {
+                // Keep indentation beyond the block's own.
+                let base = *base.get_or_insert(line.indent);
+                lines.push(format!("{}{}", " ".repeat(line.indent - base), line.raw));
+            }
+            self.pos += 1;
+        }
+        while lines.last().is_some_and(|l| l.is_empty()) {
+            lines.pop();
+        }
+        let mut text = if header.starts_with('|') {
+            lines.join("\n")
+        } else {
+            lines
+                .split(|l| l.is_empty())
+                .map(|paragraph| paragraph.join(" "))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        if !header.ends_with('-') {
+            text.push('\n');
+        }
+        text
+    }
+}
+
+fn error(line: &Line, message: impl Into<String>) -> YamlError {
+    YamlError {
+        line: line.number,
+        message: message.into(),
+    }
+}
+
+fn is_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+/// Removes a `#` comment that is not inside quotes.
+fn strip_comment(text: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            // Quotes only open a scalar, so `don't` stays plain.
+            (None, '\'' | '"') if matches!(previous, ' ' | '[' | ',') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '#') if previous == ' ' => return &text[..index],
+            _ => {}
+        }
+        previous = c;
+    }
+    text
+}
+
+/// `key: rest` outside quotes and brackets.
+fn split_key(text: &str) -> Option<(&str, &str)> {
+    let mut quote = None;
+    let mut depth = 0usize;
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '\'' | '"') if index == 0 => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '[' | '{') => depth += 1,
+            (None, ']' | '}') => depth = depth.saturating_sub(1),
+            (None, ':') if depth == 0 => {
+                let rest = &text[index + 1..];
+                if rest.is_empty() || rest.starts_with(' ') {
+                    return Some((text[..index].trim_end(), rest.trim_start()));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn scalar(text: &str) -> Result<Value, String> {
+    if let Some(inner) = text.strip_prefix('[') {
+        let inner = inner
+            .strip_suffix(']')
+            .ok_or("unterminated flow sequence")?;
+        return split_flow(inner)
+            .into_iter()
+            .filter(|item| !item.is_empty())
+            .map(scalar)
+            .collect::<Result<_, _>>()
+            .map(Value::Array);
+    }
+    if text == "{}" {
+        return Ok(Value::Object(Map::new()));
+    }
+    if text.starts_with('{') {
+        return Err(
+            "flow mappings are not supported; write the mapping as an indented block".into(),
+        );
+    }
+    if text.starts_with('&') || text.starts_with('*') {
+        return Err("anchors and aliases are not supported; repeat the value".into());
+    }
+    if text.starts_with('!') {
+        return Err("tags are not supported".into());
+    }
+    if text.starts_with('"') || text.starts_with('\'') {
+        let quote = &text[..1];
+        if text.len() < 2 || !text.ends_with(quote) {
+            return Err("unterminated string".into());
+        }
+        return Ok(Value::String(unquote(text)));
+    }
+    Ok(match text {
+        "true" | "True" => Value::Bool(true),
+        "false" | "False" => Value::Bool(false),
+        "null" | "~" => Value::Null,
+        _ => match (text.parse::<i64>(), text.parse::<f64>()) {
+            (Ok(int), _) => Value::from(int),
+            (_, Ok(float)) if text.contains('.') => Value::from(float),
+            _ => Value::String(text.to_string()),
+        },
+    })
+}
+
+/// Items of a flow sequence body, split on commas outside quotes.
+fn split_flow(text: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, ',') => {
+                items.push(text[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(text[start..].trim());
+    items
+}
+
+fn unquote(text: &str) -> String {
+    if let Some(inner) = text.strip_prefix('\'').and_then(|t| t.strip_suffix('\'')) {
+        return inner.replace("''", "'");
+    }
+    let Some(inner) = text.strip_prefix('"').and_then(|t| t.strip_suffix('"')) else {
+        return text.to_string();
+    };
+    let mut out = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_rule_file() {
+        let src = r#"
+# Project rules
+rules:
+  - id: supply-emits-transfer
+    severity: medium   # default is medium anyway
+    match:
+      writes: totalSupply
+    require:
+      emits: [Transfer, "Mint"]
+    description: >
+      Supply changes must be
+      visible to indexers.
+  - id: no-timestamp
+    match: {}
+    pattern: 'block\.timestamp # not a comment'
+    tags:
+    - time
+    enabled: true
+"#;
+        assert_eq!(
+            parse(src).unwrap(),
+            json!({
+                "rules": [
+                    {
+                        "id": "supply-emits-transfer",
+                        "severity": "medium",
+                        "match": {"writes": "totalSupply"},
+                        "require": {"emits": ["Transfer", "Mint"]},
+                        "description": "Supply changes must be visible to indexers.\n"
+                    },
+                    {
+                        "id": "no-timestamp",
+                        "match": {},
+                        "pattern": "block\\.timestamp # not a comment",
+                        "tags": ["time"],
+                        "enabled": true
+                    }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_report_line() {
+        let err = parse("a: 1\n   b: 2\n").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(parse("key: \"open\n").is_err());
+    }
+
+    #[test]
+    fn test_unsupported_features_are_named() {
+        let message = |src: &str| parse(src).unwrap_err().to_string();
+        assert_eq!(
+            message("rules:\n  - id: a\n    match: {writes: totalSupply}\n"),
+            "line 3: flow mappings are not supported; write the mapping as an indented block"
+        );
+        assert_eq!(
+            message("base: &base\n  writes: x\nother: *base\n"),
+            "line 1: anchors and aliases are not supported; repeat the value"
+        );
+        assert_eq!(
+            message("a: 1\n---\nb: 2\n"),
+            "line 2: multi-document files are not supported; put all rules in one document"
+        );
+    }
+}
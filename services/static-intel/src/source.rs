@@ -0,0 +1,146 @@
+//! Source files and byte-offset to line/column mapping.
+
+use solang_parser::pt::Loc;
+
+pub use rukh_findings::Location;
+
+/// Index of a file inside a [`SourceMap`]; matches the `file_no` of solang locations.
+pub type FileId = usize;
+
+/// A single Solidity source file with a precomputed line index.
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub id: FileId,
+    pub path: String,
+    pub content: String,
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    pub fn new(id: FileId, path: impl Into<String>, content: impl Into<String>) -> Self {
+        let content = content.into();
+        let line_starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            id,
+            path: path.into(),
+            content,
+            line_starts,
+        }
+    }
+
+    /// 1-based line and column (in characters) of a byte offset.
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.content.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let start = self.line_starts[line];
+        let column = self
+            .content
+            .get(start..offset)
+            .map(|s| s.chars().count())
+            .unwrap_or(offset - start);
+        (line + 1, column + 1)
+    }
+
+    pub fn location(&self, start: usize, end: usize) -> Location {
+        let (line, column) = self.position(start);
+        let (end_line, end_column) = self.position(end);
+        Location {
+            file: self.path.clone(),
+            line,
+            column,
+            end_line,
+            end_column,
+        }
+    }
+
+    /// Source text covered by a byte range, or an empty string if out of bounds.
+    pub fn text(&self, start: usize, end: usize) -> &str {
+        self.content.get(start..end).unwrap_or_default()
+    }
+}
+
+/// All files taking part in one analysis, indexed by [`FileId`].
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, path: impl Into<String>, content: impl Into<String>) -> FileId {
+        let id = self.files.len();
+        self.files.push(SourceFile::new(id, path, content));
+        id
+    }
+
+    pub fn get(&self, id: FileId) -> Option<&SourceFile> {
+        self.files.get(id)
+    }
+
+    pub fn files(&self) -> &[SourceFile] {
+        &self.files
+    }
+
+    /// Resolves a solang location to a human readable one. Non-file locations yield `None`.
+    pub fn location(&self, loc: &Loc) -> Option<Location> {
+        match loc {
+            Loc::File(file, start, end) => Some(self.get(*file)?.location(*start, *end)),
+            _ => None,
+        }
+    }
+
+    /// Whole source lines `location` spans, at most `max_lines` of them.
+    pub fn lines(&self, location: &Location, max_lines: usize) -> Option<&str> {
+        let file = self.files.iter().find(|f| f.path == location.file)?;
+        let start = *file.line_starts.get(location.line.checked_sub(1)?)?;
+        let last = location
+            .end_line
+            .min(location.line + max_lines.saturating_sub(1));
+        let end = file
+            .line_starts
+            .get(last)
+            .map_or(file.content.len(), |&next| next);
+        Some(file.content.get(start..end)?.trim_end())
+    }
+
+    pub fn snippet(&self, loc: &Loc) -> Option<&str> {
+        match loc {
+            Loc::File(file, start, end) => Some(self.get(*file)?.text(*start, *end)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position() {
+        let file = SourceFile::new(0, "A.sol", "ab\ncd\n\nxyz");
+        assert_eq!(file.position(0), (1, 1));
+        assert_eq!(file.position(1), (1, 2));
+        assert_eq!(file.position(3), (2, 1));
+        assert_eq!(file.position(6), (3, 1));
+        assert_eq!(file.position(9), (4, 3));
+    }
+
+    #[test]
+    fn test_location_from_loc() {
+        let mut map = SourceMap::new();
+        let id = map.add("A.sol", "contract A {}\ncontract B {}");
+        let loc = map.location(&Loc::File(id, 14, 27)).unwrap();
+        assert_eq!(
+            (loc.line, loc.column, loc.end_line, loc.end_column),
+            (2, 1, 2, 14)
+        );
+        assert_eq!(map.snippet(&Loc::File(id, 14, 22)), Some("contract"));
+        assert_eq!(map.lines(&loc, 5), Some("contract B {}"));
+        assert!(map.location(&Loc::Builtin).is_none());
+    }
+}
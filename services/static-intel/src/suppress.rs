@@ -0,0 +1,214 @@
+//! Inline suppression comments:
+//!
+//! ```solidity
+//! // rukh-disable-file arithmetic
+//! // rukh-disable-next-line reentrancy -- balance is zeroed by the vault lock
+//! token.transfer(to, amount); // rukh-disable-line unchecked-calls
+//! ```
+//!
+//! A directive names the detectors it silences, separated by spaces or commas,
+//! or all of them when it names none; text after `--` is the justification.
+//! Directives are read from `//` and single-line `/* */` comments. Suppressed
+//! findings stay in the results, marked with their [`Suppression`].
+
+use std::collections::HashMap;
+
+use crate::finding::{Finding, Suppression, SuppressionKind};
+use crate::source::SourceMap;
+
+const PREFIX: &str = "rukh-disable";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    File,
+    /// The given 1-based line.
+    Line(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Directive {
+    scope: Scope,
+    /// Detector ids; every detector when empty.
+    ids: Vec<String>,
+    justification: Option<String>,
+}
+
+impl Directive {
+    /// Parses the text of a comment on `line`.
+    fn parse(comment: &str, line: usize) -> Option<Self> {
+        let rest = comment.trim().strip_prefix(PREFIX)?;
+        let (kind, rest) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let scope = match kind {
+            "-file" => Scope::File,
+            "-next-line" => Scope::Line(line + 1),
+            "-line" => Scope::Line(line),
+            _ => return None,
+        };
+        let (ids, justification) = match rest.split_once("--") {
+            Some((ids, justification)) => (ids, Some(justification.trim().to_string())),
+            None => (rest, None),
+        };
+        Some(Directive {
+            scope,
+            ids: ids
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|id| !id.is_empty())
+                .map(str::to_string)
+                .collect(),
+            justification: justification.filter(|j| !j.is_empty()),
+        })
+    }
+
+    fn covers(&self, finding: &Finding, line: usize) -> bool {
+        (self.scope == Scope::File || self.scope == Scope::Line(line))
+            && (self.ids.is_empty() || self.ids.contains(&finding.check_name))
+    }
+}
+
+/// Text of the comments on a line, ignoring `//` inside string literals.
+fn comments(line: &str) -> Vec<&str> {
+    let mut found = Vec::new();
+    let mut quote = None;
+    let mut chars = line.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match (quote, c) {
+            (Some(_), '\\') => {
+                chars.next();
+            }
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '/') => match chars.peek() {
+                Some((_, '/')) => {
+                    found.push(&line[index + 2..]);
+                    break;
+                }
+                Some((_, '*')) => {
+                    let body = &line[index + 2..];
+                    let end = body.find("*/").unwrap_or(body.len());
+                    found.push(&body[..end]);
+                    chars.next();
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    found
+}
+
+/// Suppression directives of all files, by path.
+#[derive(Debug, Default)]
+pub struct Suppressions {
+    files: HashMap<String, Vec<Directive>>,
+}
+
+impl Suppressions {
+    pub fn collect(sources: &SourceMap) -> Self {
+        let mut files = HashMap::new();
+        for file in sources.files() {
+            let directives: Vec<Directive> = file
+                .content
+                .lines()
+                .enumerate()
+                .flat_map(|(index, line)| {
+                    comments(line)
+                        .into_iter()
+                        .filter_map(move |comment| Directive::parse(comment, index + 1))
+                })
+                .collect();
+            if !directives.is_empty() {
+                files.insert(file.path.clone(), directives);
+            }
+        }
+        Self { files }
+    }
+
+    /// Marks the findings a directive covers as suppressed in source.
+    pub fn apply(&self, findings: &mut [Finding]) {
+        for finding in findings {
+            let Some(location) = &finding.location else {
+                continue;
+            };
+            let Some(directives) = self.files.get(&location.file) else {
+                continue;
+            };
+            if let Some(directive) = directives.iter().find(|d| d.covers(finding, location.line)) {
+                finding.extra_data.suppression = Some(Suppression {
+                    kind: SuppressionKind::InSource,
+                    justification: directive.justification.clone(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::Location;
+
+    #[test]
+    fn test_parse_directives() {
+        assert_eq!(
+            Directive::parse(
+                " rukh-disable-next-line reentrancy, arithmetic -- audited",
+                4
+            ),
+            Some(Directive {
+                scope: Scope::Line(5),
+                ids: vec!["reentrancy".into(), "arithmetic".into()],
+                justification: Some("audited".into()),
+            })
+        );
+        assert_eq!(
+            Directive::parse("rukh-disable-file", 1).map(|d| (d.scope, d.ids)),
+            Some((Scope::File, vec![]))
+        );
+        assert_eq!(Directive::parse("rukh-disable-everything", 1), None);
+        assert_eq!(
+            comments(r#"x = "a // b"; /* one */ // two"#),
+            [" one ", " two"]
+        );
+    }
+
+    #[test]
+    fn test_apply_suppressions() {
+        let mut sources = SourceMap::new();
+        sources.add(
+            "Vault.sol",
+            "contract Vault {\n\
+             // rukh-disable-next-line reentrancy -- guarded by lock\n\
+             function a() external {}\n\
+             function b() external {} // rukh-disable-line\n\
+             function c() external {}\n\
+             }\n",
+        );
+        let finding = |check_name: &str, line| Finding {
+            check_name: check_name.into(),
+            location: Some(Location {
+                file: "Vault.sol".into(),
+                line,
+                column: 1,
+                end_line: line,
+                end_column: 2,
+            }),
+            ..Default::default()
+        };
+        let mut findings = vec![
+            finding("reentrancy", 3),
+            finding("arithmetic", 3),
+            finding("arithmetic", 4),
+            finding("reentrancy", 5),
+        ];
+        Suppressions::collect(&sources).apply(&mut findings);
+        let suppressed: Vec<bool> = findings.iter().map(Finding::is_suppressed).collect();
+        assert_eq!(suppressed, [true, false, true, false]);
+        assert_eq!(
+            findings[0].extra_data.suppression,
+            Some(Suppression {
+                kind: SuppressionKind::InSource,
+                justification: Some("guarded by lock".into()),
+            })
+        );
+    }
+}
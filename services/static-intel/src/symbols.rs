@@ -0,0 +1,885 @@
+//! Inheritance resolution and the project-wide symbol table.
+//!
+//! Every contract gets its C3 linearization (as solc computes it), the effective
+//! set of functions, modifiers and state variables it ends up with, and helpers to
+//! resolve `super` calls, overrides, modifier invocations and identifiers.
+//! Resolution is always relative to a *context* contract: the most-derived
+//! contract being analysed, which decides which virtual function or modifier an
+//! inherited body actually runs.
+
+use std::collections::{HashMap, HashSet};
+
+use solang_parser::pt::{self, Expression, Loc, Statement};
+
+use crate::ast::{Contract, Function, FunctionKind, SourceUnit, StateVariable, UsingDirective};
+use crate::parser::Diagnostic;
+use crate::project::Project;
+use crate::source::FileId;
+use crate::visit::{self, Visitor};
+
+/// Index into [`SymbolTable::contracts`].
+pub type ContractId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FunctionId {
+    Function { contract: ContractId, index: usize },
+    Modifier { contract: ContractId, index: usize },
+    Free { file: FileId, index: usize },
+}
+
+impl FunctionId {
+    pub fn contract(self) -> Option<ContractId> {
+        match self {
+            FunctionId::Function { contract, .. } | FunctionId::Modifier { contract, .. } => {
+                Some(contract)
+            }
+            FunctionId::Free { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StateVarId {
+    pub contract: ContractId,
+    pub index: usize,
+}
+
+/// Global names provided by the language.
+pub const BUILTINS: &[&str] = &[
+    "msg",
+    "tx",
+    "block",
+    "abi",
+    "this",
+    "super",
+    "now",
+    "gasleft",
+    "blockhash",
+    "blobhash",
+    "keccak256",
+    "sha256",
+    "ripemd160",
+    "ecrecover",
+    "addmod",
+    "mulmod",
+    "selfdestruct",
+    "suicide",
+    "require",
+    "assert",
+    "revert",
+    "type",
+    "bytes",
+    "string",
+    "address",
+    "payable",
+];
+
+/// What an identifier refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Declaration {
+    /// Parameter, return variable or local of the enclosing function.
+    Local(String),
+    StateVariable(StateVarId),
+    /// Overload set, most-derived implementation first.
+    Functions(Vec<FunctionId>),
+    Modifier(FunctionId),
+    Contract(ContractId),
+    Event(String),
+    Error(String),
+    /// Struct, enum or user-defined value type.
+    Type(String),
+    Builtin(&'static str),
+}
+
+#[derive(Debug, Clone)]
+pub struct ContractInfo<'p> {
+    pub id: ContractId,
+    pub unit: &'p SourceUnit,
+    pub def: &'p Contract,
+    /// Resolved direct bases, in declaration order.
+    pub bases: Vec<ContractId>,
+    /// C3 linearization, starting with the contract itself and ending with the most base.
+    pub linearization: Vec<ContractId>,
+    /// Effective functions: the most-derived implementation of every signature,
+    /// plus the contract's own constructor.
+    pub functions: Vec<FunctionId>,
+    /// Effective modifiers, one per name.
+    pub modifiers: Vec<FunctionId>,
+    /// All state variables in storage order (most base contract first).
+    pub state_variables: Vec<StateVarId>,
+    /// `using` directives in effect, including inherited and file-level ones.
+    pub using: Vec<&'p UsingDirective>,
+}
+
+impl<'p> ContractInfo<'p> {
+    pub fn name(&self) -> &'p str {
+        &self.def.name
+    }
+
+    pub fn inherits(&self, base: ContractId) -> bool {
+        self.linearization.contains(&base)
+    }
+}
+
+#[derive(Debug)]
+pub struct SymbolTable<'p> {
+    pub project: &'p Project,
+    pub contracts: Vec<ContractInfo<'p>>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl<'p> SymbolTable<'p> {
+    pub fn build(project: &'p Project) -> Self {
+        let mut table = SymbolTable {
+            project,
+            contracts: project
+                .contracts()
+                .enumerate()
+                .map(|(id, (unit, def))| ContractInfo {
+                    id,
+                    unit,
+                    def,
+                    bases: Vec::new(),
+                    linearization: Vec::new(),
+                    functions: Vec::new(),
+                    modifiers: Vec::new(),
+                    state_variables: Vec::new(),
+                    using: Vec::new(),
+                })
+                .collect(),
+            diagnostics: Vec::new(),
+        };
+        table.resolve_bases();
+        table.linearize();
+        table.collect_members();
+        table
+    }
+
+    pub fn contract(&self, id: ContractId) -> &ContractInfo<'p> {
+        &self.contracts[id]
+    }
+
+    /// Finds a contract by name, preferring the one visible from `from`.
+    pub fn contract_by_name(&self, name: &str, from: Option<FileId>) -> Option<&ContractInfo<'p>> {
+        let name = name.rsplit('.').next().unwrap_or(name);
+        let (unit, def) = self.project.find_contract(name, from)?;
+        self.contracts
+            .iter()
+            .find(|c| std::ptr::eq(c.def, def) && c.unit.file == unit.file)
+    }
+
+    pub fn function(&self, id: FunctionId) -> &'p Function {
+        match id {
+            FunctionId::Function { contract, index } => {
+                &self.contracts[contract].def.functions[index]
+            }
+            FunctionId::Modifier { contract, index } => {
+                &self.contracts[contract].def.modifiers[index]
+            }
+            FunctionId::Free { file, index } => {
+                &self
+                    .project
+                    .unit(file)
+                    .expect("free function of unknown file")
+                    .functions[index]
+            }
+        }
+    }
+
+    pub fn state_variable(&self, id: StateVarId) -> &'p StateVariable {
+        &self.contracts[id.contract].def.state_variables[id.index]
+    }
+
+    /// All declared functions and modifiers, including free functions.
+    pub fn all_functions(&self) -> Vec<FunctionId> {
+        let mut ids = Vec::new();
+        for contract in &self.contracts {
+            ids.extend(
+                (0..contract.def.functions.len()).map(|index| FunctionId::Function {
+                    contract: contract.id,
+                    index,
+                }),
+            );
+            ids.extend(
+                (0..contract.def.modifiers.len()).map(|index| FunctionId::Modifier {
+                    contract: contract.id,
+                    index,
+                }),
+            );
+        }
+        for unit in &self.project.units {
+            ids.extend((0..unit.functions.len()).map(|index| FunctionId::Free {
+                file: unit.file,
+                index,
+            }));
+        }
+        ids
+    }
+
+    /// Qualified name for reports: `Contract.function`.
+    pub fn qualified_name(&self, id: FunctionId) -> String {
+        let func = self.function(id);
+        match id.contract() {
+            Some(contract) => format!(
+                "{}.{}",
+                self.contracts[contract].name(),
+                func.display_name()
+            ),
+            None => func.display_name().to_string(),
+        }
+    }
+
+    /// Contract whose linearization resolves names inside `func` when it runs for
+    /// `context`: `context` itself if it inherits `func`'s contract, otherwise that
+    /// contract (libraries, calls into unrelated bases). `None` for free functions.
+    pub fn dispatch_context(
+        &self,
+        context: Option<ContractId>,
+        func: FunctionId,
+    ) -> Option<ContractId> {
+        let own = func.contract()?;
+        match context {
+            Some(context) if self.contracts[context].inherits(own) => Some(context),
+            _ => Some(own),
+        }
+    }
+
+    /// Effective modifier `name` as seen from the most-derived `context` contract.
+    pub fn resolve_modifier(&self, context: ContractId, name: &str) -> Option<FunctionId> {
+        self.contracts[context]
+            .modifiers
+            .iter()
+            .copied()
+            .find(|&id| self.function(id).name == name)
+    }
+
+    /// Modifiers actually applied to `func` when executed in `context`, in
+    /// invocation order. Base constructor calls on constructors are skipped.
+    pub fn modifiers_of(&self, context: ContractId, func: FunctionId) -> Vec<FunctionId> {
+        self.function(func)
+            .modifiers
+            .iter()
+            .filter_map(|m| self.resolve_modifier(context, &m.name))
+            .collect()
+    }
+
+    /// Target of `super.name(...)` written in `current`, executed in `context`:
+    /// the next implementation after `current` in the context's linearization.
+    pub fn resolve_super(
+        &self,
+        context: ContractId,
+        current: ContractId,
+        name: &str,
+        arity: usize,
+    ) -> Option<FunctionId> {
+        let linearization = &self.contracts[context].linearization;
+        let start = linearization.iter().position(|&c| c == current)? + 1;
+        linearization[start..].iter().find_map(|&base| {
+            self.own_functions(base).find(|&id| {
+                let f = self.function(id);
+                f.name == name && f.params.len() == arity && f.body.is_some()
+            })
+        })
+    }
+
+    /// Base implementations that `func` overrides within `context`'s hierarchy.
+    pub fn overridden(&self, context: ContractId, func: FunctionId) -> Vec<FunctionId> {
+        let Some(owner) = func.contract() else {
+            return Vec::new();
+        };
+        let target = self.function(func);
+        let linearization = &self.contracts[context].linearization;
+        let Some(start) = linearization.iter().position(|&c| c == owner) else {
+            return Vec::new();
+        };
+        linearization[start + 1..]
+            .iter()
+            .flat_map(|&base| self.own_functions(base).chain(self.own_modifiers(base)))
+            .filter(|&id| same_slot(self.function(id), target))
+            .collect()
+    }
+
+    /// Candidates for an unqualified call `name(...)` with `arity` arguments made from
+    /// a function of `current`, dispatched virtually in `context`.
+    pub fn resolve_internal_call(
+        &self,
+        context: ContractId,
+        current: Option<ContractId>,
+        file: FileId,
+        name: &str,
+        arity: usize,
+    ) -> Vec<FunctionId> {
+        let matches = |f: &Function| f.name == name && f.params.len() == arity;
+        let mut found: Vec<FunctionId> = self.contracts[context]
+            .functions
+            .iter()
+            .copied()
+            .filter(|&id| matches(self.function(id)))
+            .collect();
+        // Private functions of the lexical contract are not part of the context's
+        // effective set when shadowed signatures differ; fall back to them.
+        if found.is_empty() {
+            if let Some(current) = current {
+                found.extend(
+                    self.own_functions(current)
+                        .filter(|&id| matches(self.function(id))),
+                );
+            }
+        }
+        if found.is_empty() {
+            found.extend(
+                self.free_functions(file)
+                    .filter(|&id| matches(self.function(id))),
+            );
+        }
+        found
+    }
+
+    /// Resolves `name` as used inside `func` when executed in `context`. Free
+    /// functions and library code may be resolved without a context.
+    pub fn resolve_identifier(
+        &self,
+        context: Option<ContractId>,
+        func: FunctionId,
+        name: &str,
+    ) -> Option<Declaration> {
+        if local_names(self.function(func)).contains(name) {
+            return Some(Declaration::Local(name.to_string()));
+        }
+        self.resolve_non_local(context, func, name)
+    }
+
+    /// Like [`resolve_identifier`](Self::resolve_identifier), for callers that have
+    /// already ruled out parameters and locals.
+    pub fn resolve_non_local(
+        &self,
+        context: Option<ContractId>,
+        func: FunctionId,
+        name: &str,
+    ) -> Option<Declaration> {
+        let file = self.file_of(func);
+        let lexical = func.contract().or(context);
+        let context = context.or(lexical);
+        let lexical_bases: &[ContractId] = match lexical {
+            Some(lexical) => &self.contracts[lexical].linearization,
+            None => &[],
+        };
+
+        // State variables bind lexically; functions and modifiers virtually.
+        for &base in lexical_bases {
+            if let Some(index) = self.contracts[base]
+                .def
+                .state_variables
+                .iter()
+                .position(|v| v.name == name)
+            {
+                return Some(Declaration::StateVariable(StateVarId {
+                    contract: base,
+                    index,
+                }));
+            }
+        }
+        let context_functions: &[FunctionId] = match context {
+            Some(context) => &self.contracts[context].functions,
+            None => &[],
+        };
+        let functions: Vec<FunctionId> = context_functions
+            .iter()
+            .copied()
+            .chain(self.free_functions(file))
+            .filter(|&id| self.function(id).name == name)
+            .collect();
+        if !functions.is_empty() {
+            return Some(Declaration::Functions(functions));
+        }
+        if let Some(modifier) = context.and_then(|c| self.resolve_modifier(c, name)) {
+            return Some(Declaration::Modifier(modifier));
+        }
+        for &base in lexical_bases {
+            let def = self.contracts[base].def;
+            if def.events.iter().any(|e| e.name == name) {
+                return Some(Declaration::Event(name.to_string()));
+            }
+            if def.errors.iter().any(|e| e.name == name) {
+                return Some(Declaration::Error(name.to_string()));
+            }
+            if def.structs.iter().any(|s| s.name == name)
+                || def.enums.iter().any(|e| e.name == name)
+            {
+                return Some(Declaration::Type(name.to_string()));
+            }
+        }
+        if let Some(contract) = self.contract_by_name(name, Some(file)) {
+            if contract.name() == name {
+                return Some(Declaration::Contract(contract.id));
+            }
+        }
+        for visible in self.project.visible_files(file) {
+            let Some(unit) = self.project.unit(visible) else {
+                continue;
+            };
+            if unit.events.iter().any(|e| e.name == name) {
+                return Some(Declaration::Event(name.to_string()));
+            }
+            if unit.errors.iter().any(|e| e.name == name) {
+                return Some(Declaration::Error(name.to_string()));
+            }
+            if unit.structs.iter().any(|s| s.name == name)
+                || unit.enums.iter().any(|e| e.name == name)
+            {
+                return Some(Declaration::Type(name.to_string()));
+            }
+        }
+        BUILTINS
+            .iter()
+            .find(|&&builtin| builtin == name)
+            .map(|&builtin| Declaration::Builtin(builtin))
+    }
+
+    /// Every identifier in the body of `func` with what it resolves to. Unresolved
+    /// identifiers (e.g. members of unknown imports) are omitted.
+    pub fn references(
+        &self,
+        context: Option<ContractId>,
+        func: FunctionId,
+    ) -> Vec<(Loc, Declaration)> {
+        let mut refs = Vec::new();
+        if let Some(body) = &self.function(func).body {
+            visit::for_each_expression(body, |expr| {
+                if let Expression::Variable(ident) = expr {
+                    if let Some(decl) = self.resolve_identifier(context, func, &ident.name) {
+                        refs.push((ident.loc, decl));
+                    }
+                }
+            });
+        }
+        refs
+    }
+
+    /// Declared type of field `member` of the struct named by `struct_type`.
+    pub fn field_type(&self, struct_type: &str, member: &str) -> Option<String> {
+        let name = struct_type.rsplit('.').next().unwrap_or(struct_type);
+        self.project
+            .units
+            .iter()
+            .flat_map(|unit| {
+                unit.structs
+                    .iter()
+                    .chain(unit.contracts.iter().flat_map(|c| &c.structs))
+            })
+            .filter(|s| s.name == name)
+            .flat_map(|s| &s.fields)
+            .find(|field| field.name.as_deref() == Some(member))
+            .map(|field| field.type_name.clone())
+    }
+
+    pub fn file_of(&self, func: FunctionId) -> FileId {
+        match func {
+            FunctionId::Function { contract, .. } | FunctionId::Modifier { contract, .. } => {
+                self.contracts[contract].unit.file
+            }
+            FunctionId::Free { file, .. } => file,
+        }
+    }
+
+    fn own_functions(&self, contract: ContractId) -> impl Iterator<Item = FunctionId> {
+        (0..self.contracts[contract].def.functions.len())
+            .map(move |index| FunctionId::Function { contract, index })
+    }
+
+    fn own_modifiers(&self, contract: ContractId) -> impl Iterator<Item = FunctionId> {
+        (0..self.contracts[contract].def.modifiers.len())
+            .map(move |index| FunctionId::Modifier { contract, index })
+    }
+
+    fn free_functions(&self, file: FileId) -> impl Iterator<Item = FunctionId> + '_ {
+        self.project
+            .visible_files(file)
+            .into_iter()
+            .filter_map(|f| self.project.unit(f))
+            .flat_map(|unit| {
+                (0..unit.functions.len()).map(move |index| FunctionId::Free {
+                    file: unit.file,
+                    index,
+                })
+            })
+    }
+
+    fn resolve_bases(&mut self) {
+        for id in 0..self.contracts.len() {
+            let (unit, def) = (self.contracts[id].unit, self.contracts[id].def);
+            let mut bases = Vec::new();
+            for base in &def.bases {
+                match self.contract_by_name(&base.name, Some(unit.file)) {
+                    Some(info) if info.id != id => bases.push(info.id),
+                    _ => self.diagnostics.push(Diagnostic::warning(
+                        format!("base contract `{}` of `{}` not found", base.name, def.name),
+                        self.project.location(&base.loc),
+                    )),
+                }
+            }
+            self.contracts[id].bases = bases;
+        }
+    }
+
+    fn linearize(&mut self) {
+        let mut cache: HashMap<ContractId, Option<Vec<ContractId>>> = HashMap::new();
+        for id in 0..self.contracts.len() {
+            let result = c3(&self.contracts, id, &mut cache, &mut Vec::new());
+            let linearization = match result {
+                Some(linearization) => linearization,
+                None => {
+                    let def = self.contracts[id].def;
+                    self.diagnostics.push(Diagnostic::error(
+                        format!(
+                            "linearization of inheritance graph impossible for `{}`",
+                            def.name
+                        ),
+                        self.project.location(&def.loc),
+                    ));
+                    vec![id]
+                }
+            };
+            self.contracts[id].linearization = linearization;
+        }
+    }
+
+    fn collect_members(&mut self) {
+        for id in 0..self.contracts.len() {
+            let linearization = self.contracts[id].linearization.clone();
+
+            let mut functions: Vec<FunctionId> = Vec::new();
+            for &base in &linearization {
+                for fid in self.own_functions(base) {
+                    let func = self.function(fid);
+                    let include = match func.kind {
+                        FunctionKind::Constructor => base == id,
+                        _ => !functions.iter().any(|&f| same_slot(self.function(f), func)),
+                    };
+                    if include {
+                        functions.push(fid);
+                    }
+                }
+            }
+
+            let mut modifiers: Vec<FunctionId> = Vec::new();
+            for &base in &linearization {
+                for mid in self.own_modifiers(base) {
+                    let name = &self.function(mid).name;
+                    if !modifiers.iter().any(|&m| &self.function(m).name == name) {
+                        modifiers.push(mid);
+                    }
+                }
+            }
+
+            let state_variables = linearization
+                .iter()
+                .rev()
+                .flat_map(|&base| {
+                    (0..self.contracts[base].def.state_variables.len()).map(move |index| {
+                        StateVarId {
+                            contract: base,
+                            index,
+                        }
+                    })
+                })
+                .collect();
+
+            let mut using: Vec<&UsingDirective> = linearization
+                .iter()
+                .flat_map(|&base| self.contracts[base].def.using.iter())
+                .collect();
+            using.extend(self.contracts[id].unit.using.iter());
+
+            let info = &mut self.contracts[id];
+            info.functions = functions;
+            info.modifiers = modifiers;
+            info.state_variables = state_variables;
+            info.using = using;
+        }
+    }
+}
+
+/// Two functions occupy the same dispatch slot: same kind, name and parameter types.
+fn same_slot(a: &Function, b: &Function) -> bool {
+    a.kind == b.kind && a.name == b.name && {
+        let types = |f: &Function| -> Vec<String> {
+            f.params.iter().map(|p| p.type_name.clone()).collect()
+        };
+        types(a) == types(b)
+    }
+}
+
+/// C3 linearization. Solidity lists bases from "most base-like" to "most derived",
+/// so the merge runs over the bases in reverse declaration order.
+fn c3(
+    contracts: &[ContractInfo],
+    id: ContractId,
+    cache: &mut HashMap<ContractId, Option<Vec<ContractId>>>,
+    visiting: &mut Vec<ContractId>,
+) -> Option<Vec<ContractId>> {
+    if let Some(cached) = cache.get(&id) {
+        return cached.clone();
+    }
+    if visiting.contains(&id) {
+        return None;
+    }
+    visiting.push(id);
+
+    let bases: Vec<ContractId> = contracts[id].bases.iter().rev().copied().collect();
+    let mut sequences = Vec::new();
+    let mut ok = true;
+    for &base in &bases {
+        match c3(contracts, base, cache, visiting) {
+            Some(linearization) => sequences.push(linearization),
+            None => ok = false,
+        }
+    }
+    sequences.push(bases);
+    visiting.pop();
+
+    let result = if ok {
+        merge(sequences).map(|rest| std::iter::once(id).chain(rest).collect())
+    } else {
+        None
+    };
+    cache.insert(id, result.clone());
+    result
+}
+
+fn merge(mut sequences: Vec<Vec<ContractId>>) -> Option<Vec<ContractId>> {
+    let mut result = Vec::new();
+    loop {
+        sequences.retain(|s| !s.is_empty());
+        if sequences.is_empty() {
+            return Some(result);
+        }
+        let head = sequences
+            .iter()
+            .map(|s| s[0])
+            .find(|candidate| !sequences.iter().any(|s| s[1..].contains(candidate)))?;
+        result.push(head);
+        for sequence in &mut sequences {
+            if sequence[0] == head {
+                sequence.remove(0);
+            }
+        }
+    }
+}
+
+/// Parameter, return and local variable names declared in a function.
+pub fn local_names(func: &Function) -> HashSet<String> {
+    struct Locals(HashSet<String>);
+    impl Visitor for Locals {
+        fn visit_statement(&mut self, stmt: &Statement) -> bool {
+            match stmt {
+                Statement::VariableDefinition(_, decl, _) => {
+                    if let Some(name) = &decl.name {
+                        self.0.insert(name.name.clone());
+                    }
+                }
+                Statement::Try(_, _, returns, catches) => {
+                    if let Some((params, _)) = returns {
+                        self.add_params(params);
+                    }
+                    for clause in catches {
+                        let param = match clause {
+                            pt::CatchClause::Simple(_, param, _) => param.as_ref(),
+                            pt::CatchClause::Named(_, _, param, _) => Some(param),
+                        };
+                        if let Some(name) = param.and_then(|p| p.name.as_ref()) {
+                            self.0.insert(name.name.clone());
+                        }
+                    }
+                }
+                _ => {}
+            }
+            true
+        }
+
+        fn visit_expression(&mut self, expr: &Expression) -> bool {
+            if let Expression::List(_, params) = expr {
+                self.add_params(params);
+            }
+            true
+        }
+    }
+    impl Locals {
+        fn add_params(&mut self, params: &pt::ParameterList) {
+            for (_, param) in params {
+                if let Some(name) = param.as_ref().and_then(|p| p.name.as_ref()) {
+                    self.0.insert(name.name.clone());
+                }
+            }
+        }
+    }
+
+    let mut locals = Locals(
+        func.params
+            .iter()
+            .chain(&func.returns)
+            .filter_map(|p| p.name.clone())
+            .collect(),
+    );
+    if let Some(body) = &func.body {
+        visit::walk_statement(&mut locals, body);
+    }
+    locals.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::ProjectBundle;
+
+    fn project(src: &str) -> Project {
+        Project::load(&ProjectBundle::single("Test.sol", src))
+    }
+
+    fn names(table: &SymbolTable, ids: &[ContractId]) -> Vec<String> {
+        ids.iter()
+            .map(|&id| table.contract(id).name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_c3_linearization() {
+        // Example from the Solidity docs on multiple inheritance.
+        let project = project(
+            "contract X {} contract A is X {} contract B is X {} contract C is A, B {}
+             contract D is X, A {}",
+        );
+        let table = SymbolTable::build(&project);
+        assert!(table.diagnostics.is_empty());
+        let c = table.contract_by_name("C", None).unwrap();
+        assert_eq!(names(&table, &c.linearization), ["C", "B", "A", "X"]);
+        let d = table.contract_by_name("D", None).unwrap();
+        assert_eq!(names(&table, &d.linearization), ["D", "A", "X"]);
+    }
+
+    #[test]
+    fn test_impossible_linearization() {
+        let project = project("contract X {} contract A is X {} contract C is A, X {}");
+        let table = SymbolTable::build(&project);
+        assert_eq!(table.diagnostics.len(), 1);
+        assert!(table.diagnostics[0].message.contains("`C`"));
+    }
+
+    const VAULT: &str = r#"
+abstract contract Ownable {
+    address internal owner;
+    modifier onlyOwner() { require(msg.sender == owner); _; }
+    function transferOwnership(address to) public virtual onlyOwner { owner = to; }
+}
+abstract contract ReentrancyGuard {
+    uint256 private status;
+    modifier nonReentrant() { status = 2; _; status = 1; }
+}
+contract Base is Ownable {
+    uint256 public total;
+    event Swept(uint256 amount);
+    function sweep() public virtual onlyOwner { total = 0; }
+    function hook(uint256 x) internal virtual returns (uint256) { return x; }
+}
+contract Vault is Base, ReentrancyGuard {
+    mapping(address => uint256) balances;
+    function sweep() public override nonReentrant {
+        super.sweep();
+        uint256 amount = hook(total);
+        emit Swept(amount);
+    }
+    function hook(uint256 x) internal override returns (uint256) { return x + 1; }
+}
+"#;
+
+    #[test]
+    fn test_effective_members() {
+        let project = project(VAULT);
+        let table = SymbolTable::build(&project);
+        let vault = table.contract_by_name("Vault", None).unwrap();
+        assert_eq!(
+            names(&table, &vault.linearization),
+            ["Vault", "ReentrancyGuard", "Base", "Ownable"]
+        );
+
+        let functions: Vec<String> = vault
+            .functions
+            .iter()
+            .map(|&f| table.qualified_name(f))
+            .collect();
+        assert_eq!(
+            functions,
+            ["Vault.sweep", "Vault.hook", "Ownable.transferOwnership"]
+        );
+        let modifiers: Vec<&str> = vault
+            .modifiers
+            .iter()
+            .map(|&m| table.function(m).name.as_str())
+            .collect();
+        assert_eq!(modifiers, ["nonReentrant", "onlyOwner"]);
+        let vars: Vec<&str> = vault
+            .state_variables
+            .iter()
+            .map(|&v| table.state_variable(v).name.as_str())
+            .collect();
+        assert_eq!(vars, ["owner", "total", "status", "balances"]);
+    }
+
+    #[test]
+    fn test_super_override_and_modifiers() {
+        let project = project(VAULT);
+        let table = SymbolTable::build(&project);
+        let vault = table.contract_by_name("Vault", None).unwrap().id;
+        let base = table.contract_by_name("Base", None).unwrap().id;
+
+        let sweep = table.contracts[vault].functions[0];
+        let target = table.resolve_super(vault, vault, "sweep", 0).unwrap();
+        assert_eq!(table.qualified_name(target), "Base.sweep");
+        assert_eq!(table.overridden(vault, sweep), [target]);
+
+        // Inherited modifiers are visible on the overriding and the overridden function.
+        let applied = |f| -> Vec<String> {
+            table
+                .modifiers_of(vault, f)
+                .iter()
+                .map(|&m| table.qualified_name(m))
+                .collect()
+        };
+        assert_eq!(applied(sweep), ["ReentrancyGuard.nonReentrant"]);
+        assert_eq!(applied(target), ["Ownable.onlyOwner"]);
+
+        // `hook` inside Vault dispatches to the override even from Base's context.
+        let calls = table.resolve_internal_call(vault, Some(base), 0, "hook", 1);
+        assert_eq!(
+            calls
+                .iter()
+                .map(|&f| table.qualified_name(f))
+                .collect::<Vec<_>>(),
+            ["Vault.hook"]
+        );
+    }
+
+    #[test]
+    fn test_identifier_references() {
+        let project = project(VAULT);
+        let table = SymbolTable::build(&project);
+        let vault = table.contract_by_name("Vault", None).unwrap().id;
+        let sweep = table.contracts[vault].functions[0];
+        let refs: Vec<Declaration> = table
+            .references(Some(vault), sweep)
+            .into_iter()
+            .map(|(_, d)| d)
+            .collect();
+        let total = table.contracts[vault].state_variables[1];
+        assert_eq!(
+            refs,
+            [
+                Declaration::Builtin("super"),
+                Declaration::Functions(vec![table.contracts[vault].functions[1]]),
+                Declaration::StateVariable(total),
+                Declaration::Event("Swept".into()),
+                Declaration::Local("amount".into()),
+            ]
+        );
+    }
+}
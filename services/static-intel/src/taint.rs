@@ -0,0 +1,846 @@
+//! Taint analysis: tracks user-controlled values to sensitive sinks.
+//!
+//! Functions are analysed in SSA form ([`IrFunction`]) with their modifiers
+//! inlined. Every function gets a summary per dispatch context — the taint of its
+//! return values, the state it writes and the sinks it reaches — expressed in
+//! terms of its own parameters, which callers substitute with the taint of their
+//! arguments. Storage connects transactions: values written by entry points
+//! anyone can call taint later reads of the same variable in every function.
+//!
+//! Each tainted value keeps the first trace found for each origin, so traces are
+//! short and the fixpoints terminate.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use solang_parser::pt::Loc;
+
+use crate::ast::ContractKind;
+use crate::finding::{Confidence, ExtraData, Finding, Severity, TraceStep};
+use crate::guards::GuardAnalysis;
+use crate::ir::{path_type, CallKind, EnvVar, Instruction, IrFunction, Op, Operand, PathElem};
+use crate::symbols::{ContractId, FunctionId, StateVarId, SymbolTable};
+
+/// Passes over storage: a value stored by one entry point can reach a sink in
+/// another only through a read in a later transaction.
+const STORAGE_ROUNDS: usize = 3;
+
+/// Passes over a function body; only loop phis need more than one.
+const BODY_PASSES: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceKind {
+    MsgSender,
+    MsgValue,
+    MsgData,
+    TxOrigin,
+    /// Calldata parameter of an entry point.
+    Parameter,
+    /// Return value or data of an external call.
+    ReturnData,
+}
+
+impl SourceKind {
+    pub fn description(self) -> &'static str {
+        match self {
+            SourceKind::MsgSender => "msg.sender",
+            SourceKind::MsgValue => "msg.value",
+            SourceKind::MsgData => "msg.data",
+            SourceKind::TxOrigin => "tx.origin",
+            SourceKind::Parameter => "a caller-supplied parameter",
+            SourceKind::ReturnData => "data returned by an external call",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SinkKind {
+    /// Target of `delegatecall` or `callcode`.
+    DelegateCallTarget,
+    /// Target of a low-level `call`.
+    CallTarget,
+    /// Ether sent by `call{value: ..}`, a high-level call, `transfer` or `send`.
+    CallValue,
+    SelfdestructBeneficiary,
+    /// Write to a state variable that access-control checks compare the caller against.
+    AccessControlStorage,
+    /// Index of a storage array write.
+    ArrayIndex,
+}
+
+impl SinkKind {
+    pub fn description(self) -> &'static str {
+        match self {
+            SinkKind::DelegateCallTarget => "delegatecall target",
+            SinkKind::CallTarget => "call target",
+            SinkKind::CallValue => "call value",
+            SinkKind::SelfdestructBeneficiary => "selfdestruct beneficiary",
+            SinkKind::AccessControlStorage => "access-control state",
+            SinkKind::ArrayIndex => "storage array index",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Origin {
+    Source(SourceKind),
+    /// Parameter of the summarised function, substituted at call sites.
+    Param(usize),
+}
+
+#[derive(Debug, Clone)]
+struct Step {
+    function: FunctionId,
+    description: String,
+    loc: Loc,
+}
+
+type Taint = BTreeMap<Origin, Vec<Step>>;
+
+#[derive(Debug, Clone)]
+struct Hit {
+    sink: SinkKind,
+    origin: Origin,
+    trace: Vec<Step>,
+    function: FunctionId,
+    loc: Loc,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Summary {
+    returns: Taint,
+    writes: BTreeMap<StateVarId, Taint>,
+    hits: Vec<Hit>,
+}
+
+/// A source reaching a sink from an entry point.
+#[derive(Debug, Clone)]
+pub struct TaintFlow {
+    pub source: SourceKind,
+    pub sink: SinkKind,
+    /// Contract the entry point is called on.
+    pub context: ContractId,
+    pub entry: FunctionId,
+    /// Function containing the sink; differs from `entry` for modifiers and callees.
+    pub function: FunctionId,
+    /// The entry point checks the caller (see [`GuardAnalysis`]).
+    pub guarded: bool,
+    pub loc: Loc,
+    pub trace: Vec<TraceStep>,
+}
+
+type Key = (Option<ContractId>, FunctionId);
+
+pub struct TaintAnalysis<'s, 'p> {
+    symbols: &'s SymbolTable<'p>,
+    guards: GuardAnalysis<'s, 'p>,
+    guard_variables: BTreeSet<StateVarId>,
+    /// Taint of state variables written by unguarded entry points.
+    storage: BTreeMap<StateVarId, Taint>,
+    summaries: HashMap<Key, Summary>,
+    active: HashSet<Key>,
+}
+
+impl<'s, 'p> TaintAnalysis<'s, 'p> {
+    pub fn new(symbols: &'s SymbolTable<'p>) -> Self {
+        let mut guards = GuardAnalysis::new(symbols);
+        let guard_variables = guards.guard_variables();
+        Self {
+            symbols,
+            guards,
+            guard_variables,
+            storage: BTreeMap::new(),
+            summaries: HashMap::new(),
+            active: HashSet::new(),
+        }
+    }
+
+    /// All source→sink flows from the entry points of deployable contracts.
+    pub fn flows(&mut self) -> Vec<TaintFlow> {
+        let entries = self.entry_points();
+        for _ in 0..STORAGE_ROUNDS {
+            self.summaries.clear();
+            let mut changed = false;
+            for &(context, func) in &entries {
+                if self.guards.is_guarded(Some(context), func) {
+                    continue;
+                }
+                let summary = self.summary(Some(context), func);
+                for (var, taint) in &summary.writes {
+                    let taint = self.instantiate(func, taint);
+                    changed |= join(self.storage.entry(*var).or_default(), &taint);
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let mut flows = Vec::new();
+        let mut seen = HashSet::new();
+        for &(context, entry) in &entries {
+            let guarded = self.guards.is_guarded(Some(context), entry);
+            let summary = self.summary(Some(context), entry);
+            for hit in &summary.hits {
+                let taint =
+                    self.instantiate(entry, &Taint::from([(hit.origin, hit.trace.clone())]));
+                for (origin, trace) in taint {
+                    let Origin::Source(source) = origin else {
+                        continue;
+                    };
+                    if !seen.insert((context, entry, hit.sink, source, hit.loc)) {
+                        continue;
+                    }
+                    flows.push(TaintFlow {
+                        source,
+                        sink: hit.sink,
+                        context,
+                        entry,
+                        function: hit.function,
+                        guarded,
+                        loc: hit.loc,
+                        trace: self.trace(&trace),
+                    });
+                }
+            }
+        }
+        flows
+    }
+
+    fn entry_points(&self) -> Vec<(ContractId, FunctionId)> {
+        self.symbols
+            .contracts
+            .iter()
+            .filter(|info| info.def.kind == ContractKind::Contract)
+            .flat_map(|info| {
+                info.functions
+                    .iter()
+                    .filter(|&&f| self.symbols.function(f).is_entry_point())
+                    .map(move |&f| (info.id, f))
+            })
+            .collect()
+    }
+
+    /// Replaces parameter origins of entry point `func` by calldata sources.
+    fn instantiate(&self, func: FunctionId, taint: &Taint) -> Taint {
+        let params = &self.symbols.function(func).params;
+        let mut out = Taint::new();
+        for (origin, trace) in taint {
+            let (origin, trace) = match origin {
+                Origin::Param(index) => {
+                    let param = &params[*index];
+                    let name = param.name.as_deref().unwrap_or("_");
+                    let mut full = vec![Step {
+                        function: func,
+                        description: format!("parameter `{name}`"),
+                        loc: param.loc,
+                    }];
+                    full.extend(trace.iter().cloned());
+                    (Origin::Source(SourceKind::Parameter), full)
+                }
+                source => (*source, trace.clone()),
+            };
+            out.entry(origin).or_insert(trace);
+        }
+        out
+    }
+
+    fn trace(&self, steps: &[Step]) -> Vec<TraceStep> {
+        steps
+            .iter()
+            .map(|step| TraceStep {
+                function: self.symbols.qualified_name(step.function),
+                description: step.description.clone(),
+                location: self.symbols.project.location(&step.loc),
+            })
+            .collect()
+    }
+
+    fn summary(&mut self, context: Option<ContractId>, func: FunctionId) -> Summary {
+        let key = (context, func);
+        if let Some(summary) = self.summaries.get(&key) {
+            return summary.clone();
+        }
+        if !self.active.insert(key) {
+            // Recursion: the recursive call contributes nothing.
+            return Summary::default();
+        }
+        let summary = self.compute(context, func);
+        self.active.remove(&key);
+        self.summaries.insert(key, summary.clone());
+        summary
+    }
+
+    fn compute(&mut self, context: Option<ContractId>, func: FunctionId) -> Summary {
+        let ir = IrFunction::build(self.symbols, context, func);
+        let mut taints = vec![Taint::new(); ir.values.len()];
+        for _ in 0..BODY_PASSES {
+            let mut changed = false;
+            for (block, inst) in ir.instructions() {
+                let Some(dst) = inst.dst else {
+                    continue;
+                };
+                let origin = ir.cfg.node(block).origin;
+                let taint = self.transfer(context, origin, inst, &taints);
+                changed |= join(&mut taints[dst], &taint);
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let mut summary = Summary::default();
+        let mut seen = HashSet::new();
+        let mut hit = |summary: &mut Summary, hit: Hit| {
+            if seen.insert((hit.sink, hit.origin, hit.loc)) {
+                summary.hits.push(hit);
+            }
+        };
+        for (block, inst) in ir.instructions() {
+            let function = ir.cfg.node(block).origin;
+            let taint_of = |operand: &Operand| operand_taint(&taints, operand);
+            let mut sinks: Vec<(SinkKind, Taint)> = Vec::new();
+            match &inst.op {
+                Op::LowLevelCall {
+                    kind: CallKind::DelegateCall | CallKind::CallCode,
+                    target,
+                    ..
+                } => sinks.push((SinkKind::DelegateCallTarget, taint_of(target))),
+                Op::LowLevelCall {
+                    kind: CallKind::Call,
+                    target,
+                    value,
+                    ..
+                } => {
+                    sinks.push((SinkKind::CallTarget, taint_of(target)));
+                    if let Some(value) = value {
+                        sinks.push((SinkKind::CallValue, taint_of(value)));
+                    }
+                }
+                Op::ExternalCall {
+                    value: Some(value), ..
+                } => sinks.push((SinkKind::CallValue, taint_of(value))),
+                Op::Transfer { amount, .. } => sinks.push((SinkKind::CallValue, taint_of(amount))),
+                Op::Selfdestruct(beneficiary) => {
+                    sinks.push((SinkKind::SelfdestructBeneficiary, taint_of(beneficiary)))
+                }
+                Op::StorageWrite { var, path, value } => {
+                    let name = &self.symbols.state_variable(*var).name;
+                    let mut written = taint_of(value);
+                    let mut ty = Some(self.symbols.state_variable(*var).type_name.clone());
+                    for (position, elem) in path.iter().enumerate() {
+                        if let PathElem::Index(index) = elem {
+                            let index = taint_of(index);
+                            if ty.as_deref().is_some_and(|t| t.ends_with(']')) {
+                                sinks.push((SinkKind::ArrayIndex, index.clone()));
+                            }
+                            join(&mut written, &index);
+                        }
+                        ty = path_type(self.symbols, ty, &path[position..=position]);
+                    }
+                    if self.guard_variables.contains(var) {
+                        sinks.push((SinkKind::AccessControlStorage, written.clone()));
+                    }
+                    let stored = extend(
+                        &written,
+                        Step {
+                            function,
+                            description: format!("stored in `{name}`"),
+                            loc: inst.loc,
+                        },
+                    );
+                    join(summary.writes.entry(*var).or_default(), &stored);
+                }
+                Op::Return(values) => {
+                    for value in values {
+                        join(&mut summary.returns, &taint_of(value));
+                    }
+                }
+                Op::InternalCall {
+                    callee: Some(callee),
+                    args,
+                    ..
+                }
+                | Op::LibraryCall {
+                    callee: Some(callee),
+                    args,
+                    ..
+                } => {
+                    let inner =
+                        self.summary(self.symbols.dispatch_context(context, *callee), *callee);
+                    let args: Vec<Taint> = args.iter().map(taint_of).collect();
+                    let call = CallSite {
+                        symbols: self.symbols,
+                        caller: function,
+                        callee: *callee,
+                        args: &args,
+                        loc: inst.loc,
+                    };
+                    for (var, taint) in &inner.writes {
+                        join(
+                            summary.writes.entry(*var).or_default(),
+                            &call.substitute(taint),
+                        );
+                    }
+                    for inner_hit in inner.hits {
+                        let taint = Taint::from([(inner_hit.origin, inner_hit.trace.clone())]);
+                        for (origin, trace) in call.substitute(&taint) {
+                            hit(
+                                &mut summary,
+                                Hit {
+                                    origin,
+                                    trace,
+                                    ..inner_hit.clone()
+                                },
+                            );
+                        }
+                    }
+                }
+                _ => {}
+            }
+            for (sink, taint) in sinks {
+                for (origin, trace) in taint {
+                    let trace = extend_trace(
+                        trace,
+                        Step {
+                            function,
+                            description: format!("reaches the {}", sink.description()),
+                            loc: inst.loc,
+                        },
+                    );
+                    hit(
+                        &mut summary,
+                        Hit {
+                            sink,
+                            origin,
+                            trace,
+                            function,
+                            loc: inst.loc,
+                        },
+                    );
+                }
+            }
+        }
+        summary
+    }
+
+    /// Taint of the value defined by `inst`.
+    fn transfer(
+        &mut self,
+        context: Option<ContractId>,
+        function: FunctionId,
+        inst: &Instruction,
+        taints: &[Taint],
+    ) -> Taint {
+        let step = |description: String| Step {
+            function,
+            description,
+            loc: inst.loc,
+        };
+        let union = |operands: Vec<&Operand>| {
+            let mut out = Taint::new();
+            for operand in operands {
+                join(&mut out, &operand_taint(taints, operand));
+            }
+            out
+        };
+        match &inst.op {
+            Op::Param(index) => Taint::from([(Origin::Param(*index), Vec::new())]),
+            Op::Env(env) => {
+                let source = match env {
+                    EnvVar::MsgSender => SourceKind::MsgSender,
+                    EnvVar::MsgValue => SourceKind::MsgValue,
+                    EnvVar::MsgData => SourceKind::MsgData,
+                    EnvVar::TxOrigin => SourceKind::TxOrigin,
+                    _ => return Taint::new(),
+                };
+                Taint::from([(
+                    Origin::Source(source),
+                    vec![step(format!("`{}`", env.name()))],
+                )])
+            }
+            Op::StorageRead { var, .. } => match self.storage.get(var) {
+                Some(taint) => {
+                    let name = &self.symbols.state_variable(*var).name;
+                    extend(taint, step(format!("read from `{name}`")))
+                }
+                None => Taint::new(),
+            },
+            Op::ExternalCall { name, .. } => Taint::from([(
+                Origin::Source(SourceKind::ReturnData),
+                vec![step(format!("return value of external call `{name}`"))],
+            )]),
+            Op::LowLevelCall { kind, .. } => Taint::from([(
+                Origin::Source(SourceKind::ReturnData),
+                vec![step(format!("return data of `{}`", kind.name()))],
+            )]),
+            Op::InternalCall {
+                callee: Some(callee),
+                args,
+                ..
+            }
+            | Op::LibraryCall {
+                callee: Some(callee),
+                args,
+                ..
+            } => {
+                let inner = self.summary(self.symbols.dispatch_context(context, *callee), *callee);
+                let args: Vec<Taint> = args.iter().map(|a| operand_taint(taints, a)).collect();
+                let call = CallSite {
+                    symbols: self.symbols,
+                    caller: function,
+                    callee: *callee,
+                    args: &args,
+                    loc: inst.loc,
+                };
+                let name = self.symbols.qualified_name(*callee);
+                extend(
+                    &call.substitute(&inner.returns),
+                    step(format!("returned by `{name}`")),
+                )
+            }
+            Op::Index(base, _)
+            | Op::Member(base, _)
+            | Op::Extract(base, _)
+            | Op::Copy(base)
+            | Op::Unary(_, base)
+            | Op::Conversion { value: base, .. } => operand_taint(taints, base),
+            Op::Select(_, a, b) => union(vec![a, b]),
+            Op::StateConstant(_) | Op::Balance(_) | Op::New { .. } | Op::Transfer { .. } => {
+                Taint::new()
+            }
+            other => union(other.operands()),
+        }
+    }
+}
+
+/// Argument binding of an internal or library call.
+struct CallSite<'a, 's, 'p> {
+    symbols: &'s SymbolTable<'p>,
+    caller: FunctionId,
+    callee: FunctionId,
+    args: &'a [Taint],
+    loc: Loc,
+}
+
+impl CallSite<'_, '_, '_> {
+    /// Replaces the callee's parameter origins by the taint of the arguments.
+    fn substitute(&self, taint: &Taint) -> Taint {
+        let mut out = Taint::new();
+        for (origin, trace) in taint {
+            match origin {
+                Origin::Param(index) => {
+                    let Some(arg) = self.args.get(*index) else {
+                        continue;
+                    };
+                    let param = self.symbols.function(self.callee).params[*index]
+                        .name
+                        .as_deref()
+                        .unwrap_or("_");
+                    let hop = Step {
+                        function: self.caller,
+                        description: format!(
+                            "passed to `{}` as `{param}`",
+                            self.symbols.qualified_name(self.callee)
+                        ),
+                        loc: self.loc,
+                    };
+                    for (arg_origin, arg_trace) in arg {
+                        let mut full = arg_trace.clone();
+                        full.push(hop.clone());
+                        full.extend(trace.iter().cloned());
+                        out.entry(*arg_origin).or_insert(full);
+                    }
+                }
+                source => {
+                    out.entry(*source).or_insert_with(|| trace.clone());
+                }
+            }
+        }
+        out
+    }
+}
+
+fn operand_taint(taints: &[Taint], operand: &Operand) -> Taint {
+    operand
+        .value()
+        .map(|v| taints[v].clone())
+        .unwrap_or_default()
+}
+
+/// Adds the origins of `from` missing in `into`.
+fn join(into: &mut Taint, from: &Taint) -> bool {
+    let mut changed = false;
+    for (origin, trace) in from {
+        if !into.contains_key(origin) {
+            into.insert(*origin, trace.clone());
+            changed = true;
+        }
+    }
+    changed
+}
+
+fn extend(taint: &Taint, step: Step) -> Taint {
+    taint
+        .iter()
+        .map(|(origin, trace)| (*origin, extend_trace(trace.clone(), step.clone())))
+        .collect()
+}
+
+fn extend_trace(mut trace: Vec<Step>, step: Step) -> Vec<Step> {
+    trace.push(step);
+    trace
+}
+
+/// Unguarded flows worth an auditor's attention, as findings. Writes to
+/// access-control state, delegatecall targets and selfdestruct beneficiaries
+/// are reported by the access-control and primitives detectors, which attach
+/// these traces.
+pub fn report(symbols: &SymbolTable) -> Vec<Finding> {
+    let mut analysis = TaintAnalysis::new(symbols);
+    let mut reported = HashSet::new();
+    let mut findings = Vec::new();
+    for flow in analysis.flows() {
+        if flow.guarded {
+            continue;
+        }
+        let severity = match (flow.sink, flow.source) {
+            (SinkKind::CallTarget, SourceKind::Parameter | SourceKind::ReturnData) => {
+                Severity::Medium
+            }
+            // Indices are bounds-checked; only before 0.6, where `length` can be
+            // set, does a caller-chosen index reach arbitrary storage slots.
+            (SinkKind::ArrayIndex, _) if allows_length_writes(symbols, flow.function) => {
+                Severity::Medium
+            }
+            _ => continue,
+        };
+        let entry = symbols.function(flow.entry);
+        if !reported.insert((flow.sink, flow.loc)) {
+            continue;
+        }
+        let title = match flow.sink {
+            SinkKind::CallTarget => "User-controlled call target",
+            SinkKind::ArrayIndex => "User-controlled storage array index",
+            _ => unreachable!(),
+        };
+        let description = format!(
+            "{} reaches the {} in `{}`, callable by anyone through `{}.{}`.",
+            capitalize(flow.source.description()),
+            flow.sink.description(),
+            symbols.qualified_name(flow.function),
+            symbols.contract(flow.context).name(),
+            entry.display_name(),
+        );
+        findings.push(Finding {
+            severity,
+            confidence: Confidence::Medium,
+            title: title.to_string(),
+            description,
+            location: symbols.project.location(&flow.loc),
+            extra_data: ExtraData {
+                trace: flow.trace,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+    }
+    findings
+}
+
+/// Whether the file of `func` may compile with Solidity before 0.6, where
+/// arrays can be resized by assigning their `length`.
+fn allows_length_writes(symbols: &SymbolTable, func: FunctionId) -> bool {
+    symbols
+        .project
+        .unit(symbols.file_of(func))
+        .and_then(|unit| unit.min_solidity_version())
+        .is_some_and(|version| version < (0, 6, 0))
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_lowercase() && !text.contains('.') => {
+            first.to_ascii_uppercase().to_string() + chars.as_str()
+        }
+        _ => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Project, ProjectBundle};
+
+    fn flows(src: &str) -> Vec<(SourceKind, SinkKind, String, bool, Vec<String>)> {
+        let project = Project::load(&ProjectBundle::single("Test.sol", src));
+        let symbols = SymbolTable::build(&project);
+        TaintAnalysis::new(&symbols)
+            .flows()
+            .into_iter()
+            .map(|flow| {
+                (
+                    flow.source,
+                    flow.sink,
+                    symbols.function(flow.entry).display_name().to_string(),
+                    flow.guarded,
+                    flow.trace.into_iter().map(|s| s.description).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parameter_to_delegatecall_through_helper() {
+        let flows = flows(
+            "contract P {
+                function exec(address target, bytes calldata data) external {
+                    _forward(target, data);
+                }
+                function _forward(address to, bytes memory data) internal {
+                    (bool ok, ) = to.delegatecall(data);
+                    require(ok);
+                }
+             }",
+        );
+        let (source, sink, entry, guarded, trace) = flows
+            .iter()
+            .find(|f| f.1 == SinkKind::DelegateCallTarget)
+            .unwrap();
+        assert_eq!(
+            (*source, *sink, entry.as_str(), *guarded),
+            (
+                SourceKind::Parameter,
+                SinkKind::DelegateCallTarget,
+                "exec",
+                false
+            )
+        );
+        assert_eq!(
+            trace,
+            &[
+                "parameter `target`",
+                "passed to `P._forward` as `to`",
+                "reaches the delegatecall target"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_storage_carries_taint_between_transactions() {
+        let flows = flows(
+            "contract P {
+                address owner;
+                address implementation;
+                function setImplementation(address impl) external { implementation = impl; }
+                function upgradeOwner(address o) external {
+                    require(msg.sender == owner);
+                    owner = o;
+                }
+                function kill() external { selfdestruct(payable(implementation)); }
+             }",
+        );
+        let kill = flows
+            .iter()
+            .find(|f| f.1 == SinkKind::SelfdestructBeneficiary)
+            .unwrap();
+        assert_eq!(
+            (kill.0, kill.2.as_str(), kill.3),
+            (SourceKind::Parameter, "kill", false)
+        );
+        assert_eq!(
+            kill.4,
+            [
+                "parameter `impl`",
+                "stored in `implementation`",
+                "read from `implementation`",
+                "reaches the selfdestruct beneficiary"
+            ]
+        );
+        // The owner write is guarded, so it does not taint `owner` for other functions.
+        let owner = flows
+            .iter()
+            .find(|f| f.1 == SinkKind::AccessControlStorage)
+            .unwrap();
+        assert_eq!((owner.2.as_str(), owner.3), ("upgradeOwner", true));
+    }
+
+    #[test]
+    fn test_sender_through_modifier_and_return_data() {
+        let flows = flows(
+            "interface IRegistry { function target() external view returns (address); }
+             contract P {
+                address[] public users;
+                IRegistry registry;
+                function _msgSender() internal view returns (address) { return msg.sender; }
+                function claim(uint i) external { users[i] = _msgSender(); }
+                function relay() external {
+                    (bool ok, ) = registry.target().call(\"\");
+                    require(ok);
+                }
+             }",
+        );
+        assert!(flows
+            .iter()
+            .any(|f| f.0 == SourceKind::Parameter && f.1 == SinkKind::ArrayIndex));
+        let relay = flows.iter().find(|f| f.1 == SinkKind::CallTarget).unwrap();
+        assert_eq!(
+            (relay.0, relay.2.as_str()),
+            (SourceKind::ReturnData, "relay")
+        );
+    }
+
+    #[test]
+    fn test_report_skips_guarded_flows() {
+        let project = Project::load(&ProjectBundle::single(
+            "Test.sol",
+            "contract P {
+                address owner;
+                function exec(address t) external {
+                    require(msg.sender == owner);
+                    (bool ok, ) = t.call(\"\");
+                    require(ok);
+                }
+                function poke(address to) external { (bool ok, ) = to.call(\"\"); require(ok); }
+             }",
+        ));
+        let symbols = SymbolTable::build(&project);
+        let findings = report(&symbols);
+        // The call in `exec` is guarded.
+        let titles: Vec<&str> = findings.iter().map(|f| f.title.as_str()).collect();
+        assert_eq!(titles, ["User-controlled call target"]);
+        let trace = &findings[0].trace();
+        assert_eq!(trace[0].function, "P.poke");
+        assert_eq!(trace[0].location.as_ref().unwrap().line, 8);
+    }
+
+    #[test]
+    fn test_report_array_index_by_version() {
+        let titles = |pragma: &str| {
+            let project = Project::load(&ProjectBundle::single(
+                "Test.sol",
+                format!(
+                    "pragma solidity {pragma};
+                     contract Board {{
+                        struct Post {{ address author; string text; }}
+                        Post[] posts;
+                        function edit(uint256 i, string memory text) public {{
+                            require(posts[i].author == msg.sender);
+                            posts[i].text = text;
+                        }}
+                     }}"
+                ),
+            ));
+            let symbols = SymbolTable::build(&project);
+            report(&symbols)
+                .into_iter()
+                .map(|f| f.title)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(titles("^0.8.20"), [] as [&str; 0]);
+        assert_eq!(titles("^0.4.24"), ["User-controlled storage array index"]);
+    }
+}
@@ -0,0 +1,516 @@
+//! Static analysis tasks as published by the analysis planner on
+//! `rukh.static.tasks.<job_id>`, and the results produced for them.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ast::ContractKind;
+use crate::callgraph::CallGraph;
+use crate::detectors::erc::{self, Conformance};
+use crate::detectors::upgradeability::{self, ProxyKind};
+use crate::detectors::{self, Detector, Registry};
+use crate::finding::{merge, Baseline, Finding};
+use crate::guards::{self, RoleMap};
+use crate::layout::{self, StorageLayout, StorageLayoutCheck};
+use crate::parser::Diagnostic;
+use crate::project::{BundleError, Project, ProjectBundle};
+use crate::rules::{self, Rule};
+use crate::suppress::Suppressions;
+use crate::symbols::SymbolTable;
+
+/// Path used for the single `source_code` string when the task carries no file name.
+const DEFAULT_SOURCE_PATH: &str = "Contract.sol";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaticTask {
+    pub job_id: String,
+    #[serde(default)]
+    pub phase: Option<String>,
+    #[serde(default)]
+    pub contract_id: Option<String>,
+    #[serde(default)]
+    pub contract_name: Option<String>,
+    #[serde(default)]
+    pub source_code: Option<String>,
+    #[serde(default)]
+    pub compiler_version: Option<String>,
+    /// Multi-file project as a path → content map.
+    #[serde(default)]
+    pub sources: Option<BTreeMap<String, String>>,
+    /// Base64 encoded `.tar`/`.tar.gz` of the project.
+    #[serde(default)]
+    pub archive: Option<String>,
+    /// Extra remappings on top of any `remappings.txt` in the bundle.
+    #[serde(default)]
+    pub remappings: Vec<String>,
+    /// Ids of the detectors to run; all registered detectors when empty.
+    #[serde(default)]
+    pub detectors: Vec<String>,
+    /// Ids of detectors to skip.
+    #[serde(default)]
+    pub disabled_detectors: Vec<String>,
+    /// Project rules to run alongside the detectors (see [`crate::rules`]).
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+    /// Accepted findings, reported as suppressed.
+    #[serde(default)]
+    pub baseline: Option<Baseline>,
+    /// The deployed version these sources upgrade, checked for storage
+    /// layout compatibility (see [`crate::layout`]).
+    #[serde(default)]
+    pub previous: Option<PreviousVersion>,
+}
+
+/// Sources of the version behind a proxy, packaged like a task's own.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PreviousVersion {
+    #[serde(default)]
+    pub sources: Option<BTreeMap<String, String>>,
+    #[serde(default)]
+    pub archive: Option<String>,
+    #[serde(default)]
+    pub remappings: Vec<String>,
+    /// Name of the task's `contract_name` in this version, if it was renamed.
+    #[serde(default)]
+    pub contract_name: Option<String>,
+}
+
+impl PreviousVersion {
+    pub fn bundle(&self) -> Result<ProjectBundle, BundleError> {
+        let mut bundle = if let Some(archive) = &self.archive {
+            ProjectBundle::from_base64_archive(archive)?
+        } else if let Some(sources) = &self.sources {
+            ProjectBundle::from_map(sources.clone(), &[])?
+        } else {
+            ProjectBundle::default()
+        };
+        for remapping in &self.remappings {
+            bundle.remappings.push(remapping.parse()?);
+        }
+        Ok(bundle)
+    }
+}
+
+impl StaticTask {
+    /// Collects the task sources: an archive takes precedence over a source map,
+    /// which takes precedence over the planner's single `source_code` string.
+    pub fn bundle(&self) -> Result<ProjectBundle, BundleError> {
+        let mut bundle = if let Some(archive) = &self.archive {
+            ProjectBundle::from_base64_archive(archive)?
+        } else if let Some(sources) = &self.sources {
+            ProjectBundle::from_map(sources.clone(), &[])?
+        } else if let Some(code) = &self.source_code {
+            ProjectBundle::single(self.source_path(), code.as_str())
+        } else {
+            ProjectBundle::default()
+        };
+        for remapping in &self.remappings {
+            bundle.remappings.push(remapping.parse()?);
+        }
+        Ok(bundle)
+    }
+
+    fn source_path(&self) -> String {
+        match &self.contract_name {
+            Some(name) if !name.is_empty() => format!("{name}.sol"),
+            _ => DEFAULT_SOURCE_PATH.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractSummary {
+    pub name: String,
+    pub kind: String,
+    pub file: String,
+    /// C3 linearization, most derived first.
+    pub linearization: Vec<String>,
+    pub functions: usize,
+    pub modifiers: usize,
+    pub state_variables: usize,
+    pub events: usize,
+    pub errors: usize,
+    /// Proxy pattern the contract takes part in, as the proxy or its implementation.
+    #[serde(default)]
+    pub proxy: Option<ProxyKind>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskResult {
+    pub job_id: String,
+    pub contract_id: Option<String>,
+    pub contracts: Vec<ContractSummary>,
+    pub diagnostics: Vec<Diagnostic>,
+    /// Suppressed findings included, see [`Finding::is_suppressed`].
+    pub vulnerabilities: Vec<Finding>,
+    /// Shared with the web UI and the attack-graph service.
+    pub call_graph: CallGraph,
+    /// `call_graph` in Graphviz DOT, for rendering as is.
+    #[serde(default)]
+    pub call_graph_dot: String,
+    /// Role → function matrix per deployable contract.
+    pub role_map: Vec<RoleMap>,
+    /// Storage layout per deployable contract.
+    #[serde(default)]
+    pub storage_layouts: Vec<StorageLayout>,
+    /// Token standards the contracts claim and how they conform.
+    #[serde(default)]
+    pub conformance: Vec<Conformance>,
+}
+
+/// Runs [`process_task_with`] with the built-in detectors.
+pub fn process_task(task: &StaticTask) -> TaskResult {
+    process_task_with(&Registry::builtin(), task)
+}
+
+/// Loads and parses the task sources. Bundle and parse errors are reported as
+/// diagnostics rather than failing the task, so the planner always receives a result.
+pub fn process_task_with(registry: &Registry, task: &StaticTask) -> TaskResult {
+    let project = match task.bundle() {
+        Ok(bundle) if bundle.sources.is_empty() => {
+            let mut project = Project::default();
+            project
+                .diagnostics
+                .push(Diagnostic::error("task has no Solidity sources", None));
+            project
+        }
+        Ok(bundle) => Project::load(&bundle),
+        Err(err) => {
+            let mut project = Project::default();
+            project
+                .diagnostics
+                .push(Diagnostic::error(err.to_string(), None));
+            project
+        }
+    };
+
+    let symbols = SymbolTable::build(&project);
+    let mut diagnostics = project.diagnostics.clone();
+    diagnostics.extend(symbols.diagnostics.iter().cloned());
+    let mut task_rules = Vec::new();
+    for rule in &task.rules {
+        match rule.validate() {
+            Ok(())
+                if detectors::is_selected(&rule.id, &task.detectors, &task.disabled_detectors) =>
+            {
+                task_rules.push(rule.clone())
+            }
+            Ok(()) => {}
+            Err(err) => diagnostics.push(Diagnostic::warning(err.to_string(), None)),
+        }
+    }
+    for id in task.detectors.iter().chain(&task.disabled_detectors) {
+        if registry.get(id).is_none() && !task.rules.iter().any(|r| &r.id == id) {
+            diagnostics.push(Diagnostic::warning(
+                format!("unknown detector `{id}`"),
+                None,
+            ));
+        }
+    }
+
+    // The version the sources upgrade, for the storage layout check.
+    let old_project = task
+        .previous
+        .as_ref()
+        .and_then(|previous| match previous.bundle() {
+            Ok(bundle) => Some(Project::load(&bundle)),
+            Err(err) => {
+                diagnostics.push(Diagnostic::warning(
+                    format!("previous version: {err}"),
+                    None,
+                ));
+                None
+            }
+        });
+    let old_symbols = old_project.as_ref().map(SymbolTable::build);
+    let contract = task.contract_name.as_deref().filter(|n| !n.is_empty());
+    let previous_contract = task
+        .previous
+        .as_ref()
+        .and_then(|p| p.contract_name.as_deref())
+        .or(contract);
+    if let Some(old_symbols) = &old_symbols {
+        for (symbols, name) in [(old_symbols, previous_contract), (&symbols, contract)] {
+            if let Some(name) = name.filter(|n| symbols.contract_by_name(n, None).is_none()) {
+                diagnostics.push(Diagnostic::warning(
+                    format!("contract `{name}` not found for the storage layout check"),
+                    None,
+                ));
+            }
+        }
+    }
+    let upgrade = old_symbols
+        .as_ref()
+        .map(|old| StorageLayoutCheck::upgrading(old, contract, previous_contract));
+    let overrides: Vec<&dyn Detector> = upgrade.iter().map(|d| d as &dyn Detector).collect();
+
+    let mut vulnerabilities = registry.run_with(
+        &symbols,
+        &task.detectors,
+        &task.disabled_detectors,
+        &overrides,
+    );
+    for mut finding in rules::evaluate(&task_rules, &symbols) {
+        if let Some(rule) = task_rules.iter().find(|r| r.id == finding.check_name) {
+            detectors::complete(&mut finding, rule, &symbols);
+        }
+        vulnerabilities.push(finding);
+    }
+    // Fingerprints key baselines, so keep one finding per fingerprint.
+    let mut vulnerabilities = merge::merge(vulnerabilities);
+    Suppressions::collect(&project.sources).apply(&mut vulnerabilities);
+    if let Some(baseline) = &task.baseline {
+        baseline.apply(&mut vulnerabilities);
+    }
+
+    let call_graph = CallGraph::build(&symbols);
+    TaskResult {
+        job_id: task.job_id.clone(),
+        contract_id: task.contract_id.clone(),
+        contracts: summarize(&symbols),
+        diagnostics,
+        vulnerabilities,
+        call_graph_dot: call_graph.to_dot(),
+        call_graph,
+        role_map: guards::role_map(&symbols),
+        storage_layouts: layout::layouts(&symbols),
+        conformance: erc::conformance(&symbols),
+    }
+}
+
+fn summarize(symbols: &SymbolTable) -> Vec<ContractSummary> {
+    symbols
+        .contracts
+        .iter()
+        .map(|info| (info, info.def))
+        .map(|(info, contract)| ContractSummary {
+            name: contract.name.clone(),
+            kind: match contract.kind {
+                ContractKind::Contract => "contract",
+                ContractKind::Abstract => "abstract",
+                ContractKind::Interface => "interface",
+                ContractKind::Library => "library",
+            }
+            .to_string(),
+            file: info.unit.path.clone(),
+            linearization: info
+                .linearization
+                .iter()
+                .map(|&id| symbols.contract(id).name().to_string())
+                .collect(),
+            functions: contract.functions.len(),
+            modifiers: contract.modifiers.len(),
+            state_variables: contract.state_variables.len(),
+            events: contract.events.len(),
+            errors: contract.errors.len(),
+            proxy: upgradeability::proxy_kind(symbols, info),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finding::{Severity, SuppressionKind};
+
+    fn task(source: &str) -> StaticTask {
+        serde_json::from_value(serde_json::json!({
+            "job_id": "job-1",
+            "phase": "static",
+            "contract_id": "c-1",
+            "contract_name": "ReentrancyVault",
+            "source_code": source,
+            "compiler_version": "0.8.20",
+            "previous_results": {},
+            "timestamp": "2025-10-20T09:36:00"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_process_planner_task() {
+        let source = include_str!("../../../integrations/foundry/src/ReentrancyVault.sol");
+        let result = process_task(&task(source));
+        assert_eq!(result.job_id, "job-1");
+        assert!(result.diagnostics.is_empty());
+        assert_eq!(result.contracts.len(), 1);
+        let vault = &result.contracts[0];
+        assert_eq!(
+            (vault.name.as_str(), vault.functions, vault.events),
+            ("ReentrancyVault", 4, 2)
+        );
+        let titles: Vec<&str> = result
+            .vulnerabilities
+            .iter()
+            .map(|v| v.title.as_str())
+            .collect();
+        assert_eq!(
+            titles,
+            [
+                "Reentrancy: state written after external call",
+                "Read-only reentrancy"
+            ]
+        );
+        let finding = &result.vulnerabilities[0];
+        assert_eq!(finding.check_name, "reentrancy");
+        assert_eq!(finding.extra_data.swc, ["SWC-107"]);
+        assert_eq!(
+            finding.extra_data.anchor.as_deref(),
+            Some("ReentrancyVault.withdraw(uint256)")
+        );
+        assert!(finding.code_snippet.is_some());
+    }
+
+    #[test]
+    fn test_process_task_with_syntax_error() {
+        let result = process_task(&task("contract A { function f( }"));
+        assert!(result.contracts.is_empty());
+        let location = result.diagnostics[0].location.as_ref().unwrap();
+        assert_eq!(
+            (location.file.as_str(), location.line),
+            ("ReentrancyVault.sol", 1)
+        );
+    }
+
+    #[test]
+    fn test_process_multi_file_task() {
+        let task: StaticTask = serde_json::from_value(serde_json::json!({
+            "job_id": "job-2",
+            "sources": {
+                "src/Vault.sol": "import {Base} from \"@lib/Base.sol\"; contract Vault is Base {}",
+                "lib/base/src/Base.sol": "contract Base { function f() public {} }"
+            },
+            "remappings": ["@lib/=lib/base/src/"]
+        }))
+        .unwrap();
+        let result = process_task(&task);
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+        let files: Vec<(&str, &str)> = result
+            .contracts
+            .iter()
+            .map(|c| (c.name.as_str(), c.file.as_str()))
+            .collect();
+        assert_eq!(
+            files,
+            [
+                ("Base", "lib/base/src/Base.sol"),
+                ("Vault", "src/Vault.sol")
+            ]
+        );
+        assert_eq!(result.contracts[1].linearization, ["Vault", "Base"]);
+        assert!(result.call_graph_dot.starts_with("digraph"));
+        assert!(result.call_graph_dot.contains("Base.f()"));
+    }
+
+    #[test]
+    fn test_process_task_with_detector_selection() {
+        let source = include_str!("../../../integrations/foundry/src/ReentrancyVault.sol");
+        let mut task = task(source);
+        task.disabled_detectors = vec!["reentrancy".into(), "no-such-detector".into()];
+        let result = process_task(&task);
+        assert!(result.vulnerabilities.is_empty());
+        assert_eq!(
+            result.diagnostics[0].message,
+            "unknown detector `no-such-detector`"
+        );
+    }
+
+    #[test]
+    fn test_process_task_with_suppressions() {
+        let source = include_str!("../../../integrations/foundry/src/ReentrancyVault.sol");
+        let result = process_task(&task(&format!("// rukh-disable-file reentrancy\n{source}")));
+        assert!(!result.vulnerabilities.is_empty());
+        assert!(result.vulnerabilities.iter().all(Finding::is_suppressed));
+
+        let mut task = task(source);
+        let first = process_task(&task);
+        assert!(!first.vulnerabilities.iter().any(Finding::is_suppressed));
+        task.baseline = Some(Baseline::from_findings(&first.vulnerabilities));
+        let second = process_task(&task);
+        assert_eq!(second.vulnerabilities.len(), first.vulnerabilities.len());
+        assert!(second.vulnerabilities.iter().all(|f| {
+            f.extra_data.suppression.as_ref().map(|s| s.kind) == Some(SuppressionKind::Baseline)
+        }));
+    }
+
+    #[test]
+    fn test_process_task_with_rules() {
+        let task: StaticTask = serde_json::from_value(serde_json::json!({
+            "job_id": "job-3",
+            "source_code": "contract A { uint256 x; function set(uint256 v) external { x = v; } }",
+            "detectors": ["x-writes"],
+            "rules": [
+                {"id": "x-writes", "title": "Writes x", "match": {"writes": "x"}},
+                {"id": "bad", "title": "Bad", "severity": "severe"}
+            ]
+        }))
+        .unwrap();
+        let result = process_task(&task);
+        assert_eq!(result.vulnerabilities.len(), 1);
+        let finding = &result.vulnerabilities[0];
+        assert_eq!(
+            (finding.title.as_str(), finding.check_name.as_str()),
+            ("Writes x", "x-writes")
+        );
+        assert_eq!(finding.severity, Severity::Medium);
+        assert_eq!(finding.source_engine, "static-intel");
+        assert_eq!(
+            finding.code_snippet.as_deref(),
+            Some("contract A { uint256 x; function set(uint256 v) external { x = v; } }")
+        );
+        assert_eq!(
+            result.diagnostics[0].message,
+            "rule `bad`: unknown severity `severe`"
+        );
+    }
+
+    #[test]
+    fn test_process_task_without_sources() {
+        let task: StaticTask = serde_json::from_value(serde_json::json!({"job_id": "j"})).unwrap();
+        let result = process_task(&task);
+        assert_eq!(result.diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_process_task_with_previous_version() {
+        let task: StaticTask = serde_json::from_value(serde_json::json!({
+            "job_id": "job-1",
+            "contract_name": "VaultV2",
+            "sources": {
+                "Vault.sol": "contract VaultV2 { address owner; address admin; uint256 total; }"
+            },
+            "previous": {
+                "sources": {"Vault.sol": "contract Vault { address owner; uint256 total; }"},
+                "contract_name": "Vault"
+            }
+        }))
+        .unwrap();
+        let result = process_task(&task);
+        assert!(result.diagnostics.is_empty());
+        let titles: Vec<(&str, &str)> = result
+            .vulnerabilities
+            .iter()
+            .map(|v| (v.check_name.as_str(), v.title.as_str()))
+            .collect();
+        assert_eq!(
+            titles,
+            [
+                ("storage-layout", "State variable moved"),
+                (
+                    "storage-layout",
+                    "State variable inserted before existing state"
+                ),
+            ]
+        );
+        // Completed like the findings of any other detector.
+        let moved = &result.vulnerabilities[0];
+        assert_eq!(moved.extra_data.cwe, ["CWE-843"]);
+        assert!(moved.code_snippet.is_some());
+        let layout = &result.storage_layouts[0];
+        assert_eq!(
+            (layout.contract.as_str(), layout.entries[2].slot),
+            ("VaultV2", 2)
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! Pre-order walker over solang statements and expressions.
+
+use solang_parser::pt::{CatchClause, Expression, Statement};
+
+/// Callbacks return `true` to descend into children.
+pub trait Visitor {
+    fn visit_statement(&mut self, _stmt: &Statement) -> bool {
+        true
+    }
+
+    fn visit_expression(&mut self, _expr: &Expression) -> bool {
+        true
+    }
+}
+
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, stmt: &Statement) {
+    if !visitor.visit_statement(stmt) {
+        return;
+    }
+    match stmt {
+        Statement::Block { statements, .. } => {
+            for stmt in statements {
+                walk_statement(visitor, stmt);
+            }
+        }
+        Statement::If(_, cond, then, otherwise) => {
+            walk_expression(visitor, cond);
+            walk_statement(visitor, then);
+            if let Some(otherwise) = otherwise {
+                walk_statement(visitor, otherwise);
+            }
+        }
+        Statement::While(_, cond, body) => {
+            walk_expression(visitor, cond);
+            walk_statement(visitor, body);
+        }
+        Statement::DoWhile(_, body, cond) => {
+            walk_statement(visitor, body);
+            walk_expression(visitor, cond);
+        }
+        Statement::For(_, init, cond, next, body) => {
+            if let Some(init) = init {
+                walk_statement(visitor, init);
+            }
+            if let Some(cond) = cond {
+                walk_expression(visitor, cond);
+            }
+            if let Some(next) = next {
+                walk_expression(visitor, next);
+            }
+            if let Some(body) = body {
+                walk_statement(visitor, body);
+            }
+        }
+        Statement::Expression(_, expr) | Statement::Emit(_, expr) => walk_expression(visitor, expr),
+        Statement::VariableDefinition(_, decl, init) => {
+            walk_expression(visitor, &decl.ty);
+            if let Some(init) = init {
+                walk_expression(visitor, init);
+            }
+        }
+        Statement::Return(_, expr) => {
+            if let Some(expr) = expr {
+                walk_expression(visitor, expr);
+            }
+        }
+        Statement::Revert(_, _, args) => {
+            for arg in args {
+                walk_expression(visitor, arg);
+            }
+        }
+        Statement::RevertNamedArgs(_, _, args) | Statement::Args(_, args) => {
+            for arg in args {
+                walk_expression(visitor, &arg.expr);
+            }
+        }
+        Statement::Try(_, expr, returns, catches) => {
+            walk_expression(visitor, expr);
+            if let Some((_, body)) = returns {
+                walk_statement(visitor, body);
+            }
+            for clause in catches {
+                match clause {
+                    CatchClause::Simple(_, _, body) | CatchClause::Named(_, _, _, body) => {
+                        walk_statement(visitor, body)
+                    }
+                }
+            }
+        }
+        Statement::Assembly { .. }
+        | Statement::Continue(_)
+        | Statement::Break(_)
+        | Statement::Error(_) => {}
+    }
+}
+
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expression) {
+    if !visitor.visit_expression(expr) {
+        return;
+    }
+    match expr {
+        Expression::ArraySubscript(_, base, index) => {
+            walk_expression(visitor, base);
+            if let Some(index) = index {
+                walk_expression(visitor, index);
+            }
+        }
+        Expression::ArraySlice(_, base, from, to) => {
+            walk_expression(visitor, base);
+            for bound in [from, to].into_iter().flatten() {
+                walk_expression(visitor, bound);
+            }
+        }
+        Expression::MemberAccess(_, base, _) => walk_expression(visitor, base),
+        Expression::FunctionCall(_, callee, args) => {
+            walk_expression(visitor, callee);
+            for arg in args {
+                walk_expression(visitor, arg);
+            }
+        }
+        Expression::FunctionCallBlock(_, callee, block) => {
+            walk_expression(visitor, callee);
+            walk_statement(visitor, block);
+        }
+        Expression::NamedFunctionCall(_, callee, args) => {
+            walk_expression(visitor, callee);
+            for arg in args {
+                walk_expression(visitor, &arg.expr);
+            }
+        }
+        Expression::ConditionalOperator(_, cond, then, otherwise) => {
+            walk_expression(visitor, cond);
+            walk_expression(visitor, then);
+            walk_expression(visitor, otherwise);
+        }
+        Expression::List(_, params) => {
+            for (_, param) in params {
+                if let Some(param) = param {
+                    walk_expression(visitor, &param.ty);
+                }
+            }
+        }
+        Expression::ArrayLiteral(_, items) => {
+            for item in items {
+                walk_expression(visitor, item);
+            }
+        }
+        Expression::Type(..)
+        | Expression::Variable(_)
+        | Expression::BoolLiteral(..)
+        | Expression::NumberLiteral(..)
+        | Expression::RationalNumberLiteral(..)
+        | Expression::HexNumberLiteral(..)
+        | Expression::StringLiteral(_)
+        | Expression::HexLiteral(_)
+        | Expression::AddressLiteral(..) => {}
+        _ => {
+            let (left, right) = expr.components();
+            for child in [left, right].into_iter().flatten() {
+                walk_expression(visitor, child);
+            }
+        }
+    }
+}
+
+/// Calls `f` on every expression (pre-order) reachable from `stmt`.
+pub fn for_each_expression(stmt: &Statement, f: impl FnMut(&Expression)) {
+    struct Collector<F>(F);
+    impl<F: FnMut(&Expression)> Visitor for Collector<F> {
+        fn visit_expression(&mut self, expr: &Expression) -> bool {
+            (self.0)(expr);
+            true
+        }
+    }
+    walk_statement(&mut Collector(f), stmt);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_file;
+    use crate::source::SourceFile;
+
+    #[test]
+    fn test_walk_collects_nested_identifiers() {
+        let src = r#"contract A {
+            function f(uint a) public returns (uint b) {
+                for (uint i = 0; i < a; i++) {
+                    if (g(i) > a) { b += i; } else { revert E(a); }
+                }
+                try this.h{value: a}() returns (uint r) { b = r; } catch { b = 0; }
+            }
+        }"#;
+        let unit = parse_file(&SourceFile::new(0, "A.sol", src)).unit.unwrap();
+        let body = unit.contracts[0].functions[0].body.as_ref().unwrap();
+        let mut names = Vec::new();
+        for_each_expression(body, |expr| {
+            if let Expression::Variable(id) = expr {
+                names.push(id.name.clone());
+            }
+        });
+        assert_eq!(
+            names,
+            ["i", "a", "i", "g", "i", "a", "b", "i", "a", "this", "a", "b", "r", "b"]
+        );
+    }
+}
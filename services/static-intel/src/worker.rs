@@ -0,0 +1,409 @@
+//! JetStream worker: a durable pull consumer on the planner's static task
+//! stream. Each task runs through [`process_task_with`] on the blocking pool,
+//! at most `concurrency` at a time. A task is acknowledged only once its
+//! result has been stored on `rukh.static.results.<job_id>`; if publishing
+//! fails it is redelivered.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context as _};
+use async_nats::jetstream::{self, consumer, stream, AckKind};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tokio::sync::Semaphore;
+use tracing::{error, info, warn};
+
+use crate::detectors::Registry;
+use crate::task::{process_task_with, StaticTask, TaskResult};
+
+pub const SUBJECT_TASKS: &str = "rukh.static.tasks";
+pub const SUBJECT_RESULTS: &str = "rukh.static.results";
+pub const SUBJECT_PROGRESS: &str = "rukh.analysis.progress";
+
+/// Phase reported in progress updates.
+const PHASE: &str = "static";
+
+#[derive(Debug, Clone)]
+pub struct WorkerConfig {
+    pub nats_url: String,
+    /// Stream holding `{tasks_subject}.>`; the planner names it `STATIC_TASKS`.
+    pub tasks_stream: String,
+    pub tasks_subject: String,
+    pub results_stream: String,
+    pub results_subject: String,
+    pub progress_subject: String,
+    /// Durable consumer name, shared by all replicas of the service.
+    pub consumer: String,
+    /// Tasks analysed at the same time.
+    pub concurrency: usize,
+    /// Time after which an unacknowledged task is redelivered. Running tasks
+    /// keep extending it.
+    pub ack_wait: Duration,
+    /// Deliveries of a task before it is given up.
+    pub max_deliver: i64,
+}
+
+impl Default for WorkerConfig {
+    fn default() -> Self {
+        Self {
+            nats_url: "nats://nats:4222".into(),
+            tasks_stream: "STATIC_TASKS".into(),
+            tasks_subject: SUBJECT_TASKS.into(),
+            results_stream: "STATIC_RESULTS".into(),
+            results_subject: SUBJECT_RESULTS.into(),
+            progress_subject: SUBJECT_PROGRESS.into(),
+            consumer: "static-intel".into(),
+            concurrency: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            ack_wait: Duration::from_secs(60),
+            max_deliver: 3,
+        }
+    }
+}
+
+impl WorkerConfig {
+    /// Defaults overridden by `NATS_URL`, `STATIC_INTEL_CONCURRENCY` and
+    /// `STATIC_INTEL_CONSUMER`.
+    pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> anyhow::Result<Self> {
+        let mut config = Self::default();
+        if let Some(url) = var("NATS_URL") {
+            config.nats_url = url;
+        }
+        if let Some(concurrency) = var("STATIC_INTEL_CONCURRENCY") {
+            config.concurrency = concurrency
+                .parse()
+                .ok()
+                .filter(|&n| n > 0)
+                .with_context(|| format!("invalid STATIC_INTEL_CONCURRENCY `{concurrency}`"))?;
+        }
+        if let Some(consumer) = var("STATIC_INTEL_CONSUMER") {
+            config.consumer = consumer;
+        }
+        Ok(config)
+    }
+}
+
+/// Progress update, in the format the planner publishes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Progress {
+    pub job_id: String,
+    pub phase: String,
+    /// Percentage, 0 to 100.
+    pub progress: f64,
+    pub timestamp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Progress {
+    fn new(job_id: &str, progress: f64, error: Option<String>) -> Self {
+        Self {
+            job_id: job_id.to_string(),
+            phase: PHASE.to_string(),
+            progress,
+            timestamp: OffsetDateTime::now_utc()
+                .format(&Rfc3339)
+                .unwrap_or_default(),
+            error,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Worker {
+    client: async_nats::Client,
+    jetstream: jetstream::Context,
+    config: Arc<WorkerConfig>,
+    registry: Arc<Registry>,
+}
+
+impl Worker {
+    pub async fn connect(config: WorkerConfig, registry: Registry) -> anyhow::Result<Self> {
+        let client = async_nats::connect(&config.nats_url)
+            .await
+            .with_context(|| format!("failed to connect to {}", config.nats_url))?;
+        info!("connected to NATS at {}", config.nats_url);
+        Ok(Self {
+            jetstream: jetstream::new(client.clone()),
+            client,
+            config: Arc::new(config),
+            registry: Arc::new(registry),
+        })
+    }
+
+    /// Consumes tasks until the connection is closed.
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let config = &self.config;
+        let tasks = self
+            .jetstream
+            .get_or_create_stream(stream::Config {
+                name: config.tasks_stream.clone(),
+                subjects: vec![format!("{}.>", config.tasks_subject)],
+                retention: stream::RetentionPolicy::WorkQueue,
+                max_age: Duration::from_secs(24 * 60 * 60),
+                ..Default::default()
+            })
+            .await
+            .context("failed to open the task stream")?;
+        self.jetstream
+            .get_or_create_stream(stream::Config {
+                name: config.results_stream.clone(),
+                subjects: vec![format!("{}.>", config.results_subject)],
+                max_age: Duration::from_secs(7 * 24 * 60 * 60),
+                ..Default::default()
+            })
+            .await
+            .context("failed to open the result stream")?;
+        let consumer: consumer::PullConsumer = tasks
+            .get_or_create_consumer(
+                &config.consumer,
+                consumer::pull::Config {
+                    durable_name: Some(config.consumer.clone()),
+                    filter_subject: format!("{}.>", config.tasks_subject),
+                    ack_policy: consumer::AckPolicy::Explicit,
+                    ack_wait: config.ack_wait,
+                    max_deliver: config.max_deliver,
+                    max_ack_pending: config.concurrency as i64,
+                    ..Default::default()
+                },
+            )
+            .await
+            .context("failed to create the task consumer")?;
+        info!(
+            "consuming {}.> as `{}` ({} at a time)",
+            config.tasks_subject, config.consumer, config.concurrency
+        );
+
+        let permits = Arc::new(Semaphore::new(config.concurrency));
+        let mut messages = consumer.messages().await?;
+        while let Some(message) = messages.next().await {
+            let message = match message {
+                Ok(message) => message,
+                Err(err) => {
+                    warn!("failed to receive task: {err}");
+                    continue;
+                }
+            };
+            let permit = permits.clone().acquire_owned().await?;
+            let worker = self.clone();
+            tokio::spawn(async move {
+                worker.handle(message).await;
+                drop(permit);
+            });
+        }
+        Ok(())
+    }
+
+    async fn handle(&self, message: jetstream::Message) {
+        let task: StaticTask = match serde_json::from_slice(&message.payload) {
+            Ok(task) => task,
+            Err(err) => {
+                // Redelivery cannot fix a malformed payload.
+                warn!("dropping malformed task on {}: {err}", message.subject);
+                if let Err(err) = message.ack_with(AckKind::Term).await {
+                    warn!("failed to terminate task: {err}");
+                }
+                return;
+            }
+        };
+        let job_id = task.job_id.clone();
+        info!("job {job_id}: analysing");
+        self.progress(Progress::new(&job_id, 0.0, None)).await;
+
+        let registry = self.registry.clone();
+        let mut analysis = tokio::task::spawn_blocking(move || process_task_with(&registry, &task));
+        // Keep the task from being redelivered while the analysis runs.
+        let mut keepalive = tokio::time::interval(self.config.ack_wait / 2);
+        keepalive.tick().await;
+        let result = loop {
+            tokio::select! {
+                result = &mut analysis => break result,
+                _ = keepalive.tick() => {
+                    if let Err(err) = message.ack_with(AckKind::Progress).await {
+                        warn!("job {job_id}: failed to extend the ack deadline: {err}");
+                    }
+                }
+            }
+        };
+
+        let result = match result {
+            Ok(result) => result,
+            Err(err) => {
+                error!("job {job_id}: analysis failed: {err}");
+                self.progress(Progress::new(&job_id, 0.0, Some(err.to_string())))
+                    .await;
+                if let Err(err) = message.ack_with(AckKind::Term).await {
+                    warn!("job {job_id}: failed to terminate task: {err}");
+                }
+                return;
+            }
+        };
+        if let Err(err) = self.publish_result(&result).await {
+            error!("job {job_id}: {err:#}");
+            if let Err(err) = message.ack_with(AckKind::Nak(None)).await {
+                warn!("job {job_id}: failed to reject task: {err}");
+            }
+            return;
+        }
+        if let Err(err) = message.ack().await {
+            // The task is redelivered and its result published again.
+            warn!("job {job_id}: failed to acknowledge task: {err}");
+            return;
+        }
+        self.progress(Progress::new(&job_id, 100.0, None)).await;
+        info!(
+            "job {job_id}: done, {} findings",
+            result.vulnerabilities.len()
+        );
+    }
+
+    /// Stores `result` in the result stream, waiting for the server's ack.
+    async fn publish_result(&self, result: &TaskResult) -> anyhow::Result<()> {
+        let subject = format!("{}.{}", self.config.results_subject, result.job_id);
+        let payload = serde_json::to_vec(result)?;
+        self.jetstream
+            .publish(subject.clone(), payload.into())
+            .await
+            .map_err(|err| anyhow!("failed to publish result to {subject}: {err}"))?
+            .await
+            .map_err(|err| anyhow!("result on {subject} not stored: {err}"))?;
+        Ok(())
+    }
+
+    /// Progress is advisory, so failures are only logged.
+    async fn progress(&self, progress: Progress) {
+        let subject = format!("{}.{}", self.config.progress_subject, progress.job_id);
+        let payload = match serde_json::to_vec(&progress) {
+            Ok(payload) => payload,
+            Err(err) => return warn!("failed to encode progress: {err}"),
+        };
+        if let Err(err) = self.client.publish(subject, payload.into()).await {
+            warn!("job {}: failed to publish progress: {err}", progress.job_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_from_env() {
+        let config = WorkerConfig::from_vars(|name| match name {
+            "NATS_URL" => Some("nats://localhost:4222".into()),
+            "STATIC_INTEL_CONCURRENCY" => Some("2".into()),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(
+            (config.nats_url.as_str(), config.concurrency),
+            ("nats://localhost:4222", 2)
+        );
+        assert_eq!(config.consumer, "static-intel");
+        assert!(WorkerConfig::from_vars(|name| {
+            (name == "STATIC_INTEL_CONCURRENCY").then(|| "0".into())
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_progress_format() {
+        let value = serde_json::to_value(Progress::new("job-1", 100.0, None)).unwrap();
+        assert_eq!(value["phase"], "static");
+        assert_eq!(value["progress"], 100.0);
+        assert!(value.get("error").is_none());
+        assert!(value["timestamp"].as_str().unwrap().ends_with('Z'));
+    }
+
+    /// Needs a JetStream-enabled server (`nats-server -js`) at `NATS_URL` or
+    /// `nats://localhost:4222`.
+    #[tokio::test]
+    #[ignore = "needs a local nats-server"]
+    async fn test_worker_against_nats_server() {
+        let prefix = format!("rukh-test-{}", std::process::id());
+        let config = WorkerConfig {
+            nats_url: std::env::var("NATS_URL").unwrap_or("nats://localhost:4222".into()),
+            tasks_stream: format!("{prefix}_TASKS").replace('-', "_"),
+            tasks_subject: format!("{prefix}.static.tasks"),
+            results_stream: format!("{prefix}_RESULTS").replace('-', "_"),
+            results_subject: format!("{prefix}.static.results"),
+            progress_subject: format!("{prefix}.analysis.progress"),
+            consumer: prefix.clone(),
+            concurrency: 2,
+            ..Default::default()
+        };
+        let worker = Worker::connect(config.clone(), Registry::builtin())
+            .await
+            .unwrap();
+        let mut progress = worker
+            .client
+            .subscribe(format!("{}.job-1", config.progress_subject))
+            .await
+            .unwrap();
+        tokio::spawn({
+            let worker = worker.clone();
+            async move { worker.run().await }
+        });
+
+        let source = include_str!("../../../integrations/foundry/src/ReentrancyVault.sol");
+        let task = serde_json::json!({"job_id": "job-1", "source_code": source});
+        // Wait for the worker to create the stream, then publish the task.
+        let js = &worker.jetstream;
+        let tasks = loop {
+            if let Ok(stream) = js.get_stream(&config.tasks_stream).await {
+                break stream;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        };
+        js.publish(
+            format!("{}.job-1", config.tasks_subject),
+            task.to_string().into(),
+        )
+        .await
+        .unwrap()
+        .await
+        .unwrap();
+
+        let results: consumer::PullConsumer = js
+            .get_stream(&config.results_stream)
+            .await
+            .unwrap()
+            .create_consumer(consumer::pull::Config::default())
+            .await
+            .unwrap();
+        let message = results
+            .messages()
+            .await
+            .unwrap()
+            .next()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            message.subject.as_str(),
+            format!("{}.job-1", config.results_subject)
+        );
+        let result: TaskResult = serde_json::from_slice(&message.payload).unwrap();
+        assert_eq!(result.vulnerabilities[0].check_name, "reentrancy");
+
+        let mut updates = Vec::new();
+        while updates.last() != Some(&100.0) {
+            let message = progress.next().await.unwrap();
+            let update: Progress = serde_json::from_slice(&message.payload).unwrap();
+            updates.push(update.progress);
+        }
+        assert_eq!(updates, [0.0, 100.0]);
+        // Acknowledged tasks leave the work queue.
+        let mut tasks = tasks;
+        assert_eq!(tasks.info().await.unwrap().state.messages, 0);
+
+        js.delete_stream(&config.tasks_stream).await.unwrap();
+        js.delete_stream(&config.results_stream).await.unwrap();
+    }
+}
@@ -97,10 +97,15 @@ class ExploitGenerator:
         return output_path
     
     def _identify_vulnerability_type(self, vulnerability: Dict) -> str:
-        """Identify vulnerability type from Slither output"""
-        check = vulnerability.get('check', '').lower()
+        """Identify vulnerability type from Slither or rukh static-intel output"""
+        # Slither names the detector `check`, rukh findings `check_name`
+        check = (vulnerability.get('check_name') or vulnerability.get('check', '')).lower()
         description = vulnerability.get('description', '').lower()
         
+        # Price manipulation (spot prices, balance-based accounting)
+        if check == 'oracle-manipulation':
+            return 'flash-loan'
+        
         # Reentrancy patterns
         if 'reentrancy' in check or 'reentrancy' in description:
             return 'reentrancy'
@@ -146,7 +151,7 @@ class ExploitGenerator:
         details_comment = f"""
 /**
  * VULNERABILITY DETAILS:
- * Type: {vulnerability.get('check', 'Unknown')}
+ * Type: {vulnerability.get('check_name') or vulnerability.get('check', 'Unknown')}
  * Severity: {vulnerability.get('impact', 'Unknown')}
  * Confidence: {vulnerability.get('confidence', 'Unknown')}
  * Location: {vuln_location}